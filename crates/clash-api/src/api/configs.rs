@@ -482,6 +482,15 @@ impl Client {
         .await
     }
 
+    /// `GET /configs` as the core sent it. clash-rs and Clash Premium report
+    /// a subset of Mihomo's [`RuntimeConfig`], which this does not require.
+    pub async fn configs_value(&self) -> Result<serde_json::Value> {
+        self.send_json(RequestMetadata::new("configs", Method::GET, true), || {
+            self.get("/configs")
+        })
+        .await
+    }
+
     pub async fn update_config(
         &self,
         request: &UpdateConfigRequest,
@@ -21,6 +21,7 @@ pub use error::{Error, ErrorBody, Result};
 pub use indexmap::IndexMap;
 pub use retry::{ExponentialRetry, NoRetry, RequestMetadata, RetryPolicy};
 pub use stream::HttpStream;
+pub use uuid::Uuid;
 
 // TODO(ws-typed-stream): consider an opt-in typed frame adapter after raw
 // WebSocket callers have migrated. The endpoint methods intentionally return
@@ -13,6 +13,7 @@ use specta::Type;
 
 use crate::{
     Error,
+    kind::CoreKind,
     spec::{CoreSpec, LocalIpcPolicy},
 };
 
@@ -48,6 +49,9 @@ pub(crate) struct ResolvedFeatures {
     /// What the manager enabled after weighing capabilities against policy.
     pub runtime: EnumSet<RuntimeFeature>,
     pub version: Option<String>,
+    /// The policy `runtime` was weighed under, read once per launch so a
+    /// concurrent change cannot split one launch across two policies.
+    pub policy: LocalIpcPolicy,
 }
 
 impl VersionCache {
@@ -61,12 +65,12 @@ impl VersionCache {
         };
 
         // Keep the lock across the short probe so concurrent resolutions in
-        // one manager issue only one `-v` call for this binary.
+        // one manager issue only one version call for this binary.
         let mut entries = self.entries.lock().await;
         if let Some(version) = entries.get(&key) {
             return Ok(version.clone());
         }
-        let version = probe_version(&spec.binary_path).await?;
+        let version = probe_version(spec.kind, &spec.binary_path).await?;
         if binary_modified(spec).await? != key.modified {
             return Err(Error::CoreVersionProbeFailed {
                 binary_path: spec.binary_path.clone(),
@@ -92,9 +96,9 @@ async fn binary_modified(spec: &CoreSpec) -> Result<SystemTime, Error> {
         })
 }
 
-async fn probe_version(binary_path: &camino::Utf8Path) -> Result<String, Error> {
+async fn probe_version(kind: CoreKind, binary_path: &camino::Utf8Path) -> Result<String, Error> {
     let output = nyanpasu_utils::process::Command::new(binary_path.as_str())
-        .arg("-v")
+        .args(crate::kind::version_args(kind))
         .timeout(Duration::from_secs(5))
         .output()
         .await
@@ -125,20 +129,35 @@ pub(crate) async fn resolve_features(
     core: &CoreSpec,
     policy: LocalIpcPolicy,
 ) -> Result<ResolvedFeatures, Error> {
-    let (capabilities, version) = if core.kind.potential_features().is_empty() {
-        (EnumSet::new(), core.version.clone())
-    } else {
-        let version = cache.resolve(core).await?;
-        (
-            core.kind.features(Some(&CoreVersion::parse(&version))),
-            Some(version),
-        )
-    };
-    let runtime = resolve_runtime(policy, core, capabilities, version.as_deref())?;
+    if core.kind.potential_features().is_empty() {
+        // Nothing is gated on the version, so policy is settled without it
+        // and a `Force` refusal spawns nothing. The version is still probed
+        // because the status reports it, but a core that cannot print one
+        // runs regardless.
+        let runtime = resolve_runtime(policy, core, EnumSet::new(), None)?;
+        let version = match cache.resolve(core).await {
+            Ok(version) => Some(version),
+            Err(error) => {
+                tracing::warn!("{error}");
+                None
+            }
+        };
+        return Ok(ResolvedFeatures {
+            capabilities: EnumSet::new(),
+            runtime,
+            version,
+            policy,
+        });
+    }
+    let version = cache.resolve(core).await?;
+    let capabilities = core.kind.features(Some(&CoreVersion::parse(&version)));
+    let runtime = resolve_runtime(policy, core, capabilities, Some(&version))?;
+    let version = Some(version);
     Ok(ResolvedFeatures {
         capabilities,
         runtime,
         version,
+        policy,
     })
 }
 
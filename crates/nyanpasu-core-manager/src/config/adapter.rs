@@ -0,0 +1,441 @@
+//! Per-core config adapters, and the deny-by-default classification of a
+//! runtime-config change built on top of them.
+//!
+//! An adapter states what one core's controller can change in place: the keys
+//! `PATCH /configs` takes, the keys a `PUT /configs` re-read picks up, the
+//! inbound listeners, and how a managed controller is written. The engine
+//! below never names a key itself, so each kind gets the cheapest route its
+//! own controller supports and anything unlisted still restarts. The tables
+//! live in per-core sibling modules; [`for_kind`] picks one.
+
+use std::collections::BTreeSet;
+
+use serde_yaml_ng::{Mapping, Value};
+
+use crate::{
+    Error, InstanceSpec,
+    config::{
+        clash, clash_rs,
+        diff::{DiffEntry, collect_leaves, diff, value_at},
+        mihomo, premium,
+    },
+    kind::{ClashCoreKind, CoreKind},
+};
+
+pub(crate) trait CoreConfigAdapter: Sync {
+    /// Top-level keys `PATCH /configs` applies without a restart.
+    fn patch_fields(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// For a patchable root that is a listener block, the nested keys the
+    /// patch may carry. `None` means the root is patched as a whole value.
+    fn nested_patch_fields(&self, _root: &str) -> Option<&'static [&'static str]> {
+        None
+    }
+
+    /// Top-level keys a `PUT /configs` re-read of the runtime copy applies in
+    /// place. Empty when the core is never reloaded.
+    fn reload_fields(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Numeric inbound listeners, the ones a bootstrap epoch zeroes.
+    fn inbound_port_fields(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Keys that decide how the manager reaches the core. A change to any of
+    /// them invalidates the live control channel.
+    fn controller_fields(&self) -> &'static [&'static str] {
+        clash::CONTROLLER_FIELDS
+    }
+
+    /// A path that restarts even when its root is patchable or reloadable.
+    fn restarts(&self, _path: &[String]) -> bool {
+        false
+    }
+
+    /// Repoints the controller at the manager-owned local endpoint.
+    fn rewrite_managed_controller(&self, document: &mut Mapping, endpoint: String) {
+        clash::rewrite_managed_controller(document, endpoint);
+    }
+
+    /// Zeroes the inbound surface so a bootstrap epoch can run alongside the
+    /// outgoing one.
+    fn zero_inbounds(&self, document: &mut Mapping) {
+        for key in self.inbound_port_fields() {
+            zero_listener(document, key);
+        }
+    }
+
+    /// What `GET /configs` must report once `patch` has landed.
+    fn projection(&self, patch: &clash_api::ConfigPatch) -> Result<RuntimeProjection, Error> {
+        let serialized = serde_yaml_ng::to_value(patch)?;
+        let mut expected = Vec::new();
+        collect_leaves(&serialized, &mut Vec::new(), &mut expected);
+        Ok(RuntimeProjection { expected })
+    }
+}
+
+/// A core the manager never reconfigures in place: every change restarts it,
+/// and only an identical config is a [`ConfigChange::Noop`].
+struct RestartOnly;
+
+impl CoreConfigAdapter for RestartOnly {}
+
+pub(crate) fn for_kind(kind: CoreKind) -> &'static dyn CoreConfigAdapter {
+    match kind {
+        CoreKind::Clash(ClashCoreKind::Mihomo) => &mihomo::Mihomo,
+        CoreKind::Clash(ClashCoreKind::ClashRust) => &clash_rs::ClashRust,
+        CoreKind::Clash(ClashCoreKind::ClashPremium) => &premium::ClashPremium,
+        // meow's controller has not been audited for in-place changes, and
+        // sing-box's Clash API takes no config PATCH at all.
+        CoreKind::Clash(ClashCoreKind::Meow) | CoreKind::SingBox => &RestartOnly,
+    }
+}
+
+pub(super) fn zero_listener(document: &mut Mapping, key: &str) {
+    let key = Value::String(key.to_owned());
+    if document
+        .get(&key)
+        .and_then(Value::as_i64)
+        .filter(|value| *value != 0)
+        .is_some()
+    {
+        document.insert(key, Value::from(0));
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum ConfigChange {
+    Noop,
+    Patch {
+        patch: Box<clash_api::ConfigPatch>,
+        projection: RuntimeProjection,
+    },
+    Reload,
+    Switch,
+}
+
+#[derive(Debug)]
+pub(crate) struct RuntimeProjection {
+    pub(super) expected: Vec<(Vec<String>, Value)>,
+}
+
+impl RuntimeProjection {
+    /// Takes `GET /configs` undecoded: each core reports its own subset of
+    /// Mihomo's view, and only the projected leaves matter.
+    pub(crate) fn verify(&self, actual: &serde_json::Value) -> Result<bool, Error> {
+        let actual = serde_yaml_ng::to_value(actual)?;
+        Ok(self.expected.iter().all(|(path, expected)| {
+            value_at(&actual, path).is_some_and(|actual| actual == expected)
+        }))
+    }
+}
+
+pub(crate) fn classify(
+    current_source: &Mapping,
+    current_effective: &Mapping,
+    current_spec: &InstanceSpec,
+    desired_source: &Mapping,
+    desired_effective: &Mapping,
+    desired_spec: &InstanceSpec,
+) -> Result<ConfigChange, Error> {
+    if process_spec_changed(current_spec, desired_spec) {
+        return Ok(ConfigChange::Switch);
+    }
+
+    // The source is checked as well as the effective document: a managed
+    // controller rewrite hides a controller edit from the effective diff.
+    let adapter = for_kind(desired_spec.core.kind);
+    if diff(current_source, desired_source).iter().any(|entry| {
+        entry
+            .path
+            .first()
+            .is_some_and(|root| adapter.controller_fields().contains(&root.as_str()))
+            || adapter.restarts(&entry.path)
+    }) {
+        return Ok(ConfigChange::Switch);
+    }
+    classify_documents(adapter, current_effective, desired_effective)
+}
+
+fn process_spec_changed(current: &InstanceSpec, desired: &InstanceSpec) -> bool {
+    current.core.kind != desired.core.kind
+        || current.core.binary_path != desired.core.binary_path
+        || current.core.version != desired.core.version
+        || current.core.features != desired.core.features
+        || current.working_dir != desired.working_dir
+        || format!("{:?}", current.options) != format!("{:?}", desired.options)
+}
+
+pub(super) fn classify_documents(
+    adapter: &dyn CoreConfigAdapter,
+    current: &Mapping,
+    desired: &Mapping,
+) -> Result<ConfigChange, Error> {
+    let changes = diff(current, desired);
+    if changes.is_empty() {
+        return Ok(ConfigChange::Noop);
+    }
+    if changes.iter().any(|entry| adapter.restarts(&entry.path)) {
+        return Ok(ConfigChange::Switch);
+    }
+
+    let patchable = changes.iter().all(|entry| {
+        entry.new.is_some()
+            && entry.path.first().is_some_and(|root| {
+                adapter.patch_fields().contains(&root.as_str())
+                    && patch_nested_path_is_supported(adapter, &entry.path)
+            })
+    });
+    if patchable {
+        return build_patch(adapter, desired, &changes);
+    }
+
+    let reloadable = changes.iter().all(|entry| {
+        entry
+            .path
+            .first()
+            .is_some_and(|root| adapter.reload_fields().contains(&root.as_str()))
+    });
+    Ok(if reloadable {
+        ConfigChange::Reload
+    } else {
+        ConfigChange::Switch
+    })
+}
+
+fn patch_nested_path_is_supported(adapter: &dyn CoreConfigAdapter, path: &[String]) -> bool {
+    let Some(root) = path.first() else {
+        return false;
+    };
+    match adapter.nested_patch_fields(root) {
+        Some(fields) => path
+            .get(1)
+            .is_some_and(|field| fields.contains(&field.as_str())),
+        None => path.len() == 1,
+    }
+}
+
+fn build_patch(
+    adapter: &dyn CoreConfigAdapter,
+    desired: &Mapping,
+    changes: &[DiffEntry],
+) -> Result<ConfigChange, Error> {
+    let roots: BTreeSet<&str> = changes
+        .iter()
+        .filter_map(|entry| entry.path.first().map(String::as_str))
+        .collect();
+    let mut document = Mapping::new();
+    for root in roots {
+        let key = Value::String(root.to_owned());
+        let Some(value) = desired.get(&key) else {
+            return Ok(ConfigChange::Switch);
+        };
+        let value = match adapter.nested_patch_fields(root) {
+            Some(fields) => {
+                let filtered = filter_mapping(value, fields)?;
+                // A listener block is patched whole, and one without `enable`
+                // would decode as `enable: false` and turn the listener off.
+                if filtered
+                    .as_mapping()
+                    .and_then(|mapping| mapping.get(Value::String("enable".to_owned())))
+                    .and_then(Value::as_bool)
+                    .is_none()
+                {
+                    return Ok(ConfigChange::Switch);
+                }
+                filtered
+            }
+            None => value.clone(),
+        };
+        document.insert(key, value);
+    }
+    let Ok(patch) = serde_yaml_ng::from_value::<clash_api::ConfigPatch>(Value::Mapping(document))
+    else {
+        return Ok(ConfigChange::Switch);
+    };
+    let projection = adapter.projection(&patch)?;
+    Ok(ConfigChange::Patch {
+        patch: Box::new(patch),
+        projection,
+    })
+}
+
+fn filter_mapping(value: &Value, allowed: &[&str]) -> Result<Value, Error> {
+    let mapping = value
+        .as_mapping()
+        .ok_or_else(|| Error::InvalidConfig("patchable nested config must be a mapping".into()))?;
+    let mut filtered = Mapping::new();
+    for (key, value) in mapping {
+        let Some(key_text) = key.as_str() else {
+            return Err(Error::InvalidConfig("config keys must be strings".into()));
+        };
+        if allowed.contains(&key_text) {
+            filtered.insert(key.clone(), value.clone());
+        }
+    }
+    Ok(Value::Mapping(filtered))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CoreSpec, InstanceOptions};
+
+    fn mapping(yaml: &str) -> Mapping {
+        serde_yaml_ng::from_str(yaml).unwrap()
+    }
+
+    fn spec(kind: CoreKind, binary: &str) -> InstanceSpec {
+        InstanceSpec {
+            core: CoreSpec {
+                kind,
+                binary_path: binary.into(),
+                version: None,
+                features: Vec::new(),
+                distribution: None,
+            },
+            config_path: "source.yaml".into(),
+            working_dir: ".".into(),
+            pid_file: None,
+            options: InstanceOptions::default(),
+        }
+    }
+
+    #[test]
+    fn identical_config_is_noop_for_every_kind() {
+        for kind in [
+            CoreKind::Clash(ClashCoreKind::Mihomo),
+            CoreKind::Clash(ClashCoreKind::ClashRust),
+            CoreKind::Clash(ClashCoreKind::ClashPremium),
+            CoreKind::Clash(ClashCoreKind::Meow),
+            CoreKind::SingBox,
+        ] {
+            let spec = spec(kind, "core");
+            assert!(
+                matches!(
+                    classify(
+                        &mapping("mixed-port: 7890"),
+                        &Mapping::new(),
+                        &spec,
+                        &mapping("mixed-port: 7890"),
+                        &Mapping::new(),
+                        &spec,
+                    )
+                    .unwrap(),
+                    ConfigChange::Noop
+                ),
+                "{kind:?} should noop on an identical config"
+            );
+        }
+    }
+
+    #[test]
+    fn a_mode_change_takes_the_cheapest_route_each_kind_supports() {
+        let current = mapping("mode: rule");
+        let desired = mapping("mode: global");
+        for (kind, patched) in [
+            (CoreKind::Clash(ClashCoreKind::Mihomo), true),
+            (CoreKind::Clash(ClashCoreKind::ClashRust), true),
+            (CoreKind::Clash(ClashCoreKind::ClashPremium), true),
+            (CoreKind::Clash(ClashCoreKind::Meow), false),
+            (CoreKind::SingBox, false),
+        ] {
+            let spec = spec(kind, "core");
+            let change = classify(&current, &current, &spec, &desired, &desired, &spec).unwrap();
+            assert_eq!(
+                matches!(change, ConfigChange::Patch { .. }),
+                patched,
+                "{kind:?}: {change:?}"
+            );
+            if !patched {
+                assert!(matches!(change, ConfigChange::Switch), "{kind:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn every_sing_box_change_restarts() {
+        // Its Clash API takes no config PATCH and no reload, so even a log
+        // level change has to go through a fresh process.
+        let spec = spec(CoreKind::SingBox, "sing-box");
+        let current = mapping("log: {level: info}");
+        let desired = mapping("log: {level: debug}");
+        assert!(matches!(
+            classify(&current, &current, &spec, &desired, &desired, &spec).unwrap(),
+            ConfigChange::Switch
+        ));
+    }
+
+    #[test]
+    fn noop_requires_unchanged_effective_config() {
+        // Same source, but capability resolution rewrote the controller:
+        // the derived config changed, so this must restart, not noop.
+        let spec = spec(CoreKind::Clash(ClashCoreKind::ClashRust), "clash-rs");
+        assert!(matches!(
+            classify(
+                &mapping("mixed-port: 7890"),
+                &mapping("external-controller: 127.0.0.1:9090"),
+                &spec,
+                &mapping("mixed-port: 7890"),
+                &mapping("external-controller-pipe: /tmp/core-1.sock"),
+                &spec,
+            )
+            .unwrap(),
+            ConfigChange::Switch
+        ));
+    }
+
+    #[test]
+    fn controller_process_and_kind_changes_switch() {
+        let current = spec(CoreKind::Clash(ClashCoreKind::Mihomo), "mihomo");
+        let mut changed_binary = current.clone();
+        changed_binary.core.binary_path = "other-mihomo".into();
+        assert!(matches!(
+            classify(
+                &mapping("external-controller: 127.0.0.1:9090"),
+                &Mapping::new(),
+                &current,
+                &mapping("external-controller: 127.0.0.1:9091"),
+                &Mapping::new(),
+                &current,
+            )
+            .unwrap(),
+            ConfigChange::Switch
+        ));
+        assert!(matches!(
+            classify(
+                &Mapping::new(),
+                &Mapping::new(),
+                &current,
+                &Mapping::new(),
+                &Mapping::new(),
+                &changed_binary,
+            )
+            .unwrap(),
+            ConfigChange::Switch
+        ));
+        assert!(matches!(
+            classify(
+                &Mapping::new(),
+                &Mapping::new(),
+                &current,
+                &Mapping::new(),
+                &Mapping::new(),
+                &spec(CoreKind::Clash(ClashCoreKind::ClashRust), "clash-rs"),
+            )
+            .unwrap(),
+            ConfigChange::Switch
+        ));
+    }
+
+    #[test]
+    fn bootstrap_zeroing_only_touches_nonzero_listeners() {
+        let mut document = mapping("mixed-port: 7890\nport: 0\nallow-lan: true");
+        for_kind(CoreKind::Clash(ClashCoreKind::ClashPremium)).zero_inbounds(&mut document);
+        assert_eq!(document, mapping("mixed-port: 0\nport: 0\nallow-lan: true"));
+    }
+}
@@ -1,11 +1,12 @@
 //! Clash-family controller vocabulary: the config keys every supported core
 //! uses to expose its RESTful control plane, plus the managed-mode rewrite.
 //!
-//! Unlike [`super::mihomo`], nothing here is gated on a kind — `prepare_full`
-//! runs this for every [`crate::CoreKind`], because `external-controller`,
-//! `external-controller-pipe` and `external-controller-unix` are the Clash API
-//! transports modelled by [`clash_api::Host`]. A core that does not speak the
-//! Clash API (sing-box, …) needs its own module rather than a branch here.
+//! Unlike [`super::mihomo`], nothing here is gated on a Clash kind —
+//! `prepare_full` runs this for every one of them, because
+//! `external-controller`, `external-controller-pipe` and
+//! `external-controller-unix` are the Clash API transports modelled by
+//! [`clash_api::Host`]. A core with its own config schema gets its own module
+//! rather than a branch here, as sing-box has [`super::singbox`].
 
 use serde_yaml_ng::{Mapping, Value};
 
@@ -34,6 +35,176 @@ pub(super) const CONTROLLER_FIELDS: &[&str] = &[
     SECRET,
 ];
 
+/// Keys whose values are credentials wherever they appear: proxy and
+/// listener passwords, keys and tokens, `reality-opts.short-id`, plus the
+/// inbound `authentication` list. Matched without regard to case.
+const CREDENTIAL_FIELDS: &[&str] = &[
+    "authentication",
+    "auth",
+    "auth-str",
+    "obfs-password",
+    "password",
+    "pre-shared-key",
+    "private-key",
+    "psk",
+    "short-id",
+    "token",
+    "users",
+    "uuid",
+];
+
+/// Keys holding a header map, as in `ws-opts.headers`. Every value is masked
+/// and only the header names stay: `Authorization` is the obvious one, but a
+/// header's meaning is up to the server it is sent to.
+const HEADERS_FIELD: &str = "headers";
+
+/// DNS keys listing nameservers. A DoH or DoT server can carry a token in its
+/// URL, so every entry goes through [`redact_url`] the way a provider `url`
+/// does; a plain address is left as it is.
+const NAMESERVER_FIELDS: &[&str] = &[
+    "default-nameserver",
+    "direct-nameserver",
+    "fallback",
+    "nameserver",
+    "nameserver-policy",
+    "proxy-server-nameserver",
+];
+
+/// Keys holding a URL: provider subscriptions and health checks. Only their
+/// userinfo and query are masked, see [`redact_url`].
+pub(super) const URL_FIELD: &str = "url";
+
+const REDACTED: &str = "<redacted>";
+
+fn is_one_of(fields: &[&str], key: &str) -> bool {
+    fields.iter().any(|field| field.eq_ignore_ascii_case(key))
+}
+
+/// Masks whatever under `value` must not leave the service. A controller key
+/// or credential at `path` itself is masked whole; otherwise credentials are
+/// masked wherever they nest, so a changed proxy list keeps its shape.
+pub(super) fn redact(path: &[String], value: &mut Value) {
+    let controller = path.len() == 1 && CONTROLLER_FIELDS.contains(&path[0].as_str());
+    let (key, parents) = match path.split_last() {
+        Some((key, parents)) => (Some(key.as_str()), parents),
+        None => (None, path),
+    };
+    if controller
+        || parents
+            .iter()
+            .any(|parent| parent.eq_ignore_ascii_case(HEADERS_FIELD))
+    {
+        *value = Value::String(REDACTED.to_owned());
+        return;
+    }
+    if parents
+        .iter()
+        .any(|parent| is_one_of(NAMESERVER_FIELDS, parent))
+    {
+        redact_urls(value);
+        return;
+    }
+    if key.is_some_and(|key| redact_field(key, value)) {
+        return;
+    }
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping.iter_mut() {
+                if !key.as_str().is_some_and(|key| redact_field(key, value)) {
+                    redact(&[], value);
+                }
+            }
+        }
+        Value::Sequence(sequence) => {
+            for value in sequence {
+                redact(&[], value);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Masks `value` the way its key asks for, or returns `false` when the key
+/// says nothing about it.
+fn redact_field(key: &str, value: &mut Value) -> bool {
+    if is_one_of(CREDENTIAL_FIELDS, key) {
+        *value = Value::String(REDACTED.to_owned());
+    } else if key.eq_ignore_ascii_case(HEADERS_FIELD) {
+        match value {
+            Value::Mapping(headers) => {
+                for (_, value) in headers.iter_mut() {
+                    *value = Value::String(REDACTED.to_owned());
+                }
+            }
+            value => *value = Value::String(REDACTED.to_owned()),
+        }
+    } else if key.eq_ignore_ascii_case(URL_FIELD) {
+        redact_url_value(value);
+    } else if is_one_of(NAMESERVER_FIELDS, key) {
+        redact_urls(value);
+    } else {
+        return false;
+    }
+    true
+}
+
+/// [`redact_url`] on every string under `value`.
+fn redact_urls(value: &mut Value) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (_, value) in mapping.iter_mut() {
+                redact_urls(value);
+            }
+        }
+        Value::Sequence(sequence) => sequence.iter_mut().for_each(redact_urls),
+        value => redact_url_value(value),
+    }
+}
+
+/// A provider `url` is a subscription link more often than not, and the token
+/// rides in its userinfo or its query. Both are masked; the scheme, host and
+/// path stay, so a diff still tells two providers apart.
+pub(super) fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_owned();
+    };
+    let (rest, fragment) = match rest.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (rest, None),
+    };
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let mut redacted = format!("{scheme}://");
+    match authority.rsplit_once('@') {
+        Some((_, host)) => {
+            redacted.push_str(REDACTED);
+            redacted.push('@');
+            redacted.push_str(host);
+        }
+        None => redacted.push_str(authority),
+    }
+    redacted.push_str(path);
+    if query.is_some() {
+        redacted.push('?');
+        redacted.push_str(REDACTED);
+    }
+    if let Some(fragment) = fragment {
+        redacted.push('#');
+        redacted.push_str(fragment);
+    }
+    redacted
+}
+
+/// [`redact_url`] on a string value; anything else is left as it is.
+pub(super) fn redact_url_value(value: &mut Value) {
+    if let Value::String(url) = value {
+        *url = redact_url(url);
+    }
+}
+
 /// Local transports win over HTTP, matching the priority documented in the
 /// crate README.
 pub(super) fn inspect(document: &Mapping) -> ConfigInfo {
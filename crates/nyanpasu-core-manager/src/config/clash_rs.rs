@@ -0,0 +1,80 @@
+//! clash-rs's [`CoreConfigAdapter`]: the scalar top-level keys its
+//! `PATCH /configs` handler takes, and nothing else in place.
+//!
+//! There is no reload route. clash-rs's `PUT /configs` prefers an inline
+//! `payload` over `path` whenever the key is present, and the manager's
+//! request always carries one, so a re-read would load an empty document.
+//! Collection changes (`rules`, `proxies`, …) therefore restart.
+
+use crate::config::adapter::CoreConfigAdapter;
+
+/// Every inbound clash-rs accepts on the top level is also patchable.
+const INBOUND_PORT_FIELDS: &[&str] = &[
+    "port",
+    "socks-port",
+    "redir-port",
+    "tproxy-port",
+    "mixed-port",
+];
+
+const PATCH_FIELDS: &[&str] = &[
+    "port",
+    "socks-port",
+    "redir-port",
+    "tproxy-port",
+    "mixed-port",
+    "allow-lan",
+    "bind-address",
+    "mode",
+    "log-level",
+    "ipv6",
+];
+
+pub(crate) struct ClashRust;
+
+impl CoreConfigAdapter for ClashRust {
+    fn patch_fields(&self) -> &'static [&'static str] {
+        PATCH_FIELDS
+    }
+
+    fn inbound_port_fields(&self) -> &'static [&'static str] {
+        INBOUND_PORT_FIELDS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_yaml_ng::Mapping;
+
+    use super::*;
+    use crate::config::adapter::{ConfigChange, classify_documents};
+
+    fn mapping(yaml: &str) -> Mapping {
+        serde_yaml_ng::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn scalars_patch_and_collections_restart() {
+        assert!(matches!(
+            classify_documents(&ClashRust, &mapping("mode: rule"), &mapping("mode: global"))
+                .unwrap(),
+            ConfigChange::Patch { .. }
+        ));
+        for (current, desired) in [
+            ("rules: [MATCH,DIRECT]", "rules: [MATCH,REJECT]"),
+            ("tun: {enable: false}", "tun: {enable: true}"),
+            (
+                "mode: rule\nrules: []",
+                "mode: global\nrules: [MATCH,DIRECT]",
+            ),
+        ] {
+            assert!(
+                matches!(
+                    classify_documents(&ClashRust, &mapping(current), &mapping(desired)).unwrap(),
+                    ConfigChange::Switch
+                ),
+                "{desired}"
+            );
+        }
+    }
+}
@@ -10,9 +10,32 @@ use serde_yaml_ng::{Mapping, Value};
 #[derive(Debug)]
 pub(super) struct DiffEntry {
     pub(super) path: Vec<String>,
+    pub(super) old: Option<Value>,
     pub(super) new: Option<Value>,
 }
 
+/// Which side of a diff holds the path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigDiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One differing leaf between the running config and a candidate.
+///
+/// Values are carried as JSON so a caller needs no YAML model. Credentials and
+/// controller keys are already redacted: the path still shows that they
+/// changed, never what to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDiffEntry {
+    /// Dotted key path, e.g. `dns.enhanced-mode`.
+    pub path: String,
+    pub kind: ConfigDiffKind,
+    pub old: Option<serde_json::Value>,
+    pub new: Option<serde_json::Value>,
+}
+
 pub(super) fn diff(current: &Mapping, desired: &Mapping) -> Vec<DiffEntry> {
     let mut changes = Vec::new();
     diff_value(
@@ -63,10 +86,19 @@ fn diff_value(
     }
     changes.push(DiffEntry {
         path: path.clone(),
+        old: current.cloned(),
         new: desired.cloned(),
     });
 }
 
+/// Dotted key paths of every leaf that differs between the two documents.
+pub(crate) fn changed_paths(current: &Mapping, desired: &Mapping) -> Vec<String> {
+    diff(current, desired)
+        .into_iter()
+        .map(|entry| entry.path.join("."))
+        .collect()
+}
+
 pub(super) fn collect_leaves(
     value: &Value,
     path: &mut Vec<String>,
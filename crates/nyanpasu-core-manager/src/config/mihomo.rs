@@ -1,23 +1,17 @@
-//! Deny-by-default classification of Mihomo runtime-config changes, and the
-//! `GET /configs` projection used to verify a `PATCH`.
+//! Mihomo's [`CoreConfigAdapter`]: the widest in-place surface of any core,
+//! and the bootstrap-overlap checks only Mihomo's graceful switch needs.
 //!
 //! Every field table here mirrors Mihomo's own config schema and its Clash
-//! RESTful API surface, so nothing in this module generalizes to another core:
-//! [`classify`] degrades every non-Mihomo kind to [`ConfigChange::Switch`]
-//! (an identical config is still [`ConfigChange::Noop`]).
-//! Other cores get their own sibling module on top of [`super::diff`].
-
-use std::collections::BTreeSet;
+//! RESTful API surface. A bootstrap epoch also turns `tun` off, and
+//! `dns.listen` always restarts: Mihomo rebinds it on neither route.
 
 use serde_yaml_ng::{Mapping, Value};
 
 use crate::{
-    Error, InstanceSpec,
-    config::{
-        clash,
-        diff::{DiffEntry, collect_leaves, diff, value_at},
+    Error,
+    config::adapter::{
+        ConfigChange, CoreConfigAdapter, RuntimeProjection, classify_documents, zero_listener,
     },
-    kind::CoreKind,
 };
 
 /// Mihomo's numeric inbound listeners. Zeroing them is what lets a bootstrap
@@ -129,58 +123,49 @@ const RELOAD_FIELDS: &[&str] = &[
     "dns",
 ];
 
-/// Zeroes Mihomo's inbound surface so a bootstrap epoch can run alongside the
-/// outgoing one. Only reached through `prepare_bootstrap`, which the graceful
-/// switch path gates on [`CoreKind::Mihomo`].
-pub(super) fn zero_inbounds(document: &mut Mapping) {
-    for key in INBOUND_PORT_FIELDS {
-        zero_listener(document, key);
+pub(crate) struct Mihomo;
+
+impl CoreConfigAdapter for Mihomo {
+    fn patch_fields(&self) -> &'static [&'static str] {
+        PATCH_FIELDS
     }
-    if let Some(tun) = document
-        .get_mut(Value::String("tun".to_owned()))
-        .and_then(Value::as_mapping_mut)
-    {
-        let enable = Value::String("enable".to_owned());
-        if tun.get(&enable).and_then(Value::as_bool) == Some(true) {
-            tun.insert(enable, Value::from(false));
+
+    fn nested_patch_fields(&self, root: &str) -> Option<&'static [&'static str]> {
+        match root {
+            "tun" => Some(TUN_PATCH_FIELDS),
+            "tuic-server" => Some(TUIC_SERVER_PATCH_FIELDS),
+            _ => None,
         }
     }
-}
 
-fn zero_listener(document: &mut Mapping, key: &str) {
-    let key = Value::String(key.to_owned());
-    if document
-        .get(&key)
-        .and_then(Value::as_i64)
-        .filter(|value| *value != 0)
-        .is_some()
-    {
-        document.insert(key, Value::from(0));
+    fn reload_fields(&self) -> &'static [&'static str] {
+        RELOAD_FIELDS
     }
-}
 
-#[derive(Debug)]
-pub(crate) enum ConfigChange {
-    Noop,
-    Patch {
-        patch: Box<clash_api::ConfigPatch>,
-        projection: RuntimeProjection,
-    },
-    Reload,
-    Switch,
-}
+    fn inbound_port_fields(&self) -> &'static [&'static str] {
+        INBOUND_PORT_FIELDS
+    }
 
-#[derive(Debug)]
-pub(crate) struct RuntimeProjection {
-    expected: Vec<(Vec<String>, Value)>,
-}
+    /// `dns.listen`, or a `dns` root replaced whole and with it the listener.
+    fn restarts(&self, path: &[String]) -> bool {
+        is_dns_listen(path) || matches!(path, [root] if root == "dns")
+    }
 
-impl RuntimeProjection {
-    pub(crate) fn verify(&self, actual: &clash_api::RuntimeConfig) -> Result<bool, Error> {
-        let actual = serde_yaml_ng::to_value(actual)?;
-        Ok(self.expected.iter().all(|(path, expected)| {
-            value_at(&actual, path).is_some_and(|actual| actual == expected)
-        }))
+    /// Also turns `tun` off. Only reached through `prepare_bootstrap`, which
+    /// the graceful switch path gates on [`crate::kind::ClashCoreKind::Mihomo`].
+    fn zero_inbounds(&self, document: &mut Mapping) {
+        for key in INBOUND_PORT_FIELDS {
+            zero_listener(document, key);
+        }
+        if let Some(tun) = document
+            .get_mut(Value::String("tun".to_owned()))
+            .and_then(Value::as_mapping_mut)
+        {
+            let enable = Value::String("enable".to_owned());
+            if tun.get(&enable).and_then(Value::as_bool) == Some(true) {
+                tun.insert(enable, Value::from(false));
+            }
+        }
     }
 }
 
@@ -188,7 +173,7 @@ pub(crate) fn restoration_patch(
     bootstrap: &Mapping,
     desired: &Mapping,
 ) -> Result<Option<(Box<clash_api::ConfigPatch>, RuntimeProjection)>, Error> {
-    match classify_documents(bootstrap, desired)? {
+    match classify_documents(&Mihomo, bootstrap, desired)? {
         ConfigChange::Noop => Ok(None),
         ConfigChange::Patch { patch, projection } => Ok(Some((patch, projection))),
         ConfigChange::Reload | ConfigChange::Switch => Err(Error::InvalidConfig(
@@ -203,172 +188,11 @@ pub(crate) enum OverlapBlock {
     InboundSurface,
 }
 
-pub(crate) fn classify(
-    current_source: &Mapping,
-    current_effective: &Mapping,
-    current_spec: &InstanceSpec,
-    desired_source: &Mapping,
-    desired_effective: &Mapping,
-    desired_spec: &InstanceSpec,
-) -> Result<ConfigChange, Error> {
-    if process_spec_changed(current_spec, desired_spec) {
-        return Ok(ConfigChange::Switch);
-    }
-
-    let source_diff = diff(current_source, desired_source);
-    if desired_spec.core.kind != CoreKind::Mihomo {
-        // Non-mihomo kinds degrade to Switch. An identical config is still a
-        // Noop — but only when the *effective* documents also match: a
-        // capability change (e.g. the binary was replaced in place and now
-        // resolves a different version) can alter the derived config and the
-        // resolved controller without any source edit, and that must restart.
-        let unchanged =
-            source_diff.is_empty() && diff(current_effective, desired_effective).is_empty();
-        return Ok(if unchanged {
-            ConfigChange::Noop
-        } else {
-            ConfigChange::Switch
-        });
-    }
-
-    if source_diff.iter().any(|entry| {
-        entry
-            .path
-            .first()
-            .is_some_and(|root| clash::CONTROLLER_FIELDS.contains(&root.as_str()))
-            || is_dns_listen(&entry.path)
-    }) {
-        return Ok(ConfigChange::Switch);
-    }
-    classify_documents(current_effective, desired_effective)
-}
-
-fn process_spec_changed(current: &InstanceSpec, desired: &InstanceSpec) -> bool {
-    current.core.kind != desired.core.kind
-        || current.core.binary_path != desired.core.binary_path
-        || current.core.version != desired.core.version
-        || current.core.features != desired.core.features
-        || current.working_dir != desired.working_dir
-        || format!("{:?}", current.options) != format!("{:?}", desired.options)
-}
-
-fn classify_documents(current: &Mapping, desired: &Mapping) -> Result<ConfigChange, Error> {
-    if dns_listen(current) != dns_listen(desired) {
-        return Ok(ConfigChange::Switch);
-    }
-    let changes = diff(current, desired);
-    if changes.is_empty() {
-        return Ok(ConfigChange::Noop);
-    }
-
-    let patchable = changes.iter().all(|entry| {
-        entry.new.is_some()
-            && entry.path.first().is_some_and(|root| {
-                PATCH_FIELDS.contains(&root.as_str()) && patch_nested_path_is_supported(&entry.path)
-            })
-    });
-    if patchable {
-        return build_patch(desired, &changes);
-    }
-
-    let reloadable = changes.iter().all(|entry| {
-        entry.path.first().is_some_and(|root| {
-            RELOAD_FIELDS.contains(&root.as_str())
-                && !is_dns_listen(&entry.path)
-                && !(root == "dns" && entry.path.len() == 1)
-        })
-    });
-    Ok(if reloadable {
-        ConfigChange::Reload
-    } else {
-        ConfigChange::Switch
-    })
-}
-
-fn patch_nested_path_is_supported(path: &[String]) -> bool {
-    match path.first().map(String::as_str) {
-        Some("tun") => path
-            .get(1)
-            .is_some_and(|field| TUN_PATCH_FIELDS.contains(&field.as_str())),
-        Some("tuic-server") => path
-            .get(1)
-            .is_some_and(|field| TUIC_SERVER_PATCH_FIELDS.contains(&field.as_str())),
-        Some(_) => path.len() == 1,
-        None => false,
-    }
-}
-
-fn build_patch(desired: &Mapping, changes: &[DiffEntry]) -> Result<ConfigChange, Error> {
-    let roots: BTreeSet<&str> = changes
-        .iter()
-        .filter_map(|entry| entry.path.first().map(String::as_str))
-        .collect();
-    let mut document = Mapping::new();
-    for root in roots {
-        let key = Value::String(root.to_owned());
-        let Some(value) = desired.get(&key) else {
-            return Ok(ConfigChange::Switch);
-        };
-        let value = match root {
-            "tun" => filter_mapping(value, TUN_PATCH_FIELDS)?,
-            "tuic-server" => filter_mapping(value, TUIC_SERVER_PATCH_FIELDS)?,
-            _ => value.clone(),
-        };
-        document.insert(key, value);
-    }
-    for required_enable in ["tun", "tuic-server"] {
-        if document.contains_key(Value::String(required_enable.to_owned()))
-            && document
-                .get(Value::String(required_enable.to_owned()))
-                .and_then(Value::as_mapping)
-                .and_then(|mapping| mapping.get(Value::String("enable".to_owned())))
-                .and_then(Value::as_bool)
-                .is_none()
-        {
-            return Ok(ConfigChange::Switch);
-        }
-    }
-    let Ok(patch) = serde_yaml_ng::from_value::<clash_api::ConfigPatch>(Value::Mapping(document))
-    else {
-        return Ok(ConfigChange::Switch);
-    };
-    let serialized = serde_yaml_ng::to_value(&patch)?;
-    let mut expected = Vec::new();
-    collect_leaves(&serialized, &mut Vec::new(), &mut expected);
-    Ok(ConfigChange::Patch {
-        patch: Box::new(patch),
-        projection: RuntimeProjection { expected },
-    })
-}
-
-fn filter_mapping(value: &Value, allowed: &[&str]) -> Result<Value, Error> {
-    let mapping = value
-        .as_mapping()
-        .ok_or_else(|| Error::InvalidConfig("patchable nested config must be a mapping".into()))?;
-    let mut filtered = Mapping::new();
-    for (key, value) in mapping {
-        let Some(key_text) = key.as_str() else {
-            return Err(Error::InvalidConfig("config keys must be strings".into()));
-        };
-        if allowed.contains(&key_text) {
-            filtered.insert(key.clone(), value.clone());
-        }
-    }
-    Ok(Value::Mapping(filtered))
-}
-
 fn is_dns_listen(path: &[String]) -> bool {
     path.first().is_some_and(|value| value == "dns")
         && path.get(1).is_some_and(|value| value == "listen")
 }
 
-fn dns_listen(document: &Mapping) -> Option<&Value> {
-    document
-        .get(Value::String("dns".into()))
-        .and_then(Value::as_mapping)
-        .and_then(|dns| dns.get(Value::String("listen".into())))
-}
-
 pub(crate) fn overlap_block(document: &Mapping) -> Option<OverlapBlock> {
     if let Some(listen) = document
         .get(Value::String("dns".into()))
@@ -456,36 +280,28 @@ fn nonempty_collection(value: &Value) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use super::*;
-    use crate::{CoreSpec, InstanceOptions};
 
     fn mapping(yaml: &str) -> Mapping {
         serde_yaml_ng::from_str(yaml).unwrap()
     }
 
-    fn spec(kind: CoreKind, binary: &str) -> InstanceSpec {
-        InstanceSpec {
-            core: CoreSpec {
-                kind,
-                binary_path: binary.into(),
-                version: None,
-                features: Vec::new(),
-            },
-            config_path: "source.yaml".into(),
-            working_dir: ".".into(),
-            pid_file: None,
-            options: InstanceOptions::default(),
-        }
-    }
-
     #[test]
     fn patch_reload_and_switch_are_deny_by_default() {
         assert!(matches!(
-            classify_documents(&mapping("allow-lan: false"), &mapping("allow-lan: true")).unwrap(),
+            classify_documents(
+                &Mihomo,
+                &mapping("allow-lan: false"),
+                &mapping("allow-lan: true")
+            )
+            .unwrap(),
             ConfigChange::Patch { .. }
         ));
         assert!(matches!(
             classify_documents(
+                &Mihomo,
                 &mapping("rules: [MATCH,DIRECT]"),
                 &mapping("rules: [MATCH,REJECT]")
             )
@@ -499,7 +315,7 @@ mod tests {
             "unknown-field: true",
         ] {
             assert!(matches!(
-                classify_documents(&Mapping::new(), &mapping(desired)).unwrap(),
+                classify_documents(&Mihomo, &Mapping::new(), &mapping(desired)).unwrap(),
                 ConfigChange::Switch
             ));
         }
@@ -508,7 +324,7 @@ mod tests {
     #[test]
     fn deletion_is_never_patch() {
         assert!(matches!(
-            classify_documents(&mapping("allow-lan: true"), &Mapping::new()).unwrap(),
+            classify_documents(&Mihomo, &mapping("allow-lan: true"), &Mapping::new()).unwrap(),
             ConfigChange::Switch
         ));
     }
@@ -519,100 +335,17 @@ mod tests {
         for desired in ["dns: null", "dns: disabled", "dns: [invalid]"] {
             assert!(
                 matches!(
-                    classify_documents(&current, &mapping(desired)).unwrap(),
+                    classify_documents(&Mihomo, &current, &mapping(desired)).unwrap(),
                     ConfigChange::Switch
                 ),
                 "{desired} bypassed dns.listen protection"
             );
         }
         assert!(matches!(
-            classify_documents(&mapping("dns: {ipv6: false}"), &mapping("dns: null")).unwrap(),
-            ConfigChange::Switch
-        ));
-    }
-
-    #[test]
-    fn identical_config_is_noop_for_every_kind() {
-        for kind in [
-            CoreKind::Mihomo,
-            CoreKind::ClashRust,
-            CoreKind::ClashPremium,
-        ] {
-            let spec = spec(kind, "core");
-            assert!(
-                matches!(
-                    classify(
-                        &mapping("mixed-port: 7890"),
-                        &Mapping::new(),
-                        &spec,
-                        &mapping("mixed-port: 7890"),
-                        &Mapping::new(),
-                        &spec,
-                    )
-                    .unwrap(),
-                    ConfigChange::Noop
-                ),
-                "{kind:?} should noop on an identical config"
-            );
-        }
-    }
-
-    #[test]
-    fn non_mihomo_noop_requires_unchanged_effective_config() {
-        // Same source, but capability resolution rewrote the controller:
-        // the derived config changed, so this must restart, not noop.
-        let spec = spec(CoreKind::ClashRust, "clash-rs");
-        assert!(matches!(
-            classify(
-                &mapping("mixed-port: 7890"),
-                &mapping("external-controller: 127.0.0.1:9090"),
-                &spec,
-                &mapping("mixed-port: 7890"),
-                &mapping("external-controller-pipe: /tmp/core-1.sock"),
-                &spec,
-            )
-            .unwrap(),
-            ConfigChange::Switch
-        ));
-    }
-
-    #[test]
-    fn controller_process_and_unsupported_core_changes_switch() {
-        let current = spec(CoreKind::Mihomo, "mihomo");
-        let mut changed_binary = current.clone();
-        changed_binary.core.binary_path = "other-mihomo".into();
-        assert!(matches!(
-            classify(
-                &mapping("external-controller: 127.0.0.1:9090"),
-                &Mapping::new(),
-                &current,
-                &mapping("external-controller: 127.0.0.1:9091"),
-                &Mapping::new(),
-                &current,
-            )
-            .unwrap(),
-            ConfigChange::Switch
-        ));
-        assert!(matches!(
-            classify(
-                &Mapping::new(),
-                &Mapping::new(),
-                &current,
-                &Mapping::new(),
-                &Mapping::new(),
-                &changed_binary,
-            )
-            .unwrap(),
-            ConfigChange::Switch
-        ));
-        assert!(matches!(
-            classify(
-                &Mapping::new(),
-                &Mapping::new(),
-                &current,
-                &Mapping::new(),
-                &Mapping::new(),
-                &spec(CoreKind::ClashRust, "clash-rs"),
+            classify_documents(
+                &Mihomo,
+                &mapping("dns: {ipv6: false}"),
+                &mapping("dns: null")
             )
             .unwrap(),
             ConfigChange::Switch
@@ -721,7 +454,7 @@ interface-name: Ethernet
 "#,
         );
         let ConfigChange::Patch { patch, projection } =
-            classify_documents(&Mapping::new(), &desired).unwrap()
+            classify_documents(&Mihomo, &Mapping::new(), &desired).unwrap()
         else {
             panic!("complete expressible document must patch")
         };
@@ -777,7 +510,11 @@ disable-keep-alive: false
         merge(&mut runtime, &patch_document);
         let runtime: clash_api::RuntimeConfig =
             serde_yaml_ng::from_value(Value::Mapping(runtime)).unwrap();
-        assert!(projection.verify(&runtime).unwrap());
+        assert!(
+            projection
+                .verify(&serde_json::to_value(runtime).unwrap())
+                .unwrap()
+        );
         assert_eq!(
             projection.expected.len(),
             leaf_count(&Value::Mapping(patch_document))
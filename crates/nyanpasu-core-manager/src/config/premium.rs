@@ -0,0 +1,93 @@
+//! Clash Premium's [`CoreConfigAdapter`]: the scalar top-level keys its
+//! `PATCH /configs` takes, and the collections a `PUT /configs` re-read
+//! replaces.
+//!
+//! Premium's DNS server is rebuilt on reload, listener included, so unlike
+//! Mihomo a `dns` change needs no restart.
+
+use crate::config::adapter::CoreConfigAdapter;
+
+const INBOUND_PORT_FIELDS: &[&str] = &[
+    "port",
+    "socks-port",
+    "redir-port",
+    "tproxy-port",
+    "mixed-port",
+];
+
+const PATCH_FIELDS: &[&str] = &[
+    "port",
+    "socks-port",
+    "redir-port",
+    "tproxy-port",
+    "mixed-port",
+    "allow-lan",
+    "bind-address",
+    "mode",
+    "log-level",
+    "ipv6",
+];
+
+const RELOAD_FIELDS: &[&str] = &[
+    "proxies",
+    "proxy-groups",
+    "proxy-providers",
+    "rule-providers",
+    "rules",
+    "hosts",
+    "dns",
+];
+
+pub(crate) struct ClashPremium;
+
+impl CoreConfigAdapter for ClashPremium {
+    fn patch_fields(&self) -> &'static [&'static str] {
+        PATCH_FIELDS
+    }
+
+    fn reload_fields(&self) -> &'static [&'static str] {
+        RELOAD_FIELDS
+    }
+
+    fn inbound_port_fields(&self) -> &'static [&'static str] {
+        INBOUND_PORT_FIELDS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_yaml_ng::Mapping;
+
+    use super::*;
+    use crate::config::adapter::{ConfigChange, classify_documents};
+
+    fn mapping(yaml: &str) -> Mapping {
+        serde_yaml_ng::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn collections_reload_and_unlisted_keys_restart() {
+        for (current, desired) in [
+            ("rules: [MATCH,DIRECT]", "rules: [MATCH,REJECT]"),
+            ("dns: {listen: ':53'}", "dns: {listen: ':1053'}"),
+        ] {
+            assert!(
+                matches!(
+                    classify_documents(&ClashPremium, &mapping(current), &mapping(desired))
+                        .unwrap(),
+                    ConfigChange::Reload
+                ),
+                "{desired}"
+            );
+        }
+        assert!(matches!(
+            classify_documents(
+                &ClashPremium,
+                &mapping("tun: {enable: false}"),
+                &mapping("tun: {enable: true}")
+            )
+            .unwrap(),
+            ConfigChange::Switch
+        ));
+    }
+}
@@ -136,6 +136,14 @@ impl RuntimeConfigStore {
         self.dir.join(format!("core-{epoch}.sock"))
     }
 
+    pub fn history_path(&self, epoch: u64, generation: u64) -> Utf8PathBuf {
+        self.dir.join(format!("history-{epoch}-{generation}.yaml"))
+    }
+
+    pub fn inline_path(&self, serial: u64) -> Utf8PathBuf {
+        self.dir.join(format!("inline-{serial}.yaml"))
+    }
+
     #[cfg(feature = "test-hooks")]
     pub(crate) fn inject_replace_parent_sync_failure_once(&self) {
         self.replace_parent_sync_failures
@@ -276,6 +284,77 @@ impl RuntimeConfigStore {
             .map_err(Error::from)
     }
 
+    /// Keeps a revision's canonical source bytes past its epoch. History is
+    /// named apart from the epoch artifacts, so neither [`Self::cleanup_epoch`]
+    /// nor the orphan sweep reaches it.
+    pub(crate) async fn record_history(
+        &self,
+        epoch: u64,
+        generation: u64,
+        contents: &[u8],
+    ) -> Result<Utf8PathBuf, Error> {
+        let mut staged = self.stage(epoch, contents).await?;
+        let target = self.history_path(epoch, generation);
+        atomic_fs::validate_absent_regular_target(&target).await?;
+        atomic_fs::atomic_move_new(&staged.path, &target).await?;
+        staged.consumed = true;
+        atomic_fs::sync_dir(&self.dir).await?;
+        Ok(target)
+    }
+
+    pub(crate) async fn remove_history(&self, path: &Utf8Path) -> Result<(), Error> {
+        atomic_fs::remove_regular_file(path)
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Keeps a source that arrived inline, so the spec that ran it has a file
+    /// to re-read on restart. Named apart from the epoch artifacts like
+    /// history, and by a process-wide serial rather than the source hash: the
+    /// hash is a change identity and a collision must not alias two payloads.
+    pub(crate) async fn keep_inline(&self, contents: &[u8]) -> Result<Utf8PathBuf, Error> {
+        let mut staged = self.stage(0, contents).await?;
+        let target = self.inline_path(TEMP_COUNTER.fetch_add(1, Ordering::Relaxed));
+        atomic_fs::validate_absent_regular_target(&target).await?;
+        atomic_fs::atomic_move_new(&staged.path, &target).await?;
+        staged.consumed = true;
+        atomic_fs::sync_dir(&self.dir).await?;
+        Ok(target)
+    }
+
+    /// Removes every inline source but those in `keep`.
+    pub(crate) async fn prune_inline(&self, keep: &[&Utf8Path]) -> Result<(), Error> {
+        self.remove_kept(|name, path| name.starts_with("inline-") && !keep.contains(&path))
+            .await
+    }
+
+    /// Drops every history entry and inline source a previous manager left
+    /// behind.
+    pub(crate) async fn clear_kept_sources(&self) -> Result<(), Error> {
+        self.remove_kept(|name, _| name.starts_with("history-") || name.starts_with("inline-"))
+            .await
+    }
+
+    async fn remove_kept(&self, remove: impl Fn(&str, &Utf8Path) -> bool) -> Result<(), Error> {
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            if !name.ends_with(".yaml") {
+                continue;
+            }
+            let path = Utf8PathBuf::from_path_buf(entry.path())
+                .map_err(|_| Error::UnsafeRuntimeArtifact(self.dir.clone()))?;
+            if remove(name, &path) {
+                atomic_fs::remove_regular_file(&path).await?;
+            }
+        }
+        atomic_fs::sync_dir(&self.dir).await?;
+        Ok(())
+    }
+
     pub async fn cleanup_epoch(&self, epoch: u64) -> Result<(), Error> {
         for path in [self.runtime_path(epoch), self.pid_path(epoch)] {
             atomic_fs::remove_regular_file(&path).await?;
@@ -0,0 +1,114 @@
+//! sing-box's config vocabulary: where it exposes the Clash RESTful API, and
+//! which of its keys hold credentials.
+//!
+//! sing-box is not a Clash core, but with `experimental.clash_api` set it
+//! serves the same control plane over TCP, which is everything the manager
+//! drives. Its config is JSON, so the runtime copy is written as JSON too; the
+//! source may still be YAML, since every JSON document already is one. sing-box
+//! reads `-c` as JSON whatever the file is named.
+//!
+//! There is no managed-mode rewrite: the Clash API has no local transport in
+//! sing-box, so [`crate::capability`] never resolves one and the source
+//! controller stays authoritative.
+
+use serde_yaml_ng::{Mapping, Value};
+
+use crate::error::Error;
+
+use super::{ConfigInfo, RawController, clash, str_value};
+
+const EXPERIMENTAL: &str = "experimental";
+const CLASH_API: &str = "clash_api";
+const EXTERNAL_CONTROLLER: &str = "external_controller";
+const SECRET: &str = "secret";
+
+/// Keys whose values are credentials wherever they appear: outbound and
+/// inbound passwords, WireGuard and SSH keys, and inbound user lists.
+const CREDENTIAL_FIELDS: &[&str] = &[
+    "auth",
+    "auth_str",
+    "password",
+    "pre_shared_key",
+    "private_key",
+    "private_key_passphrase",
+    "token",
+    "users",
+    "uuid",
+];
+
+const REDACTED: &str = "<redacted>";
+
+fn clash_api(document: &Mapping) -> Option<&Mapping> {
+    document
+        .get(Value::String(EXPERIMENTAL.to_owned()))
+        .and_then(Value::as_mapping)?
+        .get(Value::String(CLASH_API.to_owned()))
+        .and_then(Value::as_mapping)
+}
+
+pub(super) fn inspect(document: &Mapping) -> ConfigInfo {
+    let api = clash_api(document);
+    ConfigInfo {
+        controller: api
+            .and_then(|api| str_value(api, EXTERNAL_CONTROLLER))
+            .map(RawController::Http),
+        secret: api.and_then(|api| str_value(api, SECRET)),
+    }
+}
+
+/// `experimental.clash_api.external_controller` and `.secret`, or a block
+/// holding them. A diff only stops above a leaf when a mapping replaced a
+/// scalar, so masking such a block whole loses next to nothing.
+fn is_controller_path(path: &[String]) -> bool {
+    match path {
+        [experimental] => experimental == EXPERIMENTAL,
+        [experimental, api] => experimental == EXPERIMENTAL && api == CLASH_API,
+        [experimental, api, key] => {
+            experimental == EXPERIMENTAL
+                && api == CLASH_API
+                && (key == EXTERNAL_CONTROLLER || key == SECRET)
+        }
+        _ => false,
+    }
+}
+
+/// Masks whatever under `value` must not leave the service, the way
+/// [`super::clash::redact`] does for the Clash vocabulary.
+pub(super) fn redact(path: &[String], value: &mut Value) {
+    let credential = path
+        .last()
+        .is_some_and(|key| CREDENTIAL_FIELDS.contains(&key.as_str()));
+    if credential || is_controller_path(path) {
+        *value = Value::String(REDACTED.to_owned());
+        return;
+    }
+    // Remote rule sets are fetched from a `url` as well.
+    if path.last().is_some_and(|key| key == clash::URL_FIELD) {
+        clash::redact_url_value(value);
+        return;
+    }
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping.iter_mut() {
+                match key.as_str() {
+                    Some(key) if CREDENTIAL_FIELDS.contains(&key) => {
+                        *value = Value::String(REDACTED.to_owned());
+                    }
+                    Some(clash::URL_FIELD) => clash::redact_url_value(value),
+                    _ => redact(&[], value),
+                }
+            }
+        }
+        Value::Sequence(sequence) => {
+            for value in sequence {
+                redact(&[], value);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The runtime copy sing-box is launched with.
+pub(super) fn serialize(document: &Mapping) -> Result<Vec<u8>, Error> {
+    serde_json::to_vec_pretty(document).map_err(|error| Error::InvalidConfig(error.to_string()))
+}
@@ -32,6 +32,8 @@ pub enum Error {
     InvalidManagerOptions(String),
     #[error("invalid health policy: {0}")]
     InvalidHealthPolicy(String),
+    #[error("invalid connection filter: {0}")]
+    InvalidFilter(String),
     #[error("unsafe runtime artifact: {0}")]
     UnsafeRuntimeArtifact(Utf8PathBuf),
     #[error("runtime directory is already owned by another manager: {0}")]
@@ -45,6 +47,8 @@ pub enum Error {
         expected: RevisionId,
         actual: Option<RevisionId>,
     },
+    #[error("config revision {0} is not in the manager's history")]
+    RevisionNotFound(RevisionId),
     #[error("config apply failed: {0}")]
     ApplyFailed(String),
     #[error("config apply failed ({apply}); rollback also failed ({rollback})")]
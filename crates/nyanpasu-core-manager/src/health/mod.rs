@@ -184,6 +184,21 @@ pub(crate) fn build_control_client(
     Ok(builder.build()?)
 }
 
+/// [`build_control_client`] for a response that never ends by design: the
+/// deadline bounds each read instead of the whole body, so a stream lives as
+/// long as the core keeps writing and a silent one is cut after `idle`.
+pub(crate) fn build_stream_client(
+    controller: &ResolvedController,
+    idle: Duration,
+) -> Result<clash_api::Client, Error> {
+    let mut builder = clash_api::Client::builder(controller.host.clone())
+        .configure_reqwest(|builder| builder.read_timeout(idle));
+    if let Some(secret) = &controller.secret {
+        builder = builder.secret(secret.as_str());
+    }
+    Ok(builder.build()?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
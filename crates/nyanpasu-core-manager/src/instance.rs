@@ -950,7 +950,10 @@ mod tests {
             state_tx,
             user_stop: AtomicBool::new(true),
             probe_timeout: AtomicBool::new(false),
-            parser: parking_lot::Mutex::new(LogParser::new(kind::CoreKind::Mihomo, 1)),
+            parser: parking_lot::Mutex::new(LogParser::new(
+                kind::CoreKind::Clash(kind::ClashCoreKind::Mihomo),
+                1,
+            )),
             log_tail: parking_lot::Mutex::new(VecDeque::new()),
             log_tx,
             cancel: CancellationToken::new(),
@@ -1036,7 +1039,10 @@ mod tests {
             state_tx,
             user_stop: AtomicBool::new(false),
             probe_timeout: AtomicBool::new(false),
-            parser: parking_lot::Mutex::new(LogParser::new(kind::CoreKind::Mihomo, 1)),
+            parser: parking_lot::Mutex::new(LogParser::new(
+                kind::CoreKind::Clash(kind::ClashCoreKind::Mihomo),
+                1,
+            )),
             log_tail: parking_lot::Mutex::new(VecDeque::new()),
             log_tx: broadcast::channel(LOG_CHANNEL_CAPACITY).0,
             cancel: CancellationToken::new(),
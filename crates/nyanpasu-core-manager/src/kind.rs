@@ -7,7 +7,7 @@ use nyanpasu_utils::process::ProcessError;
 
 use crate::{error::Error, log::summarize_output};
 
-pub use nyanpasu_core_metadata::ClashCoreKind as CoreKind;
+pub use nyanpasu_core_metadata::{ClashCoreKind, CoreKind};
 
 /// The environment variable Mihomo consults for permitted file-system roots.
 pub const MIHOMO_SAFE_PATHS_ENV_NAME: &str = "SAFE_PATHS";
@@ -32,11 +32,22 @@ pub(crate) fn run_args(
     let cfg = OsString::from(config_path.as_str());
     Ok(match kind {
         // Meow accepts the mihomo CLI for compatibility.
-        CoreKind::Mihomo | CoreKind::Meow => {
+        CoreKind::Clash(ClashCoreKind::Mihomo) | CoreKind::Clash(ClashCoreKind::Meow) => {
             vec!["-m".into(), "-d".into(), dir, "-f".into(), cfg]
         }
-        CoreKind::ClashRust => vec!["-d".into(), dir, "-c".into(), cfg],
-        CoreKind::ClashPremium => vec!["-d".into(), dir, "-f".into(), cfg],
+        CoreKind::Clash(ClashCoreKind::ClashRust) => vec!["-d".into(), dir, "-c".into(), cfg],
+        CoreKind::Clash(ClashCoreKind::ClashPremium) => vec!["-d".into(), dir, "-f".into(), cfg],
+        // sing-box takes subcommands; `--disable-color` keeps its console
+        // layout plain for the log parser, as `CLICOLOR_FORCE=0` does for
+        // Mihomo.
+        CoreKind::SingBox => vec![
+            "run".into(),
+            "--disable-color".into(),
+            "-D".into(),
+            dir,
+            "-c".into(),
+            cfg,
+        ],
     })
 }
 
@@ -47,7 +58,7 @@ pub(crate) fn run_args(
 /// with its CLI flag value (clash-bin/src/main.rs), so for clash-rs a system
 /// IPC endpoint only takes effect when passed as `--controller-ipc`.
 pub(crate) fn controller_args(kind: CoreKind, host: &clash_api::Host) -> Vec<OsString> {
-    if !matches!(kind, CoreKind::ClashRust) {
+    if !matches!(kind, CoreKind::Clash(ClashCoreKind::ClashRust)) {
         return Vec::new();
     }
     match host {
@@ -58,16 +69,38 @@ pub(crate) fn controller_args(kind: CoreKind, host: &clash_api::Host) -> Vec<OsS
     }
 }
 
-/// Arguments for a one-shot `-t` config validation run (same for all kinds,
-/// matching the legacy `check_config_`).
-pub(crate) fn check_args(working_dir: &Utf8Path, config_path: &Utf8Path) -> Vec<OsString> {
-    vec![
-        "-t".into(),
-        "-d".into(),
-        working_dir.as_str().into(),
-        "-f".into(),
-        config_path.as_str().into(),
-    ]
+/// Arguments that make this kind print its version and exit. Every Clash core
+/// takes `-v`; sing-box has a `version` subcommand and reads `-v` as nothing.
+pub(crate) fn version_args(kind: CoreKind) -> &'static [&'static str] {
+    match kind {
+        CoreKind::SingBox => &["version"],
+        CoreKind::Clash(_) => &["-v"],
+    }
+}
+
+/// Arguments for a one-shot config validation run. Every Clash core takes
+/// `-t`, matching the legacy `check_config_`; sing-box has a `check`
+/// subcommand instead.
+pub(crate) fn check_args(
+    kind: CoreKind,
+    working_dir: &Utf8Path,
+    config_path: &Utf8Path,
+) -> Vec<OsString> {
+    let dir = OsString::from(working_dir.as_str());
+    let cfg = OsString::from(config_path.as_str());
+    match kind {
+        CoreKind::SingBox => vec![
+            "check".into(),
+            "--disable-color".into(),
+            "-D".into(),
+            dir,
+            "-c".into(),
+            cfg,
+        ],
+        CoreKind::Clash(_) => {
+            vec!["-t".into(), "-d".into(), dir, "-f".into(), cfg]
+        }
+    }
 }
 
 /// Joins the directories Mihomo may touch into its `SAFE_PATHS` format.
@@ -111,7 +144,11 @@ async fn run_check(spec: &crate::spec::InstanceSpec, timeout: Duration) -> Resul
         .parent()
         .ok_or_else(|| Error::ConfigNotFound(spec.config_path.clone()))?;
     let output = nyanpasu_utils::process::Command::new(spec.core.binary_path.as_str())
-        .args(check_args(&spec.working_dir, &spec.config_path))
+        .args(check_args(
+            spec.core.kind,
+            &spec.working_dir,
+            &spec.config_path,
+        ))
         .env(
             MIHOMO_SAFE_PATHS_ENV_NAME,
             mihomo_safe_paths(&spec.working_dir, config_dir),
@@ -150,17 +187,17 @@ mod tests {
     fn run_args_match_legacy_profiles() {
         let dir = Utf8PathBuf::from("C:/data");
         let cfg = Utf8PathBuf::from("C:/data/config.yaml");
-        let args = run_args(CoreKind::Mihomo, &dir, &cfg).unwrap();
+        let args = run_args(CoreKind::Clash(ClashCoreKind::Mihomo), &dir, &cfg).unwrap();
         assert_eq!(
             args,
             ["-m", "-d", "C:/data", "-f", "C:/data/config.yaml"].map(OsString::from)
         );
-        let args = run_args(CoreKind::ClashRust, &dir, &cfg).unwrap();
+        let args = run_args(CoreKind::Clash(ClashCoreKind::ClashRust), &dir, &cfg).unwrap();
         assert_eq!(
             args,
             ["-d", "C:/data", "-c", "C:/data/config.yaml"].map(OsString::from)
         );
-        let args = run_args(CoreKind::ClashPremium, &dir, &cfg).unwrap();
+        let args = run_args(CoreKind::Clash(ClashCoreKind::ClashPremium), &dir, &cfg).unwrap();
         assert_eq!(
             args,
             ["-d", "C:/data", "-f", "C:/data/config.yaml"].map(OsString::from)
@@ -172,8 +209,34 @@ mod tests {
         let dir = Utf8PathBuf::from("/d");
         let cfg = Utf8PathBuf::from("/d/config.yaml");
         assert_eq!(
-            run_args(CoreKind::Meow, &dir, &cfg).unwrap(),
-            run_args(CoreKind::Mihomo, &dir, &cfg).unwrap()
+            run_args(CoreKind::Clash(ClashCoreKind::Meow), &dir, &cfg).unwrap(),
+            run_args(CoreKind::Clash(ClashCoreKind::Mihomo), &dir, &cfg).unwrap()
+        );
+    }
+
+    #[test]
+    fn sing_box_runs_and_checks_through_subcommands() {
+        let dir = Utf8PathBuf::from("/d");
+        let cfg = Utf8PathBuf::from("/d/config.json");
+        assert_eq!(
+            run_args(CoreKind::SingBox, &dir, &cfg).unwrap(),
+            ["run", "--disable-color", "-D", "/d", "-c", "/d/config.json"].map(OsString::from)
+        );
+        assert_eq!(
+            check_args(CoreKind::SingBox, &dir, &cfg),
+            [
+                "check",
+                "--disable-color",
+                "-D",
+                "/d",
+                "-c",
+                "/d/config.json"
+            ]
+            .map(OsString::from)
+        );
+        assert_eq!(
+            check_args(CoreKind::Clash(ClashCoreKind::ClashRust), &dir, &cfg),
+            ["-t", "-d", "/d", "-f", "/d/config.json"].map(OsString::from)
         );
     }
 
@@ -191,7 +254,7 @@ mod tests {
         let log = "time=\"2026-07-18T10:00:00Z\" level=info msg=\"start\"\n\
                    time=\"2026-07-18T10:00:01Z\" level=error msg=\"configuration file /x.yaml test failed\"";
         assert_eq!(
-            summarize_output(CoreKind::Mihomo, log, ""),
+            summarize_output(CoreKind::Clash(ClashCoreKind::Mihomo), log, ""),
             "configuration file /x.yaml test failed"
         );
     }
@@ -199,7 +262,7 @@ mod tests {
     #[test]
     fn check_output_keeps_unrecognized_text() {
         assert_eq!(
-            summarize_output(CoreKind::Mihomo, "plain failure", ""),
+            summarize_output(CoreKind::Clash(ClashCoreKind::Mihomo), "plain failure", ""),
             "plain failure"
         );
     }
@@ -207,7 +270,11 @@ mod tests {
     #[test]
     fn check_output_no_longer_special_cases_clash_rs() {
         assert_eq!(
-            summarize_output(CoreKind::ClashRust, "", "Error: invalid config"),
+            summarize_output(
+                CoreKind::Clash(ClashCoreKind::ClashRust),
+                "",
+                "Error: invalid config"
+            ),
             "Error: invalid config"
         );
     }
@@ -10,20 +10,28 @@ mod health;
 pub mod instance;
 pub mod kind;
 mod log;
+mod log_archive;
 mod log_sink;
 pub mod manager;
 pub mod spec;
 pub mod state;
 
 pub use capability::{Feature, RuntimeFeature};
-pub use clash_api::Host;
-pub use config::runtime_store;
+pub use clash_api::{
+    Connection, ConnectionNetwork, ConnectionsSnapshot, Host, HttpStream, Memory, Proxy, ProxyName,
+    Traffic, Uuid,
+};
+pub use config::{ConfigDiffEntry, ConfigDiffKind, runtime_store};
 pub use error::Error;
 pub use health::{HealthPolicy, probe};
 pub use instance::{Instance, InstanceBuilder};
-pub use kind::CoreKind;
+pub use kind::{ClashCoreKind, CoreKind};
 pub use log::{LogField, LogFrame, LogLevel, LogStream, LogTimestamp};
-pub use manager::{ApplyOutcome, CoreManager, CoreManagerBuilder, DegradeReason, SwitchOutcome};
+pub use log_archive::{LogCursor, LogDirection, LogPage, LogQuery};
+pub use manager::{
+    ApplyOutcome, ApplyPlan, ConnectionFilter, CoreManager, CoreManagerBuilder, DegradeReason,
+    PlannedRoute, ProxySelection, RestoredSelections, RevisionRecord, SwitchOutcome,
+};
 pub use probe::{
     ControllerVersionProbe, HealthProbe, ProbeContext, ProbeFuture, ProbeHandle, ProbePhase,
     ProbeResult,
@@ -33,7 +41,8 @@ pub use runtime_store::{
     StagedRuntimeConfig,
 };
 pub use spec::{
-    CoreSpec, InstanceOptions, InstanceSpec, LocalIpcPolicy, ManagerOptions, ResolvedController,
+    Backoff, CoreDistribution, CoreSpec, InstanceOptions, InstanceSpec, LocalIpcPolicy,
+    ManagerOptions, ResolvedController, RestartPolicy, VariantTag,
 };
 pub use state::{
     ConfigRevision, CoreState, CoreStatus, HealthState, HealthStatus, InstanceState,
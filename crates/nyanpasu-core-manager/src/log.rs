@@ -1,9 +1,10 @@
 //! Normalization of core console output into a single [`LogFrame`] shape.
 //!
 //! Each kind prints its own layout (mihomo logfmt, clash premium's `PrettyPrint`,
-//! and two different tracing formats), all of them on stdout, and only meow keeps
-//! ANSI when writing to a pipe. Parsing is header-only and per kind: a line whose
-//! header does not match degrades to an unformatted frame instead of being lost.
+//! two different tracing formats, and sing-box's own formatter). All but sing-box
+//! write to stdout, and only meow keeps ANSI when writing to a pipe. Parsing is
+//! header-only and per kind: a line whose header does not match degrades to an
+//! unformatted frame instead of being lost.
 
 use std::borrow::Borrow;
 
@@ -11,7 +12,7 @@ use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone};
 
 pub use nyanpasu_core_metadata::{LogField, LogFrame, LogLevel, LogStream, LogTimestamp};
 
-use crate::kind::CoreKind;
+use crate::kind::{ClashCoreKind, CoreKind};
 
 pub(crate) const LOG_CHANNEL_CAPACITY: usize = 256;
 const MAX_CONTINUATION_LINES: usize = 16;
@@ -236,10 +237,13 @@ impl LogParser {
         observed_at: DateTime<FixedOffset>,
     ) -> Option<ParsedLine> {
         match self.kind {
-            CoreKind::Mihomo => parse_mihomo(line),
-            CoreKind::Meow => parse_meow(line),
-            CoreKind::ClashRust => parse_clash_rs(line, observed_at),
-            CoreKind::ClashPremium => parse_premium(line, observed_at, &mut self.premium_clock),
+            CoreKind::Clash(ClashCoreKind::Mihomo) => parse_mihomo(line),
+            CoreKind::Clash(ClashCoreKind::Meow) => parse_meow(line),
+            CoreKind::Clash(ClashCoreKind::ClashRust) => parse_clash_rs(line, observed_at),
+            CoreKind::Clash(ClashCoreKind::ClashPremium) => {
+                parse_premium(line, observed_at, &mut self.premium_clock)
+            }
+            CoreKind::SingBox => parse_sing_box(line),
         }
     }
 
@@ -360,7 +364,7 @@ fn parse_level(level: &str) -> Option<LogLevel> {
         "info" | "INF" | "INFO" => Some(LogLevel::Info),
         "warn" | "warning" | "WRN" | "WARN" => Some(LogLevel::Warning),
         "error" | "ERR" | "ERROR" => Some(LogLevel::Error),
-        "fatal" | "panic" | "FTL" | "PNC" => Some(LogLevel::Fatal),
+        "fatal" | "panic" | "FTL" | "PNC" | "FATAL" | "PANIC" => Some(LogLevel::Fatal),
         // phuslu/log's placeholder for a level it does not know.
         "???" => Some(LogLevel::Info),
         _ => None,
@@ -649,6 +653,81 @@ fn premium_unix_ms(
     local_unix_ms(date, time, observed_at.offset())
 }
 
+/// sing-box's console formatter: `-0700 2006-01-02 15:04:05 LEVEL message`
+/// when `log.timestamp` is set, otherwise `LEVEL[SSSS] message` with the
+/// seconds since start. A message may open with `[id duration] ` naming the
+/// connection it belongs to, then with the `tag: ` of the component that
+/// logged it.
+fn parse_sing_box(line: &str) -> Option<ParsedLine> {
+    let (timestamp, level, rest) = match sing_box_uptime_header(line) {
+        Some((level, rest)) => (None, level, rest),
+        None => {
+            let raw_time = line.get(..25)?;
+            let stamped = DateTime::parse_from_str(raw_time, "%z %Y-%m-%d %H:%M:%S").ok()?;
+            if line.as_bytes().get(25) != Some(&b' ') {
+                return None;
+            }
+            let (level, rest) = take_token(&line[25..])?;
+            let timestamp = LogTimestamp {
+                unix_ms: Some(stamped.timestamp_millis()),
+                raw: raw_time.to_owned(),
+                inferred: false,
+            };
+            (Some(timestamp), level, rest)
+        }
+    };
+    let level = parse_level(level)?;
+
+    let mut fields = Vec::new();
+    let rest = match sing_box_connection(rest) {
+        Some((id, duration, rest)) => {
+            fields.push(LogField {
+                key: "id".into(),
+                value: id.to_owned(),
+            });
+            fields.push(LogField {
+                key: "duration".into(),
+                value: duration.to_owned(),
+            });
+            rest
+        }
+        None => rest,
+    };
+    // A tag never holds a space, which is what tells it apart from a message
+    // that merely contains `: `.
+    let (target, message) = match rest.split_once(": ") {
+        Some((tag, message)) if !tag.is_empty() && !tag.contains(char::is_whitespace) => {
+            (Some(tag.to_owned()), message.to_owned())
+        }
+        _ => (None, rest.to_owned()),
+    };
+    Some(ParsedLine {
+        timestamp,
+        level,
+        target,
+        message,
+        fields,
+    })
+}
+
+/// `LEVEL[SSSS] `. The uptime is not a point in time, so it is not kept.
+fn sing_box_uptime_header(line: &str) -> Option<(&str, &str)> {
+    let (level, rest) = line.split_once('[')?;
+    let (uptime, message) = rest.split_once("] ")?;
+    let level_only = !level.is_empty() && level.bytes().all(|byte| byte.is_ascii_uppercase());
+    (level_only && !uptime.is_empty() && uptime.bytes().all(|byte| byte.is_ascii_digit()))
+        .then_some((level, message))
+}
+
+/// `[3520290387 15ms] `, the connection id and its age when the line was
+/// written.
+fn sing_box_connection(message: &str) -> Option<(&str, &str, &str)> {
+    let (context, rest) = message.strip_prefix('[')?.split_once("] ")?;
+    let (id, duration) = context.split_once(' ')?;
+    (!id.is_empty() && id.bytes().all(|byte| byte.is_ascii_digit()) && !duration.is_empty())
+        .then_some((id, duration, rest))
+}
+
 fn local_unix_ms(date: NaiveDate, time: NaiveTime, offset: &FixedOffset) -> Option<i64> {
     offset
         .from_local_datetime(&date.and_time(time))
@@ -713,7 +792,7 @@ mod tests {
     #[test]
     fn parses_mihomo_logfmt_including_escapes() {
         let info = parse_one(
-            CoreKind::Mihomo,
+            CoreKind::Clash(ClashCoreKind::Mihomo),
             LogStream::Stdout,
             r#"time="2026-07-29T00:16:22.646059400+08:00" level=info msg="Mixed(http+socks) proxy listening at: 127.0.0.1:17890""#,
             "2026-07-29T00:16:23+08:00",
@@ -732,7 +811,7 @@ mod tests {
         );
 
         let fatal = parse_one(
-            CoreKind::Mihomo,
+            CoreKind::Clash(ClashCoreKind::Mihomo),
             LogStream::Stdout,
             r#"time="2026-07-29T00:17:26.518376100+08:00" level=fatal msg="Parse config error: yaml: line 2: did not find expected node content""#,
             "2026-07-29T00:17:27+08:00",
@@ -744,7 +823,7 @@ mod tests {
         );
 
         let escaped = parse_one(
-            CoreKind::Mihomo,
+            CoreKind::Clash(ClashCoreKind::Mihomo),
             LogStream::Stdout,
             r#"time="2026-07-29T00:17:26+08:00" level=warning msg="say \"hello\" on \\path" request=7"#,
             "2026-07-29T00:17:27+08:00",
@@ -763,7 +842,7 @@ mod tests {
     #[test]
     fn colored_mihomo_layout_degrades_instead_of_being_dropped() {
         let frame = parse_one(
-            CoreKind::Mihomo,
+            CoreKind::Clash(ClashCoreKind::Mihomo),
             LogStream::Stdout,
             "\u{1b}[36mINFO\u{1b}[0m[2026-07-29T00:16:22.646059400+08:00] proxy listening",
             "2026-07-29T00:16:23+08:00",
@@ -780,7 +859,7 @@ mod tests {
     #[test]
     fn parses_premium_pretty_print() {
         let mmdb = parse_one(
-            CoreKind::ClashPremium,
+            CoreKind::Clash(ClashCoreKind::ClashPremium),
             LogStream::Stdout,
             "00:16:30 INF [MMDB] can't find DB, start download path=C:/.../Country.mmdb",
             "2026-07-29T00:16:31+08:00",
@@ -798,7 +877,7 @@ mod tests {
         assert_eq!(timestamp.unix_ms, unix_ms("2026-07-29T00:16:30+08:00"));
 
         let inbound = parse_one(
-            CoreKind::ClashPremium,
+            CoreKind::Clash(ClashCoreKind::ClashPremium),
             LogStream::Stdout,
             "00:16:33 INF inbound create success inbound=mixed addr=127.0.0.1:17890 network=tcp",
             "2026-07-29T00:16:34+08:00",
@@ -810,7 +889,7 @@ mod tests {
         );
 
         let fatal = parse_one(
-            CoreKind::ClashPremium,
+            CoreKind::Clash(ClashCoreKind::ClashPremium),
             LogStream::Stdout,
             "00:17:26 FTL [Config] parse config failed error=yaml: line 2: did not find expected node content",
             "2026-07-29T00:17:27+08:00",
@@ -823,7 +902,7 @@ mod tests {
         );
 
         let unknown = parse_one(
-            CoreKind::ClashPremium,
+            CoreKind::Clash(ClashCoreKind::ClashPremium),
             LogStream::Stdout,
             "00:17:26 ??? unlabelled record",
             "2026-07-29T00:17:27+08:00",
@@ -834,7 +913,7 @@ mod tests {
 
     #[test]
     fn premium_rolls_over_midnight_but_ignores_small_backward_drift() {
-        let mut parser = LogParser::new(CoreKind::ClashPremium, 1);
+        let mut parser = LogParser::new(CoreKind::Clash(ClashCoreKind::ClashPremium), 1);
         let before = collect(parser.push_at(
             LogStream::Stdout,
             "23:59:59 INF before".to_owned(),
@@ -856,7 +935,7 @@ mod tests {
             unix_ms("2026-07-30T00:00:01+08:00")
         );
 
-        let mut parser = LogParser::new(CoreKind::ClashPremium, 1);
+        let mut parser = LogParser::new(CoreKind::Clash(ClashCoreKind::ClashPremium), 1);
         parser.push_at(
             LogStream::Stdout,
             "10:00:00 INF first".to_owned(),
@@ -877,7 +956,7 @@ mod tests {
     #[test]
     fn premium_first_line_from_the_previous_day() {
         let frame = parse_one(
-            CoreKind::ClashPremium,
+            CoreKind::Clash(ClashCoreKind::ClashPremium),
             LogStream::Stdout,
             "23:59:59 INF late",
             "2026-07-30T00:00:02+08:00",
@@ -891,7 +970,7 @@ mod tests {
     #[test]
     fn strips_ansi_from_meow_before_parsing() {
         let info = parse_one(
-            CoreKind::Meow,
+            CoreKind::Clash(ClashCoreKind::Meow),
             LogStream::Stdout,
             "\u{1b}[2m2026-07-28T16:16:26.616489Z\u{1b}[0m \u{1b}[32m INFO\u{1b}[0m \u{1b}[2mmeow\u{1b}[0m\u{1b}[2m:\u{1b}[0m meow-rs starting...",
             "2026-07-29T00:16:27+08:00",
@@ -909,7 +988,7 @@ mod tests {
         );
 
         let error = parse_one(
-            CoreKind::Meow,
+            CoreKind::Clash(ClashCoreKind::Meow),
             LogStream::Stdout,
             "\u{1b}[2m2026-07-28T16:17:26.719459Z\u{1b}[0m \u{1b}[31mERROR\u{1b}[0m \u{1b}[2mmeow\u{1b}[0m\u{1b}[2m:\u{1b}[0m meow-rs stopped with an error \u{1b}[3merror\u{1b}[0m\u{1b}[2m=\u{1b}[0mdid not find expected node content at line 3 column 1",
             "2026-07-29T00:17:27+08:00",
@@ -923,10 +1002,63 @@ mod tests {
         assert!(!error.raw.contains('\u{1b}'));
     }
 
+    #[test]
+    fn parses_sing_box_with_and_without_timestamps() {
+        let stamped = parse_one(
+            CoreKind::SingBox,
+            LogStream::Stderr,
+            "+0800 2026-07-29 00:16:22 INFO [3520290387 15ms] inbound/mixed[mixed-in]: inbound connection to www.example.com:443",
+            "2026-07-29T00:16:23+08:00",
+        );
+        assert_eq!(stamped.level, LogLevel::Info);
+        assert_eq!(stamped.target.as_deref(), Some("inbound/mixed[mixed-in]"));
+        assert_eq!(stamped.message, "inbound connection to www.example.com:443");
+        assert_eq!(
+            stamped.fields,
+            [
+                LogField {
+                    key: "id".into(),
+                    value: "3520290387".into(),
+                },
+                LogField {
+                    key: "duration".into(),
+                    value: "15ms".into(),
+                },
+            ]
+        );
+        let timestamp = stamped.timestamp.expect("log.timestamp prints one");
+        assert!(!timestamp.inferred);
+        assert_eq!(timestamp.unix_ms, unix_ms("2026-07-29T00:16:22+08:00"));
+
+        let uptime = parse_one(
+            CoreKind::SingBox,
+            LogStream::Stderr,
+            "WARN[0004] router: missing geosite database: open geosite.db: no such file",
+            "2026-07-29T00:16:23+08:00",
+        );
+        assert_eq!(uptime.level, LogLevel::Warning);
+        assert_eq!(uptime.timestamp, None);
+        assert_eq!(uptime.target.as_deref(), Some("router"));
+        assert_eq!(
+            uptime.message,
+            "missing geosite database: open geosite.db: no such file"
+        );
+        assert!(uptime.fields.is_empty());
+
+        let untagged = parse_one(
+            CoreKind::SingBox,
+            LogStream::Stderr,
+            "INFO[0000] sing-box started (0.21s)",
+            "2026-07-29T00:16:23+08:00",
+        );
+        assert_eq!(untagged.target, None);
+        assert_eq!(untagged.message, "sing-box started (0.21s)");
+    }
+
     #[test]
     fn infers_levels_for_plain_stderr_text() {
         let warning = parse_one(
-            CoreKind::Meow,
+            CoreKind::Clash(ClashCoreKind::Meow),
             LogStream::Stderr,
             "warning: --geodata-mode is not supported and will be ignored",
             "2026-07-29T00:17:27+08:00",
@@ -935,7 +1067,7 @@ mod tests {
         assert_eq!(warning.timestamp, None);
 
         let error = parse_one(
-            CoreKind::ClashRust,
+            CoreKind::Clash(ClashCoreKind::ClashRust),
             LogStream::Stderr,
             "Error: invalid config",
             "2026-07-29T00:17:27+08:00",
@@ -943,7 +1075,7 @@ mod tests {
         assert_eq!(error.level, LogLevel::Error);
 
         let noise = parse_one(
-            CoreKind::ClashRust,
+            CoreKind::Clash(ClashCoreKind::ClashRust),
             LogStream::Stderr,
             "using env log level: debug",
             "2026-07-29T00:17:27+08:00",
@@ -954,7 +1086,7 @@ mod tests {
     #[test]
     fn parses_clash_rs_release_and_debug_layouts() {
         let debug = parse_one(
-            CoreKind::ClashRust,
+            CoreKind::Clash(ClashCoreKind::ClashRust),
             LogStream::Stdout,
             r"26-07-29 00:16:35:0919421 DEBUG clash-lib\src\lib.rs:445: initializing cache store",
             "2026-07-29T00:16:36+08:00",
@@ -971,7 +1103,7 @@ mod tests {
         );
 
         let warning = parse_one(
-            CoreKind::ClashRust,
+            CoreKind::Clash(ClashCoreKind::ClashRust),
             LogStream::Stdout,
             r"26-07-29 00:16:35:0926205  WARN clash-lib\src\app\profile\mod.rs:153: failed to read cache file: stream did not contain valid UTF-8",
             "2026-07-29T00:16:36+08:00",
@@ -987,7 +1119,7 @@ mod tests {
         );
 
         let six_digits = parse_one(
-            CoreKind::ClashRust,
+            CoreKind::Clash(ClashCoreKind::ClashRust),
             LogStream::Stdout,
             r"26-07-29 00:16:35:093078 INFO clash-lib\src\lib.rs:446: six digit subsecond",
             "2026-07-29T00:16:36+08:00",
@@ -998,7 +1130,7 @@ mod tests {
         );
 
         let instrumented = parse_one(
-            CoreKind::ClashRust,
+            CoreKind::Clash(ClashCoreKind::ClashRust),
             LogStream::Stdout,
             r"26-07-29 00:16:35:093078 DEBUG ThreadId(1) clash_lib::app clash-lib\src\lib.rs:445: debug build shape",
             "2026-07-29T00:16:36+08:00",
@@ -1012,7 +1144,7 @@ mod tests {
         // A `file:line`-shaped reference inside the message must not win over the
         // real source anchor that closes the header.
         let quoted_source = parse_one(
-            CoreKind::ClashRust,
+            CoreKind::Clash(ClashCoreKind::ClashRust),
             LogStream::Stdout,
             r"26-07-29 00:16:35:093078 ERROR clash-lib\src\lib.rs:445: failed at config.yaml:3: invalid value",
             "2026-07-29T00:16:36+08:00",
@@ -1029,7 +1161,7 @@ mod tests {
 
     #[test]
     fn clash_rs_header_shape_alone_does_not_make_a_root() {
-        let mut parser = LogParser::new(CoreKind::ClashRust, 1);
+        let mut parser = LogParser::new(CoreKind::Clash(ClashCoreKind::ClashRust), 1);
         let at = observed("2026-07-29T00:17:27+08:00");
         parser.push_at(LogStream::Stderr, "Error: invalid config".to_owned(), at);
         for line in [
@@ -1052,7 +1184,7 @@ mod tests {
 
     #[test]
     fn finish_releases_pending_roots_oldest_first() {
-        let mut parser = LogParser::new(CoreKind::Meow, 1);
+        let mut parser = LogParser::new(CoreKind::Clash(ClashCoreKind::Meow), 1);
         let at = observed("2026-07-29T00:17:27+08:00");
         parser.push_at(LogStream::Stderr, "Error: stderr first".to_owned(), at);
         parser.push_at(
@@ -1070,7 +1202,7 @@ mod tests {
     /// observed, not the instant the last continuation arrived.
     #[test]
     fn aggregates_multi_line_records_within_one_stream() {
-        let mut parser = LogParser::new(CoreKind::ClashRust, 1);
+        let mut parser = LogParser::new(CoreKind::Clash(ClashCoreKind::ClashRust), 1);
         let root_at = observed("2026-07-29T00:17:27+08:00");
         let continuation_at = observed("2026-07-29T00:17:29+08:00");
         let root =
@@ -1097,7 +1229,7 @@ mod tests {
 
     #[test]
     fn premium_stack_stays_attached_to_its_record() {
-        let mut parser = LogParser::new(CoreKind::ClashPremium, 1);
+        let mut parser = LogParser::new(CoreKind::Clash(ClashCoreKind::ClashPremium), 1);
         let at = observed("2026-07-29T00:17:27+08:00");
         for line in [
             "00:17:26 FTL [Config] parse config failed error=bad",
@@ -1115,7 +1247,7 @@ mod tests {
 
     #[test]
     fn oversized_stacks_keep_the_root_and_mark_truncation() {
-        let mut parser = LogParser::new(CoreKind::Mihomo, 1);
+        let mut parser = LogParser::new(CoreKind::Clash(ClashCoreKind::Mihomo), 1);
         let at = observed("2026-07-29T00:17:27+08:00");
         parser.push_at(
             LogStream::Stdout,
@@ -1140,7 +1272,7 @@ mod tests {
     #[test]
     fn an_oversized_root_is_bounded_for_every_diagnostic_consumer() {
         let frame = parse_one(
-            CoreKind::Meow,
+            CoreKind::Clash(ClashCoreKind::Meow),
             LogStream::Stderr,
             &format!("warning: {}", "x".repeat(MAX_LOG_TEXT_BYTES * 2)),
             "2026-07-29T00:17:27+08:00",
@@ -1165,7 +1297,7 @@ mod tests {
     /// then cut, and the prefix that survives never splits a character.
     #[test]
     fn a_continuation_is_appended_only_as_far_as_it_fits() {
-        let mut parser = LogParser::new(CoreKind::ClashRust, 1);
+        let mut parser = LogParser::new(CoreKind::Clash(ClashCoreKind::ClashRust), 1);
         let root_at = observed("2026-07-29T00:17:27+08:00");
         assert!(
             collect(parser.push_at(LogStream::Stderr, "Error: root".to_owned(), root_at))
@@ -1248,7 +1380,7 @@ mod tests {
             line.push_str(&format!(" field{index}=value"));
         }
         let structured = parse_one(
-            CoreKind::Mihomo,
+            CoreKind::Clash(ClashCoreKind::Mihomo),
             LogStream::Stdout,
             &line,
             "2026-07-29T00:17:27+08:00",
@@ -1263,7 +1395,7 @@ mod tests {
         assert!(structured.truncated);
 
         let targeted = parse_one(
-            CoreKind::Meow,
+            CoreKind::Clash(ClashCoreKind::Meow),
             LogStream::Stdout,
             &format!(
                 "{} INFO {}: message",
@@ -1283,7 +1415,7 @@ mod tests {
 
     #[test]
     fn continuations_never_cross_streams() {
-        let mut parser = LogParser::new(CoreKind::ClashRust, 1);
+        let mut parser = LogParser::new(CoreKind::Clash(ClashCoreKind::ClashRust), 1);
         let at = observed("2026-07-29T00:17:27+08:00");
         parser.push_at(LogStream::Stderr, "Error: invalid config".to_owned(), at);
         let stdout =
@@ -1298,7 +1430,7 @@ mod tests {
     fn summarizes_bad_config_output_for_every_kind() {
         assert_eq!(
             summarize_output(
-                CoreKind::Mihomo,
+                CoreKind::Clash(ClashCoreKind::Mihomo),
                 r#"time="2026-07-29T00:17:26.518376100+08:00" level=fatal msg="Parse config error: yaml: line 2: did not find expected node content""#,
                 "",
             ),
@@ -1307,7 +1439,7 @@ mod tests {
 
         assert_eq!(
             summarize_output(
-                CoreKind::ClashPremium,
+                CoreKind::Clash(ClashCoreKind::ClashPremium),
                 "00:16:30 INF [MMDB] can't find DB\n00:17:26 FTL [Config] parse config failed error=yaml: line 2: did not find expected node content",
                 "",
             ),
@@ -1315,7 +1447,7 @@ mod tests {
         );
 
         let meow = summarize_output(
-            CoreKind::Meow,
+            CoreKind::Clash(ClashCoreKind::Meow),
             "\u{1b}[2m2026-07-28T16:17:26.719459Z\u{1b}[0m \u{1b}[31mERROR\u{1b}[0m \u{1b}[2mmeow\u{1b}[0m\u{1b}[2m:\u{1b}[0m meow-rs stopped with an error \u{1b}[3merror\u{1b}[0m\u{1b}[2m=\u{1b}[0mdid not find expected node content at line 3 column 1",
             "warning: --geodata-mode is not supported and will be ignored\nError: did not find expected node content at line 3 column 1, while parsing a flow node",
         );
@@ -1325,22 +1457,35 @@ mod tests {
         );
 
         let clash_rs = summarize_output(
-            CoreKind::ClashRust,
+            CoreKind::Clash(ClashCoreKind::ClashRust),
             "",
             "Error: invalid config: couldn't not parse config content mixed-port: not-a-port\nproxies: [[[\n: did not find expected node content at line 3 column 1, while parsing a flow node",
         );
         assert!(clash_rs.contains("invalid config"), "{clash_rs}");
         assert!(clash_rs.contains("proxies: [[["), "{clash_rs}");
+
+        assert_eq!(
+            summarize_output(
+                CoreKind::SingBox,
+                "",
+                "FATAL[0000] decode config at /d/config.json: outbounds[0].type: unknown outbound type: shadowsock",
+            ),
+            "decode config at /d/config.json: outbounds[0].type: unknown outbound type: shadowsock"
+        );
     }
 
     #[test]
     fn summary_falls_back_to_verbatim_output() {
         assert_eq!(
-            summarize_output(CoreKind::Mihomo, "  ", ""),
+            summarize_output(CoreKind::Clash(ClashCoreKind::Mihomo), "  ", ""),
             "core reported no output"
         );
         assert_eq!(
-            summarize_output(CoreKind::Mihomo, "unstructured note", ""),
+            summarize_output(
+                CoreKind::Clash(ClashCoreKind::Mihomo),
+                "unstructured note",
+                ""
+            ),
             "unstructured note"
         );
     }
@@ -0,0 +1,460 @@
+//! Read side of the JSONL archive [`crate::log_sink`] writes.
+//!
+//! The reader follows the sink's on-disk contract and nothing else: files are
+//! ordered by their sequence number, every complete line is one record, and a
+//! line without its terminating `\n` is either still being written (the newest
+//! file) or was cut short by a crash (any older one). The first is left for the
+//! next query to pick up; the second is skipped for good.
+//!
+//! Paging is by byte position rather than by record count, so a cursor stays
+//! valid while the sink keeps appending. It does not survive retention: a
+//! cursor into a file that has since been pruned resumes at the oldest file
+//! that remains, and the records in between are simply gone.
+
+use camino::Utf8Path;
+use serde::Deserialize;
+
+use crate::{
+    error::Error,
+    log::{LogFrame, LogLevel, LogStream},
+    log_sink,
+};
+
+/// A position in the archive: a file's sequence number and a byte offset into
+/// that file. Always on a line boundary when the archive produced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LogCursor {
+    pub seq: u64,
+    pub offset: u64,
+}
+
+/// Which way a query walks the archive from its cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogDirection {
+    /// Oldest first. Without a cursor the walk starts at the oldest retained
+    /// record. At the end of the archive the returned cursor is where the next
+    /// record will land, so polling with it tails the archive.
+    #[default]
+    Forward,
+    /// Newest first, for scrolling back. Without a cursor the walk starts at
+    /// the newest complete record.
+    Backward,
+}
+
+/// One page request against the archive. Every filter is optional and they
+/// combine with AND.
+#[derive(Debug, Clone, Default)]
+pub struct LogQuery {
+    /// Inclusive lower bound on [`LogFrame::at`].
+    pub since: Option<i64>,
+    /// Exclusive upper bound on [`LogFrame::at`].
+    pub until: Option<i64>,
+    /// Level floor: frames below it are skipped.
+    pub min_level: Option<LogLevel>,
+    pub epoch: Option<u64>,
+    pub stream: Option<LogStream>,
+    /// Case-sensitive substring of [`LogFrame::message`].
+    pub contains: Option<String>,
+    pub cursor: Option<LogCursor>,
+    pub direction: LogDirection,
+    /// Frames per page. Zero is read as one.
+    pub limit: usize,
+}
+
+impl LogQuery {
+    fn matches(&self, frame: &LogFrame) -> bool {
+        self.since.is_none_or(|since| frame.at >= since)
+            && self.until.is_none_or(|until| frame.at < until)
+            && self.min_level.is_none_or(|level| frame.level >= level)
+            && self.epoch.is_none_or(|epoch| frame.epoch == epoch)
+            && self.stream.is_none_or(|stream| frame.stream == stream)
+            && self
+                .contains
+                .as_deref()
+                .is_none_or(|needle| frame.message.contains(needle))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogPage {
+    /// In walk order: oldest first going forward, newest first going backward.
+    pub frames: Vec<LogFrame>,
+    /// Pass back as [`LogQuery::cursor`], with the same direction, to continue.
+    pub next: LogCursor,
+    /// The walk reached the end of the archive in its direction. Going forward
+    /// that only holds until the sink writes again.
+    pub exhausted: bool,
+    /// Frames the sink recorded as lost (its `"gap"` records) in the stretch
+    /// this page walked. Filters do not apply: a gap has no level or epoch.
+    pub dropped: u64,
+}
+
+/// The two record types the sink writes. Anything else — a record type newer
+/// than this reader, or a line mangled outside the sink's contract — fails to
+/// decode and is skipped rather than failing the page.
+#[derive(Deserialize)]
+#[serde(tag = "t", rename_all = "lowercase")]
+enum Record {
+    Log(LogFrame),
+    Gap { dropped: u64 },
+}
+
+struct Collector<'q> {
+    query: &'q LogQuery,
+    frames: Vec<LogFrame>,
+    dropped: u64,
+}
+
+impl<'q> Collector<'q> {
+    fn new(query: &'q LogQuery) -> Self {
+        Self {
+            query,
+            frames: Vec::new(),
+            dropped: 0,
+        }
+    }
+
+    /// `true` once the page is full.
+    fn push(&mut self, line: &[u8]) -> bool {
+        match serde_json::from_slice::<Record>(line) {
+            Ok(Record::Log(frame)) if self.query.matches(&frame) => self.frames.push(frame),
+            Ok(Record::Log(_)) | Err(_) => {}
+            Ok(Record::Gap { dropped }) => self.dropped += dropped,
+        }
+        self.frames.len() >= self.query.limit.max(1)
+    }
+
+    fn finish(self, next: LogCursor, exhausted: bool) -> LogPage {
+        LogPage {
+            frames: self.frames,
+            next,
+            exhausted,
+            dropped: self.dropped,
+        }
+    }
+}
+
+/// Runs one page of `query` against the archive in `dir`.
+pub(crate) async fn query(dir: &Utf8Path, query: &LogQuery) -> Result<LogPage, Error> {
+    let mut seqs = log_sink::read_seqs(dir).await?;
+    seqs.sort_unstable();
+    let collector = Collector::new(query);
+    match query.direction {
+        LogDirection::Forward => forward(dir, &seqs, query.cursor, collector).await,
+        LogDirection::Backward => backward(dir, &seqs, query.cursor, collector).await,
+    }
+}
+
+async fn forward(
+    dir: &Utf8Path,
+    seqs: &[u64],
+    cursor: Option<LogCursor>,
+    mut collector: Collector<'_>,
+) -> Result<LogPage, Error> {
+    let start = cursor.unwrap_or_default();
+    let newest = seqs.last().copied();
+    let mut next = start;
+    for &seq in seqs.iter().filter(|seq| **seq >= start.seq) {
+        let Some(bytes) = read(dir, seq).await? else {
+            continue;
+        };
+        let from = if seq == start.seq {
+            clamp_offset(start.offset, &bytes)
+        } else {
+            0
+        };
+        next = LogCursor {
+            seq,
+            offset: from as u64,
+        };
+        for (line_start, line_end) in line_spans(&bytes, from) {
+            let full = collector.push(&bytes[line_start..line_end]);
+            next.offset = (line_end + 1) as u64;
+            if full {
+                return Ok(collector.finish(next, false));
+            }
+        }
+        // The newest file's unterminated tail is still being written and
+        // belongs to the next poll. An older file's was cut by a crash and will
+        // never be completed, so the walk moves past it.
+        if Some(seq) == newest {
+            break;
+        }
+    }
+    Ok(collector.finish(next, true))
+}
+
+async fn backward(
+    dir: &Utf8Path,
+    seqs: &[u64],
+    cursor: Option<LogCursor>,
+    mut collector: Collector<'_>,
+) -> Result<LogPage, Error> {
+    let mut next = cursor.unwrap_or_default();
+    for &seq in seqs
+        .iter()
+        .rev()
+        .filter(|seq| cursor.is_none_or(|cursor| **seq <= cursor.seq))
+    {
+        let Some(bytes) = read(dir, seq).await? else {
+            continue;
+        };
+        let end = match cursor {
+            Some(cursor) if cursor.seq == seq => clamp_offset(cursor.offset, &bytes),
+            _ => bytes.len(),
+        };
+        next = LogCursor { seq, offset: 0 };
+        for (line_start, line_end) in line_spans(&bytes[..end], 0).into_iter().rev() {
+            let full = collector.push(&bytes[line_start..line_end]);
+            next.offset = line_start as u64;
+            if full {
+                return Ok(collector.finish(next, false));
+            }
+        }
+        next.offset = 0;
+    }
+    Ok(collector.finish(next, true))
+}
+
+/// `None` when the file was pruned between listing the directory and opening
+/// it, which the walk treats the same as never having listed it.
+///
+/// Whole files, on purpose: the sink bounds each one at `log_max_bytes` plus a
+/// record, and a backward walk needs every line start before its cursor anyway.
+async fn read(dir: &Utf8Path, seq: u64) -> Result<Option<Vec<u8>>, Error> {
+    match tokio::fs::read(dir.join(log_sink::file_name(seq))).await {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// A cursor can only point past the end of its file if it was not produced by
+/// this archive; it degrades to the end rather than failing.
+fn clamp_offset(offset: u64, bytes: &[u8]) -> usize {
+    usize::try_from(offset).map_or(bytes.len(), |offset| offset.min(bytes.len()))
+}
+
+/// Start and end of each complete line in `bytes[from..]`, the `\n` excluded.
+fn line_spans(bytes: &[u8], from: usize) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = from;
+    while let Some(len) = bytes[start..].iter().position(|byte| *byte == b'\n') {
+        spans.push((start, start + len));
+        start += len + 1;
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use camino::Utf8PathBuf;
+
+    use super::*;
+    use crate::kind::{ClashCoreKind, CoreKind};
+
+    fn temp_dir() -> (tempfile::TempDir, Utf8PathBuf) {
+        let guard = tempfile::tempdir().unwrap();
+        let path = Utf8PathBuf::from_path_buf(guard.path().to_path_buf()).unwrap();
+        (guard, path)
+    }
+
+    fn frame(at: i64, epoch: u64, level: LogLevel, message: &str) -> LogFrame {
+        LogFrame {
+            at,
+            epoch,
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
+            stream: LogStream::Stdout,
+            level,
+            timestamp: None,
+            target: None,
+            message: message.to_owned(),
+            fields: Vec::new(),
+            raw: message.to_owned(),
+            truncated: false,
+        }
+    }
+
+    /// The sink's own shape: the frame flattened beside `t`.
+    fn log_line(frame: &LogFrame) -> String {
+        let mut value = serde_json::to_value(frame).unwrap();
+        value["t"] = "log".into();
+        format!("{value}\n")
+    }
+
+    fn append(dir: &Utf8Path, seq: u64, text: &str) {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(log_sink::file_name(seq)))
+            .unwrap()
+            .write_all(text.as_bytes())
+            .unwrap();
+    }
+
+    fn messages(page: &LogPage) -> Vec<&str> {
+        page.frames
+            .iter()
+            .map(|frame| frame.message.as_str())
+            .collect()
+    }
+
+    fn page_of(limit: usize) -> LogQuery {
+        LogQuery {
+            limit,
+            ..LogQuery::default()
+        }
+    }
+
+    #[test]
+    fn only_terminated_lines_are_spans() {
+        assert_eq!(line_spans(b"ab\ncd\nef", 0), [(0, 2), (3, 5)]);
+        assert_eq!(line_spans(b"ab\ncd\n", 3), [(3, 5)]);
+        assert!(line_spans(b"partial", 0).is_empty());
+    }
+
+    #[tokio::test]
+    async fn forward_pages_resume_across_files_and_tail_the_newest() {
+        let (_guard, dir) = temp_dir();
+        for index in 0..3 {
+            append(
+                &dir,
+                1,
+                &log_line(&frame(index, 1, LogLevel::Info, &format!("a{index}"))),
+            );
+        }
+        append(&dir, 2, &log_line(&frame(10, 2, LogLevel::Info, "b0")));
+
+        let first = query(&dir, &page_of(2)).await.unwrap();
+        assert_eq!(messages(&first), ["a0", "a1"]);
+        assert!(!first.exhausted);
+
+        let mut resume = page_of(2);
+        resume.cursor = Some(first.next);
+        let second = query(&dir, &resume).await.unwrap();
+        assert_eq!(messages(&second), ["a2", "b0"]);
+
+        // Half a record is still being written: it is not consumed, and the
+        // cursor waits in front of it until the line is complete.
+        let tail = log_line(&frame(11, 2, LogLevel::Info, "b1"));
+        let (head, rest) = tail.split_at(tail.len() / 2);
+        append(&dir, 2, head);
+        resume.cursor = Some(second.next);
+        let third = query(&dir, &resume).await.unwrap();
+        assert!(third.frames.is_empty());
+        assert!(third.exhausted);
+        assert_eq!(third.next, second.next);
+
+        append(&dir, 2, rest);
+        resume.cursor = Some(third.next);
+        assert_eq!(messages(&query(&dir, &resume).await.unwrap()), ["b1"]);
+    }
+
+    #[tokio::test]
+    async fn backward_pages_walk_newest_first_to_the_start() {
+        let (_guard, dir) = temp_dir();
+        append(&dir, 1, &log_line(&frame(0, 1, LogLevel::Info, "a0")));
+        append(&dir, 1, &log_line(&frame(1, 1, LogLevel::Info, "a1")));
+        append(&dir, 2, &log_line(&frame(2, 1, LogLevel::Info, "b0")));
+
+        let mut backward = page_of(2);
+        backward.direction = LogDirection::Backward;
+        let first = query(&dir, &backward).await.unwrap();
+        assert_eq!(messages(&first), ["b0", "a1"]);
+        assert!(!first.exhausted);
+
+        backward.cursor = Some(first.next);
+        let second = query(&dir, &backward).await.unwrap();
+        assert_eq!(messages(&second), ["a0"]);
+        assert!(second.exhausted);
+    }
+
+    #[tokio::test]
+    async fn every_filter_narrows_the_page() {
+        let (_guard, dir) = temp_dir();
+        let mut stderr = frame(5, 2, LogLevel::Error, "dial failed");
+        stderr.stream = LogStream::Stderr;
+        for frame in [
+            frame(1, 1, LogLevel::Debug, "noise"),
+            frame(2, 1, LogLevel::Warning, "dial timeout"),
+            frame(3, 2, LogLevel::Info, "dial ok"),
+            stderr,
+        ] {
+            append(&dir, 1, &log_line(&frame));
+        }
+
+        let run = |query: LogQuery| {
+            let dir = dir.clone();
+            async move {
+                let page = super::query(&dir, &query).await.unwrap();
+                page.frames
+                    .into_iter()
+                    .map(|frame| frame.message)
+                    .collect::<Vec<_>>()
+            }
+        };
+        let all = page_of(10);
+        assert_eq!(
+            run(LogQuery {
+                min_level: Some(LogLevel::Warning),
+                ..all.clone()
+            })
+            .await,
+            ["dial timeout", "dial failed"]
+        );
+        assert_eq!(
+            run(LogQuery {
+                epoch: Some(2),
+                ..all.clone()
+            })
+            .await,
+            ["dial ok", "dial failed"]
+        );
+        assert_eq!(
+            run(LogQuery {
+                stream: Some(LogStream::Stderr),
+                ..all.clone()
+            })
+            .await,
+            ["dial failed"]
+        );
+        assert_eq!(
+            run(LogQuery {
+                contains: Some("dial".to_owned()),
+                since: Some(2),
+                until: Some(5),
+                ..all
+            })
+            .await,
+            ["dial timeout", "dial ok"]
+        );
+    }
+
+    /// A crash can cut the last line of any file but the active one. Going
+    /// forward that tail must not stall the walk, and the sink's gap records
+    /// are counted rather than returned.
+    #[tokio::test]
+    async fn a_truncated_older_file_and_gap_records_do_not_stall_the_walk() {
+        let (_guard, dir) = temp_dir();
+        append(&dir, 1, &log_line(&frame(0, 1, LogLevel::Info, "before")));
+        append(&dir, 1, r#"{"t":"gap","at":1,"dropped":4}"#);
+        append(&dir, 1, "\n{\"t\":\"log\",\"at\":");
+        append(&dir, 2, &log_line(&frame(2, 2, LogLevel::Info, "after")));
+
+        let page = query(&dir, &page_of(10)).await.unwrap();
+        assert_eq!(messages(&page), ["before", "after"]);
+        assert_eq!(page.dropped, 4);
+        assert!(page.exhausted);
+    }
+
+    #[tokio::test]
+    async fn a_cursor_into_a_pruned_file_resumes_at_the_oldest_retained_one() {
+        let (_guard, dir) = temp_dir();
+        append(&dir, 5, &log_line(&frame(0, 1, LogLevel::Info, "survivor")));
+        let mut resume = page_of(10);
+        resume.cursor = Some(LogCursor { seq: 2, offset: 9 });
+        assert_eq!(messages(&query(&dir, &resume).await.unwrap()), ["survivor"]);
+    }
+}
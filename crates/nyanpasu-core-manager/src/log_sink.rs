@@ -28,9 +28,12 @@ use camino::{Utf8Path, Utf8PathBuf};
 use serde::Serialize;
 use tokio::{
     io::AsyncWriteExt,
-    sync::broadcast::{
-        Receiver,
-        error::{RecvError, TryRecvError},
+    sync::{
+        broadcast::{
+            Receiver,
+            error::{RecvError, TryRecvError},
+        },
+        watch,
     },
 };
 use tokio_util::sync::CancellationToken;
@@ -66,9 +69,17 @@ pub(crate) struct SinkOptions {
 pub(crate) struct SinkHandle {
     cancel: CancellationToken,
     task: tokio::task::JoinHandle<()>,
+    options: watch::Sender<SinkOptions>,
 }
 
 impl SinkHandle {
+    /// New limits for the running writer, read before its next batch. A
+    /// lowered `max_files` is enforced at the next rollover, not here: pruning
+    /// is the writer's alone, so two tasks never race over the same files.
+    pub(crate) fn set_options(&self, options: SinkOptions) {
+        self.options.send_replace(options);
+    }
+
     /// An aborted writer can cut the final batch mid-write, the same contract
     /// as a crash; readers already discard a truncated last line.
     pub(crate) async fn shutdown(mut self) {
@@ -173,19 +184,33 @@ pub(crate) async fn spawn(
     cancel: CancellationToken,
 ) -> Result<SinkHandle, Error> {
     let writer = Writer::open(dir, options).await?;
-    let task = tokio::spawn(run(writer, logs, cancel.clone()));
-    Ok(SinkHandle { cancel, task })
+    let (options, options_rx) = watch::channel(options);
+    let task = tokio::spawn(run(writer, logs, options_rx, cancel.clone()));
+    Ok(SinkHandle {
+        cancel,
+        task,
+        options,
+    })
 }
 
 /// Drains the broadcast in batches until the token is cancelled or the last
 /// sender is gone. One `recv().await` for the first frame, then `try_recv()`
 /// until empty: a start-up burst costs one write instead of one per line.
-async fn run(mut writer: Writer, mut logs: Receiver<Arc<LogFrame>>, cancel: CancellationToken) {
+async fn run(
+    mut writer: Writer,
+    mut logs: Receiver<Arc<LogFrame>>,
+    mut options: watch::Receiver<SinkOptions>,
+    cancel: CancellationToken,
+) {
     let mut batch = Vec::new();
     loop {
         // Cleared before the select, never after: the cancellation arm writes
         // this same buffer, and a stale batch there would be written twice.
         batch.clear();
+        // A closed channel is a handle already dropped; keep the last limits.
+        if options.has_changed().unwrap_or(false) {
+            writer.options = *options.borrow_and_update();
+        }
         let first = tokio::select! {
             _ = cancel.cancelled() => {
                 let closed = drain(&mut logs, &mut batch);
@@ -385,7 +410,7 @@ async fn create(dir: &Utf8Path, seq: u64) -> Result<tokio::fs::File, Error> {
     Ok(options.open(dir.join(file_name(seq))).await?)
 }
 
-fn file_name(seq: u64) -> String {
+pub(crate) fn file_name(seq: u64) -> String {
     format!("{FILE_PREFIX}{seq:06}{FILE_SUFFIX}")
 }
 
@@ -398,7 +423,7 @@ fn file_seq(name: &str) -> Option<u64> {
         .and_then(|digits| digits.parse().ok())
 }
 
-async fn read_seqs(dir: &Utf8Path) -> Result<Vec<u64>, std::io::Error> {
+pub(crate) async fn read_seqs(dir: &Utf8Path) -> Result<Vec<u64>, std::io::Error> {
     let mut seqs = Vec::new();
     let mut entries = tokio::fs::read_dir(dir).await?;
     while let Some(entry) = entries.next_entry().await? {
@@ -430,7 +455,7 @@ mod tests {
     use super::*;
 
     use crate::{
-        kind::CoreKind,
+        kind::{ClashCoreKind, CoreKind},
         log::{LogField, LogLevel, LogStream, LogTimestamp},
     };
 
@@ -451,7 +476,7 @@ mod tests {
         LogFrame {
             at: 1_700_000_000_000,
             epoch: 7,
-            kind: CoreKind::Mihomo,
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
             stream: LogStream::Stdout,
             level: LogLevel::Info,
             timestamp: Some(LogTimestamp {
@@ -768,7 +793,8 @@ mod tests {
         let writer = Writer::open(dir.clone(), options(1024 * 1024, 5))
             .await
             .unwrap();
-        run(writer, logs, CancellationToken::new()).await;
+        let (_options, options_rx) = watch::channel(writer.options);
+        run(writer, logs, options_rx, CancellationToken::new()).await;
 
         let records = lines(&dir.join(file_name(1)));
         assert_eq!(records.len(), 3);
@@ -792,13 +818,44 @@ mod tests {
         let writer = Writer::open(dir.clone(), options(1024 * 1024, 5))
             .await
             .unwrap();
-        run(writer, logs, CancellationToken::new()).await;
+        let (_options, options_rx) = watch::channel(writer.options);
+        run(writer, logs, options_rx, CancellationToken::new()).await;
 
         let records = lines(&dir.join(file_name(1)));
         assert_eq!(records.len(), 300);
         assert!(records.iter().all(|record| record["t"] == "log"));
     }
 
+    /// The limits are read per batch, so a writer opened with room to spare
+    /// rolls on every record once told `max_bytes = 1`.
+    #[tokio::test]
+    async fn new_limits_reach_a_running_writer_before_its_next_batch() {
+        let (_guard, dir) = temp_dir();
+        let (log_tx, logs) = tokio::sync::broadcast::channel(16);
+        for index in 0..3 {
+            log_tx
+                .send(Arc::new(frame(&format!("line {index}"))))
+                .unwrap();
+        }
+        drop(log_tx);
+
+        let writer = Writer::open(dir.clone(), options(1024 * 1024, 5))
+            .await
+            .unwrap();
+        let (options_tx, options_rx) = watch::channel(writer.options);
+        options_tx.send_replace(options(1, 5));
+        run(writer, logs, options_rx, CancellationToken::new()).await;
+
+        assert_eq!(
+            names(&dir),
+            [
+                "core-000001.jsonl",
+                "core-000002.jsonl",
+                "core-000003.jsonl"
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn graceful_sink_shutdown_drains_every_buffered_frame() {
         let (_guard, dir) = temp_dir();
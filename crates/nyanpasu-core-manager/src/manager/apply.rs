@@ -1,7 +1,10 @@
 use crate::{
+    RuntimeFeature,
+    capability::ResolvedFeatures,
     config::{
-        ConfigSnapshot,
-        mihomo::{self, ConfigChange},
+        ConfigDiffEntry, ConfigSnapshot, PreparedConfig,
+        adapter::{self, ConfigChange},
+        changed_paths, describe_diff, mihomo,
     },
     error::Error,
     instance::Instance,
@@ -11,8 +14,9 @@ use crate::{
 };
 
 use super::{
-    Active, ApplyOutcome, CoreManager, Ctrl, PreparedApply, PreparedLaunch, abort_and_await,
-    publish::spec_summary, quarantine::reject_quarantine, spawn_forwarder,
+    Active, ApplyOutcome, ApplyPlan, CoreManager, Ctrl, DegradeReason, PlannedRoute, PreparedApply,
+    PreparedLaunch, RestoredSelections, SwitchOutcome, abort_and_await, publish::spec_summary,
+    quarantine::reject_quarantine, spawn_forwarder, switching::graceful_degrade_reason,
 };
 
 impl CoreManager {
@@ -22,26 +26,49 @@ impl CoreManager {
         expected_revision: Option<RevisionId>,
     ) -> Result<ApplyOutcome, Error> {
         let mut ctrl = self.inner.ctrl.lock().await;
-        reject_quarantine(&ctrl)?;
-        let current = ctrl.current.as_ref().ok_or(Error::NotStarted)?;
-        if current.instance.state().borrow().state.is_terminal() {
-            return Err(Error::NotStarted);
-        }
-        let actual_revision = current.revision.id();
-        if let Some(expected) = expected_revision
-            && expected != actual_revision
-        {
-            return Err(Error::RevisionConflict {
-                expected,
-                actual: Some(actual_revision),
-            });
-        }
-
+        applicable(&ctrl, expected_revision.as_ref())?;
         let snapshot = ConfigSnapshot::load(&input.config_path).await?;
+        let result = self.apply_snapshot(&mut ctrl, input, snapshot).await;
+        self.record_revision(&mut ctrl).await;
+        result
+    }
+
+    /// [`Self::apply_config`] for a source that arrived as bytes.
+    ///
+    /// `input.config_path` is replaced by a copy the manager keeps in its
+    /// runtime directory, so a later restart re-reads the same bytes. The
+    /// revision's `source_hash` is the one a file holding them would get.
+    pub async fn apply_inline(
+        &self,
+        mut input: InstanceSpec,
+        source: &[u8],
+        expected_revision: Option<RevisionId>,
+    ) -> Result<ApplyOutcome, Error> {
+        let mut ctrl = self.inner.ctrl.lock().await;
+        applicable(&ctrl, expected_revision.as_ref())?;
+        let snapshot = ConfigSnapshot::inline(source)?;
+        input.config_path = self.inner.store.keep_inline(source).await?;
+        let snapshot = snapshot.kept_at(input.config_path.clone());
+        let result = self.apply_snapshot(&mut ctrl, input, snapshot).await;
+        self.record_revision(&mut ctrl).await;
+        self.prune_inline(&ctrl).await;
+        result
+    }
+
+    /// The apply engine proper, once the guards have passed and the source is
+    /// read. `snapshot` may stand in for `input.config_path`, as a rollback's
+    /// kept copy does.
+    pub(super) async fn apply_snapshot(
+        &self,
+        ctrl: &mut Ctrl,
+        input: InstanceSpec,
+        snapshot: ConfigSnapshot,
+    ) -> Result<ApplyOutcome, Error> {
+        let current = ctrl.current.as_ref().expect("checked by applicable");
         let prepared = self
             .prepare_apply(current, input.clone(), &snapshot)
             .await?;
-        let change = mihomo::classify(
+        let change = adapter::classify(
             &current.source_document,
             &current.effective_document,
             &current.source_spec,
@@ -56,9 +83,7 @@ impl CoreManager {
         }
         if matches!(change, ConfigChange::Switch) {
             drop(prepared);
-            return self
-                .switch_with_compensation(&mut ctrl, input, snapshot)
-                .await;
+            return self.apply_switch(ctrl, input, snapshot).await;
         }
 
         let backup = self
@@ -73,6 +98,7 @@ impl CoreManager {
             revision,
             capabilities,
             runtime_features,
+            version,
             source_document,
             effective_document,
             staged,
@@ -97,6 +123,7 @@ impl CoreManager {
             revision,
             capabilities,
             runtime_features,
+            version,
             source_document,
             effective_document,
         };
@@ -124,6 +151,7 @@ impl CoreManager {
                 active.revision = desired.revision;
                 active.capabilities = desired.capabilities;
                 active.runtime_features = desired.runtime_features;
+                active.version = desired.version;
                 active.source_document = desired.source_document;
                 active.effective_document = desired.effective_document;
                 self.inner.publish_active(
@@ -142,12 +170,84 @@ impl CoreManager {
             return Ok(with_durability_warning(outcome, durability_warning));
         }
 
-        let result = self
-            .restart_with_compensation(&mut ctrl, desired, backup)
-            .await;
+        let result = self.restart_with_compensation(ctrl, desired, backup).await;
         with_durability_result(result, durability_warning)
     }
 
+    /// Predicts the route [`Self::apply_config`] would take for `input`, from
+    /// the same snapshot and classification, without staging a runtime copy
+    /// or touching the running core.
+    ///
+    /// The plan is made against the revision running now; a later apply can
+    /// still take a different route if something else moves it first. The
+    /// core binary is not asked to check the config either — that is
+    /// [`Self::check_config`].
+    pub async fn plan_apply(&self, input: InstanceSpec) -> Result<ApplyPlan, Error> {
+        let ctrl = self.inner.ctrl.lock().await;
+        let current = applicable(&ctrl, None)?;
+        let (snapshot, resolved, prepared) = self.prepare_preview(current, &input).await?;
+        let change = adapter::classify(
+            &current.source_document,
+            &current.effective_document,
+            &current.source_spec,
+            snapshot.document(),
+            &prepared.document,
+            &input,
+        )?;
+        let route = match change {
+            ConfigChange::Noop => PlannedRoute::Noop,
+            ConfigChange::Patch { .. } => PlannedRoute::Patch,
+            ConfigChange::Reload => PlannedRoute::Reload,
+            ConfigChange::Switch => PlannedRoute::Switch {
+                degraded: graceful_degrade_reason(
+                    resolved.runtime.contains(RuntimeFeature::LocalIpc),
+                    input.core.kind,
+                    mihomo::overlap_block(snapshot.document()),
+                ),
+            },
+        };
+        Ok(ApplyPlan {
+            route,
+            changed: changed_paths(&current.source_document, snapshot.document()),
+        })
+    }
+
+    /// Every leaf an apply of `input` would change in the running core's
+    /// effective config, with credentials and controller keys redacted.
+    ///
+    /// Compares effective documents, so the controller rewrite the manager
+    /// applies to both sides never shows up as a change. Like
+    /// [`Self::plan_apply`], it stages nothing.
+    pub async fn diff_config(&self, input: InstanceSpec) -> Result<Vec<ConfigDiffEntry>, Error> {
+        let ctrl = self.inner.ctrl.lock().await;
+        let current = applicable(&ctrl, None)?;
+        let (_, _, prepared) = self.prepare_preview(current, &input).await?;
+        describe_diff(
+            (current.source_spec.core.kind, &current.effective_document),
+            (input.core.kind, &prepared.document),
+        )
+    }
+
+    /// The candidate's effective document exactly as [`Self::prepare_apply`]
+    /// would derive it, minus the staging and the binary's own check.
+    async fn prepare_preview(
+        &self,
+        current: &Active,
+        input: &InstanceSpec,
+    ) -> Result<(ConfigSnapshot, ResolvedFeatures, PreparedConfig), Error> {
+        let snapshot = ConfigSnapshot::load(&input.config_path).await?;
+        self.validate_launchable(input).await?;
+        let resolved = self.resolve_features(&input.core).await?;
+        let prepared = snapshot.prepare_full(
+            input.core.kind,
+            self.inner.options.controller_template.as_deref(),
+            self.inner.store.dir(),
+            current.revision.epoch,
+            resolved.runtime,
+        )?;
+        Ok((snapshot, resolved, prepared))
+    }
+
     async fn prepare_apply(
         &self,
         current: &Active,
@@ -158,16 +258,13 @@ impl CoreManager {
         let resolved = self.resolve_features(&input.core).await?;
         let epoch = current.revision.epoch;
         let prepared = snapshot.prepare_full(
+            input.core.kind,
             self.inner.options.controller_template.as_deref(),
             self.inner.store.dir(),
             epoch,
             resolved.runtime,
         )?;
-        self.warn_http_fallback(
-            &input.core,
-            resolved.version.as_deref(),
-            prepared.rewrote_controller,
-        );
+        self.warn_http_fallback(&input.core, &resolved, prepared.rewrote_controller);
         let staged = self.inner.store.stage(epoch, &prepared.bytes).await?;
         let mut check_spec = input.clone();
         check_spec.config_path = staged.path().to_owned();
@@ -190,6 +287,7 @@ impl CoreManager {
             },
             capabilities: resolved.capabilities,
             runtime_features: resolved.runtime,
+            version: resolved.version,
             source_document: snapshot.document().clone(),
             effective_document: prepared.document,
             staged,
@@ -251,7 +349,7 @@ impl CoreManager {
         &self,
         instance: &Instance,
         patch: &clash_api::ConfigPatch,
-        projection: &mihomo::RuntimeProjection,
+        projection: &adapter::RuntimeProjection,
     ) -> bool {
         let client = match crate::health::build_control_client(
             instance.controller(),
@@ -266,7 +364,7 @@ impl CoreManager {
         if let Err(error) = client.patch_config(patch).await {
             tracing::warn!("config PATCH returned an uncertain result: {error}");
         }
-        match client.configs().await {
+        match client.configs_value().await {
             Ok(runtime) => match projection.verify(&runtime) {
                 Ok(true) => {}
                 Ok(false) => return false,
@@ -296,9 +394,11 @@ impl CoreManager {
         let old_revision = old.revision.clone();
         let old_capabilities = old.capabilities;
         let old_runtime_features = old.runtime_features;
+        let old_version = old.version.clone();
         let old_source_document = old.source_document.clone();
         let old_effective_document = old.effective_document.clone();
         abort_and_await(old.forwarder).await;
+        let saved = self.snapshot_selections(&old.instance).await;
         if let Err(error) = old
             .instance
             .stop_and_confirm_dead(self.inner.options.stop_timeout)
@@ -321,6 +421,7 @@ impl CoreManager {
                 &desired.source_spec,
                 desired.capabilities,
                 desired.runtime_features,
+                desired.version.as_deref(),
             )),
             Some(desired.controller.host.clone()),
             Some(desired.revision.clone()),
@@ -346,6 +447,7 @@ impl CoreManager {
                     revision: desired.revision,
                     capabilities: desired.capabilities,
                     runtime_features: desired.runtime_features,
+                    version: desired.version,
                     source_document: desired.source_document,
                     effective_document: desired.effective_document,
                 });
@@ -360,7 +462,11 @@ impl CoreManager {
                 if let Err(error) = self.inner.store.remove_backup(backup).await {
                     tracing::warn!("failed to remove successful restart backup: {error}");
                 }
-                Ok(ApplyOutcome::Restarted { revision })
+                let selections = self.restore_selections(ctrl, saved).await;
+                Ok(ApplyOutcome::Restarted {
+                    revision,
+                    selections,
+                })
             }
             Err(error @ Error::StopUnconfirmed(_)) => {
                 Err(self.latch_quarantine(ctrl, desired.revision.epoch, error))
@@ -388,6 +494,7 @@ impl CoreManager {
                         &old_source_spec,
                         old_capabilities,
                         old_runtime_features,
+                        old_version.as_deref(),
                     )),
                     Some(old_controller.host.clone()),
                     Some(old_revision.clone()),
@@ -408,6 +515,7 @@ impl CoreManager {
                             revision: old_revision.clone(),
                             capabilities: old_capabilities,
                             runtime_features: old_runtime_features,
+                            version: old_version,
                             source_document: old_source_document,
                             effective_document: old_effective_document,
                         });
@@ -422,9 +530,11 @@ impl CoreManager {
                         if let Err(error) = self.inner.store.remove_backup(backup).await {
                             tracing::warn!("failed to remove rollback backup: {error}");
                         }
+                        let selections = self.restore_selections(ctrl, saved).await;
                         Ok(ApplyOutcome::RolledBack {
                             revision: old_revision,
                             failed_apply: apply_text,
+                            selections,
                         })
                     }
                     Err(rollback_error @ Error::StopUnconfirmed(_)) => {
@@ -447,14 +557,107 @@ impl CoreManager {
         }
     }
 
+    /// A switch-class change takes the graceful bootstrap-epoch switch whenever
+    /// the switch matrix allows it, and the hard stop → start otherwise. The
+    /// outcome says which one ran and why.
+    async fn apply_switch(
+        &self,
+        ctrl: &mut Ctrl,
+        input: InstanceSpec,
+        snapshot: ConfigSnapshot,
+    ) -> Result<ApplyOutcome, Error> {
+        self.validate_launchable(&input).await?;
+        let resolved = self.resolve_features(&input.core).await?;
+        let local_controller = resolved.runtime.contains(RuntimeFeature::LocalIpc);
+        match graceful_degrade_reason(
+            local_controller,
+            input.core.kind,
+            mihomo::overlap_block(snapshot.document()),
+        ) {
+            Some(reason) => {
+                self.switch_with_compensation(ctrl, input, snapshot, resolved, reason)
+                    .await
+            }
+            None => {
+                self.graceful_switch_for_apply(ctrl, input, snapshot, resolved)
+                    .await
+            }
+        }
+    }
+
+    async fn graceful_switch_for_apply(
+        &self,
+        ctrl: &mut Ctrl,
+        input: InstanceSpec,
+        snapshot: ConfigSnapshot,
+        resolved: ResolvedFeatures,
+    ) -> Result<ApplyOutcome, Error> {
+        let old_revision = ctrl
+            .current
+            .as_ref()
+            .expect("current held by control lock")
+            .revision
+            .clone();
+        let epoch = self.next_epoch();
+        let prepared = match self
+            .prepare_graceful(&input, epoch, &snapshot, resolved)
+            .await
+        {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                let _ = self.inner.store.cleanup_epoch(epoch).await;
+                return Err(error);
+            }
+        };
+        match self.graceful_switch_prepared(ctrl, epoch, prepared).await {
+            Ok(outcome) => {
+                let revision = ctrl
+                    .current
+                    .as_ref()
+                    .expect("switch installed")
+                    .revision
+                    .clone();
+                Ok(switched_outcome(outcome, revision))
+            }
+            // The bootstrap epoch never became ready, so the old one was never
+            // stopped: the old config is what runs, exactly as after a hard
+            // switch's rollback.
+            Err(error)
+                if !matches!(error, Error::StopUnconfirmed(_))
+                    && ctrl
+                        .current
+                        .as_ref()
+                        .is_some_and(|active| active.revision.epoch == old_revision.epoch) =>
+            {
+                Ok(ApplyOutcome::RolledBack {
+                    revision: old_revision,
+                    failed_apply: error.to_string(),
+                    selections: RestoredSelections::default(),
+                })
+            }
+            Err(error) => Err(error),
+        }
+    }
+
     async fn switch_with_compensation(
         &self,
         ctrl: &mut Ctrl,
         input: InstanceSpec,
         snapshot: ConfigSnapshot,
+        resolved: ResolvedFeatures,
+        reason: DegradeReason,
     ) -> Result<ApplyOutcome, Error> {
         let epoch = self.next_epoch();
-        let desired = self.prepare_launch(&input, epoch, &snapshot).await?;
+        let desired = match self
+            .prepare_launch_with_features(&input, epoch, &snapshot, resolved)
+            .await
+        {
+            Ok(desired) => desired,
+            Err(error) => {
+                let _ = self.inner.store.cleanup_epoch(epoch).await;
+                return Err(error);
+            }
+        };
         let old = ctrl.current.take().expect("current held by control lock");
         let old_epoch = old.revision.epoch;
         let old_effective_spec = old.instance.spec().clone();
@@ -463,9 +666,11 @@ impl CoreManager {
         let old_revision = old.revision.clone();
         let old_capabilities = old.capabilities;
         let old_runtime_features = old.runtime_features;
+        let old_version = old.version.clone();
         let old_source_document = old.source_document.clone();
         let old_effective_document = old.effective_document.clone();
         abort_and_await(old.forwarder).await;
+        let saved = self.snapshot_selections(&old.instance).await;
         if let Err(error) = old
             .instance
             .stop_and_confirm_dead(self.inner.options.stop_timeout)
@@ -489,6 +694,7 @@ impl CoreManager {
                 &desired.source_spec,
                 desired.capabilities,
                 desired.runtime_features,
+                desired.version.as_deref(),
             )),
             Some(desired.controller.host.clone()),
             Some(desired.revision.clone()),
@@ -514,6 +720,7 @@ impl CoreManager {
                     revision: desired.revision,
                     capabilities: desired.capabilities,
                     runtime_features: desired.runtime_features,
+                    version: desired.version,
                     source_document: desired.source_document,
                     effective_document: desired.effective_document,
                 });
@@ -528,7 +735,12 @@ impl CoreManager {
                 if let Err(error) = self.inner.store.cleanup_epoch(old_epoch).await {
                     tracing::warn!("failed to clean switched-out epoch: {error}");
                 }
-                Ok(ApplyOutcome::Switched { revision })
+                let selections = self.restore_selections(ctrl, saved).await;
+                Ok(ApplyOutcome::Switched {
+                    revision,
+                    selections,
+                    degraded: Some(reason),
+                })
             }
             Err(error @ Error::StopUnconfirmed(_)) => {
                 Err(self.latch_quarantine(ctrl, desired.revision.epoch, error))
@@ -547,6 +759,7 @@ impl CoreManager {
                         &old_source_spec,
                         old_capabilities,
                         old_runtime_features,
+                        old_version.as_deref(),
                     )),
                     Some(old_controller.host.clone()),
                     Some(old_revision.clone()),
@@ -567,6 +780,7 @@ impl CoreManager {
                             revision: old_revision.clone(),
                             capabilities: old_capabilities,
                             runtime_features: old_runtime_features,
+                            version: old_version,
                             source_document: old_source_document,
                             effective_document: old_effective_document,
                         });
@@ -578,9 +792,11 @@ impl CoreManager {
                                 pid,
                             },
                         );
+                        let selections = self.restore_selections(ctrl, saved).await;
                         Ok(ApplyOutcome::RolledBack {
                             revision: old_revision,
                             failed_apply: apply_text,
+                            selections,
                         })
                     }
                     Err(rollback_error @ Error::StopUnconfirmed(_)) => {
@@ -603,6 +819,54 @@ impl CoreManager {
     }
 }
 
+/// The active epoch an apply would replace, once the guards every apply-class
+/// operation shares have passed: no quarantine, a live core, and a matching
+/// compare-and-swap token when one is given. Previews pass no token.
+pub(super) fn applicable<'a>(
+    ctrl: &'a Ctrl,
+    expected_revision: Option<&RevisionId>,
+) -> Result<&'a Active, Error> {
+    reject_quarantine(ctrl)?;
+    let current = ctrl.current.as_ref().ok_or(Error::NotStarted)?;
+    if current.instance.state().borrow().state.is_terminal() {
+        return Err(Error::NotStarted);
+    }
+    let actual_revision = current.revision.id();
+    if let Some(expected) = expected_revision
+        && *expected != actual_revision
+    {
+        return Err(Error::RevisionConflict {
+            expected: expected.clone(),
+            actual: Some(actual_revision),
+        });
+    }
+    Ok(current)
+}
+
+/// Re-express a graceful switch's outcome as the apply outcome it carried. The
+/// durability wrapper survives the translation rather than being flattened
+/// into a warning nobody branches on.
+fn switched_outcome(outcome: SwitchOutcome, revision: ConfigRevision) -> ApplyOutcome {
+    match outcome {
+        SwitchOutcome::Graceful { selections } => ApplyOutcome::Switched {
+            revision,
+            selections,
+            degraded: None,
+        },
+        SwitchOutcome::Hard { reason, selections } => ApplyOutcome::Switched {
+            revision,
+            selections,
+            degraded: Some(reason),
+        },
+        SwitchOutcome::DurabilityUncertain { outcome, warning } => {
+            ApplyOutcome::DurabilityUncertain {
+                outcome: Box::new(switched_outcome(*outcome, revision)),
+                warning,
+            }
+        }
+    }
+}
+
 fn with_durability_warning(outcome: ApplyOutcome, warning: Option<String>) -> ApplyOutcome {
     match warning {
         Some(warning) => ApplyOutcome::DurabilityUncertain {
@@ -0,0 +1,158 @@
+//! Connection listing and termination on behalf of callers that must not hold
+//! the controller secret.
+//!
+//! Same rules as proxy control: each call resolves the active epoch's
+//! controller afresh. A connection id is the core's random UUID and carries no
+//! epoch, so an id from an earlier core simply matches nothing in the current
+//! one. Closing an id the core no longer knows is not an error; the connection
+//! is gone either way.
+
+use clash_api::{Connection, ConnectionNetwork, ConnectionsSnapshot, Uuid};
+
+use crate::error::Error;
+
+use super::CoreManager;
+
+/// Which connections a listing keeps or a close terminates.
+///
+/// Every field that is set must match; the default matches everything. Text
+/// fields match case-insensitively anywhere in the value, so `"github"`
+/// finds `api.github.com`. A blank text field counts as unset: it would be
+/// found in every value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionFilter {
+    /// The requested host, its sniffed host or the destination IP.
+    pub host: Option<String>,
+    /// The matched rule or its payload, e.g. `DomainSuffix` or `github.com`.
+    pub rule: Option<String>,
+    /// Any proxy or group in the chain the connection went through.
+    pub chain: Option<String>,
+    /// The originating process name or path, when the core resolved one.
+    pub process: Option<String>,
+    pub network: Option<ConnectionNetwork>,
+}
+
+impl ConnectionFilter {
+    pub fn is_empty(&self) -> bool {
+        [&self.host, &self.rule, &self.chain, &self.process]
+            .into_iter()
+            .all(|field| text(field).is_none())
+            && self.network.is_none()
+    }
+
+    pub fn matches(&self, connection: &Connection) -> bool {
+        if let Some(chain) = text(&self.chain)
+            && !connection.chains.iter().any(|hop| contains(hop, chain))
+        {
+            return false;
+        }
+        if let Some(rule) = text(&self.rule)
+            && !contains(&connection.rule, rule)
+            && !contains(&connection.rule_payload, rule)
+        {
+            return false;
+        }
+        let host = text(&self.host);
+        let process = text(&self.process);
+        if host.is_none() && process.is_none() && self.network.is_none() {
+            return true;
+        }
+        // Everything below is metadata, and a connection without any cannot
+        // be shown to match.
+        let Some(metadata) = &connection.metadata else {
+            return false;
+        };
+        if let Some(host) = host
+            && !contains(&metadata.host, host)
+            && !contains(&metadata.sniff_host, host)
+            && !contains(&metadata.destination_ip, host)
+        {
+            return false;
+        }
+        if let Some(process) = process
+            && !contains(&metadata.process, process)
+            && !contains(&metadata.process_path, process)
+        {
+            return false;
+        }
+        self.network
+            .is_none_or(|network| metadata.network == network)
+    }
+}
+
+/// A text field that is set to something other than whitespace.
+fn text(field: &Option<String>) -> Option<&str> {
+    field.as_deref().filter(|value| !value.trim().is_empty())
+}
+
+fn contains(value: &str, needle: &str) -> bool {
+    value.to_lowercase().contains(&needle.to_lowercase())
+}
+
+impl CoreManager {
+    /// The running core's connection snapshot, reduced to what `filter`
+    /// matches. The totals are the core's and are never filtered.
+    pub async fn connections(
+        &self,
+        filter: &ConnectionFilter,
+    ) -> Result<ConnectionsSnapshot, Error> {
+        let client = self
+            .control_client(self.inner.options.control_timeout)
+            .await?;
+        let mut snapshot = client.connections().await?;
+        if let Some(connections) = snapshot.connections.as_mut() {
+            connections.retain(|connection| filter.matches(connection));
+        }
+        Ok(snapshot)
+    }
+
+    /// Close the given connections, one request each. Stops at the first
+    /// failure; the ones before it are already closed.
+    pub async fn close_connections(&self, ids: &[Uuid]) -> Result<(), Error> {
+        let client = self
+            .control_client(self.inner.options.control_timeout)
+            .await?;
+        for id in ids {
+            client.close_connection(*id).await?;
+        }
+        Ok(())
+    }
+
+    /// Close every connection `filter` matches at the moment of the call, and
+    /// report which ones those were. An empty filter is refused rather than
+    /// read as "everything": that is [`Self::close_all_connections`], and it
+    /// should never be reached by leaving a field out.
+    pub async fn close_matching_connections(
+        &self,
+        filter: &ConnectionFilter,
+    ) -> Result<Vec<Uuid>, Error> {
+        if filter.is_empty() {
+            return Err(Error::InvalidFilter(
+                "an empty filter would close every connection".to_owned(),
+            ));
+        }
+        let client = self
+            .control_client(self.inner.options.control_timeout)
+            .await?;
+        let ids: Vec<Uuid> = client
+            .connections()
+            .await?
+            .connections
+            .into_iter()
+            .flatten()
+            .filter(|connection| filter.matches(connection))
+            .map(|connection| connection.id)
+            .collect();
+        for id in &ids {
+            client.close_connection(*id).await?;
+        }
+        Ok(ids)
+    }
+
+    pub async fn close_all_connections(&self) -> Result<(), Error> {
+        let client = self
+            .control_client(self.inner.options.control_timeout)
+            .await?;
+        Ok(client.close_all_connections().await?)
+    }
+}
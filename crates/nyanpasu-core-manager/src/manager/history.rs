@@ -0,0 +1,171 @@
+//! A bounded record of the revisions this manager has run, so a bad config
+//! that was accepted can still be undone.
+//!
+//! Each entry keeps the canonical *source* bytes rather than the effective
+//! ones: a rollback re-enters the apply engine, which derives a fresh effective
+//! document for whichever epoch is running by then. Entries last as long as
+//! the manager does. A new manager starts with an empty history, because
+//! nothing a previous one remembered ever ran under it.
+//!
+//! Inline sources are kept here too: a spec whose config arrived as bytes
+//! points at a copy that must live as long as anything can re-read it.
+
+use std::collections::VecDeque;
+
+use camino::Utf8PathBuf;
+
+use crate::{
+    config::{self, ConfigSnapshot},
+    error::Error,
+    kind::CoreKind,
+    spec::InstanceSpec,
+    state::{ConfigRevision, RevisionId, now_ms},
+};
+
+use super::{ApplyOutcome, CoreManager, Ctrl, apply::applicable};
+
+/// One revision this manager has run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevisionRecord {
+    pub revision: ConfigRevision,
+    pub kind: CoreKind,
+    /// The binary it ran, which a rollback to it launches again.
+    pub binary_path: Utf8PathBuf,
+    /// Unix milliseconds at which the revision started running.
+    pub recorded_at: i64,
+}
+
+#[derive(Default)]
+pub(super) struct RevisionHistory {
+    /// Oldest first.
+    entries: VecDeque<HistoryEntry>,
+}
+
+struct HistoryEntry {
+    record: RevisionRecord,
+    /// The spec the revision ran under, with the caller's own config path: a
+    /// rollback re-applies under it, pointed at a kept copy of `copy`, so one
+    /// across a core switch switches back.
+    spec: InstanceSpec,
+    copy: Utf8PathBuf,
+}
+
+impl CoreManager {
+    /// Revisions this manager has run, newest first, the running one included.
+    pub async fn revisions(&self) -> Vec<RevisionRecord> {
+        let ctrl = self.inner.ctrl.lock().await;
+        ctrl.history
+            .entries
+            .iter()
+            .rev()
+            .map(|entry| entry.record.clone())
+            .collect()
+    }
+
+    /// Re-apply a revision from the history through [`Self::apply_config`]'s
+    /// engine, with the same compare-and-swap guard.
+    ///
+    /// The result is a new revision with the old one's source: the route is
+    /// classified afresh against what runs now. The caller's config file is
+    /// not rewritten; the rolled-back spec points at a copy kept the way
+    /// [`Self::apply_inline`] keeps one, so a later restart runs the restored
+    /// config rather than whatever the caller's file holds by then.
+    pub async fn rollback(
+        &self,
+        target: &RevisionId,
+        expected_revision: Option<RevisionId>,
+    ) -> Result<ApplyOutcome, Error> {
+        let mut ctrl = self.inner.ctrl.lock().await;
+        applicable(&ctrl, expected_revision.as_ref())?;
+        let (mut spec, copy) = ctrl
+            .history
+            .entries
+            .iter()
+            .find(|entry| entry.record.revision.id() == *target)
+            .map(|entry| (entry.spec.clone(), entry.copy.clone()))
+            .ok_or_else(|| Error::RevisionNotFound(target.clone()))?;
+        let source = tokio::fs::read(&copy).await?;
+        let snapshot = ConfigSnapshot::inline(&source)?;
+        spec.config_path = self.inner.store.keep_inline(&source).await?;
+        let snapshot = snapshot.kept_at(spec.config_path.clone());
+        let result = self.apply_snapshot(&mut ctrl, spec, snapshot).await;
+        self.record_revision(&mut ctrl).await;
+        self.prune_inline(&ctrl).await;
+        result
+    }
+
+    /// Removes the inline sources nothing can re-read any more: those named
+    /// by neither the running spec, the last spec, nor the history. Run after
+    /// every operation that can drop a reference to one, whether it succeeded
+    /// or not: an inline apply or a rollback, a start, and a stop.
+    ///
+    /// Best effort, like [`Self::record_revision`].
+    pub(super) async fn prune_inline(&self, ctrl: &Ctrl) {
+        let keep = ctrl
+            .current
+            .iter()
+            .map(|active| active.source_spec.config_path.as_path())
+            .chain(ctrl.last_spec.iter().map(|spec| spec.config_path.as_path()))
+            .chain(
+                ctrl.history
+                    .entries
+                    .iter()
+                    .map(|entry| entry.spec.config_path.as_path()),
+            )
+            .collect::<Vec<_>>();
+        if let Err(error) = self.inner.store.prune_inline(&keep).await {
+            tracing::warn!("failed to remove unused inline config sources: {error}");
+        }
+    }
+
+    /// Adds the running revision to the history unless it is already there.
+    ///
+    /// Best effort: a history that cannot be written costs a future rollback,
+    /// never the operation that just succeeded.
+    pub(super) async fn record_revision(&self, ctrl: &mut Ctrl) {
+        let limit = self.inner.options.revision_history;
+        let Some(active) = ctrl.current.as_ref().filter(|_| limit > 0) else {
+            return;
+        };
+        let id = active.revision.id();
+        if ctrl
+            .history
+            .entries
+            .iter()
+            .any(|entry| entry.record.revision.id() == id)
+        {
+            return;
+        }
+        let copy = async {
+            let bytes = config::canonical_bytes(&active.source_document)?;
+            self.inner
+                .store
+                .record_history(id.epoch, id.generation, &bytes)
+                .await
+        }
+        .await;
+        let copy = match copy {
+            Ok(copy) => copy,
+            Err(error) => {
+                tracing::warn!("failed to record config revision {id}: {error}");
+                return;
+            }
+        };
+        ctrl.history.entries.push_back(HistoryEntry {
+            record: RevisionRecord {
+                revision: active.revision.clone(),
+                kind: active.source_spec.core.kind,
+                binary_path: active.source_spec.core.binary_path.clone(),
+                recorded_at: now_ms(),
+            },
+            spec: active.source_spec.clone(),
+            copy,
+        });
+        while ctrl.history.entries.len() > limit {
+            let evicted = ctrl.history.entries.pop_front().expect("longer than limit");
+            if let Err(error) = self.inner.store.remove_history(&evicted.copy).await {
+                tracing::warn!("failed to remove evicted config revision: {error}");
+            }
+        }
+    }
+}
@@ -2,15 +2,20 @@
 //! atomic status publication.
 
 mod apply;
+mod connections;
+mod history;
+mod proxies;
 mod publish;
 mod quarantine;
 mod switching;
+mod telemetry;
 
 use std::sync::{
     Arc,
     atomic::{AtomicU64, Ordering},
 };
 
+use clash_api::ProxyName;
 use enumset::EnumSet;
 use serde_yaml_ng::Mapping;
 use tokio::sync::{broadcast, watch};
@@ -18,10 +23,12 @@ use tokio::sync::{broadcast, watch};
 use crate::{
     Feature, RuntimeFeature,
     capability::{ResolvedFeatures, VersionCache},
-    config::{self, ConfigSnapshot, mihomo},
+    config::{self, ConfigSnapshot, adapter},
     error::Error,
     instance::Instance,
+    kind::CoreKind,
     log::{LOG_CHANNEL_CAPACITY, LogFrame},
+    log_archive::{self, LogPage, LogQuery},
     log_sink::{self, SinkOptions},
     probe::ProbeHandle,
     runtime_store::{RuntimeConfigStore, RuntimeDirectoryLock, StagedRuntimeConfig},
@@ -29,6 +36,9 @@ use crate::{
     state::{ConfigRevision, CoreState, CoreStatus, InstanceStatus, StopReason},
 };
 
+pub use connections::ConnectionFilter;
+use history::RevisionHistory;
+pub use history::RevisionRecord;
 use publish::{instance_core_state, spec_summary};
 use quarantine::{reject_quarantine, sweep_orphans};
 
@@ -42,11 +52,33 @@ pub enum DegradeReason {
     HttpController,
 }
 
+/// One selector group's chosen member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxySelection {
+    pub group: ProxyName,
+    pub proxy: ProxyName,
+}
+
+/// What became of the outgoing epoch's selector choices once its replacement
+/// was ready. A fresh core process starts every selector on its first member,
+/// so without this a switch silently undoes the user's picks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoredSelections {
+    /// Choices the new epoch now carries.
+    pub restored: Vec<ProxySelection>,
+    /// Choices that could not be carried over: the group or member is gone
+    /// from the new config, or the core refused the selection.
+    pub skipped: Vec<ProxySelection>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SwitchOutcome {
-    Graceful,
+    Graceful {
+        selections: RestoredSelections,
+    },
     Hard {
         reason: DegradeReason,
+        selections: RestoredSelections,
     },
     DurabilityUncertain {
         outcome: Box<SwitchOutcome>,
@@ -67,16 +99,25 @@ pub enum ApplyOutcome {
     },
     Restarted {
         revision: ConfigRevision,
+        selections: RestoredSelections,
     },
     /// The process spec itself changed (a different core, binary, or launch
-    /// option), so the old epoch was stopped and a new one started. Distinct
-    /// from [`Self::Restarted`], which replaces the process inside one epoch.
+    /// option), so a new epoch replaced the old one. Distinct from
+    /// [`Self::Restarted`], which replaces the process inside one epoch.
     Switched {
         revision: ConfigRevision,
+        selections: RestoredSelections,
+        /// `None` when the new epoch overlapped the old one and took over its
+        /// listeners without dropping connections; otherwise why the switch
+        /// had to stop the old epoch first.
+        degraded: Option<DegradeReason>,
     },
     RolledBack {
         revision: ConfigRevision,
         failed_apply: String,
+        /// The old process's choices, put back once it was relaunched. Empty
+        /// when the old process never stopped.
+        selections: RestoredSelections,
     },
     DurabilityUncertain {
         outcome: Box<ApplyOutcome>,
@@ -84,6 +125,30 @@ pub enum ApplyOutcome {
     },
 }
 
+/// The route [`CoreManager::apply_config`] is predicted to take.
+///
+/// There is no restart here: a restart is what an in-place route falls back
+/// to when the core rejects it, and only trying can tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlannedRoute {
+    Noop,
+    Patch,
+    Reload,
+    Switch {
+        /// `None` when the new epoch is expected to overlap the old one.
+        degraded: Option<DegradeReason>,
+    },
+}
+
+/// What an apply would do, worked out without staging anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyPlan {
+    pub route: PlannedRoute,
+    /// Dotted key paths of the source config that differ from the running
+    /// one, sorted.
+    pub changed: Vec<String>,
+}
+
 pub struct CoreManager {
     inner: Arc<Inner>,
 }
@@ -102,6 +167,9 @@ struct ProbePlan {
 
 struct Inner {
     options: ManagerOptions,
+    /// Starts as `options.local_ipc_policy`, which nothing reads after
+    /// construction; [`CoreManager::set_local_ipc_policy`] moves it.
+    local_ipc_policy: parking_lot::Mutex<LocalIpcPolicy>,
     probes: ProbePlan,
     store: RuntimeConfigStore,
     ctrl: tokio::sync::Mutex<Ctrl>,
@@ -129,6 +197,7 @@ struct Ctrl {
     current: Option<Active>,
     last_spec: Option<InstanceSpec>,
     quarantine: Vec<QuarantinedEpoch>,
+    history: RevisionHistory,
 }
 
 #[derive(Debug, Clone)]
@@ -145,6 +214,7 @@ struct Active {
     revision: ConfigRevision,
     capabilities: EnumSet<Feature>,
     runtime_features: EnumSet<RuntimeFeature>,
+    version: Option<String>,
     source_document: Mapping,
     effective_document: Mapping,
 }
@@ -156,6 +226,7 @@ struct PreparedLaunch {
     revision: ConfigRevision,
     capabilities: EnumSet<Feature>,
     runtime_features: EnumSet<RuntimeFeature>,
+    version: Option<String>,
     source_document: Mapping,
     effective_document: Mapping,
 }
@@ -163,7 +234,7 @@ struct PreparedLaunch {
 struct PreparedGraceful {
     launch: PreparedLaunch,
     full_staged: StagedRuntimeConfig,
-    restoration: Option<(Box<clash_api::ConfigPatch>, mihomo::RuntimeProjection)>,
+    restoration: Option<(Box<clash_api::ConfigPatch>, adapter::RuntimeProjection)>,
 }
 
 struct PreparedApply {
@@ -173,6 +244,7 @@ struct PreparedApply {
     revision: ConfigRevision,
     capabilities: EnumSet<Feature>,
     runtime_features: EnumSet<RuntimeFeature>,
+    version: Option<String>,
     source_document: Mapping,
     effective_document: Mapping,
     staged: StagedRuntimeConfig,
@@ -252,6 +324,7 @@ impl CoreManager {
             ));
         }
         let max_epoch = sweep_orphans(&store).await?;
+        store.clear_kept_sources().await?;
         let (status_tx, _) = watch::channel(CoreStatus::initial());
         let (log_tx, _) = broadcast::channel(LOG_CHANNEL_CAPACITY);
         // Subscribed here rather than inside the task, and before any instance
@@ -274,6 +347,7 @@ impl CoreManager {
         };
         Ok(Self {
             inner: Arc::new(Inner {
+                local_ipc_policy: parking_lot::Mutex::new(options.local_ipc_policy),
                 options,
                 probes,
                 store,
@@ -305,6 +379,46 @@ impl CoreManager {
         self.inner.log_dir.as_deref()
     }
 
+    /// Changes the core-log archive's rotation limits without restarting the
+    /// sink, under the same rules construction applies. The running file is
+    /// kept; the new size limit decides when it rolls, and a lowered file count
+    /// is enforced at that rollover. A no-op when the sink is disabled.
+    pub async fn set_log_rotation(&self, max_bytes: u64, max_files: usize) -> Result<(), Error> {
+        if max_bytes == 0 {
+            return Err(Error::InvalidManagerOptions(
+                "log_max_bytes must be greater than zero".into(),
+            ));
+        }
+        if max_files == 0 {
+            return Err(Error::InvalidManagerOptions(
+                "log_max_files must be greater than zero".into(),
+            ));
+        }
+        if let Some(log_sink) = self.inner.log_sink.lock().await.as_ref() {
+            log_sink.set_options(SinkOptions {
+                max_bytes,
+                max_files,
+            });
+        }
+        Ok(())
+    }
+
+    /// The local IPC policy for every launch from here on. An epoch already
+    /// running keeps the controller transport it was launched with.
+    pub fn set_local_ipc_policy(&self, policy: LocalIpcPolicy) {
+        *self.inner.local_ipc_policy.lock() = policy;
+    }
+
+    /// One page of the core-log archive, or `None` when the sink is disabled
+    /// and there is no archive to read. Reads only what the sink has already
+    /// written; frames still queued in the writer show up on the next page.
+    pub async fn query_logs(&self, query: &LogQuery) -> Result<Option<LogPage>, Error> {
+        match self.log_dir() {
+            Some(dir) => log_archive::query(dir, query).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
     pub fn status(&self) -> CoreStatus {
         self.inner.status_tx.borrow().clone()
     }
@@ -341,7 +455,10 @@ impl CoreManager {
             }
             self.inner.store.cleanup_epoch(epoch).await?;
         }
-        self.start_locked(&mut ctrl, spec).await
+        let result = self.start_locked(&mut ctrl, spec).await;
+        self.record_revision(&mut ctrl).await;
+        self.prune_inline(&ctrl).await;
+        result
     }
 
     async fn start_locked(&self, ctrl: &mut Ctrl, spec: InstanceSpec) -> Result<(), Error> {
@@ -372,6 +489,7 @@ impl CoreManager {
                 &prepared.source_spec,
                 prepared.capabilities,
                 prepared.runtime_features,
+                prepared.version.as_deref(),
             )),
             Some(prepared.controller.host.clone()),
             Some(prepared.revision.clone()),
@@ -415,6 +533,7 @@ impl CoreManager {
             &prepared.revision,
             prepared.capabilities,
             prepared.runtime_features,
+            prepared.version.as_deref(),
         );
         let forwarder = spawn_forwarder(&self.inner, instance.state(), epoch);
         ctrl.last_spec = Some(prepared.source_spec.clone());
@@ -425,6 +544,7 @@ impl CoreManager {
             revision: prepared.revision,
             capabilities: prepared.capabilities,
             runtime_features: prepared.runtime_features,
+            version: prepared.version,
             source_document: prepared.source_document,
             effective_document: prepared.effective_document,
         });
@@ -437,6 +557,12 @@ impl CoreManager {
     /// the number of possibly live processes; it does not clear quarantine.
     pub async fn stop(&self) -> Result<(), Error> {
         let mut ctrl = self.inner.ctrl.lock().await;
+        let result = self.stop_locked(&mut ctrl).await;
+        self.prune_inline(&ctrl).await;
+        result
+    }
+
+    async fn stop_locked(&self, ctrl: &mut Ctrl) -> Result<(), Error> {
         let Some(active) = ctrl.current.take() else {
             return Err(Error::NotStarted);
         };
@@ -447,6 +573,7 @@ impl CoreManager {
             revision,
             capabilities,
             runtime_features,
+            version,
             ..
         } = active;
         let captured_status = instance.state().borrow().clone();
@@ -455,7 +582,12 @@ impl CoreManager {
             let epoch = instance.epoch();
             self.inner.publish(
                 instance_core_state(epoch, &captured_status.state),
-                Some(spec_summary(&source_spec, capabilities, runtime_features)),
+                Some(spec_summary(
+                    &source_spec,
+                    capabilities,
+                    runtime_features,
+                    version.as_deref(),
+                )),
                 Some(instance.controller().host.clone()),
                 Some(revision),
             );
@@ -464,7 +596,7 @@ impl CoreManager {
                 .await
             {
                 if matches!(error, Error::StopUnconfirmed(_)) {
-                    return Err(self.latch_quarantine(&mut ctrl, epoch, error));
+                    return Err(self.latch_quarantine(ctrl, epoch, error));
                 }
                 return Err(error);
             }
@@ -477,7 +609,12 @@ impl CoreManager {
         let epoch = instance.epoch();
         self.inner.publish(
             CoreState::Stopping { epoch },
-            Some(spec_summary(&source_spec, capabilities, runtime_features)),
+            Some(spec_summary(
+                &source_spec,
+                capabilities,
+                runtime_features,
+                version.as_deref(),
+            )),
             Some(instance.controller().host.clone()),
             Some(revision),
         );
@@ -486,7 +623,7 @@ impl CoreManager {
             .await
         {
             if matches!(error, Error::StopUnconfirmed(_)) {
-                return Err(self.latch_quarantine(&mut ctrl, epoch, error));
+                return Err(self.latch_quarantine(ctrl, epoch, error));
             }
             self.publish_terminal_error(&error);
             return Err(error);
@@ -506,15 +643,48 @@ impl CoreManager {
         Ok(())
     }
 
+    /// Runs the binary's own check on `spec.config_path`. sing-box only reads
+    /// JSON, so its source is checked through the same serialized copy it
+    /// would be launched with rather than as written.
     pub async fn check_config(&self, spec: &InstanceSpec) -> Result<(), Error> {
-        crate::kind::check_config(spec).await
+        if spec.core.kind != CoreKind::SingBox {
+            return crate::kind::check_config(spec).await;
+        }
+        if tokio::fs::metadata(&spec.config_path).await.is_err() {
+            return Err(Error::ConfigNotFound(spec.config_path.clone()));
+        }
+        let snapshot = ConfigSnapshot::load(&spec.config_path).await?;
+        self.check_staged(spec, &snapshot.check_bytes(spec.core.kind)?)
+            .await
+    }
+
+    /// [`Self::check_config`] for a source that arrived as bytes;
+    /// `spec.config_path` is ignored. Parsed first, so a payload that is not a
+    /// YAML mapping never reaches the binary, then staged for the binary to
+    /// read and removed once it has.
+    pub async fn check_inline(&self, spec: &InstanceSpec, source: &[u8]) -> Result<(), Error> {
+        let snapshot = ConfigSnapshot::inline(source)?;
+        match spec.core.kind {
+            CoreKind::SingBox => {
+                self.check_staged(spec, &snapshot.check_bytes(spec.core.kind)?)
+                    .await
+            }
+            _ => self.check_staged(spec, source).await,
+        }
+    }
+
+    async fn check_staged(&self, spec: &InstanceSpec, bytes: &[u8]) -> Result<(), Error> {
+        let staged = self.inner.store.stage(0, bytes).await?;
+        let mut spec = spec.clone();
+        spec.config_path = staged.path().to_owned();
+        crate::kind::check_config(&spec).await
     }
 
     async fn resolve_features(&self, core: &CoreSpec) -> Result<ResolvedFeatures, Error> {
         crate::capability::resolve_features(
             &self.inner.version_cache,
             core,
-            self.inner.options.local_ipc_policy,
+            *self.inner.local_ipc_policy.lock(),
         )
         .await
     }
@@ -522,13 +692,13 @@ impl CoreManager {
     fn warn_http_fallback(
         &self,
         core: &CoreSpec,
-        resolved_version: Option<&str>,
+        resolved: &ResolvedFeatures,
         rewrote_controller: bool,
     ) {
-        if self.inner.options.local_ipc_policy == LocalIpcPolicy::Prefer && !rewrote_controller {
+        if resolved.policy == LocalIpcPolicy::Prefer && !rewrote_controller {
             tracing::warn!(
                 kind = %core.kind,
-                version = resolved_version.or(core.version.as_deref()).unwrap_or("unknown"),
+                version = resolved.version.as_deref().or(core.version.as_deref()).unwrap_or("unknown"),
                 "local IPC is unsupported; falling back to the configured HTTP controller"
             );
         }
@@ -551,13 +721,19 @@ impl CoreManager {
                     revision,
                     capabilities,
                     runtime_features,
+                    version,
                     ..
                 } = active;
                 abort_and_await(forwarder).await;
                 let epoch = instance.epoch();
                 self.inner.publish(
                     CoreState::Stopping { epoch },
-                    Some(spec_summary(&source_spec, capabilities, runtime_features)),
+                    Some(spec_summary(
+                        &source_spec,
+                        capabilities,
+                        runtime_features,
+                        version.as_deref(),
+                    )),
                     Some(instance.controller().host.clone()),
                     Some(revision),
                 );
@@ -0,0 +1,222 @@
+//! Proxy-group control on behalf of callers that must not hold the controller
+//! secret.
+//!
+//! Every call resolves the active epoch's controller afresh and holds the
+//! control lock only for that lookup, never across the HTTP request. A call
+//! that races a switch therefore either waits for it and reaches the new epoch
+//! or reaches the old one before it is stopped; a caller keeps no client that a
+//! switch could leave stale.
+//!
+//! The same module carries selector choices across epochs. Those helpers run
+//! inside a switch that already holds the control lock, so they take the
+//! controller they talk to instead of resolving it.
+
+use std::time::Duration;
+
+use clash_api::{DelayQuery, IndexMap, Proxy, ProxyName};
+
+use crate::{error::Error, instance::Instance, spec::ResolvedController, state::HealthState};
+
+use super::{CoreManager, Ctrl, ProxySelection, RestoredSelections};
+
+/// The group type that accepts a selection; every other type picks for itself.
+const SELECTOR: &str = "Selector";
+
+/// The longest a stop waits on the outgoing core to list its selections. The
+/// stop itself has its own timeout; this is only what it may be delayed by.
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(2);
+
+impl CoreManager {
+    /// Every proxy group the running core reports, in its own order.
+    pub async fn proxy_groups(&self) -> Result<Vec<Proxy>, Error> {
+        let client = self
+            .control_client(self.inner.options.control_timeout)
+            .await?;
+        Ok(client.groups().await?)
+    }
+
+    /// Point a selector group at one of its members.
+    pub async fn select_proxy(&self, group: &str, proxy: &str) -> Result<(), Error> {
+        let client = self
+            .control_client(self.inner.options.control_timeout)
+            .await?;
+        client
+            .select_proxy(&ProxyName::from(group), &ProxyName::from(proxy))
+            .await?;
+        Ok(())
+    }
+
+    /// URL-test a single proxy, in milliseconds.
+    pub async fn proxy_delay(
+        &self,
+        proxy: &str,
+        url: &str,
+        timeout: Duration,
+    ) -> Result<u16, Error> {
+        let query = delay_query(url, timeout)?;
+        let client = self.control_client(self.delay_timeout(timeout)).await?;
+        Ok(client
+            .proxy_delay(&ProxyName::from(proxy), &query)
+            .await?
+            .delay)
+    }
+
+    /// URL-test every member of a group at once, in milliseconds per member.
+    pub async fn group_delay(
+        &self,
+        group: &str,
+        url: &str,
+        timeout: Duration,
+    ) -> Result<IndexMap<ProxyName, u16>, Error> {
+        let query = delay_query(url, timeout)?;
+        let client = self.control_client(self.delay_timeout(timeout)).await?;
+        Ok(client.group_delay(&ProxyName::from(group), &query).await?)
+    }
+
+    pub(super) async fn active_controller(&self) -> Result<ResolvedController, Error> {
+        let ctrl = self.inner.ctrl.lock().await;
+        ctrl.current
+            .as_ref()
+            .map(|active| active.instance.controller().clone())
+            .ok_or(Error::NotStarted)
+    }
+
+    pub(super) async fn control_client(
+        &self,
+        timeout: Duration,
+    ) -> Result<clash_api::Client, Error> {
+        let controller = self.active_controller().await?;
+        crate::health::build_control_client(&controller, timeout)
+    }
+
+    /// Every selector group's current member on an epoch about to stop.
+    ///
+    /// Best effort: the core is being replaced anyway, and refusing the switch
+    /// over a lost choice would be the worse failure. A core already failing
+    /// its health checks is not asked at all, and a slow one is given up on
+    /// after [`SNAPSHOT_TIMEOUT`], so a hung core is stopped without waiting
+    /// out the full control timeout first.
+    pub(super) async fn snapshot_selections(&self, instance: &Instance) -> Vec<ProxySelection> {
+        let failing = instance
+            .state()
+            .borrow()
+            .health
+            .as_ref()
+            .is_some_and(|health| health.state == HealthState::Unhealthy);
+        if failing {
+            tracing::warn!(
+                "not reading proxy selections from an unhealthy core before stopping it"
+            );
+            return Vec::new();
+        }
+        let timeout = self.inner.options.control_timeout.min(SNAPSHOT_TIMEOUT);
+        let groups = match self.groups_at(instance.controller(), timeout).await {
+            Ok(groups) => groups,
+            Err(error) => {
+                tracing::warn!("could not read proxy selections before stopping: {error}");
+                return Vec::new();
+            }
+        };
+        groups
+            .into_iter()
+            .filter(|group| group.proxy_type == SELECTOR)
+            .filter_map(|group| {
+                Some(ProxySelection {
+                    proxy: group.now?,
+                    group: group.name,
+                })
+            })
+            .collect()
+    }
+
+    /// Point the active epoch's selectors back at `saved`, once it is ready.
+    ///
+    /// A choice whose group is no longer a selector, or whose member the group
+    /// no longer lists, is skipped rather than forced: the new config won.
+    pub(super) async fn restore_selections(
+        &self,
+        ctrl: &Ctrl,
+        saved: Vec<ProxySelection>,
+    ) -> RestoredSelections {
+        let mut report = RestoredSelections::default();
+        let Some(active) = ctrl.current.as_ref().filter(|_| !saved.is_empty()) else {
+            report.skipped = saved;
+            return report;
+        };
+        let fetched = async {
+            let client = crate::health::build_control_client(
+                active.instance.controller(),
+                self.inner.options.control_timeout,
+            )?;
+            let groups = client.groups().await?;
+            Ok::<_, Error>((client, groups))
+        }
+        .await;
+        let (client, groups) = match fetched {
+            Ok(fetched) => fetched,
+            Err(error) => {
+                tracing::warn!("could not restore proxy selections: {error}");
+                report.skipped = saved;
+                return report;
+            }
+        };
+        for selection in saved {
+            let Some(group) = groups.iter().find(|group| {
+                group.name == selection.group
+                    && group.proxy_type == SELECTOR
+                    && group
+                        .all
+                        .as_ref()
+                        .is_some_and(|all| all.contains(&selection.proxy))
+            }) else {
+                report.skipped.push(selection);
+                continue;
+            };
+            if group.now.as_ref() == Some(&selection.proxy) {
+                report.restored.push(selection);
+                continue;
+            }
+            match client
+                .select_proxy(&selection.group, &selection.proxy)
+                .await
+            {
+                Ok(()) => report.restored.push(selection),
+                Err(error) => {
+                    tracing::warn!(
+                        "could not restore selection {} in {}: {error}",
+                        selection.proxy,
+                        selection.group
+                    );
+                    report.skipped.push(selection);
+                }
+            }
+        }
+        report
+    }
+
+    async fn groups_at(
+        &self,
+        controller: &ResolvedController,
+        timeout: Duration,
+    ) -> Result<Vec<Proxy>, Error> {
+        let client = crate::health::build_control_client(controller, timeout)?;
+        Ok(client.groups().await?)
+    }
+
+    /// The core holds the request open for the whole test, so the client's own
+    /// deadline has to outlast it rather than cut it short.
+    fn delay_timeout(&self, timeout: Duration) -> Duration {
+        self.inner.options.control_timeout + timeout
+    }
+}
+
+fn delay_query(url: &str, timeout: Duration) -> Result<DelayQuery, Error> {
+    match url.parse() {
+        Ok(url) => Ok(DelayQuery::new(url, timeout)?),
+        Err(error) => Err(clash_api::Error::InvalidArgument {
+            argument: "url",
+            message: error.to_string(),
+        }
+        .into()),
+    }
+}
@@ -41,6 +41,7 @@ impl Inner {
             &active.revision,
             active.capabilities,
             active.runtime_features,
+            active.version.as_deref(),
         );
     }
 
@@ -52,13 +53,19 @@ impl Inner {
         revision: &ConfigRevision,
         capabilities: enumset::EnumSet<Feature>,
         runtime_features: enumset::EnumSet<RuntimeFeature>,
+        version: Option<&str>,
     ) {
         let health = instance.state().borrow().health.clone();
         self.status_tx.send_modify(|status| {
             let lifecycle_changed = status.state != state;
             status.state = state;
             status.health = health;
-            status.spec = Some(spec_summary(source_spec, capabilities, runtime_features));
+            status.spec = Some(spec_summary(
+                source_spec,
+                capabilities,
+                runtime_features,
+                version,
+            ));
             status.controller = Some(instance.controller().host.clone());
             status.revision = Some(revision.clone());
             if lifecycle_changed {
@@ -117,12 +124,17 @@ pub(super) fn spec_summary(
     spec: &InstanceSpec,
     capabilities: enumset::EnumSet<Feature>,
     runtime_features: enumset::EnumSet<RuntimeFeature>,
+    version: Option<&str>,
 ) -> SpecSummary {
     SpecSummary {
         kind: spec.core.kind,
+        binary_path: spec.core.binary_path.clone(),
+        version: version.map(str::to_owned),
+        distribution: spec.core.distribution.clone(),
         config_path: spec.config_path.clone(),
         capabilities: capabilities.iter().collect(),
         runtime_features: runtime_features.iter().collect(),
+        options: spec.options.clone(),
     }
 }
 
@@ -7,21 +7,21 @@ use crate::{
     },
     error::Error,
     instance::Instance,
-    kind::CoreKind,
+    kind::{ClashCoreKind, CoreKind},
     probe::ProbePhase,
     spec::{InstanceSpec, ResolvedController},
     state::{ConfigRevision, CoreState},
 };
 
 use super::{
-    Active, CoreManager, Ctrl, DegradeReason, PreparedGraceful, PreparedLaunch, SwitchOutcome,
-    abort_and_await,
+    Active, CoreManager, Ctrl, DegradeReason, PreparedGraceful, PreparedLaunch, RestoredSelections,
+    SwitchOutcome, abort_and_await,
     publish::spec_summary,
     quarantine::{record_quarantine, reject_quarantine},
     spawn_forwarder,
 };
 
-fn graceful_degrade_reason(
+pub(super) fn graceful_degrade_reason(
     local_controller: bool,
     kind: CoreKind,
     overlap_block: Option<OverlapBlock>,
@@ -29,7 +29,7 @@ fn graceful_degrade_reason(
     if !local_controller {
         return Some(DegradeReason::HttpController);
     }
-    if !matches!(kind, CoreKind::Mihomo) {
+    if !matches!(kind, CoreKind::Clash(ClashCoreKind::Mihomo)) {
         return Some(DegradeReason::UnsupportedKind);
     }
     if let Some(block) = overlap_block {
@@ -46,13 +46,17 @@ impl CoreManager {
         let mut ctrl = self.inner.ctrl.lock().await;
         reject_quarantine(&ctrl)?;
         let spec = ctrl.last_spec.clone().ok_or(Error::NotStarted)?;
-        self.switch_locked(&mut ctrl, spec).await
+        let result = self.switch_locked(&mut ctrl, spec).await;
+        self.record_revision(&mut ctrl).await;
+        result
     }
 
     pub async fn switch(&self, spec: InstanceSpec) -> Result<SwitchOutcome, Error> {
         let mut ctrl = self.inner.ctrl.lock().await;
         reject_quarantine(&ctrl)?;
-        self.switch_locked(&mut ctrl, spec).await
+        let result = self.switch_locked(&mut ctrl, spec).await;
+        self.record_revision(&mut ctrl).await;
+        result
     }
 
     async fn switch_locked(
@@ -83,6 +87,7 @@ impl CoreManager {
             self.start_locked(ctrl, spec).await?;
             return Ok(SwitchOutcome::Hard {
                 reason: DegradeReason::NotRunning,
+                selections: RestoredSelections::default(),
             });
         }
 
@@ -96,8 +101,8 @@ impl CoreManager {
             mihomo::overlap_block(snapshot.document()),
         ) {
             Some(reason) => {
-                self.hard_switch(ctrl, spec, snapshot, resolved).await?;
-                Ok(SwitchOutcome::Hard { reason })
+                let selections = self.hard_switch(ctrl, spec, snapshot, resolved).await?;
+                Ok(SwitchOutcome::Hard { reason, selections })
             }
             None => self.graceful_switch(ctrl, spec, snapshot, resolved).await,
         }
@@ -109,7 +114,7 @@ impl CoreManager {
         spec: InstanceSpec,
         snapshot: ConfigSnapshot,
         resolved: ResolvedFeatures,
-    ) -> Result<(), Error> {
+    ) -> Result<RestoredSelections, Error> {
         let epoch = self.next_epoch();
         let prepared = match self
             .prepare_launch_with_features(&spec, epoch, &snapshot, resolved)
@@ -132,6 +137,7 @@ impl CoreManager {
                 &prepared.source_spec,
                 prepared.capabilities,
                 prepared.runtime_features,
+                prepared.version.as_deref(),
             )),
             Some(prepared.controller.host.clone()),
             Some(prepared.revision.clone()),
@@ -140,6 +146,7 @@ impl CoreManager {
         let old = ctrl.current.take().expect("running checked by caller");
         abort_and_await(old.forwarder).await;
         let old_epoch = old.instance.epoch();
+        let saved = self.snapshot_selections(&old.instance).await;
         if let Err(error) = old
             .instance
             .stop_and_confirm_dead(self.inner.options.stop_timeout)
@@ -156,7 +163,8 @@ impl CoreManager {
             self.publish_terminal_error(&error);
             return Err(error);
         }
-        self.start_prepared(ctrl, prepared).await
+        self.start_prepared(ctrl, prepared).await?;
+        Ok(self.restore_selections(ctrl, saved).await)
     }
 
     async fn graceful_switch(
@@ -166,7 +174,6 @@ impl CoreManager {
         snapshot: ConfigSnapshot,
         resolved: ResolvedFeatures,
     ) -> Result<SwitchOutcome, Error> {
-        let old_epoch = ctrl.current.as_ref().map(|active| active.instance.epoch());
         let epoch = self.next_epoch();
         let prepared = match self
             .prepare_graceful(&spec, epoch, &snapshot, resolved)
@@ -179,6 +186,19 @@ impl CoreManager {
                 return Err(error);
             }
         };
+        self.graceful_switch_prepared(ctrl, epoch, prepared).await
+    }
+
+    /// Everything after preparation. Until the bootstrap epoch is ready the old
+    /// epoch is never touched, so a failure up to that point leaves it current
+    /// and running; the apply path relies on that to report a rollback.
+    pub(super) async fn graceful_switch_prepared(
+        &self,
+        ctrl: &mut Ctrl,
+        epoch: u64,
+        prepared: PreparedGraceful,
+    ) -> Result<SwitchOutcome, Error> {
+        let old_epoch = ctrl.current.as_ref().map(|active| active.instance.epoch());
         let PreparedGraceful {
             launch,
             full_staged,
@@ -193,6 +213,7 @@ impl CoreManager {
                 &launch.source_spec,
                 launch.capabilities,
                 launch.runtime_features,
+                launch.version.as_deref(),
             )),
             Some(launch.controller.host.clone()),
             Some(launch.revision.clone()),
@@ -237,6 +258,7 @@ impl CoreManager {
         let old = ctrl.current.take().expect("running checked by caller");
         abort_and_await(old.forwarder).await;
         let old_epoch = old.instance.epoch();
+        let saved = self.snapshot_selections(&old.instance).await;
         if let Err(error) = old
             .instance
             .stop_and_confirm_dead(self.inner.options.stop_timeout)
@@ -308,12 +330,13 @@ impl CoreManager {
         .unwrap_or(false);
         if reconciled {
             self.install_switched(ctrl, instance, launch);
+            let selections = self.restore_selections(ctrl, saved).await;
             let result = self
                 .inner
                 .store
                 .cleanup_epoch(old_epoch)
                 .await
-                .map(|()| SwitchOutcome::Graceful);
+                .map(|()| SwitchOutcome::Graceful { selections });
             return with_switch_durability_result(result, durability_warning);
         }
 
@@ -347,6 +370,7 @@ impl CoreManager {
             }
         };
         self.install_switched(ctrl, replacement, launch);
+        let selections = self.restore_selections(ctrl, saved).await;
         let result =
             self.inner
                 .store
@@ -354,6 +378,7 @@ impl CoreManager {
                 .await
                 .map(|()| SwitchOutcome::Hard {
                     reason: DegradeReason::PatchFailed,
+                    selections,
                 });
         with_switch_durability_result(result, durability_warning)
     }
@@ -368,6 +393,7 @@ impl CoreManager {
             &prepared.revision,
             prepared.capabilities,
             prepared.runtime_features,
+            prepared.version.as_deref(),
         );
         let forwarder = spawn_forwarder(&self.inner, instance.state(), epoch);
         ctrl.last_spec = Some(prepared.source_spec.clone());
@@ -378,6 +404,7 @@ impl CoreManager {
             revision: prepared.revision,
             capabilities: prepared.capabilities,
             runtime_features: prepared.runtime_features,
+            version: prepared.version,
             source_document: prepared.source_document,
             effective_document: prepared.effective_document,
         });
@@ -405,7 +432,7 @@ impl CoreManager {
             .await
     }
 
-    async fn prepare_launch_with_features(
+    pub(super) async fn prepare_launch_with_features(
         &self,
         spec: &InstanceSpec,
         epoch: u64,
@@ -414,16 +441,13 @@ impl CoreManager {
     ) -> Result<PreparedLaunch, Error> {
         debug_assert_eq!(snapshot.source_path(), spec.config_path);
         let prepared = snapshot.prepare_full(
+            spec.core.kind,
             self.inner.options.controller_template.as_deref(),
             self.inner.store.dir(),
             epoch,
             resolved.runtime,
         )?;
-        self.warn_http_fallback(
-            &spec.core,
-            resolved.version.as_deref(),
-            prepared.rewrote_controller,
-        );
+        self.warn_http_fallback(&spec.core, &resolved, prepared.rewrote_controller);
         let staged = self.inner.store.stage(epoch, &prepared.bytes).await?;
 
         let mut check_spec = spec.clone();
@@ -447,12 +471,13 @@ impl CoreManager {
             },
             capabilities: resolved.capabilities,
             runtime_features: resolved.runtime,
+            version: resolved.version,
             source_document: snapshot.document().clone(),
             effective_document: prepared.document,
         })
     }
 
-    async fn prepare_graceful(
+    pub(super) async fn prepare_graceful(
         &self,
         spec: &InstanceSpec,
         epoch: u64,
@@ -461,22 +486,20 @@ impl CoreManager {
     ) -> Result<PreparedGraceful, Error> {
         debug_assert_eq!(snapshot.source_path(), spec.config_path);
         let full = snapshot.prepare_full(
+            spec.core.kind,
             self.inner.options.controller_template.as_deref(),
             self.inner.store.dir(),
             epoch,
             resolved.runtime,
         )?;
         let bootstrap = snapshot.prepare_bootstrap(
+            spec.core.kind,
             self.inner.options.controller_template.as_deref(),
             self.inner.store.dir(),
             epoch,
             resolved.runtime,
         )?;
-        self.warn_http_fallback(
-            &spec.core,
-            resolved.version.as_deref(),
-            full.rewrote_controller,
-        );
+        self.warn_http_fallback(&spec.core, &resolved, full.rewrote_controller);
         if full.controller.host != bootstrap.controller.host
             || full.controller.secret != bootstrap.controller.secret
         {
@@ -513,6 +536,7 @@ impl CoreManager {
                 },
                 capabilities: resolved.capabilities,
                 runtime_features: resolved.runtime,
+                version: resolved.version,
                 source_document: snapshot.document().clone(),
                 effective_document: full.document,
             },
@@ -579,21 +603,32 @@ mod tests {
     #[test]
     fn switch_matrix_matches_the_spec() {
         assert_eq!(
-            graceful_degrade_reason(false, CoreKind::Mihomo, None),
+            graceful_degrade_reason(false, CoreKind::Clash(ClashCoreKind::Mihomo), None),
             Some(DegradeReason::HttpController)
         );
         assert_eq!(
-            graceful_degrade_reason(true, CoreKind::ClashRust, None),
+            graceful_degrade_reason(true, CoreKind::Clash(ClashCoreKind::ClashRust), None),
             Some(DegradeReason::UnsupportedKind)
         );
         assert_eq!(
-            graceful_degrade_reason(true, CoreKind::Mihomo, Some(OverlapBlock::DnsListen)),
+            graceful_degrade_reason(
+                true,
+                CoreKind::Clash(ClashCoreKind::Mihomo),
+                Some(OverlapBlock::DnsListen)
+            ),
             Some(DegradeReason::DnsListen)
         );
         assert_eq!(
-            graceful_degrade_reason(true, CoreKind::Mihomo, Some(OverlapBlock::InboundSurface)),
+            graceful_degrade_reason(
+                true,
+                CoreKind::Clash(ClashCoreKind::Mihomo),
+                Some(OverlapBlock::InboundSurface)
+            ),
             Some(DegradeReason::InboundConflict)
         );
-        assert_eq!(graceful_degrade_reason(true, CoreKind::Mihomo, None), None);
+        assert_eq!(
+            graceful_degrade_reason(true, CoreKind::Clash(ClashCoreKind::Mihomo), None),
+            None
+        );
     }
 }
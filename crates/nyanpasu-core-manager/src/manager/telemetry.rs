@@ -0,0 +1,38 @@
+//! Live traffic and memory streams on behalf of callers that must not hold
+//! the controller secret.
+//!
+//! Like proxy control, each call resolves the active epoch's controller afresh
+//! and keeps no client past it. A stream belongs to the epoch it was opened on
+//! and simply ends with it: following the core across restarts and switches is
+//! the caller's job, keyed on the epoch each call returns.
+
+use clash_api::{HttpStream, Memory, Traffic};
+
+use crate::error::Error;
+
+use super::CoreManager;
+
+impl CoreManager {
+    /// The running core's `/traffic` stream, with the epoch it belongs to.
+    pub async fn traffic(&self) -> Result<(u64, HttpStream<Traffic>), Error> {
+        let (epoch, client) = self.stream_client().await?;
+        Ok((epoch, client.traffic().await?))
+    }
+
+    /// The running core's `/memory` stream, with the epoch it belongs to.
+    pub async fn memory(&self) -> Result<(u64, HttpStream<Memory>), Error> {
+        let (epoch, client) = self.stream_client().await?;
+        Ok((epoch, client.memory().await?))
+    }
+
+    async fn stream_client(&self) -> Result<(u64, clash_api::Client), Error> {
+        let (epoch, controller) = {
+            let ctrl = self.inner.ctrl.lock().await;
+            let active = ctrl.current.as_ref().ok_or(Error::NotStarted)?;
+            (active.revision.epoch, active.instance.controller().clone())
+        };
+        let client =
+            crate::health::build_stream_client(&controller, self.inner.options.control_timeout)?;
+        Ok((epoch, client))
+    }
+}
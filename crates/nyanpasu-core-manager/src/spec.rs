@@ -3,7 +3,8 @@
 use std::time::Duration;
 
 use camino::Utf8PathBuf;
-use nyanpasu_utils::process::{Backoff, RestartPolicy};
+pub use nyanpasu_core_metadata::{CoreDistribution, VariantTag};
+pub use nyanpasu_utils::process::{Backoff, RestartPolicy};
 use tokio_util::sync::CancellationToken;
 
 use crate::{health::HealthPolicy, kind::CoreKind};
@@ -16,6 +17,9 @@ pub struct CoreSpec {
     /// Authoritative capability version. The manager probes `-v` when absent.
     pub version: Option<String>,
     pub features: Vec<String>,
+    /// Which distributed build this is, as the caller's resource layer knows
+    /// it. Reported in status, never acted on.
+    pub distribution: Option<CoreDistribution>,
 }
 
 /// Immutable per-epoch launch spec. Changing the config means a new epoch.
@@ -81,7 +85,9 @@ pub struct ManagerOptions {
     /// Manager-owned runtime artifact directory: effective configs, pid files,
     /// and by default the epoch-scoped Unix sockets. Required.
     pub runtime_dir: Option<Utf8PathBuf>,
-    /// Whether local IPC is required, preferred, or disabled.
+    /// Whether local IPC is required, preferred, or disabled, until
+    /// [`CoreManager::set_local_ipc_policy`](crate::CoreManager::set_local_ipc_policy)
+    /// changes it.
     pub local_ipc_policy: LocalIpcPolicy,
     /// Endpoint template containing `{epoch}`; platform default when `None`.
     /// Only consulted when the policy selects local IPC, but validated at
@@ -101,6 +107,10 @@ pub struct ManagerOptions {
     /// How many core-log files to keep, the active one included. With
     /// `log_max_bytes` this is the directory's hard disk budget.
     pub log_max_files: usize,
+    /// How many committed revisions to keep for rollback, the running one
+    /// included. Each costs one canonical config copy in `runtime_dir`; zero
+    /// keeps none and makes every rollback fail as not found.
+    pub revision_history: usize,
 }
 
 impl Default for ManagerOptions {
@@ -123,6 +133,7 @@ impl Default for ManagerOptions {
             // size. At roughly 300-600 B per record a file covers 7k-14k lines.
             log_max_bytes: 4 * 1024 * 1024,
             log_max_files: 5,
+            revision_history: 10,
         }
     }
 }
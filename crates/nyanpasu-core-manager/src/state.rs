@@ -1,8 +1,9 @@
 //! Instance and manager state machines and the published status snapshot.
 
 use camino::Utf8PathBuf;
+use nyanpasu_core_metadata::CoreDistribution;
 
-use crate::{Feature, RuntimeFeature, kind::CoreKind};
+use crate::{Feature, RuntimeFeature, kind::CoreKind, spec::InstanceOptions};
 
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -133,11 +134,20 @@ pub enum CoreState {
 #[derive(Debug, Clone)]
 pub struct SpecSummary {
     pub kind: CoreKind,
+    /// The binary the active epoch was launched from.
+    pub binary_path: Utf8PathBuf,
+    /// The version capabilities were resolved from: the spec's own, or what
+    /// the binary printed when asked. `None` when neither was available, which
+    /// only a kind with no version-gated features runs without.
+    pub version: Option<String>,
+    pub distribution: Option<CoreDistribution>,
     pub config_path: Utf8PathBuf,
     /// Capabilities the active core build supports, resolved from its version.
     pub capabilities: Vec<Feature>,
     /// Functionality the manager actually enabled for the active epoch.
     pub runtime_features: Vec<RuntimeFeature>,
+    /// What the active epoch was launched with.
+    pub options: InstanceOptions,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -9,8 +9,8 @@ use std::{
 
 use camino::{Utf8Path, Utf8PathBuf};
 use nyanpasu_core_manager::{
-    CoreKind, CoreManager, CoreState, Error, Feature, Host, LocalIpcPolicy, ManagerOptions,
-    RuntimeFeature,
+    ClashCoreKind, CoreDistribution, CoreKind, CoreManager, CoreState, Error, Feature, Host,
+    LocalIpcPolicy, ManagerOptions, RuntimeFeature, VariantTag,
 };
 
 fn unique_template() -> Option<String> {
@@ -125,7 +125,7 @@ async fn force_rejects_unsupported_mihomo_before_staging_check_or_spawn() {
     assert!(matches!(
         error,
         Error::RequiredLocalIpcUnsupported {
-            kind: CoreKind::Mihomo,
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
             ref version,
         } if version == unsupported_mihomo_version()
     ));
@@ -138,6 +138,27 @@ async fn force_rejects_unsupported_mihomo_before_staging_check_or_spawn() {
     ));
 }
 
+#[tokio::test]
+async fn a_changed_policy_governs_the_next_launch() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let port = common::free_port();
+    let config = common::write_config(&dir, &format!("external-controller: 127.0.0.1:{port}\n"));
+    let mut spec = common::mihomo_spec(&dir, config);
+    spec.core.version = Some(unsupported_mihomo_version().into());
+    let manager = manager_with_policy(dir.join("runtime"), LocalIpcPolicy::Disable).await;
+
+    manager.set_local_ipc_policy(LocalIpcPolicy::Force);
+    let error = manager
+        .start(spec.clone())
+        .await
+        .expect_err("Force must reject");
+    assert!(matches!(error, Error::RequiredLocalIpcUnsupported { .. }));
+
+    manager.set_local_ipc_policy(LocalIpcPolicy::Disable);
+    manager.start(spec).await.expect("Disable starts over HTTP");
+    manager.shutdown().await.unwrap();
+}
+
 #[tokio::test]
 async fn force_rejects_clash_premium_without_probing_its_version() {
     let (_guard, dir) = common::utf8_tempdir();
@@ -146,7 +167,7 @@ async fn force_rejects_clash_premium_without_probing_its_version() {
     let port = common::free_port();
     let config = common::write_config(&dir, &format!("external-controller: 127.0.0.1:{port}\n"));
     let mut spec = common::mihomo_spec(&dir, config);
-    spec.core.kind = CoreKind::ClashPremium;
+    spec.core.kind = CoreKind::Clash(ClashCoreKind::ClashPremium);
     spec.core.binary_path = binary.clone();
     spec.core.version = None;
     let manager = manager_with_policy(runtime.clone(), LocalIpcPolicy::Force).await;
@@ -156,7 +177,7 @@ async fn force_rejects_clash_premium_without_probing_its_version() {
     assert!(matches!(
         error,
         Error::RequiredLocalIpcUnsupported {
-            kind: CoreKind::ClashPremium,
+            kind: CoreKind::Clash(ClashCoreKind::ClashPremium),
             ..
         }
     ));
@@ -164,6 +185,32 @@ async fn force_rejects_clash_premium_without_probing_its_version() {
     assert!(!runtime.join("config-1.yaml").exists());
 }
 
+#[tokio::test]
+async fn a_kind_without_gated_features_still_reports_its_probed_version() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let binary = copied_probe_binary(&dir);
+    let port = common::free_port();
+    let config = common::write_config(
+        &dir,
+        &format!("experimental:\n  clash_api:\n    external_controller: 127.0.0.1:{port}\n"),
+    );
+    let mut spec = common::mihomo_spec(&dir, config);
+    spec.core.kind = CoreKind::SingBox;
+    spec.core.binary_path = binary.clone();
+    spec.core.version = None;
+    let manager = manager_with_policy(dir.join("runtime"), LocalIpcPolicy::Disable).await;
+
+    manager.start(spec).await.expect("sing-box starts");
+
+    // The fake answers `-v` as Mihomo, so this is sing-box's own subcommand.
+    let status = manager.status();
+    let summary = status.spec.as_ref().expect("spec summary");
+    assert_eq!(summary.version.as_deref(), Some("sing-box version 1.10.1"));
+    assert!(summary.capabilities.is_empty());
+    assert_eq!(probe_count(&binary), 1);
+    manager.shutdown().await.unwrap();
+}
+
 #[tokio::test]
 async fn prefer_falls_back_to_the_upstream_http_controller_and_secret() {
     let (_guard, dir) = common::utf8_tempdir();
@@ -188,6 +235,10 @@ async fn prefer_falls_back_to_the_upstream_http_controller_and_secret() {
         Some(Host::Http(url)) if url.as_str() == format!("http://127.0.0.1:{port}/")
     ));
     let summary = status.spec.as_ref().expect("spec summary");
+    assert_eq!(
+        summary.version.as_deref(),
+        Some(unsupported_mihomo_version())
+    );
     assert!(!summary.capabilities.contains(&platform_local_feature()));
     assert!(!summary.runtime_features.contains(&RuntimeFeature::LocalIpc));
     let runtime = std::fs::read_to_string(&status.revision.as_ref().unwrap().runtime_path).unwrap();
@@ -209,15 +260,18 @@ async fn disable_uses_http_even_when_local_ipc_is_supported() {
         ),
     );
     let manager = manager_with_policy(dir.join("runtime"), LocalIpcPolicy::Disable).await;
+    let mut spec = common::mihomo_spec(&dir, config);
+    let mut distribution = CoreDistribution::new(spec.core.kind);
+    distribution.tags.insert(VariantTag::new("channel:alpha"));
+    spec.core.distribution = Some(distribution.clone());
 
-    manager
-        .start(common::mihomo_spec(&dir, config))
-        .await
-        .expect("Disable start");
+    manager.start(spec).await.expect("Disable start");
 
     let status = manager.status();
     assert!(matches!(status.controller, Some(Host::Http(_))));
     let summary = status.spec.as_ref().unwrap();
+    // Reported as given: the manager never inspects the distribution.
+    assert_eq!(summary.distribution, Some(distribution));
     // The capability is still reported even though policy keeps it off.
     assert!(summary.capabilities.contains(&platform_local_feature()));
     assert!(!summary.runtime_features.contains(&RuntimeFeature::LocalIpc));
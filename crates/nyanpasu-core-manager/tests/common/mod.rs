@@ -8,7 +8,7 @@ use std::{sync::Arc, time::Duration};
 
 use camino::{Utf8Path, Utf8PathBuf};
 use nyanpasu_core_manager::{
-    CoreKind, CoreSpec, HealthPolicy, InstanceOptions, InstanceSpec,
+    ClashCoreKind, CoreKind, CoreSpec, HealthPolicy, InstanceOptions, InstanceSpec,
     state::{HealthState, InstanceState, InstanceStatus},
 };
 use nyanpasu_utils::process::{Backoff, RestartPolicy};
@@ -53,10 +53,11 @@ pub fn write_config(dir: &Utf8Path, body: &str) -> Utf8PathBuf {
 pub fn mihomo_spec(dir: &Utf8Path, config_path: Utf8PathBuf) -> InstanceSpec {
     InstanceSpec {
         core: CoreSpec {
-            kind: CoreKind::Mihomo,
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
             binary_path: fake_core_bin(),
             version: Some("v1.18.9".into()),
             features: Vec::new(),
+            distribution: None,
         },
         config_path,
         working_dir: dir.to_owned(),
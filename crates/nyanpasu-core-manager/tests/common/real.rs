@@ -5,7 +5,8 @@ use std::time::Duration;
 
 use camino::{Utf8Path, Utf8PathBuf};
 use nyanpasu_core_manager::{
-    CoreKind, CoreManager, CoreSpec, InstanceOptions, InstanceSpec, LocalIpcPolicy, ManagerOptions,
+    ClashCoreKind, CoreKind, CoreManager, CoreSpec, InstanceOptions, InstanceSpec, LocalIpcPolicy,
+    ManagerOptions,
 };
 
 /// A real core under test: its manager kind and the binary name in tests/bin.
@@ -19,22 +20,22 @@ pub struct RealCore {
 }
 
 pub const MIHOMO: RealCore = RealCore {
-    kind: CoreKind::Mihomo,
+    kind: CoreKind::Clash(ClashCoreKind::Mihomo),
     name: "mihomo",
     system_ipc: true,
 };
 pub const CLASH_RS: RealCore = RealCore {
-    kind: CoreKind::ClashRust,
+    kind: CoreKind::Clash(ClashCoreKind::ClashRust),
     name: "clash-rs",
     system_ipc: true,
 };
 pub const CLASH_PREMIUM: RealCore = RealCore {
-    kind: CoreKind::ClashPremium,
+    kind: CoreKind::Clash(ClashCoreKind::ClashPremium),
     name: "clash",
     system_ipc: false,
 };
 pub const MEOW: RealCore = RealCore {
-    kind: CoreKind::Meow,
+    kind: CoreKind::Clash(ClashCoreKind::Meow),
     name: "meow",
     system_ipc: false,
 };
@@ -88,6 +89,7 @@ pub fn real_spec(core: &RealCore, dir: &Utf8Path, config_path: Utf8PathBuf) -> I
             binary_path: real_core_bin(core),
             version: None,
             features: Vec::new(),
+            distribution: None,
         },
         config_path,
         working_dir: dir.to_owned(),
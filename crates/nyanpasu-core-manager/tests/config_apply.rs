@@ -10,8 +10,9 @@ use std::{
 };
 
 use nyanpasu_core_manager::{
-    ApplyOutcome, ControllerVersionProbe, CoreManager, CoreState, Error, HealthProbe, InstanceSpec,
-    LocalIpcPolicy, ManagerOptions, ProbeHandle, ProbePhase, ProbeResult, RevisionId,
+    ApplyOutcome, ApplyPlan, ConfigDiffEntry, ConfigDiffKind, ControllerVersionProbe, CoreKind,
+    CoreManager, CoreState, DegradeReason, Error, HealthProbe, InstanceSpec, LocalIpcPolicy,
+    ManagerOptions, PlannedRoute, ProbeHandle, ProbePhase, ProbeResult, RevisionId,
 };
 use parking_lot::Mutex;
 
@@ -173,6 +174,145 @@ async fn apply_patch_updates_the_revision_without_restarting() {
     manager.shutdown().await.expect("shutdown");
 }
 
+/// An inline source hashes like the same bytes in a file, and outlives the
+/// apply: a restart re-reads the kept copy.
+#[tokio::test]
+async fn inline_apply_keeps_a_copy_that_restarts_reread() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let port = common::free_port();
+    let body = http_controller_yaml(port, "allow-lan: true\n");
+    let twin = write_named(&dir, "twin.yaml", &body);
+    let other = write_named(&dir, "other.yaml", &http_controller_yaml(port, ""));
+    let manager = manager(&dir, Duration::from_secs(1)).await;
+    manager.start(spec(&dir, twin)).await.expect("start");
+    let from_file = manager.status().revision.expect("revision");
+    manager
+        .apply_config(spec(&dir, other.clone()), None)
+        .await
+        .expect("apply");
+
+    let outcome = manager
+        .apply_inline(spec(&dir, other.clone()), body.as_bytes(), None)
+        .await
+        .expect("inline apply");
+    let ApplyOutcome::Patched { revision } = outcome else {
+        panic!("expected Patched, got {outcome:?}")
+    };
+    assert_eq!(revision.source_hash, from_file.source_hash);
+    let kept = manager.status().spec.expect("spec").config_path;
+    assert_ne!(kept, other);
+    assert!(kept.starts_with(dir.join("runtime")));
+
+    manager.restart().await.expect("restart");
+    let restarted = manager.status().revision.expect("revision");
+    assert_eq!(restarted.source_hash, from_file.source_hash);
+    manager.shutdown().await.expect("shutdown");
+}
+
+/// A kept copy goes once nothing names it: here the last spec stops naming it
+/// when a start from a file replaces it, with no history holding on.
+#[tokio::test]
+async fn an_inline_copy_is_pruned_once_a_start_replaces_it() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let port = common::free_port();
+    let file = write_named(&dir, "file.yaml", &http_controller_yaml(port, ""));
+    let manager = CoreManager::new(ManagerOptions {
+        runtime_dir: Some(dir.join("runtime")),
+        control_timeout: Duration::from_secs(1),
+        reconcile_timeout: Duration::from_secs(5),
+        revision_history: 0,
+        ..ManagerOptions::default()
+    })
+    .await
+    .expect("construct manager");
+    manager
+        .start(spec(&dir, file.clone()))
+        .await
+        .expect("start");
+    let body = http_controller_yaml(port, "allow-lan: true\n");
+    manager
+        .apply_inline(spec(&dir, file.clone()), body.as_bytes(), None)
+        .await
+        .expect("inline apply");
+    let kept = manager.status().spec.expect("spec").config_path;
+    assert!(kept.exists());
+
+    manager.stop().await.expect("stop");
+    assert!(kept.exists(), "a restart could still re-read it");
+    manager.start(spec(&dir, file)).await.expect("start again");
+    assert!(!kept.exists());
+    manager.shutdown().await.expect("shutdown");
+}
+
+#[tokio::test]
+async fn inline_check_parses_before_it_spawns() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir, Duration::from_secs(1)).await;
+    let spec = spec(&dir, dir.join("unused.yaml"));
+
+    manager
+        .check_inline(&spec, b"mixed-port: 7890\n")
+        .await
+        .expect("valid inline config passes");
+    assert!(matches!(
+        manager
+            .check_inline(&spec, b"x-fake-core:\n  check-fail: port already in use\n")
+            .await,
+        Err(Error::ConfigCheckFailed(message)) if message == "port already in use"
+    ));
+    assert!(matches!(
+        manager.check_inline(&spec, b"- not a mapping\n").await,
+        Err(Error::InvalidConfig(_))
+    ));
+}
+
+#[tokio::test]
+async fn a_yaml_singbox_source_is_checked_as_json() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir, Duration::from_secs(1)).await;
+    let source = write_named(&dir, "singbox.yaml", "log:\n  level: info\ninbounds: []\n");
+    let mut spec = spec(&dir, source);
+    spec.core.kind = CoreKind::SingBox;
+
+    manager
+        .check_config(&spec)
+        .await
+        .expect("a YAML source reaches sing-box as JSON");
+    manager
+        .check_inline(&spec, b"log:\n  level: info\n")
+        .await
+        .expect("an inline YAML source reaches sing-box as JSON");
+}
+
+#[tokio::test]
+async fn a_singbox_core_runs_from_its_clash_api_block() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir, Duration::from_secs(1)).await;
+    let port = common::free_port();
+    let source = write_named(
+        &dir,
+        "singbox-run.yaml",
+        &format!(
+            "log:\n  level: info\nexperimental:\n  clash_api:\n    external_controller: 127.0.0.1:{port}\n    secret: s3cret\n"
+        ),
+    );
+    let mut spec = spec(&dir, source);
+    spec.core.kind = CoreKind::SingBox;
+
+    // Running means the health probe reached the controller sing-box was
+    // launched with, which only `run -c` on the JSON runtime copy can give it.
+    manager.start(spec).await.expect("sing-box starts");
+    let (epoch, _) = running(&manager);
+    assert_eq!(epoch, 1);
+    assert_eq!(
+        manager.status().spec.map(|spec| spec.kind),
+        Some(CoreKind::SingBox)
+    );
+
+    manager.stop().await.expect("stop");
+    common::wait_port_refused(port).await;
+}
+
 #[tokio::test]
 async fn custom_reconcile_failure_uses_the_existing_restart_path() {
     let (_guard, dir) = common::utf8_tempdir();
@@ -397,13 +537,142 @@ async fn apply_switch_class_change_switches_to_the_committed_desired_config() {
         .await
         .expect("restart");
 
-    assert!(matches!(outcome, ApplyOutcome::Switched { .. }));
+    // An HTTP controller cannot be shared by two overlapping epochs.
+    assert!(matches!(
+        outcome,
+        ApplyOutcome::Switched {
+            degraded: Some(DegradeReason::HttpController),
+            ..
+        }
+    ));
     let after = running(&manager);
     assert!(after.0 > before.0, "switch-class change gets a new epoch");
     assert_ne!(after.1, before.1);
     manager.shutdown().await.expect("shutdown");
 }
 
+#[tokio::test]
+async fn plan_predicts_each_route_without_touching_the_core() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let port = common::free_port();
+    let rules = "rules:\n  - MATCH,DIRECT\n";
+    let first = write_named(&dir, "first.yaml", &http_controller_yaml(port, rules));
+    let patch = write_named(
+        &dir,
+        "patch.yaml",
+        &http_controller_yaml(port, &format!("allow-lan: true\n{rules}")),
+    );
+    let reload = write_named(
+        &dir,
+        "reload.yaml",
+        &http_controller_yaml(port, "rules:\n  - MATCH,REJECT\n"),
+    );
+    let switch = write_named(
+        &dir,
+        "switch.yaml",
+        &http_controller_yaml(port, &format!("x-setting: new\n{rules}")),
+    );
+    let manager = manager(&dir, Duration::from_secs(1)).await;
+    assert!(matches!(
+        manager.plan_apply(spec(&dir, first.clone())).await,
+        Err(Error::NotStarted)
+    ));
+    manager
+        .start(spec(&dir, first.clone()))
+        .await
+        .expect("start");
+    let before_process = running(&manager);
+    let before_revision = manager.status().revision.expect("revision");
+
+    let plan = |path| manager.plan_apply(spec(&dir, path));
+    assert_eq!(
+        plan(first).await.expect("plan noop"),
+        ApplyPlan {
+            route: PlannedRoute::Noop,
+            changed: Vec::new(),
+        }
+    );
+    assert_eq!(
+        plan(patch).await.expect("plan patch"),
+        ApplyPlan {
+            route: PlannedRoute::Patch,
+            changed: vec!["allow-lan".into()],
+        }
+    );
+    assert_eq!(
+        plan(reload.clone()).await.expect("plan reload"),
+        ApplyPlan {
+            route: PlannedRoute::Reload,
+            changed: vec!["rules".into()],
+        }
+    );
+    assert_eq!(
+        plan(switch).await.expect("plan switch"),
+        ApplyPlan {
+            route: PlannedRoute::Switch {
+                degraded: Some(DegradeReason::HttpController),
+            },
+            changed: vec!["x-setting".into()],
+        }
+    );
+    assert_eq!(running(&manager), before_process);
+    assert_eq!(manager.status().revision, Some(before_revision));
+
+    let outcome = manager
+        .apply_config(spec(&dir, reload), None)
+        .await
+        .expect("reload");
+    assert!(
+        matches!(outcome, ApplyOutcome::Reloaded { .. }),
+        "got {outcome:?}"
+    );
+    manager.shutdown().await.expect("shutdown");
+}
+
+#[tokio::test]
+async fn diff_compares_effective_documents_and_hides_the_secret() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let port = common::free_port();
+    let first = write_named(
+        &dir,
+        "first.yaml",
+        &http_controller_yaml(port, "secret: old\n"),
+    );
+    let desired = write_named(
+        &dir,
+        "desired.yaml",
+        &http_controller_yaml(port, "secret: new\nallow-lan: true\n"),
+    );
+    let manager = manager(&dir, Duration::from_secs(1)).await;
+    manager.start(spec(&dir, first)).await.expect("start");
+    let before = manager.status().revision.expect("revision");
+
+    let entries = manager
+        .diff_config(spec(&dir, desired))
+        .await
+        .expect("diff");
+
+    assert_eq!(
+        entries,
+        vec![
+            ConfigDiffEntry {
+                path: "allow-lan".into(),
+                kind: ConfigDiffKind::Added,
+                old: None,
+                new: Some(serde_json::json!(true)),
+            },
+            ConfigDiffEntry {
+                path: "secret".into(),
+                kind: ConfigDiffKind::Changed,
+                old: Some(serde_json::json!("<redacted>")),
+                new: Some(serde_json::json!("<redacted>")),
+            },
+        ]
+    );
+    assert_eq!(manager.status().revision, Some(before));
+    manager.shutdown().await.expect("shutdown");
+}
+
 #[tokio::test]
 async fn failed_desired_restart_restores_and_restarts_the_old_revision() {
     let (_guard, dir) = common::utf8_tempdir();
@@ -427,6 +696,7 @@ async fn failed_desired_restart_restores_and_restarts_the_old_revision() {
     let ApplyOutcome::RolledBack {
         revision,
         failed_apply,
+        ..
     } = outcome
     else {
         panic!("expected RolledBack")
@@ -0,0 +1,193 @@
+mod common;
+
+use std::time::Duration;
+
+use nyanpasu_core_manager::{
+    ConnectionFilter, ConnectionNetwork, ConnectionsSnapshot, CoreManager, Error, ManagerOptions,
+    Uuid,
+};
+
+const DOWNLOAD: &str = "5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01";
+const GITHUB: &str = "5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d02";
+const DNS: &str = "5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d03";
+
+const CONNECTIONS: &str = "\
+x-fake-core:
+  connections:
+    - id: 5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01
+      host: dl.example.com
+      process: curl
+      rule: DomainSuffix
+      rule-payload: example.com
+      chains: [hk-01, PROXY]
+    - id: 5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d02
+      host: api.github.com
+      process: Code.exe
+      rule: DomainKeyword
+      rule-payload: github
+      chains: [DIRECT]
+    - id: 5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d03
+      host: ''
+      rule: Match
+      chains: [DIRECT]
+      network: udp
+";
+
+async fn started(dir: &camino::Utf8Path) -> CoreManager {
+    let manager = CoreManager::new(ManagerOptions {
+        runtime_dir: Some(dir.join("runtime")),
+        control_timeout: Duration::from_secs(2),
+        ..ManagerOptions::default()
+    })
+    .await
+    .expect("construct manager");
+    let port = common::free_port();
+    let path = common::write_config(
+        dir,
+        &format!("external-controller: 127.0.0.1:{port}\nsecret: connections\n{CONNECTIONS}"),
+    );
+    manager
+        .start(common::mihomo_spec(dir, path))
+        .await
+        .expect("start");
+    manager
+}
+
+fn ids(snapshot: &ConnectionsSnapshot) -> Vec<String> {
+    snapshot
+        .connections
+        .iter()
+        .flatten()
+        .map(|connection| connection.id.to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn connection_control_without_a_core_is_not_started() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = CoreManager::new(ManagerOptions {
+        runtime_dir: Some(dir.join("runtime")),
+        ..ManagerOptions::default()
+    })
+    .await
+    .expect("construct manager");
+
+    assert!(matches!(
+        manager.connections(&ConnectionFilter::default()).await,
+        Err(Error::NotStarted)
+    ));
+    assert!(matches!(
+        manager.close_all_connections().await,
+        Err(Error::NotStarted)
+    ));
+}
+
+#[tokio::test]
+async fn a_listing_is_filtered_on_every_field_that_is_set() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = started(&dir).await;
+    let list = |filter: ConnectionFilter| {
+        let manager = &manager;
+        async move { ids(&manager.connections(&filter).await.expect("list")) }
+    };
+
+    let everything = manager
+        .connections(&ConnectionFilter::default())
+        .await
+        .expect("list");
+    assert_eq!(ids(&everything), [DOWNLOAD, GITHUB, DNS]);
+    assert_eq!(everything.download_total, 2048);
+
+    let host = ConnectionFilter {
+        host: Some("GITHUB".into()),
+        ..ConnectionFilter::default()
+    };
+    assert_eq!(list(host).await, [GITHUB]);
+    let chain = ConnectionFilter {
+        chain: Some("direct".into()),
+        ..ConnectionFilter::default()
+    };
+    assert_eq!(list(chain).await, [GITHUB, DNS]);
+    let rule_payload = ConnectionFilter {
+        rule: Some("example.com".into()),
+        ..ConnectionFilter::default()
+    };
+    assert_eq!(list(rule_payload).await, [DOWNLOAD]);
+    let process = ConnectionFilter {
+        process: Some("code".into()),
+        ..ConnectionFilter::default()
+    };
+    assert_eq!(list(process).await, [GITHUB]);
+    let both = ConnectionFilter {
+        chain: Some("DIRECT".into()),
+        network: Some(ConnectionNetwork::Udp),
+        ..ConnectionFilter::default()
+    };
+    assert_eq!(list(both).await, [DNS]);
+
+    manager.stop().await.expect("stop");
+}
+
+#[tokio::test]
+async fn connections_close_by_id_by_filter_and_all_at_once() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = started(&dir).await;
+    let all = ConnectionFilter::default();
+
+    manager
+        .close_connections(&[Uuid::parse_str(DOWNLOAD).unwrap()])
+        .await
+        .expect("close by id");
+    // The core already forgot it; closing it again is still a success.
+    manager
+        .close_connections(&[Uuid::parse_str(DOWNLOAD).unwrap()])
+        .await
+        .expect("close a closed id");
+    assert_eq!(
+        ids(&manager.connections(&all).await.expect("list")),
+        [GITHUB, DNS]
+    );
+
+    let refused = manager.close_matching_connections(&all).await;
+    assert!(
+        matches!(refused, Err(Error::InvalidFilter(_))),
+        "{refused:?}"
+    );
+    // `""` is in every host, so a blank field must not get past the guard.
+    let blank = ConnectionFilter {
+        host: Some(String::new()),
+        process: Some("  ".into()),
+        ..ConnectionFilter::default()
+    };
+    assert!(blank.is_empty());
+    let refused = manager.close_matching_connections(&blank).await;
+    assert!(
+        matches!(refused, Err(Error::InvalidFilter(_))),
+        "{refused:?}"
+    );
+    assert_eq!(
+        ids(&manager.connections(&all).await.expect("list")),
+        [GITHUB, DNS]
+    );
+
+    let closed = manager
+        .close_matching_connections(&ConnectionFilter {
+            network: Some(ConnectionNetwork::Udp),
+            ..ConnectionFilter::default()
+        })
+        .await
+        .expect("close by filter");
+    assert_eq!(closed, [Uuid::parse_str(DNS).unwrap()]);
+    assert_eq!(
+        ids(&manager.connections(&all).await.expect("list")),
+        [GITHUB]
+    );
+
+    manager
+        .close_all_connections()
+        .await
+        .expect("close everything");
+    assert!(ids(&manager.connections(&all).await.expect("list")).is_empty());
+
+    manager.stop().await.expect("stop");
+}
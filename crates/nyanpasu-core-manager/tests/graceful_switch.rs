@@ -148,10 +148,68 @@ async fn stop_cleans_the_runtime_config_for_terminal_instance() {
     manager.shutdown().await.expect("shutdown");
 }
 
-use nyanpasu_core_manager::SwitchOutcome;
+use nyanpasu_core_manager::{ApplyOutcome, RestoredSelections, SwitchOutcome};
 use parking_lot::Mutex;
 use std::sync::Arc;
 
+/// None of these configs declares a proxy group, so no switch has a selection
+/// to carry over.
+fn graceful() -> SwitchOutcome {
+    SwitchOutcome::Graceful {
+        selections: RestoredSelections::default(),
+    }
+}
+
+#[tokio::test]
+async fn a_switch_class_apply_overlaps_epochs_instead_of_stopping_first() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let mixed = common::free_port();
+    let config_a = common::write_config(&dir, &format!("mixed-port: {mixed}\n"));
+    let config_b = dir.join("config-b.yaml");
+    std::fs::write(&config_b, format!("mixed-port: {mixed}\nx-setting: new\n")).unwrap();
+
+    let manager = local_ipc_manager(dir.join("runtime")).await;
+    manager
+        .start(common::mihomo_spec(&dir, config_a))
+        .await
+        .expect("start A");
+
+    let mut rx = manager.subscribe();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen_ = seen.clone();
+    let recorder = tokio::spawn(async move {
+        while rx.changed().await.is_ok() {
+            seen_.lock().push(rx.borrow_and_update().state.clone());
+        }
+    });
+
+    let outcome = manager
+        .apply_config(common::mihomo_spec(&dir, config_b), None)
+        .await
+        .expect("apply");
+    recorder.abort();
+
+    let ApplyOutcome::Switched {
+        revision, degraded, ..
+    } = outcome
+    else {
+        panic!("expected a switch, got {outcome:?}");
+    };
+    assert_eq!(degraded, None);
+    assert_eq!(revision.epoch, 2);
+    let states = seen.lock().clone();
+    assert!(
+        !states
+            .iter()
+            .any(|s| matches!(s, CoreState::Stopped { .. })),
+        "sequence was {states:?}"
+    );
+    tokio::net::TcpStream::connect(("127.0.0.1", mixed))
+        .await
+        .expect("the new epoch holds the mixed port");
+    manager.shutdown().await.expect("shutdown");
+}
+
 #[tokio::test]
 async fn graceful_switch_overlaps_and_restores_listeners() {
     let (_guard, dir) = common::utf8_tempdir();
@@ -192,7 +250,7 @@ async fn graceful_switch_overlaps_and_restores_listeners() {
     let mut spec_b = common::mihomo_spec(&dir, config_b_path.clone());
     spec_b.config_path = config_b_path;
     let outcome = manager.switch(spec_b.clone()).await.expect("switch");
-    assert_eq!(outcome, SwitchOutcome::Graceful);
+    assert_eq!(outcome, graceful());
     recorder.abort();
 
     // The user-visible overlap guarantee: never Stopped during the switch.
@@ -272,7 +330,7 @@ async fn graceful_switch_surfaces_installed_but_uncertain_durability() {
     let SwitchOutcome::DurabilityUncertain { outcome, warning } = outcome else {
         panic!("expected durability wrapper")
     };
-    assert_eq!(*outcome, SwitchOutcome::Graceful);
+    assert_eq!(*outcome, graceful());
     assert!(warning.contains("injected"), "{warning}");
     manager.shutdown().await.expect("shutdown");
 }
@@ -458,7 +516,7 @@ async fn graceful_patch_timeout_with_matching_get_is_success() {
         .await
         .expect("switch converges");
 
-    assert_eq!(outcome, SwitchOutcome::Graceful);
+    assert_eq!(outcome, graceful());
     let CoreState::Running { epoch, pid } = manager.status().state else {
         panic!("new core is not running")
     };
@@ -525,7 +583,7 @@ async fn graceful_overlap_keeps_both_epoch_pid_records_without_miskilling_old()
         .await
         .expect("old core still serves during overlap");
 
-    assert_eq!(switching.await.unwrap().unwrap(), SwitchOutcome::Graceful);
+    assert_eq!(switching.await.unwrap().unwrap(), graceful());
     manager.shutdown().await.expect("shutdown");
 }
 
@@ -602,7 +660,7 @@ async fn graceful_overlap_removes_shared_http_controller_from_both_epochs() {
         );
     }
 
-    assert_eq!(switching.await.unwrap().unwrap(), SwitchOutcome::Graceful);
+    assert_eq!(switching.await.unwrap().unwrap(), graceful());
     manager.shutdown().await.expect("shutdown");
 }
 
@@ -696,7 +754,8 @@ async fn hard_switch_removes_the_old_runtime_config() {
     assert_eq!(
         outcome,
         SwitchOutcome::Hard {
-            reason: DegradeReason::DnsListen
+            reason: DegradeReason::DnsListen,
+            selections: RestoredSelections::default(),
         }
     );
     assert!(
@@ -746,7 +805,8 @@ async fn prefer_http_fallback_degrades_switch_to_hard() {
     assert_eq!(
         outcome,
         SwitchOutcome::Hard {
-            reason: DegradeReason::HttpController
+            reason: DegradeReason::HttpController,
+            selections: RestoredSelections::default(),
         }
     );
     assert!(matches!(
@@ -964,7 +1024,8 @@ async fn rejected_patch_falls_back_to_a_hard_restart() {
     assert_eq!(
         outcome,
         SwitchOutcome::Hard {
-            reason: nyanpasu_core_manager::DegradeReason::PatchFailed
+            reason: nyanpasu_core_manager::DegradeReason::PatchFailed,
+            selections: RestoredSelections::default(),
         }
     );
     // The fallback instance boots on the FULL config, so it binds the port itself.
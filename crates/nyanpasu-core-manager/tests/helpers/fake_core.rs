@@ -1,6 +1,7 @@
 //! Scripted mihomo simulator for nyanpasu-core-manager tests. Not production code.
-//! CLI mirrors mihomo: `[-m] [-t] -d <dir> -f <config>`. See the implementation
-//! plan for the `x-fake-core` behavior keys.
+//! CLI mirrors mihomo: `[-m] [-t] -d <dir> -f <config>`, plus sing-box's
+//! `check ... -c <config>` and `run ... -c <config>`. See the implementation plan for the `x-fake-core`
+//! behavior keys.
 #![allow(dead_code)] // several Behavior fields are platform-conditional
 
 use std::{
@@ -45,6 +46,24 @@ struct Behavior {
     check_delay_ms: u64,
     check_started_file: Option<String>,
     check_fail: Option<String>,
+    proxy_groups: Vec<ProxyGroup>,
+    connections: Vec<FakeConnection>,
+}
+
+struct FakeConnection {
+    id: String,
+    host: String,
+    process: String,
+    rule: String,
+    rule_payload: String,
+    chains: Vec<String>,
+    network: String,
+}
+
+struct ProxyGroup {
+    name: String,
+    kind: String,
+    proxies: Vec<String>,
 }
 
 fn s(doc: &Mapping, key: &str) -> Option<String> {
@@ -75,6 +94,42 @@ fn lines(doc: &Mapping, key: &str) -> Vec<String> {
         .unwrap_or_default()
 }
 
+fn proxy_groups(doc: &Mapping) -> Vec<ProxyGroup> {
+    doc.get(Value::String("proxy-groups".into()))
+        .and_then(Value::as_sequence)
+        .map(|seq| {
+            seq.iter()
+                .filter_map(Value::as_mapping)
+                .map(|group| ProxyGroup {
+                    name: s(group, "name").unwrap_or_default(),
+                    kind: s(group, "type").unwrap_or_else(|| "select".into()),
+                    proxies: lines(group, "proxies"),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn connections(doc: &Mapping) -> Vec<FakeConnection> {
+    doc.get(Value::String("connections".into()))
+        .and_then(Value::as_sequence)
+        .map(|seq| {
+            seq.iter()
+                .filter_map(Value::as_mapping)
+                .map(|connection| FakeConnection {
+                    id: s(connection, "id").unwrap_or_default(),
+                    host: s(connection, "host").unwrap_or_default(),
+                    process: s(connection, "process").unwrap_or_default(),
+                    rule: s(connection, "rule").unwrap_or_else(|| "Match".into()),
+                    rule_payload: s(connection, "rule-payload").unwrap_or_default(),
+                    chains: lines(connection, "chains"),
+                    network: s(connection, "network").unwrap_or_else(|| "tcp".into()),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn parse(config: &str) -> Behavior {
     let doc: Mapping = serde_yaml_ng::from_str(config).expect("valid yaml");
     let x = doc
@@ -111,6 +166,28 @@ fn parse(config: &str) -> Behavior {
         check_delay_ms: u(&x, "check-delay-ms"),
         check_started_file: s(&x, "check-started-file"),
         check_fail: s(&x, "check-fail"),
+        proxy_groups: proxy_groups(&doc),
+        connections: connections(&x),
+    }
+}
+
+fn parse_sing_box(config: &str) -> Behavior {
+    if let Err(error) = serde_json::from_str::<serde_json::Value>(config) {
+        println!("FATAL[0000] decode config: {error}");
+        exit(1);
+    }
+    let doc: Mapping = serde_yaml_ng::from_str(config).expect("JSON is YAML");
+    let api = doc
+        .get(Value::String("experimental".into()))
+        .and_then(Value::as_mapping)
+        .and_then(|experimental| experimental.get(Value::String("clash_api".into())))
+        .and_then(Value::as_mapping)
+        .cloned()
+        .unwrap_or_default();
+    Behavior {
+        external_controller: s(&api, "external_controller"),
+        secret: s(&api, "secret"),
+        ..parse(config)
     }
 }
 
@@ -118,12 +195,19 @@ struct Ctx {
     ready: AtomicBool,
     behavior: Behavior,
     runtime: Mutex<Mapping>,
+    /// Current member per group; every launch starts from the first member,
+    /// as a real core without a cache file does.
+    selections: Mutex<Vec<String>>,
+    /// Indices into `behavior.connections` that have not been closed.
+    open_connections: Mutex<Vec<usize>>,
 }
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
     let args: Vec<String> = std::env::args().skip(1).collect();
-    if args.iter().any(|arg| arg == "-v") {
+    // sing-box spells it as a subcommand.
+    let sing_box_version = args.first().is_some_and(|arg| arg == "version");
+    if sing_box_version || args.iter().any(|arg| arg == "-v") {
         if let Ok(binary) = std::env::current_exe()
             && binary
                 .file_stem()
@@ -137,17 +221,43 @@ async fn main() {
                 + 1;
             std::fs::write(counter, count.to_string()).expect("write version probe counter");
         }
-        println!("Mihomo Meta v1.18.9 linux test");
+        if sing_box_version {
+            println!("sing-box version 1.10.1");
+        } else {
+            println!("Mihomo Meta v1.18.9 linux test");
+        }
         return;
     }
+    // sing-box's spelling: `check ... -c <config>`, and it only reads JSON.
+    if args.first().is_some_and(|arg| arg == "check") {
+        let config_path = args
+            .iter()
+            .position(|a| a == "-c")
+            .and_then(|i| args.get(i + 1))
+            .expect("-c <config> required");
+        let config = std::fs::read_to_string(config_path).expect("readable config");
+        if let Err(error) = serde_json::from_str::<serde_json::Value>(&config) {
+            println!("FATAL[0000] decode config at {config_path}: {error}");
+            exit(1);
+        }
+        exit(0);
+    }
+    // `run ... -c <config>`: JSON again, with the controller under
+    // `experimental.clash_api`.
+    let sing_box = args.first().is_some_and(|arg| arg == "run");
     let check_mode = args.iter().any(|a| a == "-t");
+    let config_flag = if sing_box { "-c" } else { "-f" };
     let config_path = args
         .iter()
-        .position(|a| a == "-f")
+        .position(|a| a == config_flag)
         .and_then(|i| args.get(i + 1))
-        .expect("-f <config> required");
+        .expect("a config path is required");
     let config = std::fs::read_to_string(config_path).expect("readable config");
-    let behavior = parse(&config);
+    let behavior = if sing_box {
+        parse_sing_box(&config)
+    } else {
+        parse(&config)
+    };
 
     if check_mode {
         if let Some(path) = &behavior.check_started_file {
@@ -211,10 +321,18 @@ async fn main() {
         hold_listener(listener);
     }
 
+    let selections = behavior
+        .proxy_groups
+        .iter()
+        .map(|group| group.proxies.first().cloned().unwrap_or_default())
+        .collect();
+    let behavior_connections = behavior.connections.len();
     let ctx = Arc::new(Ctx {
         ready: AtomicBool::new(false),
         behavior,
         runtime: Mutex::new(serde_yaml_ng::from_str(&config).expect("runtime mapping")),
+        selections: Mutex::new(selections),
+        open_connections: Mutex::new((0..behavior_connections).collect()),
     });
     if !ctx.behavior.never_ready {
         let ctx = ctx.clone();
@@ -370,6 +488,15 @@ where
         return;
     }
 
+    if let Some((status, body)) = proxy_route(&ctx, &method, &path, &body) {
+        respond(&mut stream, status, &body).await;
+        return;
+    }
+    if let Some((status, body)) = connection_route(&ctx, &method, &path) {
+        respond(&mut stream, status, &body).await;
+        return;
+    }
+
     match (method.as_str(), path.as_str()) {
         ("GET", "/version") => {
             if ctx.ready.load(Ordering::SeqCst) {
@@ -429,6 +556,160 @@ where
     }
 }
 
+/// The slice of mihomo's proxy API the manager drives: group listing, selector
+/// updates and URL tests. Delays are made up but stable, 42 ms plus the
+/// member's index.
+fn proxy_route(ctx: &Ctx, method: &str, path: &str, body: &str) -> Option<(u16, String)> {
+    let groups = &ctx.behavior.proxy_groups;
+    let group_index = |name: &str| groups.iter().position(|group| group.name == name);
+    let not_found = || (404, r#"{"message":"resource not found"}"#.to_owned());
+
+    if method == "GET" && path == "/group/" {
+        let selections = ctx.selections.lock();
+        let items: Vec<String> = groups
+            .iter()
+            .zip(selections.iter())
+            .map(|(group, now)| group_json(group, now))
+            .collect();
+        return Some((200, format!(r#"{{"proxies":[{}]}}"#, items.join(","))));
+    }
+    if method == "PUT"
+        && let Some(name) = path
+            .strip_prefix("/proxies/")
+            .and_then(|rest| rest.strip_suffix('/'))
+    {
+        let Some(index) = group_index(name) else {
+            return Some(not_found());
+        };
+        let request: Mapping = serde_yaml_ng::from_str(body).unwrap_or_default();
+        let target = s(&request, "name").unwrap_or_default();
+        let group = &groups[index];
+        if group.kind != "select" || !group.proxies.contains(&target) {
+            return Some((
+                400,
+                r#"{"message":"Selector update error: not found"}"#.to_owned(),
+            ));
+        }
+        ctx.selections.lock()[index] = target;
+        return Some((204, String::new()));
+    }
+    if method != "GET" {
+        return None;
+    }
+    if let Some(name) = path
+        .strip_prefix("/group/")
+        .and_then(|rest| rest.strip_suffix("/delay"))
+    {
+        let index = group_index(name)?;
+        let entries: Vec<String> = groups[index]
+            .proxies
+            .iter()
+            .enumerate()
+            .map(|(i, member)| format!("{}:{}", json_string(member), 42 + i))
+            .collect();
+        return Some((200, format!("{{{}}}", entries.join(","))));
+    }
+    if let Some(name) = path
+        .strip_prefix("/proxies/")
+        .and_then(|rest| rest.strip_suffix("/delay"))
+    {
+        let delay = groups
+            .iter()
+            .find_map(|group| group.proxies.iter().position(|member| member == name))
+            .map(|i| 42 + i)
+            .or_else(|| group_index(name).map(|_| 42));
+        return Some(match delay {
+            Some(delay) => (200, format!(r#"{{"delay":{delay}}}"#)),
+            None => not_found(),
+        });
+    }
+    None
+}
+
+/// `/connections` over the `x-fake-core.connections` list. Closing removes a
+/// connection for the life of the process; like mihomo, an unknown id is still
+/// a success.
+fn connection_route(ctx: &Ctx, method: &str, path: &str) -> Option<(u16, String)> {
+    let connections = &ctx.behavior.connections;
+    let mut open = ctx.open_connections.lock();
+    match (method, path) {
+        ("GET", "/connections") => {
+            let items: Vec<String> = open
+                .iter()
+                .map(|&index| connection_json(&connections[index]))
+                .collect();
+            Some((
+                200,
+                format!(
+                    r#"{{"downloadTotal":2048,"uploadTotal":1024,"connections":[{}],"memory":0}}"#,
+                    items.join(",")
+                ),
+            ))
+        }
+        ("DELETE", "/connections") => {
+            open.clear();
+            Some((204, String::new()))
+        }
+        ("DELETE", _) => {
+            let id = path.strip_prefix("/connections/")?;
+            open.retain(|&index| connections[index].id != id);
+            Some((204, String::new()))
+        }
+        _ => None,
+    }
+}
+
+fn connection_json(connection: &FakeConnection) -> String {
+    let chains: Vec<String> = connection
+        .chains
+        .iter()
+        .map(|hop| json_string(hop))
+        .collect();
+    format!(
+        concat!(
+            r#"{{"id":{},"metadata":{{"network":{},"type":"HTTP","#,
+            r#""sourceIP":"127.0.0.1","destinationIP":"93.184.216.34","#,
+            r#""sourceGeoIP":null,"destinationGeoIP":null,"sourceIPASN":"","#,
+            r#""destinationIPASN":"","sourcePort":"50000","destinationPort":"443","#,
+            r#""inboundIP":"127.0.0.1","inboundPort":"7890","inboundName":"mixed","#,
+            r#""inboundUser":"","rematchName":"","host":{},"dnsMode":"normal","uid":0,"#,
+            r#""process":{},"processPath":"","specialProxy":"","specialRules":"","#,
+            r#""remoteDestination":"","dscp":0,"sniffHost":""}},"upload":10,"download":20,"#,
+            r#""start":"2026-07-29T00:16:22.646059400+08:00","chains":[{}],"#,
+            r#""providerChains":[],"rule":{},"rulePayload":{}}}"#
+        ),
+        json_string(&connection.id),
+        json_string(&connection.network),
+        json_string(&connection.host),
+        json_string(&connection.process),
+        chains.join(","),
+        json_string(&connection.rule),
+        json_string(&connection.rule_payload),
+    )
+}
+
+fn group_json(group: &ProxyGroup, now: &str) -> String {
+    let kind = match group.kind.as_str() {
+        "select" => "Selector",
+        "url-test" => "URLTest",
+        "fallback" => "Fallback",
+        "load-balance" => "LoadBalance",
+        other => other,
+    };
+    let all: Vec<String> = group
+        .proxies
+        .iter()
+        .map(|member| json_string(member))
+        .collect();
+    format!(
+        r#"{{"name":{},"type":{},"history":[],"extra":{{}},"alive":true,"udp":true,"uot":false,"xudp":false,"tfo":false,"mptcp":false,"smux":false,"interface":"","routing-mark":0,"provider-name":"","dialer-proxy":"","now":{},"all":[{}]}}"#,
+        json_string(&group.name),
+        json_string(kind),
+        json_string(now),
+        all.join(","),
+    )
+}
+
 fn merge_mapping(target: &mut Mapping, patch: &Mapping) {
     for (key, value) in patch {
         if let (Some(target), Some(patch)) = (
@@ -568,6 +849,7 @@ async fn respond<S: AsyncWriteExt + Unpin>(stream: &mut S, status: u16, body: &s
     let reason = match status {
         200 => "OK",
         204 => "No Content",
+        400 => "Bad Request",
         401 => "Unauthorized",
         503 => "Service Unavailable",
         500 => "Internal Server Error",
@@ -5,7 +5,7 @@ use std::future::pending;
 
 use camino::Utf8PathBuf;
 use nyanpasu_core_manager::{
-    CoreKind, CoreManager, CoreSpec, CoreState, InstanceOptions, InstanceSpec, ManagerOptions,
+    ClashCoreKind, CoreKind, CoreManager, CoreSpec, CoreState, InstanceOptions, InstanceSpec, ManagerOptions,
 };
 
 #[tokio::main]
@@ -26,10 +26,11 @@ async fn main() {
     manager
         .start(InstanceSpec {
             core: CoreSpec {
-                kind: CoreKind::Mihomo,
+                kind: CoreKind::Clash(ClashCoreKind::Mihomo),
                 binary_path: core_binary,
                 version: None,
                 features: Vec::new(),
+                distribution: None,
             },
             config_path: source_config,
             working_dir,
@@ -200,6 +200,9 @@ async fn start_publishes_running_and_rejects_double_start() {
         status.spec.as_ref().map(|s| s.config_path.clone()),
         Some(spec.config_path.clone())
     );
+    let options = &status.spec.as_ref().expect("spec summary").options;
+    assert_eq!(options.startup_timeout, spec.options.startup_timeout);
+    assert_eq!(options.health, spec.options.health);
     assert!(status.changed_at > 0);
 
     let err = manager.start(spec).await.expect_err("double start");
@@ -291,6 +294,25 @@ async fn core_logs_are_archived_under_the_manager_log_directory() {
     manager.shutdown().await.expect("shutdown");
 }
 
+/// Live rotation limits are held to the rules construction applies.
+#[tokio::test]
+async fn log_rotation_limits_change_live_and_refuse_zero() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir).await;
+
+    manager
+        .set_log_rotation(64 * 1024, 2)
+        .await
+        .expect("new limits are accepted while the sink runs");
+    for (max_bytes, max_files) in [(0, 2), (64 * 1024, 0)] {
+        assert!(matches!(
+            manager.set_log_rotation(max_bytes, max_files).await,
+            Err(Error::InvalidManagerOptions(_))
+        ));
+    }
+    manager.shutdown().await.expect("shutdown");
+}
+
 fn read_archived(log_dir: &camino::Utf8Path, message: &str) -> Option<serde_json::Value> {
     std::fs::read_dir(log_dir)
         .ok()?
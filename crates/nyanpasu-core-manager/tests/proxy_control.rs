@@ -0,0 +1,300 @@
+mod common;
+
+use std::time::Duration;
+
+use nyanpasu_core_manager::{
+    ApplyOutcome, CoreManager, DegradeReason, Error, ManagerOptions, ProxySelection,
+    RestoredSelections, SwitchOutcome,
+};
+
+const PROXY_GROUPS: &str = "\
+proxy-groups:
+  - name: PROXY
+    type: select
+    proxies: [hk-01, jp-01, DIRECT]
+  - name: auto
+    type: url-test
+    proxies: [hk-01, jp-01]
+";
+
+/// `jp-01` leaves `PROXY` across the apply; `Media` keeps both members.
+const BEFORE_APPLY: &str = "\
+proxy-groups:
+  - {name: PROXY, type: select, proxies: [hk-01, jp-01, DIRECT]}
+  - {name: Media, type: select, proxies: [hk-01, DIRECT]}
+";
+const AFTER_APPLY: &str = "\
+proxy-groups:
+  - {name: PROXY, type: select, proxies: [hk-01, DIRECT]}
+  - {name: Media, type: select, proxies: [hk-01, DIRECT]}
+";
+
+async fn manager(dir: &camino::Utf8Path) -> CoreManager {
+    CoreManager::new(ManagerOptions {
+        runtime_dir: Some(dir.join("runtime")),
+        control_timeout: Duration::from_secs(2),
+        ..ManagerOptions::default()
+    })
+    .await
+    .expect("construct manager")
+}
+
+fn config(dir: &camino::Utf8Path, secret: Option<&str>) -> camino::Utf8PathBuf {
+    let port = common::free_port();
+    let secret = secret
+        .map(|secret| format!("secret: {secret}\n"))
+        .unwrap_or_default();
+    common::write_config(
+        dir,
+        &format!("external-controller: 127.0.0.1:{port}\n{secret}{PROXY_GROUPS}"),
+    )
+}
+
+fn selection(group: &str, proxy: &str) -> ProxySelection {
+    ProxySelection {
+        group: group.into(),
+        proxy: proxy.into(),
+    }
+}
+
+fn now_of(groups: &[nyanpasu_core_manager::Proxy], name: &str) -> Option<String> {
+    groups
+        .iter()
+        .find(|group| group.name.as_str() == name)
+        .and_then(|group| group.now.as_ref())
+        .map(|now| now.as_str().to_owned())
+}
+
+#[tokio::test]
+async fn proxy_control_without_a_core_is_not_started() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir).await;
+
+    assert!(matches!(
+        manager.proxy_groups().await,
+        Err(Error::NotStarted)
+    ));
+    assert!(matches!(
+        manager.select_proxy("PROXY", "jp-01").await,
+        Err(Error::NotStarted)
+    ));
+}
+
+#[tokio::test]
+async fn selection_goes_through_the_active_controller_and_its_secret() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir).await;
+    let path = config(&dir, Some("only-the-service-knows"));
+    manager
+        .start(common::mihomo_spec(&dir, path))
+        .await
+        .expect("start");
+
+    let groups = manager.proxy_groups().await.expect("list groups");
+    let names: Vec<_> = groups.iter().map(|group| group.name.as_str()).collect();
+    assert_eq!(names, ["PROXY", "auto"]);
+    assert_eq!(now_of(&groups, "PROXY").as_deref(), Some("hk-01"));
+
+    manager
+        .select_proxy("PROXY", "jp-01")
+        .await
+        .expect("select");
+    let groups = manager.proxy_groups().await.expect("list groups");
+    assert_eq!(now_of(&groups, "PROXY").as_deref(), Some("jp-01"));
+
+    let refused = manager.select_proxy("PROXY", "nowhere").await;
+    assert!(matches!(refused, Err(Error::Api(_))), "{refused:?}");
+
+    manager.stop().await.expect("stop");
+}
+
+#[tokio::test]
+async fn delay_tests_cover_single_proxies_and_whole_groups() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir).await;
+    let path = config(&dir, None);
+    manager
+        .start(common::mihomo_spec(&dir, path))
+        .await
+        .expect("start");
+
+    let url = "https://www.gstatic.com/generate_204";
+    let delay = manager
+        .proxy_delay("jp-01", url, Duration::from_secs(1))
+        .await
+        .expect("proxy delay");
+    assert_eq!(delay, 43);
+
+    let delays = manager
+        .group_delay("auto", url, Duration::from_secs(1))
+        .await
+        .expect("group delay");
+    let delays: Vec<_> = delays
+        .iter()
+        .map(|(name, delay)| (name.as_str(), *delay))
+        .collect();
+    assert_eq!(delays, [("hk-01", 42), ("jp-01", 43)]);
+
+    let invalid = manager
+        .proxy_delay("jp-01", "not a url", Duration::from_secs(1))
+        .await;
+    assert!(matches!(invalid, Err(Error::Api(_))), "{invalid:?}");
+
+    manager.stop().await.expect("stop");
+}
+
+#[tokio::test]
+async fn proxy_control_follows_the_core_across_a_switch() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir).await;
+    let first = config(&dir, Some("first-secret"));
+    manager
+        .start(common::mihomo_spec(&dir, first))
+        .await
+        .expect("start");
+    manager.proxy_groups().await.expect("list before switch");
+
+    let second_dir = dir.join("second");
+    std::fs::create_dir_all(&second_dir).expect("second dir");
+    let second = config(&second_dir, Some("second-secret"));
+    manager
+        .switch(common::mihomo_spec(&dir, second))
+        .await
+        .expect("switch");
+
+    manager
+        .select_proxy("PROXY", "DIRECT")
+        .await
+        .expect("select after switch");
+    let groups = manager.proxy_groups().await.expect("list after switch");
+    assert_eq!(now_of(&groups, "PROXY").as_deref(), Some("DIRECT"));
+
+    manager.stop().await.expect("stop");
+}
+
+#[tokio::test]
+async fn a_restart_carries_selector_choices_into_the_new_process() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir).await;
+    let path = config(&dir, Some("restart-secret"));
+    manager
+        .start(common::mihomo_spec(&dir, path))
+        .await
+        .expect("start");
+    manager
+        .select_proxy("PROXY", "jp-01")
+        .await
+        .expect("select");
+
+    let outcome = manager.restart().await.expect("restart");
+
+    // The url-test group picks for itself and is never snapshotted.
+    assert_eq!(
+        outcome,
+        SwitchOutcome::Hard {
+            reason: DegradeReason::HttpController,
+            selections: RestoredSelections {
+                restored: vec![selection("PROXY", "jp-01")],
+                skipped: Vec::new(),
+            },
+        }
+    );
+    let groups = manager.proxy_groups().await.expect("list after restart");
+    assert_eq!(now_of(&groups, "PROXY").as_deref(), Some("jp-01"));
+
+    manager.stop().await.expect("stop");
+}
+
+#[tokio::test]
+async fn an_apply_that_replaces_the_core_skips_choices_the_new_config_dropped() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir).await;
+    let port = common::free_port();
+    let first = dir.join("first.yaml");
+    std::fs::write(
+        &first,
+        format!("external-controller: 127.0.0.1:{port}\n{BEFORE_APPLY}"),
+    )
+    .expect("write first config");
+    let desired = dir.join("desired.yaml");
+    std::fs::write(
+        &desired,
+        format!("external-controller: 127.0.0.1:{port}\nx-setting: new\n{AFTER_APPLY}"),
+    )
+    .expect("write desired config");
+    manager
+        .start(common::mihomo_spec(&dir, first))
+        .await
+        .expect("start");
+    manager
+        .select_proxy("PROXY", "jp-01")
+        .await
+        .expect("select");
+    manager
+        .select_proxy("Media", "DIRECT")
+        .await
+        .expect("select");
+
+    let outcome = manager
+        .apply_config(common::mihomo_spec(&dir, desired), None)
+        .await
+        .expect("apply");
+
+    let ApplyOutcome::Switched { selections, .. } = outcome else {
+        panic!("expected a switch, got {outcome:?}");
+    };
+    assert_eq!(
+        selections,
+        RestoredSelections {
+            restored: vec![selection("Media", "DIRECT")],
+            skipped: vec![selection("PROXY", "jp-01")],
+        }
+    );
+    let groups = manager.proxy_groups().await.expect("list after apply");
+    assert_eq!(now_of(&groups, "PROXY").as_deref(), Some("hk-01"));
+    assert_eq!(now_of(&groups, "Media").as_deref(), Some("DIRECT"));
+
+    manager.stop().await.expect("stop");
+}
+
+#[tokio::test]
+async fn a_rolled_back_apply_puts_the_old_choices_back() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let manager = manager(&dir).await;
+    let port = common::free_port();
+    let behavior = "x-fake-core:\n  patch-no-effect: true\n  fail-start-when-allow-lan: true\n";
+    let first = dir.join("first.yaml");
+    std::fs::write(
+        &first,
+        format!("external-controller: 127.0.0.1:{port}\n{behavior}{BEFORE_APPLY}"),
+    )
+    .expect("write first config");
+    let desired = dir.join("desired.yaml");
+    std::fs::write(
+        &desired,
+        format!("external-controller: 127.0.0.1:{port}\nallow-lan: true\n{behavior}{BEFORE_APPLY}"),
+    )
+    .expect("write desired config");
+    manager
+        .start(common::mihomo_spec(&dir, first))
+        .await
+        .expect("start");
+    manager
+        .select_proxy("PROXY", "jp-01")
+        .await
+        .expect("select");
+
+    let outcome = manager
+        .apply_config(common::mihomo_spec(&dir, desired), None)
+        .await
+        .expect("apply");
+
+    let ApplyOutcome::RolledBack { selections, .. } = outcome else {
+        panic!("expected a rollback, got {outcome:?}");
+    };
+    assert_eq!(selections.restored, vec![selection("PROXY", "jp-01")]);
+    let groups = manager.proxy_groups().await.expect("list after rollback");
+    assert_eq!(now_of(&groups, "PROXY").as_deref(), Some("jp-01"));
+
+    manager.stop().await.expect("stop");
+}
@@ -211,8 +211,8 @@ async fn config_update(core: RealCore) {
         "got {error}"
     );
 
-    // Scalar change (log-level). Mihomo patches it at runtime; the other
-    // kinds conservatively restart into a new epoch.
+    // Scalar change (log-level). Every core with a config adapter patches it
+    // at runtime; the rest conservatively restart into a new epoch.
     extra.push_str("log-level: debug\n");
     write_proxy_config(&bed, &extra);
     let outcome = manager
@@ -220,9 +220,14 @@ async fn config_update(core: RealCore) {
         .await
         .expect("log-level apply");
     match core.kind {
-        nyanpasu_core_manager::CoreKind::Mihomo => assert!(
+        nyanpasu_core_manager::CoreKind::Clash(nyanpasu_core_manager::ClashCoreKind::Mihomo)
+        | nyanpasu_core_manager::CoreKind::Clash(nyanpasu_core_manager::ClashCoreKind::ClashRust)
+        | nyanpasu_core_manager::CoreKind::Clash(
+            nyanpasu_core_manager::ClashCoreKind::ClashPremium,
+        ) => assert!(
             matches!(outcome, ApplyOutcome::Patched { .. }),
-            "mihomo log-level change should patch in place, got {outcome:?}"
+            "{} log-level change should patch in place, got {outcome:?}",
+            core.name
         ),
         _ => assert!(
             matches!(outcome, ApplyOutcome::Switched { .. }),
@@ -233,9 +238,15 @@ async fn config_update(core: RealCore) {
     assert!(matches!(manager.status().state, CoreState::Running { .. }));
     assert_proxy_chain(&bed).await;
 
-    // Collection change in the reload set (hosts). Only meaningful for
-    // mihomo, which can reload without a restart.
-    if matches!(core.kind, nyanpasu_core_manager::CoreKind::Mihomo) {
+    // Collection change in the reload set (hosts). Only meaningful for the
+    // cores that can reload without a restart.
+    if matches!(
+        core.kind,
+        nyanpasu_core_manager::CoreKind::Clash(nyanpasu_core_manager::ClashCoreKind::Mihomo)
+            | nyanpasu_core_manager::CoreKind::Clash(
+                nyanpasu_core_manager::ClashCoreKind::ClashPremium
+            )
+    ) {
         extra.push_str("hosts:\n  fixture.test: 198.18.0.1\n");
         write_proxy_config(&bed, &extra);
         let outcome = manager
@@ -244,13 +255,14 @@ async fn config_update(core: RealCore) {
             .expect("hosts apply");
         assert!(
             matches!(outcome, ApplyOutcome::Reloaded { .. }),
-            "mihomo hosts change should reload, got {outcome:?}"
+            "{} hosts change should reload, got {outcome:?}",
+            core.name
         );
         assert_proxy_chain(&bed).await;
     }
 
-    // Inbound change (mixed-port). Mihomo re-binds the listener at runtime;
-    // the others restart. Either way the new port must serve and the old one
+    // Inbound change (mixed-port). The adapter cores re-bind the listener at
+    // runtime; the others restart. Either way the new port must serve and the old one
     // must be gone.
     let old_port = bed.mixed_port;
     bed.mixed_port = common::free_port();
@@ -260,9 +272,14 @@ async fn config_update(core: RealCore) {
         .await
         .expect("mixed-port apply");
     match core.kind {
-        nyanpasu_core_manager::CoreKind::Mihomo => assert!(
+        nyanpasu_core_manager::CoreKind::Clash(nyanpasu_core_manager::ClashCoreKind::Mihomo)
+        | nyanpasu_core_manager::CoreKind::Clash(nyanpasu_core_manager::ClashCoreKind::ClashRust)
+        | nyanpasu_core_manager::CoreKind::Clash(
+            nyanpasu_core_manager::ClashCoreKind::ClashPremium,
+        ) => assert!(
             matches!(outcome, ApplyOutcome::Patched { .. }),
-            "mihomo mixed-port change should patch in place, got {outcome:?}"
+            "{} mixed-port change should patch in place, got {outcome:?}",
+            core.name
         ),
         _ => assert!(
             matches!(outcome, ApplyOutcome::Switched { .. }),
@@ -321,7 +338,11 @@ async fn ipc_mode_start(core: RealCore, policy: LocalIpcPolicy, expect_local: bo
         .await
         .expect("apply over the negotiated transport");
     match core.kind {
-        nyanpasu_core_manager::CoreKind::Mihomo => assert!(
+        nyanpasu_core_manager::CoreKind::Clash(nyanpasu_core_manager::ClashCoreKind::Mihomo)
+        | nyanpasu_core_manager::CoreKind::Clash(nyanpasu_core_manager::ClashCoreKind::ClashRust)
+        | nyanpasu_core_manager::CoreKind::Clash(
+            nyanpasu_core_manager::ClashCoreKind::ClashPremium,
+        ) => assert!(
             matches!(outcome, ApplyOutcome::Patched { .. }),
             "got {outcome:?}"
         ),
@@ -8,8 +8,8 @@ use std::time::Duration;
 
 use camino::{Utf8Path, Utf8PathBuf};
 use nyanpasu_core_manager::{
-    CoreKind, CoreSpec, Error, Instance, InstanceOptions, InstanceSpec, kind::check_config,
-    spec::ResolvedController, state::InstanceState,
+    ClashCoreKind, CoreKind, CoreSpec, Error, Instance, InstanceOptions, InstanceSpec,
+    kind::check_config, spec::ResolvedController, state::InstanceState,
 };
 use tokio_util::sync::CancellationToken;
 
@@ -40,10 +40,11 @@ fn real_mihomo_bin() -> Utf8PathBuf {
 fn real_spec(dir: &Utf8Path, config_path: Utf8PathBuf) -> InstanceSpec {
     InstanceSpec {
         core: CoreSpec {
-            kind: CoreKind::Mihomo,
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
             binary_path: real_mihomo_bin(),
             version: None,
             features: Vec::new(),
+            distribution: None,
         },
         config_path,
         working_dir: dir.to_owned(),
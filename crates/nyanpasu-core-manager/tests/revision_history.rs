@@ -0,0 +1,169 @@
+mod common;
+
+use std::time::Duration;
+
+use nyanpasu_core_manager::{
+    ApplyOutcome, ClashCoreKind, CoreKind, CoreManager, Error, ManagerOptions,
+};
+
+async fn manager(dir: &camino::Utf8Path, revision_history: usize) -> CoreManager {
+    CoreManager::new(ManagerOptions {
+        runtime_dir: Some(dir.join("runtime")),
+        control_timeout: Duration::from_secs(1),
+        reconcile_timeout: Duration::from_secs(5),
+        revision_history,
+        ..ManagerOptions::default()
+    })
+    .await
+    .expect("construct manager")
+}
+
+fn config(port: u16, extra: &str) -> String {
+    format!("external-controller: 127.0.0.1:{port}\nmode: rule\n{extra}")
+}
+
+fn write_named(dir: &camino::Utf8Path, name: &str, body: &str) -> camino::Utf8PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, body).expect("write config");
+    path
+}
+
+#[tokio::test]
+async fn a_rollback_reapplies_an_earlier_source_as_a_new_revision() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let port = common::free_port();
+    let path = write_named(&dir, "config.yaml", &config(port, "allow-lan: false\n"));
+    let manager = manager(&dir, 10).await;
+    manager
+        .start(common::mihomo_spec(&dir, path.clone()))
+        .await
+        .expect("start");
+    let good = manager.status().revision.expect("revision");
+
+    // The caller overwrites its own file: only the kept copy remembers `good`.
+    write_named(&dir, "config.yaml", &config(port, "allow-lan: true\n"));
+    manager
+        .apply_config(common::mihomo_spec(&dir, path.clone()), None)
+        .await
+        .expect("apply");
+    let bad = manager.status().revision.expect("revision");
+
+    let listed = manager.revisions().await;
+    assert_eq!(
+        listed
+            .iter()
+            .map(|record| record.revision.clone())
+            .collect::<Vec<_>>(),
+        vec![bad.clone(), good.clone()]
+    );
+    assert!(
+        listed
+            .iter()
+            .all(|record| record.kind == CoreKind::Clash(ClashCoreKind::Mihomo))
+    );
+
+    let outcome = manager
+        .rollback(&good.id(), Some(bad.id()))
+        .await
+        .expect("rollback");
+
+    let ApplyOutcome::Patched { revision } = outcome else {
+        panic!("expected Patched, got {outcome:?}")
+    };
+    assert_eq!(revision.generation, bad.generation + 1);
+    assert_eq!(revision.source_hash, good.source_hash);
+    assert_eq!(revision.effective_hash, good.effective_hash);
+    assert_eq!(manager.revisions().await.len(), 3);
+    manager.shutdown().await.expect("shutdown");
+}
+
+/// The caller's file still holds the bad config after a rollback, and a
+/// restart must not quietly bring it back.
+#[tokio::test]
+async fn a_restart_after_a_rollback_stays_on_the_restored_config() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let port = common::free_port();
+    let path = write_named(&dir, "config.yaml", &config(port, "allow-lan: false\n"));
+    let manager = manager(&dir, 10).await;
+    manager
+        .start(common::mihomo_spec(&dir, path.clone()))
+        .await
+        .expect("start");
+    let good = manager.status().revision.expect("revision");
+    write_named(&dir, "config.yaml", &config(port, "allow-lan: true\n"));
+    manager
+        .apply_config(common::mihomo_spec(&dir, path.clone()), None)
+        .await
+        .expect("apply");
+    manager.rollback(&good.id(), None).await.expect("rollback");
+
+    manager.restart().await.expect("restart");
+
+    let status = manager.status();
+    let running = status.revision.expect("revision");
+    assert_eq!(running.source_hash, good.source_hash);
+    assert_ne!(status.spec.expect("spec").config_path, path);
+    manager.shutdown().await.expect("shutdown");
+}
+
+#[tokio::test]
+async fn a_rollback_keeps_the_apply_guards() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let port = common::free_port();
+    let path = write_named(&dir, "config.yaml", &config(port, ""));
+    let manager = manager(&dir, 10).await;
+    manager
+        .start(common::mihomo_spec(&dir, path.clone()))
+        .await
+        .expect("start");
+    let first = manager.status().revision.expect("revision");
+    write_named(&dir, "config.yaml", &config(port, "allow-lan: true\n"));
+    manager
+        .apply_config(common::mihomo_spec(&dir, path), None)
+        .await
+        .expect("apply");
+    let second = manager.status().revision.expect("revision");
+
+    assert!(matches!(
+        manager.rollback(&first.id(), Some(first.id())).await,
+        Err(Error::RevisionConflict { .. })
+    ));
+    let mut unknown = first.id();
+    unknown.generation = 99;
+    assert!(matches!(
+        manager.rollback(&unknown, None).await,
+        Err(Error::RevisionNotFound(id)) if id == unknown
+    ));
+    assert_eq!(manager.status().revision, Some(second));
+    manager.shutdown().await.expect("shutdown");
+}
+
+#[tokio::test]
+async fn the_history_is_bounded_and_forgets_what_it_evicts() {
+    let (_guard, dir) = common::utf8_tempdir();
+    let port = common::free_port();
+    let path = write_named(&dir, "config.yaml", &config(port, ""));
+    let manager = manager(&dir, 2).await;
+    manager
+        .start(common::mihomo_spec(&dir, path.clone()))
+        .await
+        .expect("start");
+    let first = manager.status().revision.expect("revision");
+    for allow_lan in ["allow-lan: true\n", "allow-lan: false\n"] {
+        write_named(&dir, "config.yaml", &config(port, allow_lan));
+        manager
+            .apply_config(common::mihomo_spec(&dir, path.clone()), None)
+            .await
+            .expect("apply");
+    }
+
+    let listed = manager.revisions().await;
+    assert_eq!(listed.len(), 2);
+    assert!(listed.iter().all(|record| record.revision != first));
+    assert!(!dir.join("runtime").join("history-1-1.yaml").exists());
+    assert!(matches!(
+        manager.rollback(&first.id(), None).await,
+        Err(Error::RevisionNotFound(_))
+    ));
+    manager.shutdown().await.expect("shutdown");
+}
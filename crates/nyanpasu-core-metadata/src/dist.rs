@@ -1,6 +1,6 @@
 //! Identity of a *distributed* core artifact, as the resource layer knows it.
 //!
-//! [`ClashCoreKind`] is the behavioral axis alone — the family whose console
+//! [`CoreKind`] is the behavioral axis alone — the family whose console
 //! layout and config semantics the service dispatches on. Which build of that
 //! family is installed (release channel, compile variant, anything a future
 //! manifest invents) is a separate, open question, and this module mirrors how
@@ -9,10 +9,11 @@
 
 use std::collections::BTreeSet;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::ClashCoreKind;
+use crate::CoreKind;
 
 /// One variant tag, mirroring the manifest's `ResourceTag.id`: `group:value`
 /// with a single colon, or a bare `value` with no group.
@@ -22,7 +23,9 @@ use crate::ClashCoreKind;
 /// hash, sort and round-trip rather than fail. Normalization (trim, ASCII
 /// lowercase) happens on construction *and* on decode, which is what lets
 /// equality stay plain string equality.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Type, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Type, JsonSchema, Serialize, Deserialize,
+)]
 #[serde(from = "String")]
 #[specta(transparent)]
 pub struct VariantTag(String);
@@ -77,9 +80,9 @@ impl std::fmt::Display for VariantTag {
 /// The canonical `tag_key` is not carried and not re-derived. That key is the
 /// manifest generator's own normalization, and reimplementing it here would give
 /// two algorithms one chance each to drift; comparing tag sets needs neither.
-#[derive(Debug, Clone, PartialEq, Eq, Type, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Type, JsonSchema, Serialize, Deserialize)]
 pub struct CoreDistribution {
-    pub kind: ClashCoreKind,
+    pub kind: CoreKind,
     /// The manifest variant's stable alias (`ResourceVariant.id`), e.g.
     /// `"alpha-goamd64-v2"`. A display and reference handle only — the semantic
     /// identity is [`Self::tags`], exactly as in the manifest.
@@ -95,7 +98,7 @@ pub struct CoreDistribution {
 }
 
 impl CoreDistribution {
-    pub fn new(kind: ClashCoreKind) -> Self {
+    pub fn new(kind: CoreKind) -> Self {
         Self {
             kind,
             variant: None,
@@ -120,6 +123,7 @@ impl CoreDistribution {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ClashCoreKind;
 
     fn tag(value: &str) -> VariantTag {
         VariantTag::new(value)
@@ -172,7 +176,7 @@ mod tests {
 
     #[test]
     fn a_distribution_encodes_its_three_states() {
-        let untagged = CoreDistribution::new(ClashCoreKind::Mihomo);
+        let untagged = CoreDistribution::new(CoreKind::Clash(ClashCoreKind::Mihomo));
         assert_eq!(
             serde_json::to_string(&untagged).unwrap(),
             r#"{"kind":"mihomo"}"#,
@@ -184,7 +188,7 @@ mod tests {
         );
 
         let full = CoreDistribution {
-            kind: ClashCoreKind::Mihomo,
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
             variant: Some("alpha-goamd64-v2".to_owned()),
             tags: BTreeSet::from([tag("channel:alpha"), tag("goamd64:v2")]),
         };
@@ -201,14 +205,25 @@ mod tests {
         let unknown =
             serde_json::from_str::<CoreDistribution>(r#"{"kind":"clash-rs","tags":["Future:X"]}"#)
                 .unwrap();
-        assert_eq!(unknown.kind, ClashCoreKind::ClashRust);
+        assert_eq!(unknown.kind, CoreKind::Clash(ClashCoreKind::ClashRust));
         assert!(unknown.tags.contains(&tag("future:x")));
+
+        // sing-box is a kind of its own, spelled like every other one.
+        let sing_box = CoreDistribution::new(CoreKind::SingBox);
+        assert_eq!(
+            serde_json::to_string(&sing_box).unwrap(),
+            r#"{"kind":"sing-box"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<CoreDistribution>(r#"{"kind":"sing-box"}"#).unwrap(),
+            sing_box
+        );
     }
 
     #[test]
     fn a_distribution_answers_channel_and_arbitrary_group_queries() {
         let distribution = CoreDistribution {
-            kind: ClashCoreKind::Mihomo,
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
             variant: Some("alpha-goamd64-v3".to_owned()),
             tags: BTreeSet::from([tag("channel:alpha"), tag("goamd64:v3"), tag("portable")]),
         };
@@ -219,6 +234,9 @@ mod tests {
         // An ungrouped tag belongs to no group and answers no group query.
         assert_eq!(distribution.tag_value("portable"), None);
 
-        assert_eq!(CoreDistribution::new(ClashCoreKind::Meow).channel(), None);
+        assert_eq!(
+            CoreDistribution::new(CoreKind::Clash(ClashCoreKind::Meow)).channel(),
+            None
+        );
     }
 }
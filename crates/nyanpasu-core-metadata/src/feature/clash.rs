@@ -123,6 +123,21 @@ impl FeatureSupport for crate::kind::ClashCoreKind {
     }
 }
 
+impl FeatureSupport for crate::kind::CoreKind {
+    fn supports(&self, feature: Feature, version: Option<&CoreVersion>) -> Support {
+        match self {
+            crate::kind::CoreKind::Clash(kind) => kind.supports(feature, version),
+            // sing-box's Clash API only ever listens on
+            // `experimental.clash_api.external_controller`, a TCP address.
+            crate::kind::CoreKind::SingBox => match feature {
+                Feature::NamedPipeIpc | Feature::UnixSocketIpc | Feature::DisableTcpController => {
+                    Support::No
+                }
+            },
+        }
+    }
+}
+
 /// Resolves a floor against a known version, or hands the requirement back when
 /// the version is unknown.
 fn since(req: &LazyLock<VersionReq>, version: Option<&CoreVersion>) -> Support {
@@ -137,7 +152,7 @@ fn since(req: &LazyLock<VersionReq>, version: Option<&CoreVersion>) -> Support {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::kind::ClashCoreKind;
+    use crate::kind::{ClashCoreKind, CoreKind};
 
     fn version(raw: &str) -> Option<CoreVersion> {
         Some(CoreVersion::parse(raw))
@@ -278,7 +293,11 @@ mod tests {
 
     #[test]
     fn tcp_only_cores_never_support_local_ipc() {
-        for kind in [ClashCoreKind::ClashPremium, ClashCoreKind::Meow] {
+        for kind in [
+            CoreKind::Clash(ClashCoreKind::ClashPremium),
+            CoreKind::Clash(ClashCoreKind::Meow),
+            CoreKind::SingBox,
+        ] {
             for feature in [
                 Feature::NamedPipeIpc,
                 Feature::UnixSocketIpc,
@@ -300,10 +319,11 @@ mod tests {
     #[test]
     fn no_core_can_disable_its_tcp_controller_today() {
         for kind in [
-            ClashCoreKind::Mihomo,
-            ClashCoreKind::ClashRust,
-            ClashCoreKind::ClashPremium,
-            ClashCoreKind::Meow,
+            CoreKind::Clash(ClashCoreKind::Mihomo),
+            CoreKind::Clash(ClashCoreKind::ClashRust),
+            CoreKind::Clash(ClashCoreKind::ClashPremium),
+            CoreKind::Clash(ClashCoreKind::Meow),
+            CoreKind::SingBox,
         ] {
             assert!(matches!(
                 kind.supports(Feature::DisableTcpController, Some(&CoreVersion::Nightly)),
@@ -3,13 +3,66 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
+/// Every core kind the service can run. sing-box is not a Clash core, but it
+/// serves the Clash RESTful API from `experimental.clash_api`, which is all the
+/// manager drives.
+///
+/// On the wire this is one flat string: a Clash kind's own name, or
+/// `"sing-box"`.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Type, JsonSchema, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum CoreKind {
     Clash(ClashCoreKind),
+    // Untagged, a unit variant would be `null`; this keeps it a name.
+    #[serde(with = "sing_box_name")]
+    #[schemars(with = "SingBoxName")]
     SingBox,
 }
 
+impl From<ClashCoreKind> for CoreKind {
+    fn from(kind: ClashCoreKind) -> Self {
+        Self::Clash(kind)
+    }
+}
+
+impl AsRef<str> for CoreKind {
+    fn as_ref(&self) -> &str {
+        match self {
+            CoreKind::Clash(kind) => kind.as_ref(),
+            CoreKind::SingBox => SINGBOX_NAME,
+        }
+    }
+}
+
+impl std::fmt::Display for CoreKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+const SINGBOX_NAME: &str = "sing-box";
+
+/// The one name [`CoreKind::SingBox`] goes by.
+#[derive(JsonSchema, Serialize, Deserialize)]
+enum SingBoxName {
+    #[serde(rename = "sing-box")]
+    SingBox,
+}
+
+mod sing_box_name {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::SingBoxName;
+
+    pub fn serialize<S: Serializer>(serializer: S) -> Result<S::Ok, S::Error> {
+        SingBoxName::SingBox.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(), D::Error> {
+        SingBoxName::deserialize(deserializer).map(|_| ())
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Type, JsonSchema, Serialize, Deserialize)]
 #[repr(u8)]
 /// Supported Clash core kinds. This is used to gate the launch arguments and api favors.
@@ -45,7 +98,7 @@ impl std::fmt::Display for ClashCoreKind {
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Type, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Type, JsonSchema, Serialize, Deserialize)]
 #[repr(u8)]
 /// The resource variant of a Clash core. This is used to determine which resource to download for a given core kind.
 pub enum ClashCoreResourceVariant {
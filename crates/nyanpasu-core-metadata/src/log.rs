@@ -5,15 +5,18 @@
 //! onto disk, and the IPC event that pushes it to a client. Every representation
 //! that used to project this shape has been retired.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::ClashCoreKind;
+use crate::CoreKind;
 
 /// Normalized severity. Go's `fatal` and `panic` both terminate the process, so
 /// they collapse into `Fatal`; the original spelling survives in
 /// [`LogFrame::raw`].
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Type, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Type, JsonSchema, Serialize, Deserialize,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     Trace,
@@ -25,14 +28,14 @@ pub enum LogLevel {
 }
 
 /// Which console stream a record arrived on.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type, JsonSchema, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum LogStream {
     Stdout,
     Stderr,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Type, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Type, JsonSchema, Serialize, Deserialize)]
 pub struct LogTimestamp {
     /// Exactly as the core printed it.
     pub raw: String,
@@ -44,7 +47,7 @@ pub struct LogTimestamp {
 }
 
 /// One structured field the core printed beside its message.
-#[derive(Debug, Clone, PartialEq, Eq, Type, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Type, JsonSchema, Serialize, Deserialize)]
 pub struct LogField {
     pub key: String,
     pub value: String,
@@ -58,7 +61,7 @@ pub struct LogField {
 ///
 /// The field order is the on-disk JSONL order: the archive flattens this struct
 /// straight into its envelope.
-#[derive(Debug, Clone, PartialEq, Eq, Type, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Type, JsonSchema, Serialize, Deserialize)]
 pub struct LogFrame {
     /// Unix milliseconds at which the parser observed the record's **root**
     /// line. Always present, which is what makes a stream of frames sortable: a
@@ -67,7 +70,7 @@ pub struct LogFrame {
     /// instant of the line that opened it.
     pub at: i64,
     pub epoch: u64,
-    pub kind: ClashCoreKind,
+    pub kind: CoreKind,
     pub stream: LogStream,
     pub level: LogLevel,
     pub timestamp: Option<LogTimestamp>,
@@ -0,0 +1,102 @@
+use nyanpasu_ipc::{
+    api::audit::{AUDIT_MAX_LIMIT, AuditQueryReq, AuditRecord},
+    client::shortcuts::Client,
+};
+
+use super::CommandError;
+
+#[derive(Debug, clap::Args)]
+pub struct AuditCommand {
+    /// How many of the newest records to print
+    #[arg(long, default_value = "20", conflicts_with = "all")]
+    limit: u32,
+
+    /// Print the whole trail, oldest first
+    #[arg(long, default_value = "false")]
+    all: bool,
+
+    /// Print one JSON record per line, as the trail stores them
+    #[arg(long, default_value = "false")]
+    json: bool,
+}
+
+pub async fn audit(ctx: AuditCommand) -> Result<(), CommandError> {
+    let client = Client::service_default();
+    let records = if ctx.all {
+        let mut records = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = client
+                .audit(&AuditQueryReq {
+                    cursor,
+                    limit: Some(AUDIT_MAX_LIMIT),
+                    backward: false,
+                })
+                .await
+                .map_err(|e| CommandError::Other(e.into()))?;
+            records.extend(page.records);
+            if page.exhausted {
+                break;
+            }
+            cursor = Some(page.cursor);
+        }
+        records
+    } else {
+        let page = client
+            .audit(&AuditQueryReq {
+                cursor: None,
+                limit: Some(ctx.limit),
+                backward: true,
+            })
+            .await
+            .map_err(|e| CommandError::Other(e.into()))?;
+        // Newest first on the wire; printed oldest first, like a log.
+        page.records.into_iter().rev().collect()
+    };
+    for record in &records {
+        if ctx.json {
+            println!(
+                "{}",
+                serde_json::to_string(record).map_err(|e| CommandError::Other(e.into()))?
+            );
+        } else {
+            println!("{}", line(record));
+        }
+    }
+    Ok(())
+}
+
+/// `<time> <operation> <caller> <outcome> [request id]`, one record a line.
+fn line(record: &AuditRecord) -> String {
+    let at = chrono::DateTime::from_timestamp_millis(record.at)
+        .map(|at| {
+            at.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S%.3f")
+                .to_string()
+        })
+        .unwrap_or_else(|| record.at.to_string());
+    let caller = match &record.caller {
+        Some(caller) => format!("uid={} gid={}", caller.uid, caller.gid),
+        None => "uid=?".to_owned(),
+    };
+    let outcome = if record.outcome.ok {
+        "ok".to_owned()
+    } else {
+        format!(
+            "failed({}{}): {}",
+            record.outcome.status,
+            record
+                .outcome
+                .error_kind
+                .as_deref()
+                .map(|kind| format!(", {kind}"))
+                .unwrap_or_default(),
+            record.outcome.msg.as_deref().unwrap_or_default()
+        )
+    };
+    let mut line = format!("{at} {} {caller} {outcome}", record.operation);
+    if let Some(request_id) = &record.request_id {
+        line.push_str(&format!(" [{request_id}]"));
+    }
+    line
+}
@@ -8,6 +8,13 @@ use crate::consts::{APP_NAME, SERVICE_LABEL};
 
 use super::{CommandError, LocalIpcPolicyArg};
 
+/// What a directory-list variable is split on: the platform's own `PATH`
+/// separator, since a Windows path already contains `:`.
+#[cfg(windows)]
+const DIR_LIST_DELIMITER: char = ';';
+#[cfg(not(windows))]
+const DIR_LIST_DELIMITER: char = ':';
+
 /// Every argument may come from its `NYANPASU_*` variable instead; an explicit
 /// flag always wins. Note for callers: `sudo` resets the environment by
 /// default, so the fallbacks need `sudo -E` (or `sudo NYANPASU_…=…`) to reach
@@ -34,6 +41,23 @@ pub struct InstallCommand {
         env = "NYANPASU_LOCAL_IPC_POLICY"
     )]
     local_ipc_policy: LocalIpcPolicyArg,
+    /// An extra directory the service searches for a core binary. Repeatable
+    #[clap(
+        long = "core-search-dir",
+        env = "NYANPASU_CORE_SEARCH_DIRS",
+        value_delimiter = DIR_LIST_DELIMITER
+    )]
+    core_search_dirs: Vec<PathBuf>,
+    /// Let the service search its own `PATH` for a core binary
+    #[clap(long, env = "NYANPASU_CORE_SEARCH_SYSTEM_PATH")]
+    core_search_system_path: bool,
+    /// A directory clients may name an explicit core binary in. Repeatable
+    #[clap(
+        long = "allowed-core-dir",
+        env = "NYANPASU_ALLOWED_CORE_DIRS",
+        value_delimiter = DIR_LIST_DELIMITER
+    )]
+    allowed_core_dirs: Vec<PathBuf>,
 }
 
 pub fn install(ctx: InstallCommand) -> Result<(), CommandError> {
@@ -151,21 +175,33 @@ fn build_install_ctx(
     ctx: &InstallCommand,
     environment: Vec<(String, String)>,
 ) -> ServiceInstallCtx {
+    let mut args = vec![
+        OsString::from("server"),
+        OsString::from("--nyanpasu-data-dir"),
+        ctx.nyanpasu_data_dir.clone().into(),
+        OsString::from("--nyanpasu-config-dir"),
+        ctx.nyanpasu_config_dir.clone().into(),
+        OsString::from("--nyanpasu-app-dir"),
+        ctx.nyanpasu_app_dir.clone().into(),
+        OsString::from("--local-ipc-policy"),
+        OsString::from(policy_value(ctx.local_ipc_policy)),
+    ];
+    // One flag per directory, never a joined list: the service definition
+    // must not depend on the installer's platform separator.
+    for dir in &ctx.core_search_dirs {
+        args.extend([OsString::from("--core-search-dir"), dir.clone().into()]);
+    }
+    if ctx.core_search_system_path {
+        args.push(OsString::from("--core-search-system-path"));
+    }
+    for dir in &ctx.allowed_core_dirs {
+        args.extend([OsString::from("--allowed-core-dir"), dir.clone().into()]);
+    }
+    args.push(OsString::from("--service"));
     ServiceInstallCtx {
         label,
         program,
-        args: vec![
-            OsString::from("server"),
-            OsString::from("--nyanpasu-data-dir"),
-            ctx.nyanpasu_data_dir.clone().into(),
-            OsString::from("--nyanpasu-config-dir"),
-            ctx.nyanpasu_config_dir.clone().into(),
-            OsString::from("--nyanpasu-app-dir"),
-            ctx.nyanpasu_app_dir.clone().into(),
-            OsString::from("--local-ipc-policy"),
-            OsString::from(policy_value(ctx.local_ipc_policy)),
-            OsString::from("--service"),
-        ],
+        args,
         contents: None,
         username: None, // because we just need to run the service as root
         working_directory: Some(working_directory),
@@ -191,6 +227,9 @@ mod tests {
                 nyanpasu_config_dir: "config".into(),
                 nyanpasu_app_dir: "app".into(),
                 local_ipc_policy: LocalIpcPolicyArg::Disable,
+                core_search_dirs: Vec::new(),
+                core_search_system_path: false,
+                allowed_core_dirs: Vec::new(),
             },
         );
 
@@ -209,6 +248,9 @@ mod tests {
                 nyanpasu_config_dir: "config".into(),
                 nyanpasu_app_dir: "app".into(),
                 local_ipc_policy: LocalIpcPolicyArg::Disable,
+                core_search_dirs: Vec::new(),
+                core_search_system_path: false,
+                allowed_core_dirs: Vec::new(),
             },
         );
 
@@ -224,6 +266,9 @@ mod tests {
             nyanpasu_config_dir: "config".into(),
             nyanpasu_app_dir: "app".into(),
             local_ipc_policy: LocalIpcPolicyArg::Disable,
+            core_search_dirs: Vec::new(),
+            core_search_system_path: false,
+            allowed_core_dirs: Vec::new(),
         };
         let environment = vec![("HOME".into(), "home".into())];
         let install_ctx = build_install_ctx(
@@ -263,4 +308,40 @@ mod tests {
         assert!(install_ctx.autostart);
         assert_eq!(install_ctx.restart_policy, RestartPolicy::default());
     }
+
+    #[test]
+    fn install_ctx_passes_every_core_binary_dir_as_its_own_flag() {
+        let ctx = InstallCommand {
+            user: "user".into(),
+            nyanpasu_data_dir: "data".into(),
+            nyanpasu_config_dir: "config".into(),
+            nyanpasu_app_dir: "app".into(),
+            local_ipc_policy: LocalIpcPolicyArg::Disable,
+            core_search_dirs: vec!["/opt/cores".into(), "/srv/cores".into()],
+            core_search_system_path: true,
+            allowed_core_dirs: vec!["/usr/bin".into()],
+        };
+        let install_ctx = build_install_ctx(
+            label(),
+            PathBuf::from("program"),
+            PathBuf::from("working-directory"),
+            &ctx,
+            Vec::new(),
+        );
+
+        assert_eq!(
+            install_ctx.args[9..],
+            [
+                "--core-search-dir",
+                "/opt/cores",
+                "--core-search-dir",
+                "/srv/cores",
+                "--core-search-system-path",
+                "--allowed-core-dir",
+                "/usr/bin",
+                "--service",
+            ]
+            .map(OsString::from)
+        );
+    }
 }
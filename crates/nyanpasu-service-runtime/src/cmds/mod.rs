@@ -1,10 +1,13 @@
 use crate::logging;
 use clap::{Parser, Subcommand};
 
+mod audit;
 mod completions;
 mod install;
+mod pin_core;
 mod restart;
 mod rpc;
+mod schema;
 mod server;
 mod start;
 mod status;
@@ -47,6 +50,16 @@ impl From<LocalIpcPolicyArg> for nyanpasu_core_manager::LocalIpcPolicy {
     }
 }
 
+impl From<LocalIpcPolicyArg> for nyanpasu_ipc::api::service_config::LocalIpcPolicyConfig {
+    fn from(value: LocalIpcPolicyArg) -> Self {
+        match value {
+            LocalIpcPolicyArg::Force => Self::Force,
+            LocalIpcPolicyArg::Prefer => Self::Prefer,
+            LocalIpcPolicyArg::Disable => Self::Disable,
+        }
+    }
+}
+
 /// Nyanpasu Service, a privileged service for managing the core service.
 ///
 /// The main entry point for the service, Other commands are the control plane for the service.
@@ -94,12 +107,20 @@ enum Commands {
     Status(status::StatusCommand),
     /// Update the service
     Update(update::UpdateCommand),
+    /// Pin core binaries at their current SHA-256; the service refuses any
+    /// core binary that is not pinned once a pin exists
+    PinCore(pin_core::PinCoreCommand),
     /// RPC commands, a shortcut for client rpc calls
     #[command(subcommand)]
     Rpc(rpc::RpcCommand),
+    /// Print the audit trail of control-plane calls
+    Audit(audit::AuditCommand),
     /// Print a shell completion script on stdout
     #[command(hide = true)]
     Completions(completions::CompletionsCommand),
+    /// Write the IPC contract as TypeScript definitions and JSON Schema
+    #[command(hide = true)]
+    Schema(schema::SchemaCommand),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -141,7 +162,9 @@ fn is_unprivileged(command: &Option<Commands>) -> bool {
         None
         | Some(Commands::Status(_))
         | Some(Commands::Rpc(_))
-        | Some(Commands::Completions(_)) => true,
+        | Some(Commands::Audit(_))
+        | Some(Commands::Completions(_))
+        | Some(Commands::Schema(_)) => true,
         // `--check` only compares versions; a real update still writes to the
         // service data dir and still needs elevation.
         Some(Commands::Update(ctx)) => ctx.check,
@@ -181,14 +204,19 @@ pub async fn process() -> Result<(), CommandError> {
             update::update(ctx).await?;
             Ok(())
         }
+        Some(Commands::PinCore(ctx)) => {
+            Ok(tokio::task::spawn_blocking(move || pin_core::pin_core(ctx)).await??)
+        }
         Some(Commands::Rpc(ctx)) => {
             rpc::rpc(ctx).await?;
             Ok(())
         }
+        Some(Commands::Audit(ctx)) => Ok(audit::audit(ctx).await?),
         Some(Commands::Completions(ctx)) => {
             completions::completions(ctx);
             Ok(())
         }
+        Some(Commands::Schema(ctx)) => schema::schema(ctx),
         None => {
             eprintln!("No command specified");
             Ok(())
@@ -506,12 +534,15 @@ mod tests {
     #[test]
     fn the_completions_subcommand_is_hidden() {
         let cli = Cli::command();
-        assert!(
-            cli.find_subcommand("completions")
-                .expect("completions is declared")
-                .is_hide_set()
-        );
-        for name in ["install", "status", "update", "rpc", "server"] {
+        for name in ["completions", "schema"] {
+            assert!(
+                cli.find_subcommand(name)
+                    .unwrap_or_else(|| panic!("{name} is declared"))
+                    .is_hide_set(),
+                "{name} must stay hidden"
+            );
+        }
+        for name in ["install", "status", "update", "rpc", "audit", "server"] {
             assert!(
                 !cli.find_subcommand(name)
                     .unwrap_or_else(|| panic!("{name} is declared"))
@@ -532,6 +563,8 @@ mod tests {
         assert!(unprivileged(&["nyanpasu-service", "status"]));
         assert!(unprivileged(&["nyanpasu-service", "rpc", "stop-core"]));
         assert!(unprivileged(&["nyanpasu-service", "completions", "bash"]));
+        assert!(unprivileged(&["nyanpasu-service", "schema"]));
+        assert!(unprivileged(&["nyanpasu-service", "audit", "--all"]));
         assert!(unprivileged(&["nyanpasu-service", "update", "--check"]));
         assert!(unprivileged(&["nyanpasu-service", "-V"]));
 
@@ -539,6 +572,11 @@ mod tests {
         assert!(!unprivileged(&["nyanpasu-service", "start"]));
         assert!(!unprivileged(&["nyanpasu-service", "stop"]));
         assert!(!unprivileged(&["nyanpasu-service", "restart"]));
+        assert!(!unprivileged(&[
+            "nyanpasu-service",
+            "pin-core",
+            "/usr/bin/mihomo"
+        ]));
         assert!(!unprivileged(&["nyanpasu-service", "uninstall"]));
         assert!(!unprivileged(&[
             "nyanpasu-service",
@@ -564,13 +602,83 @@ mod tests {
         ]));
     }
 
-    fn server_policy(argv: &[&str]) -> LocalIpcPolicyArg {
+    fn server_context(argv: &[&str]) -> server::ServerContext {
         let cli = Cli::try_parse_from(argv)
             .unwrap_or_else(|err| panic!("{argv:?} does not parse:\n{err}"));
         let Some(Commands::Server(ctx)) = cli.command else {
             panic!("{argv:?} is not a server invocation")
         };
-        ctx.local_ipc_policy
+        ctx
+    }
+
+    fn server_policy(argv: &[&str]) -> LocalIpcPolicyArg {
+        server_context(argv).local_ipc_policy
+    }
+
+    /// Same back-compat rule as the policy: an old service definition has no
+    /// `--telemetry-interval-ms`. Zero would make the relay's ticker panic, so
+    /// it is refused at parse time instead.
+    #[test]
+    fn the_server_telemetry_interval_defaults_to_a_second() {
+        let argv = [
+            "nyanpasu-service",
+            "server",
+            "--nyanpasu-data-dir",
+            "data",
+            "--nyanpasu-config-dir",
+            "config",
+            "--nyanpasu-app-dir",
+            "app",
+        ];
+        assert_eq!(server_context(&argv).telemetry_interval_ms, 1000);
+        let with = |value: &str| {
+            let mut argv = argv.to_vec();
+            argv.extend(["--telemetry-interval-ms", value]);
+            Cli::try_parse_from(argv).map(|cli| match cli.command {
+                Some(Commands::Server(ctx)) => ctx.telemetry_interval_ms,
+                _ => unreachable!(),
+            })
+        };
+        assert_eq!(with("250").unwrap(), 250);
+        assert!(with("0").is_err());
+    }
+
+    /// An old service definition carries none of the core-binary flags, and
+    /// must keep the data-dir-then-app-dir search with nothing explicit allowed.
+    #[test]
+    fn the_server_core_binary_lookup_defaults_to_the_nyanpasu_dirs() {
+        let argv = [
+            "nyanpasu-service",
+            "server",
+            "--nyanpasu-data-dir",
+            "data",
+            "--nyanpasu-config-dir",
+            "config",
+            "--nyanpasu-app-dir",
+            "app",
+        ];
+        let ctx = server_context(&argv);
+        assert!(ctx.core_search_dirs.is_empty());
+        assert!(!ctx.core_search_system_path);
+        assert!(ctx.allowed_core_dirs.is_empty());
+
+        let mut argv = argv.to_vec();
+        argv.extend([
+            "--core-search-dir",
+            "a",
+            "--core-search-dir",
+            "b",
+            "--core-search-system-path",
+            "--allowed-core-dir",
+            "c",
+        ]);
+        let ctx = server_context(&argv);
+        assert_eq!(
+            ctx.core_search_dirs,
+            [std::path::PathBuf::from("a"), "b".into()]
+        );
+        assert!(ctx.core_search_system_path);
+        assert_eq!(ctx.allowed_core_dirs, [std::path::PathBuf::from("c")]);
     }
 
     /// The transition default (report §4 P2). Two things ride on it: a service
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use crate::utils::core_manifest::{CoreManifest, sha256_file};
+
+use super::CommandError;
+
+#[derive(Debug, clap::Args)]
+pub struct PinCoreCommand {
+    /// The core binaries to pin at their current SHA-256
+    #[clap(required = true, value_name = "BINARY")]
+    binaries: Vec<PathBuf>,
+
+    /// Drop the binaries from the manifest instead of pinning them
+    #[clap(long, default_value = "false")]
+    remove: bool,
+}
+
+/// Records the binaries' digests in the pinned core manifest, creating it on
+/// first use. From then on the service refuses any core the manifest does not
+/// list; see [`crate::utils::core_manifest`].
+pub fn pin_core(ctx: PinCoreCommand) -> Result<(), CommandError> {
+    let service_config_dir = crate::utils::dirs::service_config_dir();
+    std::fs::create_dir_all(&service_config_dir)?;
+    let mut manifest = CoreManifest::load(&service_config_dir)?.unwrap_or_default();
+    for binary in &ctx.binaries {
+        let canonical = dunce::canonicalize(binary)?;
+        if ctx.remove {
+            if manifest.binaries.remove(&canonical).is_none() {
+                tracing::warn!("{} is not pinned", canonical.display());
+            }
+            continue;
+        }
+        let sha256 = pin(&mut manifest, &canonical)?;
+        tracing::info!("pinned {} at {sha256}", canonical.display());
+    }
+    manifest.save(&service_config_dir)?;
+    Ok(())
+}
+
+fn pin(manifest: &mut CoreManifest, canonical: &Path) -> std::io::Result<String> {
+    if !canonical.is_file() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} is not a file", canonical.display()),
+        ));
+    }
+    let sha256 = sha256_file(canonical)?;
+    manifest
+        .binaries
+        .insert(canonical.to_path_buf(), sha256.clone());
+    Ok(sha256)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinning_again_replaces_the_recorded_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("mihomo");
+        std::fs::write(&binary, b"v1").unwrap();
+        let mut manifest = CoreManifest::default();
+        let first = pin(&mut manifest, &binary).unwrap();
+
+        std::fs::write(&binary, b"v2").unwrap();
+        let second = pin(&mut manifest, &binary).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(manifest.pinned(&binary), Some(second.as_str()));
+        assert!(pin(&mut manifest, dir.path()).is_err());
+    }
+}
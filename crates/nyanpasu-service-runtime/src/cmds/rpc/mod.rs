@@ -7,15 +7,18 @@ use clap::{
     builder::{PossibleValue, TypedValueParser},
 };
 use nyanpasu_ipc::{
-    api::{network::set_dns::NetworkSetDnsReq, status::RevisionIdInfo},
+    api::{
+        core::start::CorePolicyOverrides, network::set_dns::NetworkSetDnsReq,
+        status::RevisionIdInfo,
+    },
     client::shortcuts::Client,
 };
 use nyanpasu_utils::core::{ClashCoreType, CoreType};
 
 /// The core names `--core-type` accepts, spelled exactly as they are on the
 /// wire (`ClashCoreType`'s serde renames) so a CLI value and an IPC payload can
-/// never disagree. `CoreType::SingBox` is deliberately absent: it is not in
-/// `CoreType::get_supported_cores`. The legacy JSON form still reaches it.
+/// never disagree. That includes `singbox`, which `CoreType::SingBox`
+/// serializes as even though it is not in `CoreType::get_supported_cores`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 enum CoreTypeArg {
     #[value(name = "mihomo")]
@@ -30,18 +33,21 @@ enum CoreTypeArg {
     ClashPremium,
     #[value(name = "meow")]
     Meow,
+    #[value(name = "singbox")]
+    SingBox,
 }
 
 impl From<CoreTypeArg> for CoreType {
     fn from(value: CoreTypeArg) -> Self {
-        CoreType::Clash(match value {
-            CoreTypeArg::Mihomo => ClashCoreType::Mihomo,
-            CoreTypeArg::MihomoAlpha => ClashCoreType::MihomoAlpha,
-            CoreTypeArg::ClashRust => ClashCoreType::ClashRust,
-            CoreTypeArg::ClashRustAlpha => ClashCoreType::ClashRustAlpha,
-            CoreTypeArg::ClashPremium => ClashCoreType::ClashPremium,
-            CoreTypeArg::Meow => ClashCoreType::Meow,
-        })
+        match value {
+            CoreTypeArg::Mihomo => CoreType::Clash(ClashCoreType::Mihomo),
+            CoreTypeArg::MihomoAlpha => CoreType::Clash(ClashCoreType::MihomoAlpha),
+            CoreTypeArg::ClashRust => CoreType::Clash(ClashCoreType::ClashRust),
+            CoreTypeArg::ClashRustAlpha => CoreType::Clash(ClashCoreType::ClashRustAlpha),
+            CoreTypeArg::ClashPremium => CoreType::Clash(ClashCoreType::ClashPremium),
+            CoreTypeArg::Meow => CoreType::Clash(ClashCoreType::Meow),
+            CoreTypeArg::SingBox => CoreType::SingBox,
+        }
     }
 }
 
@@ -103,6 +109,51 @@ fn parse_revision_id(value: &str) -> Result<RevisionIdInfo, String> {
     })
 }
 
+/// The `policy` overrides of a start or apply. Any left out keep the service's
+/// own setting; the service refuses values outside its bounds.
+#[derive(Debug, clap::Args)]
+pub struct PolicyArgs {
+    /// Startup timeout for this launch, in milliseconds
+    #[clap(long)]
+    startup_timeout_ms: Option<u64>,
+
+    /// Health probe interval, in milliseconds
+    #[clap(long)]
+    health_interval_ms: Option<u64>,
+
+    /// Failed probes before the core is reported unhealthy
+    #[clap(long)]
+    failure_threshold: Option<u32>,
+
+    /// Passing probes before it is reported healthy again
+    #[clap(long)]
+    success_threshold: Option<u32>,
+
+    /// Probe failures inside this window after a start do not count, in
+    /// milliseconds
+    #[clap(long)]
+    start_period_ms: Option<u64>,
+
+    /// Restarts allowed after the core exits on its own; 0 disables them
+    #[clap(long)]
+    max_restarts: Option<u32>,
+}
+
+impl PolicyArgs {
+    /// `None` when no flag was given, so the request carries no `policy` key.
+    fn into_overrides(self) -> Option<CorePolicyOverrides> {
+        let overrides = CorePolicyOverrides {
+            startup_timeout_ms: self.startup_timeout_ms,
+            health_interval_ms: self.health_interval_ms,
+            failure_threshold: self.failure_threshold,
+            success_threshold: self.success_threshold,
+            start_period_ms: self.start_period_ms,
+            max_restarts: self.max_restarts,
+        };
+        (overrides != CorePolicyOverrides::default()).then_some(overrides)
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum RpcCommand {
     /// Start specific core with the given config file
@@ -115,6 +166,14 @@ pub enum RpcCommand {
         /// The path to the core config fileW
         #[clap(long)]
         config_file: std::path::PathBuf,
+
+        /// Run this core binary instead of searching for one. It must sit in
+        /// a directory the service allows
+        #[clap(long)]
+        binary_path: Option<std::path::PathBuf>,
+
+        #[clap(flatten)]
+        policy: PolicyArgs,
     },
     /// Stop the running core
     StopCore,
@@ -138,6 +197,14 @@ pub enum RpcCommand {
         #[clap(long)]
         #[arg(value_parser = parse_revision_id)]
         expected_revision: Option<RevisionIdInfo>,
+
+        /// Run this core binary instead of searching for one. It must sit in
+        /// a directory the service allows
+        #[clap(long)]
+        binary_path: Option<std::path::PathBuf>,
+
+        #[clap(flatten)]
+        policy: PolicyArgs,
     },
     /// Dry-run a config against a core binary without touching the running core
     CheckConfig {
@@ -156,6 +223,8 @@ pub enum RpcCommand {
     InspectLogs,
     /// Set the dns servers
     SetDns { dns_servers: Option<Vec<IpAddr>> },
+    /// Re-read the service config and apply what can change without a restart
+    Reload,
 }
 
 pub async fn rpc(commands: RpcCommand) -> Result<(), crate::cmds::CommandError> {
@@ -164,12 +233,17 @@ pub async fn rpc(commands: RpcCommand) -> Result<(), crate::cmds::CommandError>
         RpcCommand::StartCore {
             core_type,
             config_file,
+            binary_path,
+            policy,
         } => {
             let client = Client::service_default();
 
             let payload = nyanpasu_ipc::api::core::start::CoreStartReq {
                 core_type: Cow::Borrowed(&core_type),
                 config_file: Cow::Borrowed(&config_file),
+                binary_path: binary_path.as_ref().map(Cow::Borrowed),
+                distribution: None,
+                policy: policy.into_overrides(),
             };
             client
                 .start_core(&payload)
@@ -185,21 +259,32 @@ pub async fn rpc(commands: RpcCommand) -> Result<(), crate::cmds::CommandError>
         }
         RpcCommand::RestartCore => {
             let client = Client::service_default();
-            client
+            let data = client
                 .restart_core()
                 .await
                 .map_err(|e| crate::cmds::CommandError::Other(e.into()))?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&data)
+                    .map_err(|e| crate::cmds::CommandError::Other(e.into()))?
+            );
         }
         RpcCommand::ApplyConfig {
             core_type,
             config_file,
             expected_revision,
+            binary_path,
+            policy,
         } => {
             let client = Client::service_default();
             let payload = nyanpasu_ipc::api::core::apply::CoreApplyReq {
                 core_type: Cow::Borrowed(&core_type),
-                config_file: Cow::Borrowed(&config_file),
+                config_file: Some(Cow::Borrowed(&config_file)),
+                config: None,
                 expected_revision,
+                binary_path: binary_path.as_ref().map(Cow::Borrowed),
+                distribution: None,
+                policy: policy.into_overrides(),
             };
             let data = client
                 .apply_config(&payload)
@@ -220,7 +305,9 @@ pub async fn rpc(commands: RpcCommand) -> Result<(), crate::cmds::CommandError>
             let client = Client::service_default();
             let payload = nyanpasu_ipc::api::core::check::CoreCheckReq {
                 core_type: Cow::Borrowed(&core_type),
-                config_file: Cow::Borrowed(&config_file),
+                config_file: Some(Cow::Borrowed(&config_file)),
+                config: None,
+                binary_path: None,
             };
             client
                 .check_config(&payload)
@@ -255,6 +342,19 @@ pub async fn rpc(commands: RpcCommand) -> Result<(), crate::cmds::CommandError>
                 .await
                 .map_err(|e| crate::cmds::CommandError::Other(e.into()))?;
         }
+        RpcCommand::Reload => {
+            let client = Client::service_default();
+            let data = client
+                .reload_service()
+                .await
+                .map_err(|e| crate::cmds::CommandError::Other(e.into()))?;
+            // `restart_required` is what the administrator has to act on.
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&data)
+                    .map_err(|e| crate::cmds::CommandError::Other(e.into()))?
+            );
+        }
     }
     Ok(())
 }
@@ -280,6 +380,7 @@ mod tests {
         for (name, expected) in cases {
             assert_eq!(parse(name).unwrap(), CoreType::Clash(expected), "{name}");
         }
+        assert_eq!(parse("singbox").unwrap(), CoreType::SingBox);
     }
 
     /// The CLI names are the wire names. If a core is ever added to
@@ -328,7 +429,8 @@ mod tests {
                 "clash-rs",
                 "clash-rs-alpha",
                 "clash",
-                "meow"
+                "meow",
+                "singbox"
             ]
         );
     }
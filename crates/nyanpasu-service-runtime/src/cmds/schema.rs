@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+/// Hidden on purpose: the output is for the GUI's build, which regenerates
+/// its bindings from it, not for anyone operating the service.
+#[derive(Debug, clap::Args)]
+pub struct SchemaCommand {
+    /// The directory to write into; created if missing
+    #[arg(long, default_value = "schema")]
+    out_dir: PathBuf,
+}
+
+pub fn schema(ctx: SchemaCommand) -> Result<(), crate::cmds::CommandError> {
+    let schema =
+        nyanpasu_ipc::schema::export().map_err(|e| crate::cmds::CommandError::Other(e.into()))?;
+    schema.write_to(&ctx.out_dir)?;
+    println!(
+        "wrote {} files to {}",
+        schema.files.len(),
+        ctx.out_dir.display()
+    );
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use std::{collections::BTreeSet, path::PathBuf, sync::OnceLock};
+use std::{collections::BTreeSet, path::PathBuf, sync::OnceLock, time::Duration};
 
 #[cfg(windows)]
 use anyhow::Context;
@@ -6,7 +6,7 @@ use clap::Args;
 use tokio_util::sync::CancellationToken;
 use tracing_attributes::instrument;
 
-use crate::server::consts::RuntimeInfos;
+use crate::server::{ServiceSettings, consts::RuntimeInfos};
 
 use super::CommandError;
 
@@ -21,6 +21,19 @@ pub struct ServerContext {
     /// The nyanpasu install directory, allowing to search the sidecar binary
     #[clap(long)]
     pub nyanpasu_app_dir: PathBuf,
+    /// An extra directory to search for a core binary, after the nyanpasu
+    /// data and app dirs. Repeatable; searched in order.
+    #[clap(long = "core-search-dir")]
+    pub core_search_dirs: Vec<PathBuf>,
+    /// Search the service's own `PATH` for a core binary, after every
+    /// `--core-search-dir`.
+    #[clap(long, default_value = "false")]
+    pub core_search_system_path: bool,
+    /// A directory a client may name an explicit core binary in, besides the
+    /// nyanpasu data and app dirs and every `--core-search-dir`. Only files
+    /// named as a core's executable are accepted from it. Repeatable.
+    #[clap(long = "allowed-core-dir")]
+    pub allowed_core_dirs: Vec<PathBuf>,
     /// run as service
     #[clap(long, default_value = "false")]
     pub service: bool,
@@ -36,6 +49,15 @@ pub struct ServerContext {
         env = "NYANPASU_LOCAL_IPC_POLICY"
     )]
     pub local_ipc_policy: super::LocalIpcPolicyArg,
+    /// The most often, in milliseconds, the event stream relays a traffic or
+    /// memory sample from the running core.
+    #[clap(
+        long,
+        default_value_t = 1000,
+        value_parser = clap::value_parser!(u64).range(1..),
+        env = "NYANPASU_TELEMETRY_INTERVAL_MS"
+    )]
+    pub telemetry_interval_ms: u64,
 }
 
 pub static SHUTDOWN_TOKEN: OnceLock<CancellationToken> = OnceLock::new();
@@ -53,6 +75,7 @@ pub async fn server_inner(
     tracing::info!("nyanpasu config dir: {:?}", ctx.nyanpasu_config_dir);
     tracing::info!("nyanpasu data dir: {:?}", ctx.nyanpasu_data_dir);
     tracing::info!("local ipc policy: {:?}", ctx.local_ipc_policy);
+    tracing::info!("telemetry interval: {}ms", ctx.telemetry_interval_ms);
 
     // Names only, never values: this buffer is served by /logs and
     // /logs/inspect to every socket-ACL user, and the environment routinely
@@ -67,6 +90,25 @@ pub async fn server_inner(
     let nyanpasu_config_dir = dunce::canonicalize(&ctx.nyanpasu_config_dir)?;
     let nyanpasu_data_dir = dunce::canonicalize(&ctx.nyanpasu_data_dir)?;
     let nyanpasu_app_dir = dunce::canonicalize(&ctx.nyanpasu_app_dir)?;
+    // Both lists are canonical because an explicit binary's parent is
+    // compared against them. A missing dir is skipped rather than fatal: it
+    // may be where a distro package installs a core that is not installed yet.
+    let canonical_dirs = |dirs: &[PathBuf], what: &str| {
+        dirs.iter()
+            .filter_map(|dir| match dunce::canonicalize(dir) {
+                Ok(dir) => Some(dir),
+                Err(error) => {
+                    tracing::warn!("skipping {what} {dir:?}: {error}");
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let core_search_dirs = canonical_dirs(&ctx.core_search_dirs, "core search dir");
+    tracing::info!("core search dirs: {:?}", core_search_dirs);
+    tracing::info!("core search system path: {}", ctx.core_search_system_path);
+    let allowed_core_dirs = canonical_dirs(&ctx.allowed_core_dirs, "allowed core dir");
+    tracing::info!("allowed core dirs: {:?}", allowed_core_dirs);
 
     let service_data_dir = crate::utils::dirs::service_data_dir();
     let service_config_dir = crate::utils::dirs::service_config_dir();
@@ -79,6 +121,14 @@ pub async fn server_inner(
     if !service_config_dir.exists() {
         std::fs::create_dir_all(&service_config_dir)?;
     }
+    // Before the pid file: a service that cannot start on its config should
+    // not look as if it had.
+    let settings = ServiceSettings::load(&service_config_dir, ctx.local_ipc_policy.into())?;
+    tracing::info!(
+        "service config {:?} (loaded: {})",
+        settings.report.path,
+        settings.report.loaded
+    );
 
     // Write current process id to file
     if let Err(e) = nyanpasu_utils::os::create_pid_file(
@@ -96,21 +146,29 @@ pub async fn server_inner(
         nyanpasu_config_dir,
         nyanpasu_data_dir,
         nyanpasu_app_dir,
+        core_search_dirs,
+        core_search_system_path: ctx.core_search_system_path,
+        allowed_core_dirs,
     };
 
     #[cfg(windows)]
     let sids = crate::utils::acl::read_acl_file()
         .await
         .context("failed to read acl file")?;
-    #[cfg(windows)]
-    let sids_str = &sids.iter().map(|s| s.as_str()).collect::<Vec<_>>();
     #[cfg(not(windows))]
-    let sids_str = ();
+    let sids = ();
 
     #[cfg(windows)]
-    tracing::info!(sids = ?sids_str, "Loaded acl file");
-
-    crate::server::run(runtime_infos, ctx.local_ipc_policy.into(), token, sids_str).await?;
+    tracing::info!(?sids, "Loaded acl file");
+
+    crate::server::run(
+        runtime_infos,
+        settings,
+        Duration::from_millis(ctx.telemetry_interval_ms),
+        token,
+        sids,
+    )
+    .await?;
     Ok(())
 }
 
@@ -0,0 +1,241 @@
+//! The service config's `[access]` table, resolved into the lookups each
+//! request is checked against.
+
+use std::collections::{BTreeMap, HashMap};
+
+use nyanpasu_ipc::{
+    api::{
+        access::{Role, default_roles},
+        service_config::AccessConfig,
+    },
+    server::PeerCredentials,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessPolicy {
+    default_role: Role,
+    users: HashMap<u32, Role>,
+    groups: HashMap<u32, Role>,
+    /// Every address's role, the file's overrides over the built-in ones.
+    required: BTreeMap<String, Role>,
+}
+
+impl AccessPolicy {
+    pub fn resolve(config: &AccessConfig) -> Result<Self, String> {
+        let mut required = default_roles();
+        for (path, role) in &config.operations {
+            let Some(slot) = required.get_mut(path) else {
+                return Err(format!(
+                    "`access.operations` names `{path}`, which is not an IPC address"
+                ));
+            };
+            *slot = *role;
+        }
+        Ok(Self {
+            default_role: config.default_role,
+            users: ids("access.users", &config.users)?,
+            groups: ids("access.groups", &config.groups)?,
+            required,
+        })
+    }
+
+    /// The role of the caller `credentials` describe, or of one the service
+    /// could not identify.
+    pub fn role_of(&self, credentials: Option<&PeerCredentials>) -> Role {
+        let Some(credentials) = credentials else {
+            return self.default_role;
+        };
+        // Skips the `/proc` read when no group could match anyway.
+        let supplementary = if self.groups.is_empty() {
+            Vec::new()
+        } else {
+            supplementary_groups(credentials)
+        };
+        self.role_among(credentials, &supplementary)
+    }
+
+    /// [`Self::role_of`] once the caller's supplementary groups are known. A
+    /// user entry wins; otherwise the highest role any of its groups has.
+    fn role_among(&self, credentials: &PeerCredentials, supplementary: &[u32]) -> Role {
+        if credentials.uid == 0 {
+            return Role::Admin;
+        }
+        if let Some(role) = self.users.get(&credentials.uid) {
+            return *role;
+        }
+        std::iter::once(&credentials.gid)
+            .chain(supplementary)
+            .filter_map(|gid| self.groups.get(gid).copied())
+            .max()
+            .unwrap_or(self.default_role)
+    }
+
+    /// The role a call to `path` needs. A path the contract does not declare
+    /// needs `admin`, so a route mounted without a registry entry is shut
+    /// rather than open.
+    pub fn required(&self, path: &str) -> Role {
+        self.required.get(path).copied().unwrap_or(Role::Admin)
+    }
+}
+
+impl Default for AccessPolicy {
+    fn default() -> Self {
+        Self::resolve(&AccessConfig::default()).expect("the default access table resolves")
+    }
+}
+
+/// The caller's supplementary groups, which the kernel leaves out of a socket
+/// peer's credentials, from `/proc/<pid>/status`. Empty when the pid is unknown
+/// or the process is gone.
+#[cfg(target_os = "linux")]
+fn supplementary_groups(credentials: &PeerCredentials) -> Vec<u32> {
+    let Some(pid) = credentials.pid else {
+        return Vec::new();
+    };
+    match std::fs::read_to_string(format!("/proc/{pid}/status")) {
+        Ok(status) => status_groups(&status, credentials),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn supplementary_groups(_credentials: &PeerCredentials) -> Vec<u32> {
+    Vec::new()
+}
+
+/// The `Groups:` line of a `/proc/<pid>/status`, when its effective uid and
+/// gid are still `credentials`'. The pid may have exited since it connected
+/// and been reused by another process, whose groups must not count.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn status_groups(status: &str, credentials: &PeerCredentials) -> Vec<u32> {
+    let ids = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(|rest| {
+                rest.split_whitespace()
+                    .filter_map(|id| id.parse::<u32>().ok())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    // Real, effective, saved and filesystem ids, in that order.
+    if ids("Uid:").get(1) != Some(&credentials.uid) || ids("Gid:").get(1) != Some(&credentials.gid)
+    {
+        return Vec::new();
+    }
+    ids("Groups:")
+}
+
+fn ids(key: &str, table: &BTreeMap<String, Role>) -> Result<HashMap<u32, Role>, String> {
+    table
+        .iter()
+        .map(|(id, role)| {
+            id.parse::<u32>()
+                .map(|id| (id, *role))
+                .map_err(|_| format!("`{key}` key `{id}` is not a numeric id"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caller(uid: u32, gid: u32) -> PeerCredentials {
+        PeerCredentials {
+            uid,
+            gid,
+            pid: Some(4242),
+        }
+    }
+
+    fn policy(toml: &str) -> Result<AccessPolicy, String> {
+        let config = toml::from_str::<AccessConfig>(toml).map_err(|error| error.to_string())?;
+        AccessPolicy::resolve(&config)
+    }
+
+    #[test]
+    fn the_default_table_lets_everyone_in() {
+        let policy = AccessPolicy::default();
+        assert_eq!(policy.role_of(Some(&caller(1000, 1000))), Role::Admin);
+        assert_eq!(policy.role_of(None), Role::Admin);
+        assert_eq!(policy.required("/status"), Role::ReadOnly);
+        assert_eq!(policy.required("/core/start"), Role::Control);
+        assert_eq!(policy.required("/core/recover"), Role::Admin);
+    }
+
+    #[test]
+    fn a_user_entry_wins_over_the_group_and_root_is_always_admin() {
+        let policy = policy(
+            r#"
+            default_role = "read-only"
+            users = { 1000 = "admin", 1001 = "read-only" }
+            groups = { 500 = "control" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(policy.role_of(Some(&caller(1000, 500))), Role::Admin);
+        assert_eq!(policy.role_of(Some(&caller(1001, 500))), Role::ReadOnly);
+        assert_eq!(policy.role_of(Some(&caller(1002, 500))), Role::Control);
+        assert_eq!(policy.role_of(Some(&caller(1002, 100))), Role::ReadOnly);
+        assert_eq!(policy.role_of(Some(&caller(0, 0))), Role::Admin);
+        assert_eq!(policy.role_of(None), Role::ReadOnly);
+    }
+
+    #[test]
+    fn the_highest_role_among_the_callers_groups_wins() {
+        let policy = policy(
+            r#"
+            default_role = "read-only"
+            users = { 1001 = "read-only" }
+            groups = { 100 = "read-only", 500 = "control", 600 = "admin" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(policy.role_among(&caller(1000, 100), &[]), Role::ReadOnly);
+        assert_eq!(policy.role_among(&caller(1000, 100), &[500]), Role::Control);
+        assert_eq!(
+            policy.role_among(&caller(1000, 100), &[600, 500]),
+            Role::Admin
+        );
+        assert_eq!(policy.role_among(&caller(1000, 42), &[43]), Role::ReadOnly);
+        assert_eq!(
+            policy.role_among(&caller(1001, 100), &[600]),
+            Role::ReadOnly
+        );
+    }
+
+    #[test]
+    fn supplementary_groups_come_only_from_the_same_caller() {
+        let status = "Name:\tmihomo\nUid:\t1000\t1000\t1000\t1000\nGid:\t100\t100\t100\t100\nGroups:\t27 500 \n";
+        assert_eq!(status_groups(status, &caller(1000, 100)), [27, 500]);
+        assert!(status_groups(status, &caller(1001, 100)).is_empty());
+        assert!(status_groups(status, &caller(1000, 101)).is_empty());
+        let none = "Uid:\t1000\t1000\t1000\t1000\nGid:\t100\t100\t100\t100\nGroups:\t\n";
+        assert!(status_groups(none, &caller(1000, 100)).is_empty());
+    }
+
+    #[test]
+    fn an_override_replaces_the_built_in_role() {
+        let policy = policy(
+            r#"
+            operations = { "/core/stop" = "admin", "/ws/events" = "control" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(policy.required("/core/stop"), Role::Admin);
+        assert_eq!(policy.required("/ws/events"), Role::Control);
+        assert_eq!(policy.required("/core/start"), Role::Control);
+        assert_eq!(policy.required("/not/declared"), Role::Admin);
+    }
+
+    #[test]
+    fn a_bad_table_is_refused_with_the_key_named() {
+        let error = policy(r#"users = { alice = "admin" }"#).unwrap_err();
+        assert!(error.contains("`access.users` key `alice`"), "{error}");
+        let error = policy(r#"operations = { "/core/stopp" = "admin" }"#).unwrap_err();
+        assert!(error.contains("`/core/stopp`"), "{error}");
+        assert!(policy(r#"default_role = "root""#).is_err());
+    }
+}
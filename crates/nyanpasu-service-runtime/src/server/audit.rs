@@ -0,0 +1,586 @@
+//! The audit trail file: appended to by the audit middleware, paged back by
+//! `POST /audit`.
+//!
+//! One JSON record per line, only ever appended to. Past
+//! [`AUDIT_MAX_BYTES`] the file is renamed after the number of its first
+//! record (`audit.<n>.jsonl`) and a new one started, so the cursor keeps
+//! counting across files. A page is read by streaming the files it spans a
+//! line at a time; no more than the page is held.
+
+use std::{
+    borrow::Cow,
+    io,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+
+use nyanpasu_ipc::api::{
+    R, ResponseCode,
+    audit::{
+        AUDIT_DEFAULT_LIMIT, AUDIT_FILE_NAME, AUDIT_MAX_BYTES, AUDIT_MAX_FILES, AUDIT_MAX_LIMIT,
+        AuditOutcome, AuditQueryData, AuditQueryReq, AuditRecord, AuditRequestSummary,
+    },
+    connections::ConnectionCloseReq,
+    contract::{
+        ConnectionsClose, ConnectionsCloseAll, CoreApply, CoreCheck, CoreRecover, CoreRestart,
+        CoreRollback, CoreStart, CoreStop, IpcOperation, NetworkSetDns, ProxiesSelect,
+        ServiceReload,
+    },
+    core::{
+        apply::CoreApplyReq, check::CoreCheckReq, rollback::CoreRollbackReq, start::CoreStartReq,
+    },
+    network::set_dns::NetworkSetDnsReq,
+    proxies::ProxySelectReq,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+    sync::RwLockReadGuard,
+};
+
+/// The operations the trail records: everything that changes which core
+/// runs, with what config, how the host resolves names, or where the running
+/// core sends traffic.
+pub const AUDITED: &[&str] = &[
+    CoreStart::PATH,
+    CoreStop::PATH,
+    CoreRestart::PATH,
+    CoreApply::PATH,
+    CoreCheck::PATH,
+    CoreRollback::PATH,
+    CoreRecover::PATH,
+    NetworkSetDns::PATH,
+    ServiceReload::PATH,
+    ProxiesSelect::PATH,
+    ConnectionsClose::PATH,
+    ConnectionsCloseAll::PATH,
+];
+
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    /// The number of the first record in the file being written, once known.
+    /// Appends hold it for writing, one at a time so two records never share
+    /// a line; queries hold it for reading, so no rotation renames a file out
+    /// from under a page.
+    live_first: tokio::sync::RwLock<Option<u64>>,
+}
+
+/// One file of the trail and the number of its first record.
+struct Segment {
+    first: u64,
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(service_data_dir: &Path) -> Self {
+        Self::with_rotation(service_data_dir, AUDIT_MAX_BYTES, AUDIT_MAX_FILES)
+    }
+
+    /// `max_files` counts the file being written. At least one set-aside file
+    /// is always kept: the cursor is counted on from the newest of them.
+    pub fn with_rotation(service_data_dir: &Path, max_bytes: u64, max_files: usize) -> Self {
+        Self {
+            path: service_data_dir.join(AUDIT_FILE_NAME),
+            max_bytes,
+            max_files: max_files.max(2),
+            live_first: tokio::sync::RwLock::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub async fn append(&self, record: &AuditRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let mut live_first = self.live_first.write().await;
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let first = match *live_first {
+            Some(first) => first,
+            None => current_first(&self.rotated().await?).await?,
+        };
+        *live_first = Some(first);
+        match tokio::fs::metadata(&self.path).await {
+            Ok(metadata) if metadata.len() >= self.max_bytes => {
+                *live_first = Some(self.rotate(first).await?);
+            }
+            Ok(_) => {}
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error),
+        }
+        let mut options = tokio::fs::OpenOptions::new();
+        options.create(true).append(true).read(true);
+        // Callers' uids and config paths are nobody else's business.
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&self.path).await?;
+        // A write cut off by a crash left its line open; close it so this
+        // record starts a line of its own rather than joining the torn one.
+        if file.metadata().await?.len() > 0 {
+            let mut last = [0];
+            file.seek(io::SeekFrom::End(-1)).await?;
+            file.read_exact(&mut last).await?;
+            if last[0] != b'\n' {
+                line.insert(0, b'\n');
+            }
+        }
+        file.write_all(&line).await?;
+        file.flush().await
+    }
+
+    /// Set the current file, whose first record is `first`, aside under that
+    /// number, then drop the oldest set-aside files past `max_files`. Returns
+    /// the number the next file starts from. Called with the write lock held.
+    async fn rotate(&self, first: u64) -> io::Result<u64> {
+        let mut rotated = self.rotated().await?;
+        let next = scan(&self.path, first, |_, _| ControlFlow::Continue(())).await?;
+        let path = self.segment_path(first);
+        tokio::fs::rename(&self.path, &path).await?;
+        rotated.push(Segment { first, path });
+        let excess = rotated.len().saturating_sub(self.max_files - 1);
+        for segment in &rotated[..excess] {
+            tokio::fs::remove_file(&segment.path).await?;
+        }
+        Ok(next)
+    }
+
+    /// The number of the live file's first record, held for reading so the
+    /// files stay as they are while the caller reads them. Worked out once,
+    /// from the newest set-aside file; appends keep it current after that.
+    async fn read_live_first(&self) -> io::Result<RwLockReadGuard<'_, Option<u64>>> {
+        let live_first = self.live_first.read().await;
+        if live_first.is_some() {
+            return Ok(live_first);
+        }
+        drop(live_first);
+        let mut live_first = self.live_first.write().await;
+        if live_first.is_none() {
+            *live_first = Some(current_first(&self.rotated().await?).await?);
+        }
+        Ok(live_first.downgrade())
+    }
+
+    fn segment_path(&self, first: u64) -> PathBuf {
+        let (stem, extension) = AUDIT_FILE_NAME.rsplit_once('.').unwrap();
+        self.path
+            .with_file_name(format!("{stem}.{first}.{extension}"))
+    }
+
+    /// The set-aside files, oldest first.
+    async fn rotated(&self) -> io::Result<Vec<Segment>> {
+        let Some(dir) = self.path.parent() else {
+            return Ok(Vec::new());
+        };
+        let mut entries = match tokio::fs::read_dir(dir).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        let (stem, extension) = AUDIT_FILE_NAME.rsplit_once('.').unwrap();
+        let mut rotated = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let first = name
+                .to_str()
+                .and_then(|name| name.strip_prefix(stem)?.strip_prefix('.'))
+                .and_then(|name| name.strip_suffix(extension)?.strip_suffix('.'))
+                .and_then(|first| first.parse::<u64>().ok());
+            if let Some(first) = first {
+                rotated.push(Segment {
+                    first,
+                    path: entry.path(),
+                });
+            }
+        }
+        rotated.sort_by_key(|segment| segment.first);
+        Ok(rotated)
+    }
+
+    /// A line that does not parse (the tail of a write cut off by a crash)
+    /// still counts towards the cursor, and is left out of the page.
+    pub async fn query(&self, query: &AuditQueryReq) -> io::Result<AuditQueryData> {
+        let live_first = self.read_live_first().await?;
+        let first = live_first.expect("read_live_first fills it in");
+        let mut segments = self.rotated().await?;
+        let total = scan(&self.path, first, |_, _| ControlFlow::Continue(())).await?;
+        segments.push(Segment {
+            first,
+            path: self.path.clone(),
+        });
+        let oldest = segments[0].first;
+        let limit = u64::from(
+            query
+                .limit
+                .unwrap_or(AUDIT_DEFAULT_LIMIT)
+                .min(AUDIT_MAX_LIMIT),
+        );
+        let (start, end) = if query.backward {
+            let end = query.cursor.unwrap_or(total).clamp(oldest, total);
+            (end.saturating_sub(limit).max(oldest), end)
+        } else {
+            let start = query.cursor.unwrap_or(oldest).clamp(oldest, total);
+            (start, (start + limit).min(total))
+        };
+
+        let mut records = Vec::new();
+        for (index, segment) in segments.iter().enumerate() {
+            let next = segments.get(index + 1).map_or(total, |next| next.first);
+            if next <= start || segment.first >= end {
+                continue;
+            }
+            scan(&segment.path, segment.first, |number, line| {
+                if number >= end {
+                    return ControlFlow::Break(());
+                }
+                if number >= start {
+                    match serde_json::from_slice::<AuditRecord>(line) {
+                        Ok(record) => records.push(record),
+                        Err(error) => {
+                            tracing::warn!("skipping an unreadable audit record: {error}");
+                        }
+                    }
+                }
+                ControlFlow::Continue(())
+            })
+            .await?;
+        }
+        let (cursor, exhausted) = if query.backward {
+            records.reverse();
+            (start, start == oldest)
+        } else {
+            (end, end == total)
+        };
+        Ok(AuditQueryData {
+            records,
+            cursor,
+            exhausted,
+        })
+    }
+}
+
+/// The number of the first record in the file being written: the one after
+/// the newest set-aside file's last.
+async fn current_first(rotated: &[Segment]) -> io::Result<u64> {
+    match rotated.last() {
+        Some(segment) => {
+            scan(&segment.path, segment.first, |_, _| {
+                ControlFlow::Continue(())
+            })
+            .await
+        }
+        None => Ok(0),
+    }
+}
+
+/// Hand each line of `path` to `each`, numbered from `first`, and return the
+/// number after the last line read. A missing file has no lines.
+async fn scan(
+    path: &Path,
+    first: u64,
+    mut each: impl FnMut(u64, &[u8]) -> ControlFlow<()>,
+) -> io::Result<u64> {
+    let file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(first),
+        Err(error) => return Err(error),
+    };
+    let mut reader = tokio::io::BufReader::new(file);
+    let mut line = Vec::new();
+    let mut number = first;
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line).await? == 0 {
+            return Ok(number);
+        }
+        let text = line.strip_suffix(b"\n").unwrap_or(&line);
+        if each(number, text).is_break() {
+            return Ok(number);
+        }
+        number += 1;
+    }
+}
+
+/// What the body of a call to `path` asked for. A body that does not parse
+/// summarizes to nothing; the handler refuses it and the outcome says so.
+pub fn summarize(path: &str, body: &[u8]) -> AuditRequestSummary {
+    let summary =
+        match path {
+            path if path == CoreStart::PATH => {
+                serde_json::from_slice::<CoreStartReq>(body).map(|req| AuditRequestSummary {
+                    config_file: Some(req.config_file.into_owned()),
+                    core_type: Some(req.core_type.into_owned()),
+                    ..Default::default()
+                })
+            }
+            path if path == CoreApply::PATH => {
+                serde_json::from_slice::<CoreApplyReq>(body).map(|req| AuditRequestSummary {
+                    config_file: req.config_file.map(Cow::into_owned),
+                    core_type: Some(req.core_type.into_owned()),
+                    expected_revision: req.expected_revision,
+                    ..Default::default()
+                })
+            }
+            path if path == CoreCheck::PATH => {
+                serde_json::from_slice::<CoreCheckReq>(body).map(|req| AuditRequestSummary {
+                    config_file: req.config_file.map(Cow::into_owned),
+                    core_type: Some(req.core_type.into_owned()),
+                    ..Default::default()
+                })
+            }
+            path if path == CoreRollback::PATH => serde_json::from_slice::<CoreRollbackReq>(body)
+                .map(|req| AuditRequestSummary {
+                    revision: Some(req.revision),
+                    expected_revision: req.expected_revision,
+                    ..Default::default()
+                }),
+            path if path == NetworkSetDns::PATH => serde_json::from_slice::<NetworkSetDnsReq>(body)
+                .map(|req| AuditRequestSummary {
+                    dns_servers: req
+                        .dns_servers
+                        .map(|servers| servers.into_iter().map(Cow::into_owned).collect()),
+                    ..Default::default()
+                }),
+            path if path == ProxiesSelect::PATH => serde_json::from_slice::<ProxySelectReq>(body)
+                .map(|req| AuditRequestSummary {
+                    proxy_group: Some(req.group),
+                    proxy: Some(req.proxy),
+                    ..Default::default()
+                }),
+            path if path == ConnectionsClose::PATH => {
+                serde_json::from_slice::<ConnectionCloseReq>(body).map(|req| AuditRequestSummary {
+                    connection_ids: (!req.ids.is_empty()).then_some(req.ids),
+                    connection_filter: req.filter,
+                    ..Default::default()
+                })
+            }
+            _ => return AuditRequestSummary::default(),
+        };
+    summary.unwrap_or_default()
+}
+
+/// The outcome as the response says it. Anything but an envelope is an
+/// extractor's rejection of the request, answered in plain text.
+pub fn outcome(status: u16, body: &[u8]) -> AuditOutcome {
+    match serde_json::from_slice::<R<'static, serde_json::Value>>(body) {
+        Ok(envelope) => {
+            let ok = envelope.code == ResponseCode::Ok;
+            AuditOutcome {
+                ok,
+                status,
+                error_kind: envelope.error_kind.map(Cow::into_owned),
+                msg: (!ok).then(|| envelope.msg.into_owned()),
+            }
+        }
+        Err(_) => AuditOutcome {
+            ok: (200..300).contains(&status),
+            status,
+            error_kind: None,
+            msg: Some(String::from_utf8_lossy(body).into_owned()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nyanpasu_ipc::api::error_kind;
+    use nyanpasu_utils::core::{ClashCoreType, CoreType};
+
+    use super::*;
+
+    fn record(at: i64) -> AuditRecord {
+        AuditRecord {
+            at,
+            request_id: Some(format!("request-{at}")),
+            operation: CoreStop::PATH.to_owned(),
+            caller: None,
+            request: AuditRequestSummary::default(),
+            outcome: AuditOutcome {
+                ok: true,
+                status: 200,
+                error_kind: None,
+                msg: None,
+            },
+        }
+    }
+
+    fn times(page: &AuditQueryData) -> Vec<i64> {
+        page.records.iter().map(|record| record.at).collect()
+    }
+
+    #[tokio::test]
+    async fn pages_walk_the_trail_either_way() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path());
+        let empty = log.query(&AuditQueryReq::default()).await.unwrap();
+        assert!(empty.records.is_empty() && empty.exhausted);
+
+        for at in 1..=5 {
+            log.append(&record(at)).await.unwrap();
+        }
+        let forward = AuditQueryReq {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let first = log.query(&forward).await.unwrap();
+        assert_eq!(
+            (times(&first), first.cursor, first.exhausted),
+            (vec![1, 2], 2, false)
+        );
+        let last = log
+            .query(&AuditQueryReq {
+                cursor: Some(4),
+                ..forward.clone()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            (times(&last), last.cursor, last.exhausted),
+            (vec![5], 5, true)
+        );
+
+        let backward = AuditQueryReq {
+            limit: Some(2),
+            backward: true,
+            ..Default::default()
+        };
+        let newest = log.query(&backward).await.unwrap();
+        assert_eq!(
+            (times(&newest), newest.cursor, newest.exhausted),
+            (vec![5, 4], 3, false)
+        );
+        let oldest = log
+            .query(&AuditQueryReq {
+                cursor: Some(1),
+                ..backward
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            (times(&oldest), oldest.cursor, oldest.exhausted),
+            (vec![1], 0, true)
+        );
+    }
+
+    #[tokio::test]
+    async fn a_torn_line_is_skipped_but_still_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path());
+        log.append(&record(1)).await.unwrap();
+        // What a crash mid-write leaves: the record's start, and no newline.
+        let mut text = std::fs::read_to_string(log.path()).unwrap();
+        text.push_str("{\"at\":2,\"opera");
+        std::fs::write(log.path(), text).unwrap();
+        let torn = log.query(&AuditQueryReq::default()).await.unwrap();
+        assert_eq!((times(&torn), torn.cursor), (vec![1], 2));
+
+        log.append(&record(3)).await.unwrap();
+        let page = log.query(&AuditQueryReq::default()).await.unwrap();
+        assert_eq!((times(&page), page.cursor), (vec![1, 3], 3));
+        let text = std::fs::read_to_string(log.path()).unwrap();
+        assert_eq!(text.lines().count(), 3, "{text}");
+    }
+
+    #[tokio::test]
+    async fn the_cursor_counts_on_across_rotated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let line = serde_json::to_vec(&record(1)).unwrap().len() as u64 + 1;
+        // Two records to a file, three files: the oldest two records go.
+        let log = AuditLog::with_rotation(dir.path(), 2 * line, 3);
+        for at in 1..=8 {
+            log.append(&record(at)).await.unwrap();
+        }
+        let mut names = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["audit.2.jsonl", "audit.4.jsonl", "audit.jsonl"]);
+
+        let everything = log
+            .query(&AuditQueryReq {
+                cursor: Some(0),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            (times(&everything), everything.cursor, everything.exhausted),
+            (vec![3, 4, 5, 6, 7, 8], 8, true)
+        );
+        let spanning = log
+            .query(&AuditQueryReq {
+                cursor: Some(3),
+                limit: Some(3),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!((times(&spanning), spanning.cursor), (vec![4, 5, 6], 6));
+        let backward = log
+            .query(&AuditQueryReq {
+                cursor: Some(5),
+                limit: Some(4),
+                backward: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            (times(&backward), backward.cursor, backward.exhausted),
+            (vec![5, 4, 3], 2, true)
+        );
+    }
+
+    #[test]
+    fn a_request_is_summarized_without_its_inline_config() {
+        let summary = summarize(
+            CoreApply::PATH,
+            br#"{"core_type":{"clash":"mihomo"},"config":"secret: value","expected_revision":{"epoch":1,"generation":2,"effective_hash":"ab"}}"#,
+        );
+        assert_eq!(
+            summary.core_type,
+            Some(CoreType::Clash(ClashCoreType::Mihomo))
+        );
+        assert_eq!(summary.config_file, None);
+        assert_eq!(summary.expected_revision.unwrap().generation, 2);
+        assert_eq!(summary.dns_servers, None);
+
+        let selected = summarize(
+            ProxiesSelect::PATH,
+            br#"{"group":"GLOBAL","proxy":"tokyo"}"#,
+        );
+        assert_eq!(
+            (selected.proxy_group.as_deref(), selected.proxy.as_deref()),
+            (Some("GLOBAL"), Some("tokyo"))
+        );
+        let closed = summarize(ConnectionsClose::PATH, br#"{"ids":["a1"]}"#);
+        assert_eq!(closed.connection_ids, Some(vec!["a1".to_owned()]));
+
+        assert_eq!(summarize(CoreStart::PATH, b"not json"), Default::default());
+        assert_eq!(summarize(CoreStop::PATH, b""), Default::default());
+    }
+
+    #[test]
+    fn the_outcome_comes_from_the_envelope() {
+        let refused = outcome(
+            403,
+            br#"{"code":"OtherError","msg":"permission denied","data":null,"ts":1,"error_kind":"permission_denied"}"#,
+        );
+        assert!(!refused.ok);
+        assert_eq!(
+            refused.error_kind.as_deref(),
+            Some(error_kind::PERMISSION_DENIED)
+        );
+        assert_eq!(refused.msg.as_deref(), Some("permission denied"));
+
+        let ok = outcome(200, br#"{"code":"Ok","msg":"ok","data":null,"ts":1}"#);
+        assert!(ok.ok && ok.msg.is_none());
+
+        let rejected = outcome(422, b"missing field `core_type`");
+        assert!(!rejected.ok);
+        assert_eq!(rejected.msg.as_deref(), Some("missing field `core_type`"));
+    }
+}
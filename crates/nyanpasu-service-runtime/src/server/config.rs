@@ -0,0 +1,600 @@
+//! `service.toml`: the service's own settings, read at server start and again
+//! on `/service/reload`.
+//!
+//! The file is optional. Without it every setting is the built-in default and
+//! the local IPC policy is the install-time `--local-ipc-policy`; with it, each
+//! key it names overrides exactly that setting. Everything is checked here,
+//! before any of it reaches the manager, so a bad file stops startup with the
+//! offending key named rather than surfacing later as a core that will not
+//! start.
+
+use std::{
+    collections::BTreeSet,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use nyanpasu_core_manager::{
+    Backoff, HealthPolicy, InstanceOptions, LocalIpcPolicy, ManagerOptions, RestartPolicy,
+};
+use nyanpasu_ipc::api::service_config::{
+    LocalIpcPolicyConfig, RestartPolicyConfig, SERVICE_CONFIG_FILE_NAME, ServiceConfig,
+    ServiceConfigInfo, ServiceReloadData,
+};
+use serde_json::Value;
+
+use super::{CoreManager, EventHub, access::AccessPolicy, events::EventCapacities};
+
+/// The settings a reload applies, as dotted keys or the tables holding them.
+/// Everything else is read once, into objects that live as long as the server:
+/// the manager's timeouts and controller, the log sink itself, the check
+/// semaphore and the request timeout layer.
+const LIVE_SETTINGS: &[&str] = &[
+    "access",
+    "instance",
+    "events",
+    "manager.local_ipc_policy",
+    "manager.log_max_bytes",
+    "manager.log_max_files",
+];
+
+/// Everything the server is built from, and the report `/status` serves.
+pub struct ServiceSettings {
+    /// `runtime_dir` is left for [`super::run`] to fill in.
+    pub manager: ManagerOptions,
+    pub instance: InstanceOptions,
+    pub max_concurrent_checks: usize,
+    pub events: EventCapacities,
+    pub request_timeout: Duration,
+    pub access: AccessPolicy,
+    pub report: ServiceConfigInfo,
+    /// The `--local-ipc-policy` the file was resolved against, kept so a
+    /// reload resolves it the same way.
+    pub install_local_ipc_policy: LocalIpcPolicyConfig,
+}
+
+impl ServiceSettings {
+    /// Reads `service.toml` from `service_config_dir`. `local_ipc_policy` is
+    /// the install-time one, in force unless the file sets its own.
+    pub fn load(
+        service_config_dir: &Path,
+        local_ipc_policy: LocalIpcPolicyConfig,
+    ) -> Result<Self, anyhow::Error> {
+        let path = service_config_dir.join(SERVICE_CONFIG_FILE_NAME);
+        let (config, loaded) = match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                let config = toml::from_str::<ServiceConfig>(&text).map_err(|error| {
+                    anyhow::anyhow!("failed to parse {}: {error}", path.display())
+                })?;
+                (config, true)
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                (ServiceConfig::default(), false)
+            }
+            Err(error) => {
+                anyhow::bail!("failed to read {}: {error}", path.display());
+            }
+        };
+        if let Some(policy) = config.manager.local_ipc_policy
+            && policy != local_ipc_policy
+        {
+            tracing::info!(
+                "{} sets local ipc policy {policy:?}, overriding the install-time {local_ipc_policy:?}",
+                path.display()
+            );
+        }
+        Self::resolve(config, path.clone(), loaded, local_ipc_policy)
+            .map_err(|error| anyhow::anyhow!("invalid service config {}: {error}", path.display()))
+    }
+
+    fn resolve(
+        mut config: ServiceConfig,
+        path: PathBuf,
+        loaded: bool,
+        local_ipc_policy: LocalIpcPolicyConfig,
+    ) -> Result<Self, String> {
+        let policy = *config
+            .manager
+            .local_ipc_policy
+            .get_or_insert(local_ipc_policy);
+        let manager_config = &config.manager;
+        let manager = ManagerOptions {
+            local_ipc_policy: match policy {
+                LocalIpcPolicyConfig::Force => LocalIpcPolicy::Force,
+                LocalIpcPolicyConfig::Prefer => LocalIpcPolicy::Prefer,
+                LocalIpcPolicyConfig::Disable => LocalIpcPolicy::Disable,
+            },
+            controller_template: manager_config.controller_template.clone(),
+            control_timeout: positive_ms(
+                "manager.control_timeout_ms",
+                manager_config.control_timeout_ms,
+            )?,
+            reconcile_timeout: positive_ms(
+                "manager.reconcile_timeout_ms",
+                manager_config.reconcile_timeout_ms,
+            )?,
+            stop_timeout: positive_ms("manager.stop_timeout_ms", manager_config.stop_timeout_ms)?,
+            log_sink_enabled: manager_config.log_sink_enabled,
+            log_max_bytes: positive("manager.log_max_bytes", manager_config.log_max_bytes)?,
+            log_max_files: positive("manager.log_max_files", manager_config.log_max_files)?
+                as usize,
+            revision_history: manager_config.revision_history as usize,
+            ..ManagerOptions::default()
+        };
+        if let Some(template) = &manager.controller_template
+            && !template.contains("{epoch}")
+        {
+            return Err("`manager.controller_template` must contain `{epoch}`".into());
+        }
+        let max_concurrent_checks = positive(
+            "manager.max_concurrent_checks",
+            manager_config.max_concurrent_checks,
+        )? as usize;
+
+        let instance_config = &config.instance;
+        let health_config = &instance_config.health;
+        let health = HealthPolicy::new(
+            positive_ms("instance.health.interval_ms", health_config.interval_ms)?,
+            positive_ms("instance.health.timeout_ms", health_config.timeout_ms)?,
+            non_zero(
+                "instance.health.failure_threshold",
+                health_config.failure_threshold,
+            )?,
+            non_zero(
+                "instance.health.success_threshold",
+                health_config.success_threshold,
+            )?,
+            Duration::from_millis(health_config.start_period_ms),
+        )
+        .map_err(|error| error.to_string())?;
+        let restart_config = &instance_config.restart;
+        let backoff_initial = positive_ms(
+            "instance.restart.backoff_initial_ms",
+            restart_config.backoff_initial_ms,
+        )?;
+        let backoff_max = positive_ms(
+            "instance.restart.backoff_max_ms",
+            restart_config.backoff_max_ms,
+        )?;
+        if backoff_initial > backoff_max {
+            return Err(
+                "`instance.restart.backoff_initial_ms` must not exceed `backoff_max_ms`".into(),
+            );
+        }
+        let mut backoff = Backoff::exponential(backoff_initial, backoff_max);
+        if restart_config.backoff_jitter {
+            backoff = backoff.with_jitter();
+        }
+        let instance = InstanceOptions {
+            startup_timeout: positive_ms(
+                "instance.startup_timeout_ms",
+                instance_config.startup_timeout_ms,
+            )?,
+            health,
+            restart_policy: match restart_config.policy {
+                RestartPolicyConfig::Never => RestartPolicy::Never,
+                RestartPolicyConfig::OnFailure => RestartPolicy::OnFailure {
+                    max_restarts: restart_config.max_restarts,
+                },
+            },
+            backoff,
+        };
+
+        let events_config = &config.events;
+        let events = EventCapacities {
+            status: positive("events.status_capacity", events_config.status_capacity)? as usize,
+            log: positive("events.log_capacity", events_config.log_capacity)? as usize,
+            log_backlog: positive(
+                "events.log_backlog_capacity",
+                events_config.log_backlog_capacity,
+            )? as usize,
+            telemetry: positive(
+                "events.telemetry_capacity",
+                events_config.telemetry_capacity,
+            )? as usize,
+        };
+
+        // The longest a working start or restart can take: reconcile the old
+        // core, stop it, start the new one, and stop that too if it never comes
+        // up. Anything at or under it would cut off an operation that is still
+        // making progress.
+        let request_timeout = positive_ms(
+            "server.request_timeout_ms",
+            config.server.request_timeout_ms,
+        )?;
+        let longest_operation = manager.reconcile_timeout
+            + manager.stop_timeout
+            + instance.startup_timeout
+            + manager.stop_timeout;
+        if request_timeout <= longest_operation {
+            return Err(format!(
+                "`server.request_timeout_ms` must exceed {}, the longest a start or restart may take under these timeouts",
+                longest_operation.as_millis()
+            ));
+        }
+
+        let access = AccessPolicy::resolve(&config.access)?;
+
+        Ok(Self {
+            manager,
+            instance,
+            max_concurrent_checks,
+            events,
+            request_timeout,
+            access,
+            report: ServiceConfigInfo {
+                path,
+                loaded,
+                effective: config,
+            },
+            install_local_ipc_policy: local_ipc_policy,
+        })
+    }
+}
+
+/// The service config in force, shared through `AppState`, and what a reload
+/// needs to read it again.
+pub struct ServiceConfigState {
+    install_local_ipc_policy: LocalIpcPolicyConfig,
+    /// The pipe ACL the listener follows. A reload that reads a new list
+    /// sends it here, and the listener rebinds under it for the connections
+    /// that come after.
+    #[cfg(windows)]
+    sids: tokio::sync::watch::Sender<Vec<String>>,
+    report: parking_lot::Mutex<ServiceConfigInfo>,
+    access: parking_lot::Mutex<Arc<AccessPolicy>>,
+    /// One reload at a time, so two cannot interleave their applies.
+    reloading: tokio::sync::Mutex<()>,
+}
+
+impl ServiceConfigState {
+    pub fn new(
+        report: ServiceConfigInfo,
+        access: AccessPolicy,
+        install_local_ipc_policy: LocalIpcPolicyConfig,
+        #[cfg(windows)] sids: tokio::sync::watch::Sender<Vec<String>>,
+    ) -> Self {
+        Self {
+            install_local_ipc_policy,
+            #[cfg(windows)]
+            sids,
+            report: parking_lot::Mutex::new(report),
+            access: parking_lot::Mutex::new(Arc::new(access)),
+            reloading: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// What `/status` reports: the settings in force, which after a reload
+    /// that changed a restart-only key is not quite the file.
+    pub fn report(&self) -> ServiceConfigInfo {
+        self.report.lock().clone()
+    }
+
+    /// The access table every request is checked against.
+    pub fn access(&self) -> Arc<AccessPolicy> {
+        self.access.lock().clone()
+    }
+
+    /// Re-reads `service.toml` and applies what can change live. A file that
+    /// does not load or validate changes nothing; neither does one whose
+    /// restart-only keys, held at their old values, would no longer validate
+    /// beside the new live ones.
+    pub async fn reload(
+        &self,
+        core_manager: &CoreManager,
+        hub: &EventHub,
+    ) -> Result<ServiceReloadData, anyhow::Error> {
+        let _reloading = self.reloading.lock().await;
+        let current = self.report();
+        let service_config_dir = current
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let install_local_ipc_policy = self.install_local_ipc_policy;
+        let loaded = tokio::task::spawn_blocking(move || {
+            ServiceSettings::load(&service_config_dir, install_local_ipc_policy)
+        })
+        .await??
+        .report;
+        let (in_force, mut applied, restart_required) =
+            reconcile(&current.effective, loaded.effective);
+        let settings = ServiceSettings::resolve(
+            in_force,
+            loaded.path.clone(),
+            loaded.loaded,
+            install_local_ipc_policy,
+        )
+        .map_err(|error| {
+            anyhow::anyhow!(
+                "invalid service config {} with the restart-only settings still in force: {error}",
+                loaded.path.display()
+            )
+        })?;
+        let acl = self.changed_acl().await?;
+
+        core_manager.set_instance_options(settings.instance);
+        core_manager.set_local_ipc_policy(settings.manager.local_ipc_policy);
+        core_manager
+            .set_log_rotation(
+                settings.manager.log_max_bytes,
+                settings.manager.log_max_files,
+            )
+            .await?;
+        hub.resize(settings.events);
+        *self.access.lock() = Arc::new(settings.access);
+        *self.report.lock() = settings.report.clone();
+        if let Some(_sids) = acl {
+            #[cfg(windows)]
+            self.sids.send_replace(_sids);
+            applied.push("acl".to_owned());
+        }
+        tracing::info!(
+            ?applied,
+            ?restart_required,
+            "reloaded {}",
+            settings.report.path.display()
+        );
+        Ok(ServiceReloadData {
+            config: settings.report,
+            applied,
+            restart_required,
+        })
+    }
+
+    /// The pipe ACL list, when it differs from the one the listener follows.
+    /// Outside Windows there is no list.
+    async fn changed_acl(&self) -> Result<Option<Vec<String>>, anyhow::Error> {
+        #[cfg(windows)]
+        {
+            let sids = crate::utils::acl::read_acl_file().await?;
+            return Ok((sids != *self.sids.borrow()).then_some(sids));
+        }
+        #[cfg(not(windows))]
+        Ok(None)
+    }
+}
+
+/// Splits what changed between `current` and `loaded` into the keys a reload
+/// applies and the ones that wait for a restart, and returns `loaded` with the
+/// latter put back to their current values: the config that will be in force.
+fn reconcile(
+    current: &ServiceConfig,
+    loaded: ServiceConfig,
+) -> (ServiceConfig, Vec<String>, Vec<String>) {
+    let current = serde_json::to_value(current).expect("the service config serializes");
+    let mut loaded = serde_json::to_value(loaded).expect("the service config serializes");
+    let mut changed = Vec::new();
+    changed_keys(&current, &loaded, "", &mut changed);
+    let (applied, restart_required): (Vec<_>, Vec<_>) = changed.into_iter().partition(|key| {
+        LIVE_SETTINGS.iter().any(|live| {
+            key.strip_prefix(live)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    });
+    for key in &restart_required {
+        let pointer = format!("/{}", key.replace('.', "/"));
+        let (parent, leaf) = pointer.rsplit_once('/').expect("a pointer starts with `/`");
+        let Some(Value::Object(parent)) = loaded.pointer_mut(parent) else {
+            continue;
+        };
+        match current.pointer(&pointer) {
+            Some(value) => parent.insert(leaf.to_owned(), value.clone()),
+            None => parent.remove(leaf),
+        };
+    }
+    let in_force = serde_json::from_value(loaded).expect("the service config round-trips");
+    (in_force, applied, restart_required)
+}
+
+/// Every leaf that differs, dotted. A key on one side only is a change; that
+/// is how an optional setting added or removed shows up.
+fn changed_keys(current: &Value, loaded: &Value, prefix: &str, changed: &mut Vec<String>) {
+    match (current, loaded) {
+        (Value::Object(current), Value::Object(loaded)) => {
+            let keys = current.keys().chain(loaded.keys()).collect::<BTreeSet<_>>();
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                changed_keys(
+                    current.get(key).unwrap_or(&Value::Null),
+                    loaded.get(key).unwrap_or(&Value::Null),
+                    &path,
+                    changed,
+                );
+            }
+        }
+        (current, loaded) if current != loaded => changed.push(prefix.to_owned()),
+        _ => {}
+    }
+}
+
+fn positive<T: Default + PartialEq>(key: &str, value: T) -> Result<T, String> {
+    if value == T::default() {
+        return Err(format!("`{key}` must be greater than zero"));
+    }
+    Ok(value)
+}
+
+fn positive_ms(key: &str, ms: u64) -> Result<Duration, String> {
+    positive(key, ms).map(Duration::from_millis)
+}
+
+fn non_zero(key: &str, value: u32) -> Result<NonZeroU32, String> {
+    NonZeroU32::new(value).ok_or_else(|| format!("`{key}` must be at least 1"))
+}
+
+#[cfg(test)]
+mod tests {
+    use nyanpasu_ipc::api::access::Role;
+
+    use super::*;
+
+    fn resolve(toml: &str) -> Result<ServiceSettings, String> {
+        let config = toml::from_str(toml).map_err(|error| error.to_string())?;
+        ServiceSettings::resolve(
+            config,
+            PathBuf::from("service.toml"),
+            true,
+            LocalIpcPolicyConfig::Disable,
+        )
+    }
+
+    /// The built-in values live twice, once as the wire defaults and once as
+    /// the manager's and the hub's; an empty file must land on the latter.
+    #[test]
+    fn an_empty_file_resolves_to_the_built_in_defaults() {
+        let settings = resolve("").unwrap();
+        let manager = ManagerOptions::default();
+        assert_eq!(settings.manager.local_ipc_policy, manager.local_ipc_policy);
+        assert_eq!(settings.manager.control_timeout, manager.control_timeout);
+        assert_eq!(
+            settings.manager.reconcile_timeout,
+            manager.reconcile_timeout
+        );
+        assert_eq!(settings.manager.stop_timeout, manager.stop_timeout);
+        assert_eq!(settings.manager.log_max_bytes, manager.log_max_bytes);
+        assert_eq!(settings.manager.log_max_files, manager.log_max_files);
+        assert_eq!(settings.manager.revision_history, manager.revision_history);
+        let instance = InstanceOptions::default();
+        assert_eq!(settings.instance.startup_timeout, instance.startup_timeout);
+        assert_eq!(settings.instance.health, instance.health);
+        assert_eq!(settings.instance.restart_policy, instance.restart_policy);
+        assert_eq!(settings.events, EventCapacities::default());
+        assert_eq!(settings.request_timeout, Duration::from_secs(120));
+        assert_eq!(
+            settings.report.effective.manager.local_ipc_policy,
+            Some(LocalIpcPolicyConfig::Disable)
+        );
+    }
+
+    #[test]
+    fn the_file_overrides_the_install_time_policy() {
+        let settings = resolve(
+            r#"
+            [manager]
+            local_ipc_policy = "prefer"
+            controller_template = "mihomo-{epoch}.sock"
+
+            [instance.restart]
+            policy = "never"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(settings.manager.local_ipc_policy, LocalIpcPolicy::Prefer);
+        assert_eq!(
+            settings.manager.controller_template.as_deref(),
+            Some("mihomo-{epoch}.sock")
+        );
+        assert_eq!(settings.instance.restart_policy, RestartPolicy::Never);
+    }
+
+    #[test]
+    fn invalid_values_name_the_offending_key() {
+        for (toml, key) in [
+            ("[manager]\nstop_timeout_ms = 0", "manager.stop_timeout_ms"),
+            (
+                "[manager]\nmax_concurrent_checks = 0",
+                "manager.max_concurrent_checks",
+            ),
+            (
+                "[manager]\ncontroller_template = \"core.sock\"",
+                "manager.controller_template",
+            ),
+            (
+                "[instance.health]\nfailure_threshold = 0",
+                "instance.health.failure_threshold",
+            ),
+            (
+                "[instance.restart]\nbackoff_initial_ms = 60000",
+                "instance.restart.backoff_initial_ms",
+            ),
+            ("[events]\nlog_capacity = 0", "events.log_capacity"),
+            (
+                "[server]\nrequest_timeout_ms = 60000",
+                "server.request_timeout_ms",
+            ),
+            ("[access.users]\nalice = \"admin\"", "access.users"),
+        ] {
+            let error = resolve(toml)
+                .err()
+                .unwrap_or_else(|| panic!("accepted {toml:?}"));
+            assert!(error.contains(key), "{toml:?}: {error}");
+        }
+        assert!(resolve("[manager]\nstop_timeout = 1").is_err());
+    }
+
+    /// The restart-only key keeps its old value in what is reported as in
+    /// force, and an optional key the file adds counts as a change.
+    #[test]
+    fn a_reload_applies_live_keys_and_holds_back_the_rest() {
+        let current = resolve("").unwrap().report.effective;
+        let loaded = resolve(
+            r#"
+            [manager]
+            stop_timeout_ms = 5000
+            controller_template = "mihomo-{epoch}.sock"
+            local_ipc_policy = "prefer"
+            log_max_files = 2
+
+            [instance.health]
+            interval_ms = 500
+
+            [events]
+            log_capacity = 4096
+
+            [access]
+            default_role = "control"
+            "#,
+        )
+        .unwrap()
+        .report
+        .effective;
+
+        let (in_force, applied, restart_required) = reconcile(&current, loaded);
+        assert_eq!(
+            applied,
+            [
+                "access.default_role",
+                "events.log_capacity",
+                "instance.health.interval_ms",
+                "manager.local_ipc_policy",
+                "manager.log_max_files"
+            ]
+        );
+        assert_eq!(
+            restart_required,
+            ["manager.controller_template", "manager.stop_timeout_ms"]
+        );
+        let mut expected = current.clone();
+        expected.manager.local_ipc_policy = Some(LocalIpcPolicyConfig::Prefer);
+        expected.manager.log_max_files = 2;
+        expected.instance.health.interval_ms = 500;
+        expected.events.log_capacity = 4096;
+        expected.access.default_role = Role::Control;
+        assert_eq!(in_force, expected);
+
+        let (_, applied, restart_required) = reconcile(&current, current.clone());
+        assert!(applied.is_empty() && restart_required.is_empty());
+    }
+
+    #[test]
+    fn a_missing_file_is_the_defaults_and_a_malformed_one_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = ServiceSettings::load(dir.path(), LocalIpcPolicyConfig::Force).unwrap();
+        assert!(!settings.report.loaded);
+        assert_eq!(settings.manager.local_ipc_policy, LocalIpcPolicy::Force);
+
+        std::fs::write(dir.path().join(SERVICE_CONFIG_FILE_NAME), "[manager\n").unwrap();
+        let error = ServiceSettings::load(dir.path(), LocalIpcPolicyConfig::Force)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains(SERVICE_CONFIG_FILE_NAME), "{error}");
+    }
+}
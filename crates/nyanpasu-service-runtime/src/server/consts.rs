@@ -7,4 +7,13 @@ pub struct RuntimeInfos {
     pub nyanpasu_config_dir: PathBuf,
     pub nyanpasu_data_dir: PathBuf,
     pub nyanpasu_app_dir: PathBuf,
+    /// Canonical directories searched for a core binary, in order, after the
+    /// nyanpasu data and app dirs. An explicit `binary_path` may name a binary
+    /// in one as well.
+    pub core_search_dirs: Vec<PathBuf>,
+    /// Search the service's own `PATH` last.
+    pub core_search_system_path: bool,
+    /// Canonical directories an explicit `binary_path` may name a binary in,
+    /// besides the nyanpasu data and app dirs and the search dirs.
+    pub allowed_core_dirs: Vec<PathBuf>,
 }
@@ -1,8 +1,10 @@
 use std::sync::Arc;
 
+use bounded_vec_deque::BoundedVecDeque;
 use nyanpasu_core_manager::LogFrame;
-use nyanpasu_ipc::api::ws::events::Event;
-use tokio::sync::broadcast;
+use nyanpasu_ipc::api::ws::events::{Event, LogReplay};
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::{Notify, broadcast};
 
 /// Events buffered per subscriber. A connection that falls further behind than
 /// this is told how many it lost instead of stalling the broadcast.
@@ -16,18 +18,67 @@ const EVENT_CHANNEL_CAPACITY: usize = 256;
 /// a record the resident ceiling is a few hundred KiB.
 const LOG_EVENT_CHANNEL_CAPACITY: usize = 1024;
 
-/// Fan-out point for ws events. Cloning shares both channels.
+/// Recent core log frames kept for connect-time replay. Half the log ring: it
+/// only has to cover what a console shows on reconnect, and unlike the ring it
+/// is resident whether or not anyone is connected.
+const LOG_BACKLOG_CAPACITY: usize = 512;
+
+/// Telemetry samples buffered per subscriber. Samples arrive already throttled
+/// to one of each kind per interval, and only the newest is worth anything, so
+/// a small ring is enough: a subscriber that falls behind skips straight to it.
+const TELEMETRY_EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// The four ring sizes, from the `[events]` table of the service config. The
+/// default is the constants above, which a missing table also yields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventCapacities {
+    pub status: usize,
+    pub log: usize,
+    pub log_backlog: usize,
+    pub telemetry: usize,
+}
+
+impl Default for EventCapacities {
+    fn default() -> Self {
+        Self {
+            status: EVENT_CHANNEL_CAPACITY,
+            log: LOG_EVENT_CHANNEL_CAPACITY,
+            log_backlog: LOG_BACKLOG_CAPACITY,
+            telemetry: TELEMETRY_EVENT_CHANNEL_CAPACITY,
+        }
+    }
+}
+
+/// Fan-out point for ws events. Cloning shares every channel.
 ///
-/// Two rings, not one, because a subscriber that falls behind must be able to
+/// Separate rings, not one, because a subscriber that falls behind must be able to
 /// lose a log line without paying for a status resynchronisation. Sharing one
 /// ring makes the two indistinguishable, so every loss has to be handled as if
-/// it were the expensive kind.
+/// it were the expensive kind. Telemetry gets the third ring for the same
+/// reason: a stale traffic sample is worthless, not something to resend.
 #[derive(Clone)]
 pub struct EventHub {
+    /// Behind a lock only so [`Self::resize`] can swap a ring; every send and
+    /// subscribe takes it shared.
+    rings: Arc<RwLock<Rings>>,
+    /// The frames a new connection can ask to have replayed. Its lock is also
+    /// held across every `log_tx` send, which is what makes a replay seamless:
+    /// a subscriber taken under it sees each frame either in its replay or on
+    /// the ring, exactly once. Taken before `rings` wherever both are held.
+    backlog: Arc<Mutex<BoundedVecDeque<Arc<LogFrame>>>>,
+    /// Woken by every telemetry subscription, so the relay can sleep with no
+    /// core streams open while nobody is listening.
+    telemetry_subscribed: Arc<Notify>,
+}
+
+struct Rings {
+    capacities: EventCapacities,
     tx: broadcast::Sender<Event>,
     /// Frames, not events: the ring holds what the manager produced, and the
     /// `Event` wrapper is built per connection at send time.
     log_tx: broadcast::Sender<Arc<LogFrame>>,
+    /// `Event::Traffic` and `Event::Memory` only. Never replayed.
+    telemetry_tx: broadcast::Sender<Event>,
 }
 
 impl Default for EventHub {
@@ -38,10 +89,49 @@ impl Default for EventHub {
 
 impl EventHub {
     pub fn new() -> Self {
+        Self::with_capacities(EventCapacities::default())
+    }
+
+    /// Every capacity must be non-zero; `broadcast::channel` panics on zero,
+    /// and the service config refuses it before it gets here.
+    pub fn with_capacities(capacities: EventCapacities) -> Self {
         Self {
-            tx: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
-            log_tx: broadcast::channel(LOG_EVENT_CHANNEL_CAPACITY).0,
+            rings: Arc::new(RwLock::new(Rings {
+                capacities,
+                tx: broadcast::channel(capacities.status).0,
+                log_tx: broadcast::channel(capacities.log).0,
+                telemetry_tx: broadcast::channel(capacities.telemetry).0,
+            })),
+            backlog: Arc::new(Mutex::new(BoundedVecDeque::new(capacities.log_backlog))),
+            telemetry_subscribed: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Applies new ring sizes to a running hub, for a service config reload.
+    ///
+    /// A broadcast channel cannot be resized, so a ring whose size changed is
+    /// replaced. A receiver on the old one drains what it holds and then sees
+    /// it closed, which is a subscriber's cue to take the new ring from the
+    /// hub: a ws connection does so and stays up, resending a status snapshot
+    /// for what it may have missed in between. A ring whose size did not
+    /// change is left alone, and so are its subscribers. The replay backlog is
+    /// trimmed in place, oldest first.
+    pub fn resize(&self, capacities: EventCapacities) {
+        let mut backlog = self.backlog.lock();
+        let mut rings = self.rings.write();
+        if rings.capacities.log_backlog != capacities.log_backlog {
+            drop(backlog.set_max_len(capacities.log_backlog));
+        }
+        if rings.capacities.status != capacities.status {
+            rings.tx = broadcast::channel(capacities.status).0;
+        }
+        if rings.capacities.log != capacities.log {
+            rings.log_tx = broadcast::channel(capacities.log).0;
+        }
+        if rings.capacities.telemetry != capacities.telemetry {
+            rings.telemetry_tx = broadcast::channel(capacities.telemetry).0;
         }
+        rings.capacities = capacities;
     }
 
     /// Fan out an event: synchronous and never awaits; only brief internal
@@ -49,43 +139,108 @@ impl EventHub {
     /// when nobody is subscribed, which is the normal idle state, so the result
     /// is dropped.
     pub fn send(&self, event: Event) {
-        let _ = self.tx.send(event);
+        let _ = self.rings.read().tx.send(event);
     }
 
     pub fn subscribe(&self) -> broadcast::Receiver<Event> {
-        self.tx.subscribe()
+        self.rings.read().tx.subscribe()
     }
 
-    /// Fan out one core log frame. Same contract as [`Self::send`] —
-    /// synchronous, never awaits, and a failure just means nobody is listening.
+    /// Fan out one core log frame and keep it for replay. Same contract as
+    /// [`Self::send`] — synchronous, never awaits, and a failure just means
+    /// nobody is listening. The frame is kept either way: the connection that
+    /// will want it is the one that has not been made yet.
     pub fn send_log(&self, frame: Arc<LogFrame>) {
-        let _ = self.log_tx.send(frame);
+        let mut backlog = self.backlog.lock();
+        backlog.push_back(Arc::clone(&frame));
+        let _ = self.rings.read().log_tx.send(frame);
     }
 
-    pub(crate) fn has_log_subscribers(&self) -> bool {
-        self.log_tx.receiver_count() > 0
+    pub fn subscribe_logs(&self) -> broadcast::Receiver<Arc<LogFrame>> {
+        self.rings.read().log_tx.subscribe()
     }
 
-    pub fn subscribe_logs(&self) -> broadcast::Receiver<Arc<LogFrame>> {
-        self.log_tx.subscribe()
+    /// [`Self::subscribe_logs`], plus the held frames `replay` selects, oldest
+    /// first. Taken under the backlog lock, so the two do not overlap and
+    /// leave no gap between them.
+    pub fn subscribe_logs_with_replay(
+        &self,
+        replay: LogReplay,
+    ) -> (Vec<Arc<LogFrame>>, broadcast::Receiver<Arc<LogFrame>>) {
+        let backlog = self.backlog.lock();
+        let frames = match replay {
+            LogReplay::Last(frames) => {
+                let skip = backlog.len().saturating_sub(frames as usize);
+                backlog.iter().skip(skip).cloned().collect()
+            }
+            LogReplay::Since(at) => backlog
+                .iter()
+                .filter(|frame| frame.at >= at)
+                .cloned()
+                .collect(),
+        };
+        (frames, self.rings.read().log_tx.subscribe())
+    }
+
+    /// Fan out one telemetry sample. Same contract as [`Self::send`]; nothing
+    /// is kept, because a sample nobody saw live is already out of date.
+    pub fn send_telemetry(&self, event: Event) {
+        debug_assert!(matches!(event, Event::Traffic(_) | Event::Memory(_)));
+        let _ = self.rings.read().telemetry_tx.send(event);
+    }
+
+    pub fn subscribe_telemetry(&self) -> broadcast::Receiver<Event> {
+        let receiver = self.rings.read().telemetry_tx.subscribe();
+        self.telemetry_subscribed.notify_waiters();
+        receiver
+    }
+
+    pub fn has_telemetry_subscribers(&self) -> bool {
+        self.rings.read().telemetry_tx.receiver_count() > 0
+    }
+
+    /// Resolves once the telemetry ring has a subscriber, at once if it
+    /// already has one.
+    pub async fn telemetry_subscribers(&self) {
+        loop {
+            let subscribed = self.telemetry_subscribed.notified();
+            tokio::pin!(subscribed);
+            // Registered before the check, so a subscription landing between
+            // the two still wakes it.
+            subscribed.as_mut().enable();
+            if self.has_telemetry_subscribers() {
+                return;
+            }
+            subscribed.await;
+        }
+    }
+
+    #[cfg(test)]
+    fn has_log_subscribers(&self) -> bool {
+        self.rings.read().log_tx.receiver_count() > 0
     }
 
     #[cfg(test)]
     fn receiver_count(&self) -> usize {
-        self.tx.receiver_count()
+        self.rings.read().tx.receiver_count()
     }
 
     #[cfg(test)]
     fn log_receiver_count(&self) -> usize {
-        self.log_tx.receiver_count()
+        self.rings.read().log_tx.receiver_count()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use nyanpasu_core_manager::{CoreKind, LogField, LogLevel, LogStream, LogTimestamp};
-    use nyanpasu_ipc::api::status::{CoreInfos, CoreState, CoreStateDetail};
+    use nyanpasu_core_manager::{
+        ClashCoreKind, CoreKind, LogField, LogLevel, LogStream, LogTimestamp,
+    };
+    use nyanpasu_ipc::api::{
+        status::{CoreInfos, CoreState, CoreStateDetail},
+        ws::events::{MemorySample, TrafficSample},
+    };
     use tokio::sync::broadcast::error::TryRecvError;
 
     fn state_event(state: CoreState) -> Event {
@@ -189,6 +344,8 @@ mod tests {
             health: None,
             revision: None,
             detail: Some(CoreStateDetail::Stopped { reason: None }),
+            build: None,
+            policy: None,
         });
         let frame = simd_json::to_vec(&event).unwrap();
         assert_eq!(
@@ -205,7 +362,7 @@ mod tests {
         Arc::new(LogFrame {
             at: 1_700_000_000_000,
             epoch: 1,
-            kind: CoreKind::Mihomo,
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
             stream: LogStream::Stdout,
             level: LogLevel::Info,
             timestamp: Some(LogTimestamp {
@@ -268,6 +425,99 @@ mod tests {
         ));
     }
 
+    fn frame_at(at: i64) -> Arc<LogFrame> {
+        let mut frame = (*core_log_frame()).clone();
+        frame.at = at;
+        Arc::new(frame)
+    }
+
+    fn ats(frames: &[Arc<LogFrame>]) -> Vec<i64> {
+        frames.iter().map(|frame| frame.at).collect()
+    }
+
+    /// Only a ring whose size changed is replaced: its subscriber drains what
+    /// was already sent and then sees the close that moves it to the new ring,
+    /// while the status subscriber is untouched.
+    #[test]
+    fn resizing_replaces_only_the_changed_rings_and_trims_the_backlog() {
+        let hub = EventHub::new();
+        for at in 1..=5 {
+            hub.send_log(frame_at(at));
+        }
+        let mut status = hub.subscribe();
+        let mut logs = hub.subscribe_logs();
+        hub.send_log(frame_at(6));
+
+        hub.resize(EventCapacities {
+            log: 8,
+            log_backlog: 2,
+            ..EventCapacities::default()
+        });
+
+        assert_eq!(logs.try_recv().unwrap().at, 6);
+        assert!(matches!(logs.try_recv(), Err(TryRecvError::Closed)));
+        hub.send(state_event(CoreState::Running));
+        assert!(matches!(
+            status.try_recv().unwrap(),
+            Event::CoreStateChanged(CoreState::Running)
+        ));
+        let (replayed, _) = hub.subscribe_logs_with_replay(LogReplay::Last(10));
+        assert_eq!(ats(&replayed), [5, 6]);
+    }
+
+    /// Frames sent while nobody is connected are exactly the ones a
+    /// reconnecting console is missing, so they are kept regardless.
+    #[test]
+    fn the_backlog_fills_without_subscribers_and_replays_by_count_or_instant() {
+        let hub = EventHub::new();
+        for at in 1..=5 {
+            hub.send_log(frame_at(at));
+        }
+        assert_eq!(
+            ats(&hub.subscribe_logs_with_replay(LogReplay::Last(2)).0),
+            [4, 5]
+        );
+        assert_eq!(
+            ats(&hub.subscribe_logs_with_replay(LogReplay::Last(99)).0),
+            [1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            ats(&hub.subscribe_logs_with_replay(LogReplay::Since(3)).0),
+            [3, 4, 5]
+        );
+        assert!(
+            hub.subscribe_logs_with_replay(LogReplay::Last(0))
+                .0
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn the_backlog_keeps_only_the_newest_frames() {
+        let hub = EventHub::new();
+        let sent = LOG_BACKLOG_CAPACITY as i64 + 10;
+        for at in 0..sent {
+            hub.send_log(frame_at(at));
+        }
+        let (replayed, _) = hub.subscribe_logs_with_replay(LogReplay::Since(0));
+        assert_eq!(replayed.len(), LOG_BACKLOG_CAPACITY);
+        assert_eq!(replayed.first().unwrap().at, 10);
+        assert_eq!(replayed.last().unwrap().at, sent - 1);
+    }
+
+    /// The replay and the live ring meet without overlap: the frame sent after
+    /// the subscription arrives live and is not in the replay.
+    #[test]
+    fn a_replay_hands_over_to_the_ring_without_repeating_a_frame() {
+        let hub = EventHub::new();
+        hub.send_log(frame_at(1));
+        let (replayed, mut live) = hub.subscribe_logs_with_replay(LogReplay::Last(10));
+        hub.send_log(frame_at(2));
+        assert_eq!(ats(&replayed), [1]);
+        assert_eq!(live.try_recv().unwrap().at, 2);
+        assert!(matches!(live.try_recv(), Err(TryRecvError::Empty)));
+    }
+
     /// The point of the whole stage: a log burst big enough to overrun its own
     /// ring several times over leaves the status subscriber untouched. On one
     /// ring this same burst would have forced a `Lagged`, and the ws handler
@@ -294,4 +544,81 @@ mod tests {
         // frames — which is free, by design.
         assert!(matches!(logs.try_recv(), Err(TryRecvError::Lagged(_))));
     }
+
+    /// Byte-for-byte the literals `nyanpasu_ipc`'s
+    /// `the_telemetry_events_are_pinned` asserts against `serde_json`.
+    #[test]
+    fn ws_telemetry_frames_are_pinned() {
+        let traffic = simd_json::to_vec(&Event::new_traffic(TrafficSample {
+            epoch: 2,
+            up: 1024,
+            down: 4096,
+            up_total: 1_048_576,
+            down_total: 8_388_608,
+        }))
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(traffic).unwrap(),
+            concat!(
+                r#"{"Traffic":{"epoch":2,"up":1024,"down":4096,"#,
+                r#""up_total":1048576,"down_total":8388608}}"#
+            )
+        );
+        let memory = simd_json::to_vec(&Event::new_memory(MemorySample {
+            epoch: 2,
+            in_use: 52_428_800,
+            os_limit: 0,
+        }))
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(memory).unwrap(),
+            r#"{"Memory":{"epoch":2,"in_use":52428800,"os_limit":0}}"#
+        );
+    }
+
+    /// Telemetry rides its own ring: a flood of samples lags only the
+    /// telemetry subscriber, and a status subscriber never sees one.
+    #[tokio::test]
+    async fn a_telemetry_flood_never_lags_the_status_ring() {
+        let hub = EventHub::new();
+        let mut status = hub.subscribe();
+        assert!(!hub.has_telemetry_subscribers());
+        let mut telemetry = hub.subscribe_telemetry();
+        assert!(hub.has_telemetry_subscribers());
+        hub.send(state_event(CoreState::Running));
+        for _ in 0..(TELEMETRY_EVENT_CHANNEL_CAPACITY * 2) {
+            hub.send_telemetry(Event::new_memory(MemorySample {
+                epoch: 1,
+                in_use: 1,
+                os_limit: 0,
+            }));
+        }
+
+        assert!(matches!(
+            status.try_recv().unwrap(),
+            Event::CoreStateChanged(CoreState::Running)
+        ));
+        assert!(matches!(status.try_recv(), Err(TryRecvError::Empty)));
+        assert!(matches!(telemetry.try_recv(), Err(TryRecvError::Lagged(_))));
+        drop(telemetry);
+        assert!(!hub.has_telemetry_subscribers());
+    }
+
+    /// The relay parks on this with no core streams open, so it must wake on
+    /// the first subscriber and not before.
+    #[tokio::test]
+    async fn a_telemetry_waiter_wakes_on_the_first_subscriber() {
+        let hub = EventHub::new();
+        let waiting = tokio::spawn({
+            let hub = hub.clone();
+            async move { hub.telemetry_subscribers().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        let _telemetry = hub.subscribe_telemetry();
+        tokio::time::timeout(std::time::Duration::from_secs(1), waiting)
+            .await
+            .expect("a subscription wakes the waiter")
+            .unwrap();
+    }
 }
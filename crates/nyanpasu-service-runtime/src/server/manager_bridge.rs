@@ -1,42 +1,90 @@
 use std::{
     borrow::Cow,
+    collections::HashMap,
+    fmt::Display,
+    num::NonZeroU32,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
 };
 
 use camino::{Utf8Path, Utf8PathBuf};
+use futures_util::{StreamExt, stream::BoxStream};
 use nyanpasu_core_manager::{
-    ApplyOutcome, ConfigRevision, CoreKind, CoreManager as Manager, CoreSpec,
-    CoreState as ManagerCoreState, CoreStatus, Error as ManagerError, HealthState, HealthStatus,
-    Host, InstanceOptions, InstanceSpec, LocalIpcPolicy, LogFrame, LogLevel, ManagerOptions,
-    RevisionId,
+    ApplyOutcome, ApplyPlan, ClashCoreKind, ConfigDiffEntry, ConfigDiffKind as ManagerDiffKind,
+    ConfigRevision, Connection, ConnectionFilter as ManagerConnectionFilter,
+    ConnectionNetwork as ManagerConnectionNetwork, CoreKind, CoreManager as Manager, CoreSpec,
+    CoreState as ManagerCoreState, CoreStatus, DegradeReason, Error as ManagerError, HealthPolicy,
+    HealthState, HealthStatus, Host, InstanceOptions, InstanceSpec, LocalIpcPolicy, LogCursor,
+    LogDirection, LogFrame, LogLevel, LogPage, LogQuery, ManagerOptions, PlannedRoute, Proxy,
+    ProxySelection, RestartPolicy, RestoredSelections, RevisionId, RevisionRecord, RuntimeFeature,
+    SwitchOutcome, Uuid,
 };
 use nyanpasu_ipc::api::{
     R, RBuilder,
-    core::apply::{ApplyOutcomeKind, CoreApplyData},
+    connections::{
+        ConnectionCloseData, ConnectionCloseReq, ConnectionFilter, ConnectionInfo,
+        ConnectionNetwork, ConnectionsData,
+    },
+    core::{
+        apply::{
+            ApplyOutcomeKind, CORE_INLINE_CONFIG_MAX_BYTES, CoreApplyData, CoreApplyPlanData,
+            SwitchDegradeReason,
+        },
+        config::{ConfigDiffEntryInfo, ConfigDiffKind, CoreConfigDiffData},
+        restart::CoreRestartData,
+        revisions::{CoreRevisionInfo, CoreRevisionsData},
+        start::CorePolicyOverrides,
+    },
     error_kind,
+    log::{
+        CORE_LOGS_DEFAULT_LIMIT, CORE_LOGS_MAX_LIMIT, CoreLogCursor, CoreLogsQueryData,
+        CoreLogsQueryReq,
+    },
+    proxies::{
+        PROXY_DELAY_DEFAULT_TIMEOUT_MS, PROXY_DELAY_MAX_TIMEOUT_MS, ProxiesData, ProxyDelayData,
+        ProxyDelayInfo, ProxyDelayReq, ProxyGroupInfo, ProxySelectReq, ProxySelectionInfo,
+        RestoredSelectionsInfo,
+    },
+    service_config::HealthConfig,
     status::{
-        ConfigRevisionInfo, CoreControllerInfo, CoreHealthInfo, CoreHealthState, CoreInfos,
-        CoreState, CoreStateDetail, RevisionIdInfo,
+        ConfigRevisionInfo, CoreBinaryInfo, CoreBuildInfo, CoreControllerInfo, CoreDistribution,
+        CoreHealthInfo, CoreHealthState, CoreInfos, CorePolicyInfo, CoreRuntimeFeature, CoreState,
+        CoreStateDetail, RevisionIdInfo,
     },
-    ws::events::Event as WsEvent,
+    ws::events::{Event as WsEvent, MemorySample, TrafficSample},
 };
 use nyanpasu_utils::core::{ClashCoreType, CoreType};
 use serde::{Serialize, de::DeserializeOwned};
-use tokio::sync::{Semaphore, broadcast::error::RecvError, watch};
+use tokio::{
+    sync::{Semaphore, broadcast::error::RecvError, watch},
+    time::MissedTickBehavior,
+};
 use tracing::instrument;
 
 use super::{consts::RuntimeInfos, events::EventHub};
+use crate::utils::core_manifest::{CoreManifest, sha256_copy, sha256_file};
 
 const CORE_LOG_TARGET: &str = "nyanpasu_service::core";
 
-/// Concurrent `/core/check` operations a service will run. Each one spawns a
-/// core binary, so this is a resource bound, not a fairness knob: two lets an
-/// honest client fire a second check while the first is running, and refuses
-/// the flood a third would begin. Per-service rather than a `static` so the
-/// bound is testable without the runtime crate's tests interfering with each
-/// other.
-const MAX_CONCURRENT_CHECKS: usize = 2;
+/// Under the service data dir: the copies of pinned binaries the service
+/// actually runs, one directory per digest.
+const PINNED_CORES_DIR_NAME: &str = "pinned-cores";
+
+/// Under [`PINNED_CORES_DIR_NAME`]: the scratch copy a plan or diff preview
+/// probes when no start has copied the same build yet.
+const PREVIEW_DIR_NAME: &str = ".preview";
+
+/// How long the telemetry relay waits before reopening a core stream that
+/// failed or ended while its epoch was still running. Doubled on each failure
+/// in a row, up to [`TELEMETRY_RETRY_MAX_DELAY`]: a core that does not serve a
+/// stream at all is asked again once a minute for as long as it runs.
+const TELEMETRY_RETRY_DELAY: Duration = Duration::from_secs(5);
+const TELEMETRY_RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
 
 /// Legacy wire strings the GUI branches on. These are protocol, not
 /// diagnostics: changing any of them is a breaking change to clash-nyanpasu.
@@ -46,10 +94,9 @@ pub(crate) const MSG_CORE_NOT_STARTED: &str = "core have not been started yet";
 
 /// A failed operation, carrying its wire `error_kind` next to its message.
 ///
-/// `start`/`stop`/`restart` predate `error_kind` and keep returning
-/// `anyhow::Error`; the S8 operations need the classification, and the only
-/// place it can be derived without downcasting is where the `ManagerError` is
-/// still typed.
+/// Every operation that reaches the manager returns one, because the only place
+/// the classification can be derived without downcasting is where the
+/// `ManagerError` is still typed.
 pub(crate) struct OpError {
     kind: Option<&'static str>,
     message: String,
@@ -74,6 +121,14 @@ impl OpError {
         }
     }
 
+    fn shutting_down() -> Self {
+        Self::with_kind(error_kind::SHUTTING_DOWN, "service is shutting down")
+    }
+
+    pub(crate) fn kind(&self) -> Option<&'static str> {
+        self.kind
+    }
+
     /// The error envelope for this failure, `error_kind` included.
     pub(crate) fn into_envelope<T>(self) -> R<'static, T>
     where
@@ -104,6 +159,43 @@ impl From<anyhow::Error> for OpError {
     }
 }
 
+/// Where an apply or check reads its config from. The wire carries either a
+/// path or the YAML itself, and exactly one of them.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ConfigInput<'a> {
+    File(&'a Path),
+    Inline(&'a str),
+}
+
+impl<'a> ConfigInput<'a> {
+    /// The request's two optional fields, checked. An oversized payload is an
+    /// `invalid_config`; a request naming neither or both is left unclassified,
+    /// like any other malformed request.
+    pub(crate) fn from_request(
+        config_file: Option<&'a Path>,
+        config: Option<&'a str>,
+    ) -> Result<Self, OpError> {
+        match (config_file, config) {
+            (Some(file), None) => Ok(Self::File(file)),
+            (None, Some(source)) if source.len() > CORE_INLINE_CONFIG_MAX_BYTES => {
+                Err(OpError::with_kind(
+                    error_kind::INVALID_CONFIG,
+                    format!(
+                        "inline config is {} bytes, over the {CORE_INLINE_CONFIG_MAX_BYTES}-byte \
+                         limit; pass it as config_file instead",
+                        source.len()
+                    ),
+                ))
+            }
+            (None, Some(source)) => Ok(Self::Inline(source)),
+            (Some(_), Some(_)) => Err(OpError::plain(
+                "config_file and config are exclusive; set only one",
+            )),
+            (None, None) => Err(OpError::plain("one of config_file and config is required")),
+        }
+    }
+}
+
 struct Inner {
     manager: Manager,
     /// Wire-type echo: the manager knows nothing about the alpha variants.
@@ -119,6 +211,62 @@ struct Inner {
     control: tokio::sync::Mutex<ControlState>,
     /// F2 lands here too; see §2.2.
     check_slots: Semaphore,
+    /// Concurrent `/core/check` operations this service will run, sized by the
+    /// service config. Each one spawns a core binary, so this is a resource
+    /// bound, not a fairness knob: the default two lets an honest client fire
+    /// a second check while the first is running, and refuses the flood a
+    /// third would begin. Per-service rather than a `static` so the bound is
+    /// testable without the runtime crate's tests interfering with each other.
+    max_concurrent_checks: usize,
+    /// How every core this service launches is started, watched and
+    /// restarted, from the service config. Read per launch, so a reload
+    /// reaches the next epoch and never the running one.
+    instance_options: parking_lot::RwLock<InstanceOptions>,
+    /// Each service-owned copy the pinned manifest vouched for, for `/status`
+    /// and for re-checking its pin before a restart or rollback relaunches it.
+    /// Keyed by the copy's path so a rollback to an earlier binary still finds
+    /// its own.
+    verified_binaries: parking_lot::Mutex<HashMap<Utf8PathBuf, PinnedCopy>>,
+    /// What a request's `policy.startup_timeout_ms` must stay under: the
+    /// service's request timeout less the reconcile and stop budgets a start
+    /// or restart may also spend, so no override outlives its own request.
+    startup_timeout_limit: Duration,
+}
+
+/// A binary the pinned manifest vouched for, copied where only the service
+/// can write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PinnedCopy {
+    path: PathBuf,
+    /// The canonical path `pin-core` recorded, which the copy was made from.
+    origin: PathBuf,
+    sha256: String,
+}
+
+/// What a plan or diff preview runs in place of a pinned binary. Neither kind
+/// is recorded in `verified_binaries`, so no later launch can pick it up.
+#[derive(Debug, PartialEq, Eq)]
+enum PreviewBinary {
+    /// The copy an earlier start or apply already made of the same build.
+    Copy(PathBuf),
+    /// A copy made for this preview alone, removed when it is dropped.
+    Scratch(PathBuf),
+}
+
+impl PreviewBinary {
+    fn path(&self) -> &Path {
+        match self {
+            Self::Copy(path) | Self::Scratch(path) => path,
+        }
+    }
+}
+
+impl Drop for PreviewBinary {
+    fn drop(&mut self) {
+        if let Self::Scratch(path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
 }
 
 struct ControlState {
@@ -131,34 +279,51 @@ pub struct CoreManagerService {
 }
 
 impl CoreManagerService {
+    /// `options` must carry the `runtime_dir`. `request_timeout` is the
+    /// router's, which bounds every start a request can ask for.
     pub async fn new(
-        runtime_dir: Utf8PathBuf,
-        local_ipc_policy: LocalIpcPolicy,
+        options: ManagerOptions,
+        instance_options: InstanceOptions,
+        max_concurrent_checks: usize,
+        request_timeout: Duration,
     ) -> Result<Self, anyhow::Error> {
-        let manager = Manager::new(ManagerOptions {
-            runtime_dir: Some(runtime_dir),
-            local_ipc_policy,
-            ..ManagerOptions::default()
-        })
-        .await?;
+        // Same sum the service config checks its own startup timeout against.
+        let startup_timeout_limit = request_timeout.saturating_sub(
+            options.reconcile_timeout + options.stop_timeout + options.stop_timeout,
+        );
+        let manager = Manager::new(options).await?;
         Ok(Self {
             inner: Arc::new(Inner {
                 manager,
                 requested_core: watch::Sender::new(None),
                 control: tokio::sync::Mutex::new(ControlState { closing: false }),
-                check_slots: Semaphore::new(MAX_CONCURRENT_CHECKS),
+                check_slots: Semaphore::new(max_concurrent_checks),
+                max_concurrent_checks,
+                instance_options: parking_lot::RwLock::new(instance_options),
+                verified_binaries: parking_lot::Mutex::new(HashMap::new()),
+                startup_timeout_limit,
             }),
         })
     }
 
     /// State → ws events, and core logs → both the ws log ring and tracing.
-    pub fn spawn_bridges(&self, hub: EventHub) {
+    /// `telemetry_interval` is the most often a connection is sent a traffic
+    /// or memory sample; the core's own rate is faster or equal.
+    pub fn spawn_bridges(&self, hub: EventHub, telemetry_interval: Duration) {
         // Only the two receivers are moved in, never the adapter: see `Inner`.
         tokio::spawn(status_bridge(
             self.inner.manager.subscribe(),
             self.inner.requested_core.subscribe(),
             hub.clone(),
         ));
+        // The one bridge that has to call the manager, so it gets a `Weak`:
+        // the same exit guarantee, from the other direction.
+        tokio::spawn(telemetry_bridge(
+            Arc::downgrade(&self.inner),
+            self.inner.manager.subscribe(),
+            hub.clone(),
+            telemetry_interval,
+        ));
 
         let mut logs = self.inner.manager.subscribe_logs();
         tokio::spawn(async move {
@@ -169,12 +334,10 @@ impl CoreManagerService {
                         // the parser bounded is the frame the ws ring gets.
                         // Tracing borrows it first so the subscription's own
                         // reference can then be moved on rather than cloned.
-                        // A subscriber connecting mid-frame misses that frame,
-                        // consistent with the stream's no-replay semantics.
+                        // Sent even with nobody connected: the hub keeps it for
+                        // the next connection's replay.
                         forward_log(&frame);
-                        if hub.has_log_subscribers() {
-                            hub.send_log(frame);
-                        }
+                        hub.send_log(frame);
                     }
                     Err(RecvError::Lagged(skipped)) => {
                         tracing::warn!("core log bridge dropped {skipped} frames")
@@ -203,26 +366,31 @@ impl CoreManagerService {
         &self,
         infos: &RuntimeInfos,
         core_type: &CoreType,
-        config_path: &Utf8Path,
-    ) -> Result<(), anyhow::Error> {
+        config_file: &Path,
+        binary_path: Option<&Path>,
+        distribution: Option<&CoreDistribution>,
+        policy: Option<&CorePolicyOverrides>,
+    ) -> Result<(), OpError> {
         let control = self.inner.control.lock().await;
         if control.closing {
-            anyhow::bail!("service is shutting down");
+            return Err(OpError::shutting_down());
         }
-        let config_path = config_path.canonicalize_utf8()?;
-        let config_path =
-            Utf8PathBuf::from_path_buf(dunce::simplified(config_path.as_std_path()).to_path_buf())
-                .expect("a canonical UTF-8 path stays UTF-8 after simplification");
-        tokio::fs::metadata(&config_path).await?; // check if the file exists
+        let config_path = canonical_config_path(config_file).await?;
         if !matches!(
             self.inner.manager.status().state,
             ManagerCoreState::Stopped { .. }
         ) {
-            anyhow::bail!(MSG_CORE_ALREADY_RUNNING);
+            return Err(OpError::with_kind(
+                error_kind::ALREADY_RUNNING,
+                MSG_CORE_ALREADY_RUNNING,
+            ));
+        }
+        let mut spec = self.instance_spec(infos, core_type, binary_path, config_path)?;
+        spec.core.distribution = distribution.cloned();
+        if let Some(policy) = policy {
+            override_options(&mut spec.options, policy, self.inner.startup_timeout_limit)?;
         }
-        let spec = self
-            .instance_spec(infos, core_type, config_path)
-            .map_err(|error| anyhow::anyhow!(error.message))?;
+        self.verify_binary(infos, &mut spec).await?;
         tracing::info!(
             core_type = %core_type,
             kind = %spec.core.kind,
@@ -236,55 +404,81 @@ impl CoreManagerService {
         Ok(())
     }
 
-    pub async fn stop(&self) -> Result<(), anyhow::Error> {
+    pub async fn stop(&self) -> Result<(), OpError> {
         let control = self.inner.control.lock().await;
         if control.closing {
-            anyhow::bail!("service is shutting down");
+            return Err(OpError::shutting_down());
         }
         match self.inner.manager.stop().await {
             Ok(()) => Ok(()),
-            Err(ManagerError::NotStarted) => anyhow::bail!(MSG_CORE_ALREADY_STOPPED),
+            // Stop keeps its own legacy phrasing of the same fact.
+            Err(ManagerError::NotStarted) => Err(OpError::with_kind(
+                error_kind::NOT_STARTED,
+                MSG_CORE_ALREADY_STOPPED,
+            )),
             Err(error) => Err(error.into()),
         }
     }
 
-    pub async fn restart(&self) -> Result<(), anyhow::Error> {
+    pub async fn restart(&self, infos: &RuntimeInfos) -> Result<CoreRestartData, OpError> {
         let control = self.inner.control.lock().await;
         if control.closing {
-            anyhow::bail!("service is shutting down");
+            return Err(OpError::shutting_down());
         }
-        match self.inner.manager.restart().await {
-            Ok(_outcome) => Ok(()),
-            Err(ManagerError::NotStarted) => anyhow::bail!(MSG_CORE_NOT_STARTED),
-            Err(error) => Err(error.into()),
+        if let Some(spec) = self.inner.manager.status().spec {
+            self.reverify_binary(infos, &spec.binary_path).await?;
         }
+        let outcome = self.inner.manager.restart().await?;
+        Ok(map_switch_outcome(&outcome))
     }
 
-    /// Apply `config_file` to the running core.
+    /// Apply `config` to the running core.
     ///
     /// The manager classifies the change and routes it: in-place patch, reload,
     /// same-epoch restart with rollback, or a full core switch when the process
     /// spec changed (which is what a different `core_type` produces). A stopped
     /// core is an error, never an implicit start (report §7 R2).
-    #[instrument(skip(self, infos))]
+    #[instrument(skip(self, infos, config))]
     pub async fn apply(
         &self,
         infos: &RuntimeInfos,
         core_type: &CoreType,
-        config_file: &Path,
+        config: ConfigInput<'_>,
         expected_revision: Option<&RevisionIdInfo>,
+        binary_path: Option<&Path>,
+        distribution: Option<&CoreDistribution>,
+        policy: Option<&CorePolicyOverrides>,
     ) -> Result<CoreApplyData, OpError> {
         let control = self.inner.control.lock().await;
         if control.closing {
-            return Err(OpError::plain("service is shutting down"));
+            return Err(OpError::shutting_down());
         }
-        let config_path = canonical_config_path(config_file).await?;
-        let spec = self.instance_spec(infos, core_type, config_path)?;
-        let outcome = self
-            .inner
-            .manager
-            .apply_config(spec, expected_revision.map(map_revision_id))
-            .await?;
+        let expected_revision = expected_revision.map(map_revision_id);
+        let outcome = match config {
+            ConfigInput::File(config_file) => {
+                let config_path = canonical_config_path(config_file).await?;
+                let mut spec = self.instance_spec(infos, core_type, binary_path, config_path)?;
+                spec.core.distribution = distribution.cloned();
+                self.launch_options(&mut spec, policy)?;
+                self.verify_binary(infos, &mut spec).await?;
+                self.inner
+                    .manager
+                    .apply_config(spec, expected_revision)
+                    .await?
+            }
+            ConfigInput::Inline(source) => {
+                // The manager points the spec at the copy it keeps.
+                let mut spec =
+                    self.instance_spec(infos, core_type, binary_path, Utf8PathBuf::new())?;
+                spec.core.distribution = distribution.cloned();
+                self.launch_options(&mut spec, policy)?;
+                self.verify_binary(infos, &mut spec).await?;
+                self.inner
+                    .manager
+                    .apply_inline(spec, source.as_bytes(), expected_revision)
+                    .await?
+            }
+        };
         let data = map_apply_outcome(&outcome);
         tracing::info!(
             outcome = ?data.outcome,
@@ -301,13 +495,124 @@ impl CoreManagerService {
         Ok(data)
     }
 
-    /// Dry-run a config against a core binary. Never touches the running core.
+    /// Predict how [`Self::apply`] would route `config_file`, without applying
+    /// it. Never stages a runtime copy nor touches the running core.
     #[instrument(skip(self, infos))]
-    pub async fn check(
+    pub async fn plan_apply(
         &self,
         infos: &RuntimeInfos,
         core_type: &CoreType,
         config_file: &Path,
+        binary_path: Option<&Path>,
+        policy: Option<&CorePolicyOverrides>,
+    ) -> Result<CoreApplyPlanData, OpError> {
+        let control = self.inner.control.lock().await;
+        if control.closing {
+            return Err(OpError::shutting_down());
+        }
+        let config_path = canonical_config_path(config_file).await?;
+        let mut spec = self.instance_spec(infos, core_type, binary_path, config_path)?;
+        self.launch_options(&mut spec, policy)?;
+        // The preview probes the binary's version, so it is verified like
+        // any other launch, but keeps no copy of its own.
+        let _preview = self.preview_binary(infos, &mut spec).await?;
+        let plan = self.inner.manager.plan_apply(spec).await?;
+        Ok(map_apply_plan(plan))
+    }
+
+    /// Diff `config_file` against the running core's effective config, with
+    /// credentials and controller keys redacted. Never stages a runtime copy
+    /// nor touches the running core.
+    #[instrument(skip(self, infos))]
+    pub async fn diff_config(
+        &self,
+        infos: &RuntimeInfos,
+        core_type: &CoreType,
+        config_file: &Path,
+        binary_path: Option<&Path>,
+        policy: Option<&CorePolicyOverrides>,
+    ) -> Result<CoreConfigDiffData, OpError> {
+        let control = self.inner.control.lock().await;
+        if control.closing {
+            return Err(OpError::shutting_down());
+        }
+        let config_path = canonical_config_path(config_file).await?;
+        let mut spec = self.instance_spec(infos, core_type, binary_path, config_path)?;
+        self.launch_options(&mut spec, policy)?;
+        // Probes the binary's version like `plan_apply`, so verified like it.
+        let _preview = self.preview_binary(infos, &mut spec).await?;
+        let entries = self.inner.manager.diff_config(spec).await?;
+        Ok(CoreConfigDiffData {
+            entries: entries.into_iter().map(map_config_diff_entry).collect(),
+        })
+    }
+
+    /// The manager's revision history, newest first.
+    pub async fn revisions(&self) -> CoreRevisionsData {
+        let revisions = self.inner.manager.revisions().await;
+        CoreRevisionsData {
+            revisions: revisions.iter().map(map_revision_record).collect(),
+        }
+    }
+
+    /// Re-apply a revision from the manager's history, guarded like
+    /// [`Self::apply`].
+    #[instrument(skip(self, infos))]
+    pub async fn rollback(
+        &self,
+        infos: &RuntimeInfos,
+        target: &RevisionIdInfo,
+        expected_revision: Option<&RevisionIdInfo>,
+    ) -> Result<CoreApplyData, OpError> {
+        let control = self.inner.control.lock().await;
+        if control.closing {
+            return Err(OpError::shutting_down());
+        }
+        let target_id = map_revision_id(target);
+        let target_binary = self
+            .inner
+            .manager
+            .revisions()
+            .await
+            .into_iter()
+            .find(|record| record.revision.id() == target_id)
+            .map(|record| record.binary_path);
+        // An unknown target is left for the manager to refuse as not found.
+        if let Some(binary) = target_binary {
+            self.reverify_binary(infos, &binary).await?;
+        }
+        let outcome = self
+            .inner
+            .manager
+            .rollback(&target_id, expected_revision.map(map_revision_id))
+            .await?;
+        let data = map_apply_outcome(&outcome);
+        tracing::info!(
+            outcome = ?data.outcome,
+            epoch = data.revision.epoch,
+            generation = data.revision.generation,
+            "Rolled back config"
+        );
+        // A rollback across a switch runs another kind than the echo names.
+        // The alpha builds are indistinguishable by then, so the echo falls
+        // back to the kind's stable type; within one kind it is left alone.
+        let running = self.inner.manager.status().spec.map(|spec| spec.kind);
+        let echo = self.inner.requested_core.borrow().clone();
+        let committed = running
+            .filter(|kind| echo.as_ref().map(core_kind) != Some(*kind))
+            .map(wire_core_type);
+        self.publish_requested_core(committed.as_ref());
+        Ok(data)
+    }
+
+    /// Dry-run a config against a core binary. Never touches the running core.
+    #[instrument(skip(self, infos, config))]
+    pub async fn check(
+        &self,
+        infos: &RuntimeInfos,
+        core_type: &CoreType,
+        config: ConfigInput<'_>,
+        binary_path: Option<&Path>,
     ) -> Result<(), OpError> {
         {
             // Released before the check runs: it spawns the core binary, and
@@ -315,7 +620,7 @@ impl CoreManagerService {
             // start/stop/restart for as long as that takes.
             let control = self.inner.control.lock().await;
             if control.closing {
-                return Err(OpError::plain("service is shutting down"));
+                return Err(OpError::shutting_down());
             }
         }
         // Refused, not queued: waiting would convert a flood into a backlog of
@@ -323,12 +628,27 @@ impl CoreManagerService {
         // learn nothing until its request timed out.
         let _slot = self.inner.check_slots.try_acquire().map_err(|_| {
             OpError::plain(format!(
-                "at most {MAX_CONCURRENT_CHECKS} config checks may run at once; retry"
+                "at most {} config checks may run at once; retry",
+                self.inner.max_concurrent_checks
             ))
         })?;
-        let config_path = canonical_config_path(config_file).await?;
-        let spec = self.instance_spec(infos, core_type, config_path)?;
-        self.inner.manager.check_config(&spec).await?;
+        match config {
+            ConfigInput::File(config_file) => {
+                let config_path = canonical_config_path(config_file).await?;
+                let mut spec = self.instance_spec(infos, core_type, binary_path, config_path)?;
+                self.verify_binary(infos, &mut spec).await?;
+                self.inner.manager.check_config(&spec).await?;
+            }
+            ConfigInput::Inline(source) => {
+                let mut spec =
+                    self.instance_spec(infos, core_type, binary_path, Utf8PathBuf::new())?;
+                self.verify_binary(infos, &mut spec).await?;
+                self.inner
+                    .manager
+                    .check_inline(&spec, source.as_bytes())
+                    .await?;
+            }
+        }
         Ok(())
     }
 
@@ -338,12 +658,53 @@ impl CoreManagerService {
     pub async fn recover(&self) -> Result<(), OpError> {
         let control = self.inner.control.lock().await;
         if control.closing {
-            return Err(OpError::plain("service is shutting down"));
+            return Err(OpError::shutting_down());
         }
         self.inner.manager.recover_quarantine().await?;
         Ok(())
     }
 
+    /// Instance options for every launch from here on. The running core keeps
+    /// the ones it was started with.
+    pub fn set_instance_options(&self, options: InstanceOptions) {
+        *self.inner.instance_options.write() = options;
+    }
+
+    /// The local IPC policy for every launch from here on; see
+    /// [`Manager::set_local_ipc_policy`].
+    pub fn set_local_ipc_policy(&self, policy: LocalIpcPolicy) {
+        self.inner.manager.set_local_ipc_policy(policy);
+    }
+
+    /// New rotation limits for the core log archive; see
+    /// [`Manager::set_log_rotation`].
+    pub async fn set_log_rotation(
+        &self,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> Result<(), anyhow::Error> {
+        Ok(self
+            .inner
+            .manager
+            .set_log_rotation(max_bytes, max_files)
+            .await?)
+    }
+
+    /// The running core's binary and its verified digest, when the pinned
+    /// manifest was in force as it launched.
+    pub fn core_binary(&self) -> Option<CoreBinaryInfo> {
+        let status = self.inner.manager.status();
+        if matches!(status.state, ManagerCoreState::Stopped { .. }) {
+            return None;
+        }
+        let path = status.spec?.binary_path;
+        let copy = self.inner.verified_binaries.lock().get(&path)?.clone();
+        Some(CoreBinaryInfo {
+            path: copy.origin,
+            sha256: copy.sha256,
+        })
+    }
+
     pub async fn status(&self) -> CoreInfos {
         project_core_infos(
             &self.inner.manager.status(),
@@ -361,6 +722,127 @@ impl CoreManagerService {
             .map(|dir| dir.as_std_path().to_path_buf())
     }
 
+    /// One page of the core log archive. Served here rather than by opening the
+    /// directory to callers, so the archive keeps the same ACL as the rest of
+    /// the runtime directory.
+    pub async fn query_logs(&self, req: &CoreLogsQueryReq) -> Result<CoreLogsQueryData, OpError> {
+        match self.inner.manager.query_logs(&map_log_query(req)).await? {
+            Some(page) => Ok(map_log_page(page)),
+            None => Err(OpError::with_kind(
+                error_kind::LOG_ARCHIVE_DISABLED,
+                "the core log archive is disabled",
+            )),
+        }
+    }
+
+    /// The running core's proxy groups, fetched with the controller secret the
+    /// service holds so callers never need it.
+    pub async fn proxies(&self) -> Result<ProxiesData, OpError> {
+        let groups = self.inner.manager.proxy_groups().await?;
+        Ok(ProxiesData {
+            groups: groups.iter().map(map_proxy_group).collect(),
+        })
+    }
+
+    pub async fn select_proxy(&self, req: &ProxySelectReq) -> Result<(), OpError> {
+        self.inner
+            .manager
+            .select_proxy(&req.group, &req.proxy)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn proxy_delay(&self, req: &ProxyDelayReq) -> Result<ProxyDelayData, OpError> {
+        let timeout = Duration::from_millis(
+            req.timeout_ms
+                .unwrap_or(PROXY_DELAY_DEFAULT_TIMEOUT_MS)
+                .clamp(1, PROXY_DELAY_MAX_TIMEOUT_MS)
+                .into(),
+        );
+        let delays = if req.group {
+            self.inner
+                .manager
+                .group_delay(&req.name, &req.url, timeout)
+                .await?
+                .into_iter()
+                .map(|(name, delay)| ProxyDelayInfo {
+                    name: name.to_string(),
+                    delay,
+                })
+                .collect()
+        } else {
+            let delay = self
+                .inner
+                .manager
+                .proxy_delay(&req.name, &req.url, timeout)
+                .await?;
+            vec![ProxyDelayInfo {
+                name: req.name.clone(),
+                delay,
+            }]
+        };
+        Ok(ProxyDelayData { delays })
+    }
+
+    /// The running core's connections that `filter` matches, fetched the same
+    /// way as [`Self::proxies`].
+    pub async fn connections(&self, filter: &ConnectionFilter) -> Result<ConnectionsData, OpError> {
+        let snapshot = self
+            .inner
+            .manager
+            .connections(&map_connection_filter(filter))
+            .await?;
+        Ok(ConnectionsData {
+            upload_total: snapshot.upload_total,
+            download_total: snapshot.download_total,
+            connections: snapshot
+                .connections
+                .iter()
+                .flatten()
+                .map(map_connection)
+                .collect(),
+        })
+    }
+
+    /// Every id is parsed before any is closed, so a typo closes nothing.
+    pub async fn close_connections(
+        &self,
+        req: &ConnectionCloseReq,
+    ) -> Result<ConnectionCloseData, OpError> {
+        let closed = match (req.ids.is_empty(), &req.filter) {
+            (true, Some(filter)) => {
+                self.inner
+                    .manager
+                    .close_matching_connections(&map_connection_filter(filter))
+                    .await?
+            }
+            (false, None) => {
+                let ids = req
+                    .ids
+                    .iter()
+                    .map(|id| {
+                        Uuid::parse_str(id)
+                            .map_err(|_| OpError::plain(format!("not a connection id: {id}")))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.inner.manager.close_connections(&ids).await?;
+                ids
+            }
+            (false, Some(_)) => {
+                return Err(OpError::plain("ids and filter are exclusive; set only one"));
+            }
+            (true, None) => return Err(OpError::plain("one of ids and filter is required")),
+        };
+        Ok(ConnectionCloseData {
+            closed: closed.iter().map(ToString::to_string).collect(),
+        })
+    }
+
+    pub async fn close_all_connections(&self) -> Result<(), OpError> {
+        self.inner.manager.close_all_connections().await?;
+        Ok(())
+    }
+
     /// Publish the wire-type echo the bridge projects into status snapshots.
     ///
     /// `Some` commits a new type, `None` republishes the current one; either way
@@ -380,17 +862,180 @@ impl CoreManagerService {
         });
     }
 
+    /// Refuses a core binary the pinned manifest does not vouch for, and
+    /// points `spec` at the service-owned copy that was verified.
+    ///
+    /// Runs before everything that spawns a binary the request named or the
+    /// lookup found: start, apply and check. The plan and diff previews use
+    /// [`Self::preview_binary`] instead. Every later launch of the spec, a `restart`,
+    /// a rollback to it or a supervisor respawn, runs the same copy, which no
+    /// one but the service can replace; [`Self::reverify_binary`] re-checks
+    /// its pin before the first two. With no manifest the spec is left alone.
+    async fn verify_binary(
+        &self,
+        infos: &RuntimeInfos,
+        spec: &mut InstanceSpec,
+    ) -> Result<(), OpError> {
+        let config_dir = infos.service_config_dir.clone();
+        let copies_dir = infos.service_data_dir.join(PINNED_CORES_DIR_NAME);
+        let binary = spec.core.binary_path.clone();
+        let in_use = self.binaries_in_use().await;
+        let verified = tokio::task::spawn_blocking(move || {
+            let verified = verify_pinned(&config_dir, &copies_dir, binary.as_std_path())?;
+            if verified.is_some() {
+                prune_pinned_copies(&config_dir, &copies_dir, &in_use);
+            }
+            Ok::<_, OpError>(verified)
+        })
+        .await
+        .map_err(|error| {
+            OpError::plain(format!("core binary verification did not finish: {error}"))
+        })??;
+        let Some(copy) = verified else {
+            return Ok(());
+        };
+        let path = Utf8PathBuf::from_path_buf(copy.path.clone()).map_err(|path| {
+            OpError::plain(format!("core binary copy is not UTF-8: {}", path.display()))
+        })?;
+        self.inner
+            .verified_binaries
+            .lock()
+            .insert(path.clone(), copy);
+        spec.core.binary_path = path;
+        Ok(())
+    }
+
+    /// [`Self::verify_binary`] for the plan and diff previews: refuses the
+    /// same binaries, but leaves no service-owned copy behind and records
+    /// nothing. `spec` names the copy a real apply would reuse when one
+    /// exists, so the plan compares the same path the apply would.
+    ///
+    /// The caller holds the control lock, which is what lets every preview
+    /// share one scratch path, and must keep the result alive until the
+    /// manager is done probing the binary.
+    async fn preview_binary(
+        &self,
+        infos: &RuntimeInfos,
+        spec: &mut InstanceSpec,
+    ) -> Result<Option<PreviewBinary>, OpError> {
+        let config_dir = infos.service_config_dir.clone();
+        let copies_dir = infos.service_data_dir.join(PINNED_CORES_DIR_NAME);
+        let binary = spec.core.binary_path.clone();
+        let preview = tokio::task::spawn_blocking(move || {
+            preview_pinned(&config_dir, &copies_dir, binary.as_std_path())
+        })
+        .await
+        .map_err(|error| {
+            OpError::plain(format!("core binary verification did not finish: {error}"))
+        })??;
+        if let Some(preview) = &preview {
+            spec.core.binary_path =
+                Utf8PathBuf::from_path_buf(preview.path().to_owned()).map_err(|path| {
+                    OpError::plain(format!("core binary copy is not UTF-8: {}", path.display()))
+                })?;
+        }
+        Ok(preview)
+    }
+
+    /// Refuses to relaunch `binary` unless the manifest in force now still
+    /// pins the build it is a copy of, at the digest it was copied with.
+    ///
+    /// `pin-core` may have moved or added a pin since the spec first ran. The
+    /// copy itself is not hashed again: it sits where only the service writes.
+    /// A binary that ran before any manifest existed was never copied, and is
+    /// refused until a start or apply verifies it.
+    async fn reverify_binary(
+        &self,
+        infos: &RuntimeInfos,
+        binary: &Utf8Path,
+    ) -> Result<(), OpError> {
+        let manifest = CoreManifest::load(&infos.service_config_dir).map_err(|error| {
+            OpError::plain(format!("failed to read the core manifest: {error}"))
+        })?;
+        let copy = self.inner.verified_binaries.lock().get(binary).cloned();
+        reverify_pinned(manifest.as_ref(), binary.as_std_path(), copy.as_ref())
+    }
+
+    /// The binaries a relaunch may still need: the running core's, which its
+    /// supervisor respawns, and those of every revision a rollback can reach.
+    async fn binaries_in_use(&self) -> Vec<PathBuf> {
+        let running = self
+            .inner
+            .manager
+            .status()
+            .spec
+            .map(|spec| spec.binary_path);
+        self.inner
+            .manager
+            .revisions()
+            .await
+            .into_iter()
+            .map(|record| record.binary_path)
+            .chain(running)
+            .map(Utf8PathBuf::into_std_path_buf)
+            .collect()
+    }
+
+    /// The instance options an apply or its preview launches with: `policy`
+    /// laid over the service's own, or without one the running epoch's, so
+    /// leaving `policy` out is never read as a change of process spec. That
+    /// also carries a start's overrides across applies.
+    fn launch_options(
+        &self,
+        spec: &mut InstanceSpec,
+        policy: Option<&CorePolicyOverrides>,
+    ) -> Result<(), OpError> {
+        if let Some(policy) = policy {
+            return override_options(&mut spec.options, policy, self.inner.startup_timeout_limit);
+        }
+        let status = self.inner.manager.status();
+        if !matches!(status.state, ManagerCoreState::Stopped { .. })
+            && let Some(running) = status.spec
+        {
+            spec.options = running.options;
+        }
+        Ok(())
+    }
+
+    /// The binary the running core was launched from, when it is
+    /// `core_type`'s executable. A service-owned copy is traced back to the
+    /// binary it was verified from, which is what the manifest pins and what
+    /// [`Self::verify_binary`] has to be handed again.
+    fn running_binary(&self, core_type: &CoreType) -> Option<PathBuf> {
+        let status = self.inner.manager.status();
+        if matches!(status.state, ManagerCoreState::Stopped { .. }) {
+            return None;
+        }
+        let running = status.spec?.binary_path;
+        let origin = self
+            .inner
+            .verified_binaries
+            .lock()
+            .get(&running)
+            .map(|copy| copy.origin.clone())
+            .unwrap_or_else(|| running.into_std_path_buf());
+        let executable = core_type.get_executable_name();
+        (origin.file_name().and_then(|name| name.to_str()) == Some(&*executable)).then_some(origin)
+    }
+
     /// The manager-facing spec for a wire request.
     ///
+    /// A request that names no binary keeps the running core's when it runs
+    /// `core_type`'s executable, so an apply or its preview never switches
+    /// binaries behind the caller's back; only with nothing such running is
+    /// the search consulted.
+    ///
     /// Returns `OpError` rather than `anyhow::Error` because this is where the
-    /// binary lookup fails, and `find_binary_path`'s only possible error is a
-    /// missing binary (`:601-616`) — a fact worth classifying. The other two
+    /// binary lookup fails, and both of its failures are facts worth
+    /// classifying: `find_binary_path` can only miss, and an explicit
+    /// `binary_path` can also be refused by the allowlist. The other two
     /// failures here (a non-UTF-8 directory, an unsupported core kind) stay
     /// unclassified on purpose.
     fn instance_spec(
         &self,
         infos: &RuntimeInfos,
         core_type: &CoreType,
+        binary_path: Option<&Path>,
         config_path: Utf8PathBuf,
     ) -> Result<InstanceSpec, OpError> {
         let working_dir =
@@ -400,26 +1045,31 @@ impl CoreManagerService {
                     path.display()
                 ))
             })?;
-        let binary_path = find_binary_path(infos, core_type)
-            .map_err(|error| OpError::with_kind(error_kind::BINARY_NOT_FOUND, error.to_string()))
-            .and_then(|path| {
-                Utf8PathBuf::from_path_buf(path).map_err(|path| {
-                    OpError::plain(format!("core binary path is not UTF-8: {}", path.display()))
-                })
-            })?;
-        let kind = core_kind(core_type).map_err(|error| OpError::plain(error.to_string()))?;
+        let binary_path = match binary_path {
+            Some(requested) => explicit_binary_path(infos, core_type, requested)?,
+            None => match self.running_binary(core_type) {
+                Some(running) => running,
+                None => find_binary_path(infos, core_type).map_err(|error| {
+                    OpError::with_kind(error_kind::BINARY_NOT_FOUND, error.to_string())
+                })?,
+            },
+        };
+        let binary_path = Utf8PathBuf::from_path_buf(binary_path).map_err(|path| {
+            OpError::plain(format!("core binary path is not UTF-8: {}", path.display()))
+        })?;
         Ok(InstanceSpec {
             core: CoreSpec {
-                kind,
+                kind: core_kind(core_type),
                 binary_path,
                 version: None,
                 features: Vec::new(),
+                distribution: None,
             },
             config_path,
             working_dir,
             // The manager owns the pid record and points it at its runtime dir.
             pid_file: None,
-            options: InstanceOptions::default(),
+            options: self.inner.instance_options.read().clone(),
         })
     }
 }
@@ -505,16 +1155,208 @@ async fn status_bridge(
     }
 }
 
+/// The epoch whose controller is serving, if any. Telemetry is only relayed
+/// from a running core: every other state either has no controller or is about
+/// to replace it.
+fn running_epoch(status: &CoreStatus) -> Option<u64> {
+    match status.state {
+        ManagerCoreState::Running { epoch, .. } => Some(epoch),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TelemetryStream {
+    Traffic,
+    Memory,
+}
+
+impl TelemetryStream {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Traffic => "traffic",
+            Self::Memory => "memory",
+        }
+    }
+}
+
+/// Follow the running core's traffic and memory streams onto the hub's
+/// telemetry ring, across restarts and switches.
+///
+/// The streams are opened per epoch and dropped when the core leaves it. Status
+/// transitions that keep the epoch, such as a health change, leave them alone.
+/// Exits with the manager's watch channel, or when the adapter is gone.
+async fn telemetry_bridge(
+    inner: Weak<Inner>,
+    mut states: watch::Receiver<CoreStatus>,
+    hub: EventHub,
+    interval: Duration,
+) {
+    loop {
+        let Some(epoch) = running_epoch(&states.borrow_and_update()) else {
+            if states.changed().await.is_err() {
+                return;
+            }
+            continue;
+        };
+        // One relay per stream: a core without `/memory` (Premium) still has
+        // its traffic relayed.
+        let relay = async {
+            tokio::select! {
+                () = relay_telemetry(&inner, &hub, interval, TelemetryStream::Traffic) => {}
+                () = relay_telemetry(&inner, &hub, interval, TelemetryStream::Memory) => {}
+            }
+        };
+        tokio::pin!(relay);
+        loop {
+            tokio::select! {
+                () = &mut relay => return,
+                changed = states.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    if running_epoch(&states.borrow_and_update()) != Some(epoch) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The running epoch's `stream`, as the events the hub sends.
+async fn open_telemetry(
+    manager: &Manager,
+    stream: TelemetryStream,
+) -> Result<BoxStream<'static, Result<WsEvent, String>>, ManagerError> {
+    Ok(match stream {
+        TelemetryStream::Traffic => {
+            let (epoch, samples) = manager.traffic().await?;
+            samples
+                .map(move |sample| {
+                    sample
+                        .map(|sample| {
+                            WsEvent::new_traffic(TrafficSample {
+                                epoch,
+                                up: sample.up.get(),
+                                down: sample.down.get(),
+                                up_total: sample.up_total.get(),
+                                down_total: sample.down_total.get(),
+                            })
+                        })
+                        .map_err(|error| error.to_string())
+                })
+                .boxed()
+        }
+        TelemetryStream::Memory => {
+            let (epoch, samples) = manager.memory().await?;
+            samples
+                .map(move |sample| {
+                    sample
+                        .map(|sample| {
+                            WsEvent::new_memory(MemorySample {
+                                epoch,
+                                in_use: sample.in_use,
+                                os_limit: sample.os_limit,
+                            })
+                        })
+                        .map_err(|error| error.to_string())
+                })
+                .boxed()
+        }
+    })
+}
+
+/// Relay one stream of the current epoch for as long as the telemetry ring
+/// has a subscriber, reopening it when it fails. Samples are coalesced to the
+/// newest and sent once per `interval` tick, so a core that samples faster than
+/// the service relays never floods the ring. With nobody subscribed the core
+/// stream is closed until someone is. Returns only once the adapter is gone.
+async fn relay_telemetry(
+    inner: &Weak<Inner>,
+    hub: &EventHub,
+    interval: Duration,
+    stream: TelemetryStream,
+) {
+    let mut retry = TELEMETRY_RETRY_DELAY;
+    loop {
+        hub.telemetry_subscribers().await;
+        let opened = {
+            let Some(inner) = inner.upgrade() else {
+                return;
+            };
+            open_telemetry(&inner.manager, stream).await
+        };
+        let mut samples = match opened {
+            Ok(samples) => samples,
+            Err(error) => {
+                tracing::debug!("core {} stream unavailable: {error}", stream.name());
+                tokio::time::sleep(retry).await;
+                retry = (retry * 2).min(TELEMETRY_RETRY_MAX_DELAY);
+                continue;
+            }
+        };
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut pending = None;
+        let interrupted = loop {
+            tokio::select! {
+                sample = samples.next() => match sample {
+                    Some(Ok(event)) => {
+                        pending = Some(event);
+                        retry = TELEMETRY_RETRY_DELAY;
+                    }
+                    Some(Err(error)) => {
+                        tracing::debug!("core {} stream failed: {error}", stream.name());
+                        break true;
+                    }
+                    None => break true,
+                },
+                _ = ticker.tick() => {
+                    if let Some(event) = pending.take() {
+                        hub.send_telemetry(event);
+                    }
+                    if !hub.has_telemetry_subscribers() {
+                        break false;
+                    }
+                }
+            }
+        };
+        if interrupted {
+            tokio::time::sleep(retry).await;
+            retry = (retry * 2).min(TELEMETRY_RETRY_MAX_DELAY);
+        }
+    }
+}
+
 /// One-way wire → manager mapping; the manager has no alpha variants.
-fn core_kind(core_type: &CoreType) -> Result<CoreKind, anyhow::Error> {
+fn core_kind(core_type: &CoreType) -> CoreKind {
     match core_type {
-        CoreType::Clash(ClashCoreType::Mihomo | ClashCoreType::MihomoAlpha) => Ok(CoreKind::Mihomo),
+        CoreType::Clash(ClashCoreType::Mihomo | ClashCoreType::MihomoAlpha) => {
+            CoreKind::Clash(ClashCoreKind::Mihomo)
+        }
         CoreType::Clash(ClashCoreType::ClashRust | ClashCoreType::ClashRustAlpha) => {
-            Ok(CoreKind::ClashRust)
+            CoreKind::Clash(ClashCoreKind::ClashRust)
         }
-        CoreType::Clash(ClashCoreType::ClashPremium) => Ok(CoreKind::ClashPremium),
-        CoreType::Clash(ClashCoreType::Meow) => Ok(CoreKind::Meow),
-        CoreType::SingBox => anyhow::bail!("sing-box is not a supported core"),
+        CoreType::Clash(ClashCoreType::ClashPremium) => {
+            CoreKind::Clash(ClashCoreKind::ClashPremium)
+        }
+        CoreType::Clash(ClashCoreType::Meow) => CoreKind::Clash(ClashCoreKind::Meow),
+        CoreType::SingBox => CoreKind::SingBox,
+    }
+}
+
+/// The stable wire type for a manager kind; the inverse of [`core_kind`] up
+/// to the alpha builds it cannot tell apart.
+fn wire_core_type(kind: CoreKind) -> CoreType {
+    match kind {
+        CoreKind::Clash(ClashCoreKind::Mihomo) => CoreType::Clash(ClashCoreType::Mihomo),
+        CoreKind::Clash(ClashCoreKind::ClashRust) => CoreType::Clash(ClashCoreType::ClashRust),
+        CoreKind::Clash(ClashCoreKind::ClashPremium) => {
+            CoreType::Clash(ClashCoreType::ClashPremium)
+        }
+        CoreKind::Clash(ClashCoreKind::Meow) => CoreType::Clash(ClashCoreType::Meow),
+        CoreKind::SingBox => CoreType::SingBox,
     }
 }
 
@@ -585,6 +1427,14 @@ fn map_revision(revision: &ConfigRevision) -> ConfigRevisionInfo {
     }
 }
 
+fn map_revision_record(record: &RevisionRecord) -> CoreRevisionInfo {
+    CoreRevisionInfo {
+        revision: map_revision(&record.revision),
+        kind: record.kind,
+        recorded_at: record.recorded_at,
+    }
+}
+
 /// The wire CAS token, as the manager compares it.
 fn map_revision_id(info: &RevisionIdInfo) -> RevisionId {
     RevisionId {
@@ -594,6 +1444,122 @@ fn map_revision_id(info: &RevisionIdInfo) -> RevisionId {
     }
 }
 
+/// The limit is clamped rather than refused: an oversized page is a caller
+/// asking for too much, not a malformed request.
+fn map_log_query(req: &CoreLogsQueryReq) -> LogQuery {
+    LogQuery {
+        since: req.since,
+        until: req.until,
+        min_level: req.level,
+        epoch: req.epoch,
+        stream: req.stream,
+        contains: req.contains.clone(),
+        cursor: req.cursor.map(|cursor| LogCursor {
+            seq: cursor.file,
+            offset: cursor.offset,
+        }),
+        direction: if req.backward {
+            LogDirection::Backward
+        } else {
+            LogDirection::Forward
+        },
+        limit: req
+            .limit
+            .unwrap_or(CORE_LOGS_DEFAULT_LIMIT)
+            .clamp(1, CORE_LOGS_MAX_LIMIT) as usize,
+    }
+}
+
+fn map_log_page(page: LogPage) -> CoreLogsQueryData {
+    CoreLogsQueryData {
+        frames: page.frames,
+        cursor: CoreLogCursor {
+            file: page.next.seq,
+            offset: page.next.offset,
+        },
+        exhausted: page.exhausted,
+        dropped: page.dropped,
+    }
+}
+
+/// Only the group's newest test result is carried; the core keeps a short
+/// history per proxy that no caller has asked for.
+fn map_proxy_group(group: &Proxy) -> ProxyGroupInfo {
+    ProxyGroupInfo {
+        name: group.name.to_string(),
+        r#type: group.proxy_type.clone(),
+        now: group.now.as_ref().map(ToString::to_string),
+        all: group
+            .all
+            .iter()
+            .flatten()
+            .map(ToString::to_string)
+            .collect(),
+        hidden: group.hidden.unwrap_or(false),
+        delay: group.history.last().map(|entry| entry.delay),
+    }
+}
+
+fn map_connection_filter(filter: &ConnectionFilter) -> ManagerConnectionFilter {
+    ManagerConnectionFilter {
+        host: filter.host.clone(),
+        rule: filter.rule.clone(),
+        chain: filter.chain.clone(),
+        process: filter.process.clone(),
+        network: filter.network.map(|network| match network {
+            ConnectionNetwork::Tcp => ManagerConnectionNetwork::Tcp,
+            ConnectionNetwork::Udp => ManagerConnectionNetwork::Udp,
+        }),
+    }
+}
+
+/// The metadata is flattened to what a connection list shows. A connection the
+/// core reported without metadata keeps its id, rule and counters.
+fn map_connection(connection: &Connection) -> ConnectionInfo {
+    let metadata = connection.metadata.as_ref();
+    ConnectionInfo {
+        id: connection.id.to_string(),
+        network: metadata.and_then(|metadata| match metadata.network {
+            ManagerConnectionNetwork::Tcp => Some(ConnectionNetwork::Tcp),
+            ManagerConnectionNetwork::Udp => Some(ConnectionNetwork::Udp),
+            ManagerConnectionNetwork::All | ManagerConnectionNetwork::Invalid => None,
+        }),
+        host: metadata
+            .map(|metadata| {
+                if metadata.host.is_empty() {
+                    metadata.sniff_host.clone()
+                } else {
+                    metadata.host.clone()
+                }
+            })
+            .unwrap_or_default(),
+        destination: metadata
+            .map(|metadata| endpoint(&metadata.destination_ip, metadata.destination_port))
+            .unwrap_or_default(),
+        source: metadata
+            .map(|metadata| endpoint(&metadata.source_ip, metadata.source_port))
+            .unwrap_or_default(),
+        process: metadata
+            .map(|metadata| metadata.process.clone())
+            .unwrap_or_default(),
+        rule: connection.rule.clone(),
+        rule_payload: connection.rule_payload.clone(),
+        chains: connection.chains.clone(),
+        upload: connection.upload,
+        download: connection.download,
+        start: connection.start.timestamp_millis(),
+    }
+}
+
+/// `ip:port`, bracketing an IPv6 address so the port stays unambiguous.
+fn endpoint(ip: &str, port: u16) -> String {
+    if ip.contains(':') {
+        format!("[{ip}]:{port}")
+    } else {
+        format!("{ip}:{port}")
+    }
+}
+
 /// Project an apply result onto the wire.
 ///
 /// `DurabilityUncertain` is a wrapper, not an outcome, and the apply path can
@@ -609,57 +1575,184 @@ fn map_apply_outcome(outcome: &ApplyOutcome) -> CoreApplyData {
         warnings.push(warning.clone());
         current = &**outcome;
     }
-    let (kind, revision, failed_apply) = match current {
-        ApplyOutcome::Noop { revision } => (ApplyOutcomeKind::Noop, revision, None),
-        ApplyOutcome::Patched { revision } => (ApplyOutcomeKind::Patched, revision, None),
-        ApplyOutcome::Reloaded { revision } => (ApplyOutcomeKind::Reloaded, revision, None),
-        ApplyOutcome::Restarted { revision } => (ApplyOutcomeKind::Restarted, revision, None),
+    let mut data = match current {
+        ApplyOutcome::Noop { revision } => outcome_data(ApplyOutcomeKind::Noop, revision),
+        ApplyOutcome::Patched { revision } => outcome_data(ApplyOutcomeKind::Patched, revision),
+        ApplyOutcome::Reloaded { revision } => outcome_data(ApplyOutcomeKind::Reloaded, revision),
+        ApplyOutcome::Restarted {
+            revision,
+            selections,
+        } => CoreApplyData {
+            selections: map_selections(selections),
+            ..outcome_data(ApplyOutcomeKind::Restarted, revision)
+        },
         // Live since S10: the manager reports the core-switch path separately
         // from a same-epoch restart, so the wire value S8 declared and never
         // sent is finally produced here.
-        ApplyOutcome::Switched { revision } => (ApplyOutcomeKind::Switched, revision, None),
+        ApplyOutcome::Switched {
+            revision,
+            selections,
+            degraded,
+        } => CoreApplyData {
+            selections: map_selections(selections),
+            degrade_reason: degraded.map(map_degrade_reason),
+            ..outcome_data(ApplyOutcomeKind::Switched, revision)
+        },
         ApplyOutcome::RolledBack {
             revision,
             failed_apply,
-        } => (
-            ApplyOutcomeKind::RolledBack,
-            revision,
-            Some(failed_apply.clone()),
-        ),
+            selections,
+        } => CoreApplyData {
+            failed_apply: Some(failed_apply.clone()),
+            selections: map_selections(selections),
+            ..outcome_data(ApplyOutcomeKind::RolledBack, revision)
+        },
         ApplyOutcome::DurabilityUncertain { .. } => {
             unreachable!("unwrapped by the loop above")
         }
     };
+    data.warning = (!warnings.is_empty()).then(|| warnings.join("; "));
+    data
+}
+
+/// A restart's [`SwitchOutcome`], unwrapped the way [`map_apply_outcome`]
+/// unwraps `DurabilityUncertain`. Exhaustive for the same reason.
+fn map_switch_outcome(outcome: &SwitchOutcome) -> CoreRestartData {
+    let mut warnings = Vec::new();
+    let mut current = outcome;
+    while let SwitchOutcome::DurabilityUncertain { outcome, warning } = current {
+        warnings.push(warning.clone());
+        current = &**outcome;
+    }
+    let mut data = match current {
+        SwitchOutcome::Graceful { selections } => CoreRestartData {
+            selections: map_selections(selections),
+            ..Default::default()
+        },
+        SwitchOutcome::Hard { reason, selections } => CoreRestartData {
+            selections: map_selections(selections),
+            degrade_reason: Some(map_degrade_reason(*reason)),
+            ..Default::default()
+        },
+        SwitchOutcome::DurabilityUncertain { .. } => {
+            unreachable!("unwrapped by the loop above")
+        }
+    };
+    data.warning = (!warnings.is_empty()).then(|| warnings.join("; "));
+    data
+}
+
+fn outcome_data(outcome: ApplyOutcomeKind, revision: &ConfigRevision) -> CoreApplyData {
     CoreApplyData {
-        outcome: kind,
+        outcome,
         revision: map_revision(revision),
-        warning: (!warnings.is_empty()).then(|| warnings.join("; ")),
-        failed_apply,
+        warning: None,
+        failed_apply: None,
+        selections: None,
+        degrade_reason: None,
     }
 }
 
-/// The wire classification for a manager error, or `None` when this change does
-/// not classify it yet (report P3 maps the rest).
+fn map_apply_plan(plan: ApplyPlan) -> CoreApplyPlanData {
+    let (outcome, degrade_reason) = match plan.route {
+        PlannedRoute::Noop => (ApplyOutcomeKind::Noop, None),
+        PlannedRoute::Patch => (ApplyOutcomeKind::Patched, None),
+        PlannedRoute::Reload => (ApplyOutcomeKind::Reloaded, None),
+        PlannedRoute::Switch { degraded } => {
+            (ApplyOutcomeKind::Switched, degraded.map(map_degrade_reason))
+        }
+    };
+    CoreApplyPlanData {
+        outcome,
+        degrade_reason,
+        changed: plan.changed,
+    }
+}
+
+fn map_config_diff_entry(entry: ConfigDiffEntry) -> ConfigDiffEntryInfo {
+    ConfigDiffEntryInfo {
+        path: entry.path,
+        kind: match entry.kind {
+            ManagerDiffKind::Added => ConfigDiffKind::Added,
+            ManagerDiffKind::Removed => ConfigDiffKind::Removed,
+            ManagerDiffKind::Changed => ConfigDiffKind::Changed,
+        },
+        old: entry.old,
+        new: entry.new,
+    }
+}
+
+/// Exhaustive on purpose, like [`map_apply_outcome`]: a new manager reason
+/// must not reach the wire unnamed.
+fn map_degrade_reason(reason: DegradeReason) -> SwitchDegradeReason {
+    match reason {
+        DegradeReason::NotRunning => SwitchDegradeReason::NotRunning,
+        DegradeReason::UnsupportedKind => SwitchDegradeReason::UnsupportedKind,
+        DegradeReason::DnsListen => SwitchDegradeReason::DnsListen,
+        DegradeReason::InboundConflict => SwitchDegradeReason::InboundConflict,
+        DegradeReason::PatchFailed => SwitchDegradeReason::PatchFailed,
+        DegradeReason::HttpController => SwitchDegradeReason::HttpController,
+    }
+}
+
+/// `None` when the replaced process had no selector choice to carry, so an
+/// apply over a config without selectors keeps its pre-existing wire shape.
+fn map_selections(selections: &RestoredSelections) -> Option<RestoredSelectionsInfo> {
+    let map = |list: &[ProxySelection]| {
+        list.iter()
+            .map(|selection| ProxySelectionInfo {
+                group: selection.group.to_string(),
+                proxy: selection.proxy.to_string(),
+            })
+            .collect::<Vec<_>>()
+    };
+    if selections.restored.is_empty() && selections.skipped.is_empty() {
+        return None;
+    }
+    Some(RestoredSelectionsInfo {
+        restored: map(&selections.restored),
+        skipped: map(&selections.skipped),
+    })
+}
+
+/// The wire `error_kind` for a manager error: one kind per variant, and
+/// `DurabilityUncertain` reports the kind of the failure it wraps.
 ///
-/// `Error` is `#[non_exhaustive]`, so the wildcard arm is mandatory; it must
-/// stay "no kind", never a guess.
+/// `None` only for a variant added to the manager after this table. `Error` is
+/// `#[non_exhaustive]`, so the wildcard arm is mandatory; it must stay
+/// "no kind", never a guess.
 fn map_error_kind(error: &ManagerError) -> Option<&'static str> {
     match error {
         ManagerError::NotStarted => Some(error_kind::NOT_STARTED),
         ManagerError::AlreadyRunning => Some(error_kind::ALREADY_RUNNING),
         ManagerError::RevisionConflict { .. } => Some(error_kind::REVISION_CONFLICT),
+        ManagerError::RevisionNotFound(_) => Some(error_kind::REVISION_NOT_FOUND),
         ManagerError::ManagerQuarantined { .. } => Some(error_kind::QUARANTINED),
         ManagerError::ConfigCheckFailed(_) => Some(error_kind::CONFIG_CHECK_FAILED),
         ManagerError::ConfigNotFound(_) => Some(error_kind::CONFIG_NOT_FOUND),
         ManagerError::BinaryNotFound(_) => Some(error_kind::BINARY_NOT_FOUND),
+        ManagerError::CoreVersionProbeFailed { .. } => Some(error_kind::VERSION_PROBE_FAILED),
+        ManagerError::RequiredLocalIpcUnsupported { .. } => Some(error_kind::LOCAL_IPC_UNSUPPORTED),
         ManagerError::InvalidConfig(_) | ManagerError::Yaml(_) => Some(error_kind::INVALID_CONFIG),
         ManagerError::ControllerMissing => Some(error_kind::CONTROLLER_MISSING),
+        ManagerError::InvalidManagerOptions(_) => Some(error_kind::INVALID_MANAGER_OPTIONS),
+        ManagerError::InvalidHealthPolicy(_) => Some(error_kind::INVALID_POLICY),
+        ManagerError::InvalidFilter(_) => Some(error_kind::INVALID_FILTER),
+        ManagerError::UnsafeRuntimeArtifact(_) => Some(error_kind::UNSAFE_RUNTIME_ARTIFACT),
+        ManagerError::RuntimeDirectoryOwned(_) => Some(error_kind::RUNTIME_DIR_OWNED),
         ManagerError::ApplyFailed(_) => Some(error_kind::APPLY_FAILED),
         ManagerError::ApplyRollbackFailed { .. } => Some(error_kind::APPLY_ROLLBACK_FAILED),
         ManagerError::StopUnconfirmed(_) => Some(error_kind::STOP_UNCONFIRMED),
+        ManagerError::StartupFailed { .. } => Some(error_kind::STARTUP_FAILED),
+        ManagerError::StartupTimeout { .. } => Some(error_kind::STARTUP_TIMEOUT),
+        ManagerError::Process(_) => Some(error_kind::PROCESS_FAILED),
+        ManagerError::Api(_) => Some(error_kind::CONTROLLER_REQUEST_FAILED),
+        ManagerError::Io(_) => Some(error_kind::RUNTIME_IO),
         // The durability wrapper is a warning around a real failure; report the
         // failure's kind so a caller can still branch on it.
         ManagerError::DurabilityUncertain { source, .. } => map_error_kind(source),
+        // The manager's error is non-exhaustive. A variant added after this
+        // table goes unclassified until it is given a kind here.
         _ => None,
     }
 }
@@ -718,7 +1811,147 @@ fn project_core_infos(status: &CoreStatus, requested_core: Option<CoreType>) ->
         health: status.health.as_ref().map(map_health),
         revision: status.revision.as_ref().map(map_revision),
         detail: map_state_detail(&status.state),
+        build: map_build(status),
+        policy: map_policy(status),
+    }
+}
+
+/// The running build's identity. The summary outlives a stop for the
+/// terminal frame, so it is gated on the state rather than its presence.
+fn map_build(status: &CoreStatus) -> Option<CoreBuildInfo> {
+    if matches!(status.state, ManagerCoreState::Stopped { .. }) {
+        return None;
+    }
+    let spec = status.spec.as_ref()?;
+    Some(CoreBuildInfo {
+        version: spec.version.clone(),
+        distribution: spec.distribution.clone(),
+        capabilities: spec.capabilities.clone(),
+        runtime_features: spec
+            .runtime_features
+            .iter()
+            .map(|feature| match feature {
+                RuntimeFeature::LocalIpc => CoreRuntimeFeature::LocalIpc,
+            })
+            .collect(),
+    })
+}
+
+/// The running epoch's launch policy, gated on the state like [`map_build`].
+fn map_policy(status: &CoreStatus) -> Option<CorePolicyInfo> {
+    if matches!(status.state, ManagerCoreState::Stopped { .. }) {
+        return None;
+    }
+    let options = &status.spec.as_ref()?.options;
+    let health = &options.health;
+    Some(CorePolicyInfo {
+        startup_timeout_ms: duration_ms(options.startup_timeout),
+        health: HealthConfig {
+            interval_ms: duration_ms(health.interval()),
+            timeout_ms: duration_ms(health.timeout()),
+            failure_threshold: health.failure_threshold().get(),
+            success_threshold: health.success_threshold().get(),
+            start_period_ms: duration_ms(health.start_period()),
+        },
+        max_restarts: match options.restart_policy {
+            RestartPolicy::OnFailure { max_restarts } => Some(max_restarts),
+            _ => None,
+        },
+    })
+}
+
+fn duration_ms(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Lays a request's `policy` over the service's instance options. The bounds
+/// are checked here; `HealthPolicy::new` has the last word on the result.
+/// `startup_timeout_limit` tightens the wire bound on `startup_timeout_ms` to
+/// what this service's request timeout leaves room for.
+fn override_options(
+    options: &mut InstanceOptions,
+    overrides: &CorePolicyOverrides,
+    startup_timeout_limit: Duration,
+) -> Result<(), OpError> {
+    fn bounded<T: PartialOrd + Display>(
+        key: &str,
+        value: Option<T>,
+        range: RangeInclusive<T>,
+    ) -> Result<Option<T>, OpError> {
+        match value {
+            Some(value) if !range.contains(&value) => Err(OpError::with_kind(
+                error_kind::INVALID_POLICY,
+                format!(
+                    "policy.{key} must be between {} and {}, got {value}",
+                    range.start(),
+                    range.end()
+                ),
+            )),
+            value => Ok(value),
+        }
     }
+    let startup_timeout_ms = bounded(
+        "startup_timeout_ms",
+        overrides.startup_timeout_ms,
+        CorePolicyOverrides::STARTUP_TIMEOUT_MS,
+    )?;
+    if let Some(value) = startup_timeout_ms
+        && Duration::from_millis(value) >= startup_timeout_limit
+    {
+        return Err(OpError::with_kind(
+            error_kind::INVALID_POLICY,
+            format!(
+                "policy.startup_timeout_ms must be under {}, what the service's request timeout leaves after its reconcile and stop budgets, got {value}",
+                duration_ms(startup_timeout_limit)
+            ),
+        ));
+    }
+    let health_interval_ms = bounded(
+        "health_interval_ms",
+        overrides.health_interval_ms,
+        CorePolicyOverrides::HEALTH_INTERVAL_MS,
+    )?;
+    let failure_threshold = bounded(
+        "failure_threshold",
+        overrides.failure_threshold,
+        CorePolicyOverrides::THRESHOLD,
+    )?;
+    let success_threshold = bounded(
+        "success_threshold",
+        overrides.success_threshold,
+        CorePolicyOverrides::THRESHOLD,
+    )?;
+    let start_period_ms = bounded(
+        "start_period_ms",
+        overrides.start_period_ms,
+        CorePolicyOverrides::START_PERIOD_MS,
+    )?;
+    let max_restarts = bounded(
+        "max_restarts",
+        overrides.max_restarts,
+        CorePolicyOverrides::MAX_RESTARTS,
+    )?;
+
+    let health = &options.health;
+    options.health = HealthPolicy::new(
+        health_interval_ms.map_or(health.interval(), Duration::from_millis),
+        health.timeout(),
+        failure_threshold
+            .and_then(NonZeroU32::new)
+            .unwrap_or(health.failure_threshold()),
+        success_threshold
+            .and_then(NonZeroU32::new)
+            .unwrap_or(health.success_threshold()),
+        start_period_ms.map_or(health.start_period(), Duration::from_millis),
+    )
+    .map_err(|error| OpError::with_kind(error_kind::INVALID_POLICY, error.to_string()))?;
+    if let Some(startup_timeout_ms) = startup_timeout_ms {
+        options.startup_timeout = Duration::from_millis(startup_timeout_ms);
+    }
+    if let Some(max_restarts) = max_restarts {
+        options.restart_policy = RestartPolicy::OnFailure { max_restarts };
+    }
+    Ok(())
 }
 
 /// Mirror the core's console output into the service's own tracing stream, for
@@ -744,29 +1977,328 @@ fn forward_log(frame: &LogFrame) {
         LogLevel::Trace => {
             tracing::trace!(target: CORE_LOG_TARGET, ?core_level, ?stream, %kind, epoch, "{raw}")
         }
-        _ => {
-            tracing::debug!(target: CORE_LOG_TARGET, ?core_level, ?stream, %kind, epoch, "{raw}")
+        _ => {
+            tracing::debug!(target: CORE_LOG_TARGET, ?core_level, ?stream, %kind, epoch, "{raw}")
+        }
+    }
+}
+
+/// Search the binary path of the core: Data Dir -> Sidecar Dir -> the
+/// `--core-search-dir`s -> `PATH`, when `--core-search-system-path` is set.
+///
+/// Relative `PATH` entries are skipped: they would resolve against the
+/// service's working directory, which no one chose as a binary source.
+fn find_binary_path(infos: &RuntimeInfos, core_type: &CoreType) -> std::io::Result<PathBuf> {
+    let executable = core_type.get_executable_name();
+    let system_path = infos
+        .core_search_system_path
+        .then(|| std::env::var_os("PATH"))
+        .flatten();
+    let system_dirs = system_path
+        .iter()
+        .flat_map(std::env::split_paths)
+        .filter(|dir| dir.is_absolute());
+    [&infos.nyanpasu_data_dir, &infos.nyanpasu_app_dir]
+        .into_iter()
+        .chain(&infos.core_search_dirs)
+        .cloned()
+        .chain(system_dirs)
+        .map(|dir| dir.join(&executable))
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{executable} not found"),
+            )
+        })
+}
+
+/// The copy of `binary` in `copies_dir` when the manifest pins it, `None` when
+/// there is no manifest, and `binary_untrusted` when there is one and the
+/// binary is not pinned or does not hash to its pin.
+///
+/// The manifest is re-read on every call, so `pin-core` takes effect without
+/// restarting the service. It is keyed by canonical path, the form `pin-core`
+/// records, so a symlink resolves to the file that would actually run.
+///
+/// The original sits where its user can write, so hashing it by path and then
+/// running it by path would run whatever replaced it in between. The binary
+/// is hashed while it is copied instead, from one open file, and the copy is
+/// what runs. Copies are named by digest, under the name the binary was asked
+/// for, so relaunching the same build reuses one.
+fn verify_pinned(
+    service_config_dir: &Path,
+    copies_dir: &Path,
+    binary: &Path,
+) -> Result<Option<PinnedCopy>, OpError> {
+    /// Tells apart the staging files of checks that run at once.
+    static STAGING: AtomicU64 = AtomicU64::new(0);
+
+    let manifest = CoreManifest::load(service_config_dir)
+        .map_err(|error| OpError::plain(format!("failed to read the core manifest: {error}")))?;
+    let Some(manifest) = manifest else {
+        return Ok(None);
+    };
+    let untrusted = |reason: &str| {
+        OpError::with_kind(
+            error_kind::BINARY_UNTRUSTED,
+            format!("core binary {} {reason}", binary.display()),
+        )
+    };
+    let copy_failed = |error: std::io::Error| {
+        OpError::plain(format!(
+            "failed to copy core binary {}: {error}",
+            binary.display()
+        ))
+    };
+    let canonical = dunce::canonicalize(binary).map_err(|error| {
+        OpError::plain(format!(
+            "failed to resolve core binary {}: {error}",
+            binary.display()
+        ))
+    })?;
+    let Some(pinned) = manifest.pinned(&canonical) else {
+        return Err(untrusted("is not in the pinned core manifest"));
+    };
+    let file_name = binary
+        .file_name()
+        .ok_or_else(|| untrusted("has no file name"))?;
+    std::fs::create_dir_all(copies_dir).map_err(copy_failed)?;
+    let staging = copies_dir.join(format!(
+        ".{}-{}.partial",
+        std::process::id(),
+        STAGING.fetch_add(1, Ordering::Relaxed)
+    ));
+    let actual = match sha256_copy(&canonical, &staging) {
+        Ok(actual) => actual,
+        Err(error) => {
+            let _ = std::fs::remove_file(&staging);
+            return Err(copy_failed(error));
+        }
+    };
+    if !actual.eq_ignore_ascii_case(pinned) {
+        let _ = std::fs::remove_file(&staging);
+        return Err(untrusted("does not match its pinned SHA-256"));
+    }
+    let copy_dir = copies_dir.join(&actual);
+    let copy = copy_dir.join(file_name);
+    let placed = std::fs::create_dir_all(&copy_dir).and_then(|()| {
+        if copy.is_file() {
+            // The same build, copied before: identical bytes by its name.
+            std::fs::remove_file(&staging)
+        } else {
+            std::fs::rename(&staging, &copy)
+        }
+    });
+    if let Err(error) = placed {
+        let _ = std::fs::remove_file(&staging);
+        // A concurrent check may have placed the same copy first.
+        if !copy.is_file() {
+            return Err(copy_failed(error));
+        }
+    }
+    Ok(Some(PinnedCopy {
+        path: copy,
+        origin: canonical,
+        sha256: actual,
+    }))
+}
+
+/// [`verify_pinned`] without its service-owned copy: `None` when there is no
+/// manifest, `binary_untrusted` on the same binaries, and otherwise the binary
+/// a preview may probe.
+///
+/// Where the build was copied before, the original is hashed in place and the
+/// existing copy is what runs. Otherwise the original is hashed into a
+/// scratch copy, for the same reason `verify_pinned` never runs it by path.
+fn preview_pinned(
+    service_config_dir: &Path,
+    copies_dir: &Path,
+    binary: &Path,
+) -> Result<Option<PreviewBinary>, OpError> {
+    let manifest = CoreManifest::load(service_config_dir)
+        .map_err(|error| OpError::plain(format!("failed to read the core manifest: {error}")))?;
+    let Some(manifest) = manifest else {
+        return Ok(None);
+    };
+    let untrusted = |reason: &str| {
+        OpError::with_kind(
+            error_kind::BINARY_UNTRUSTED,
+            format!("core binary {} {reason}", binary.display()),
+        )
+    };
+    let read_failed = |error: std::io::Error| {
+        OpError::plain(format!(
+            "failed to read core binary {}: {error}",
+            binary.display()
+        ))
+    };
+    let canonical = dunce::canonicalize(binary).map_err(|error| {
+        OpError::plain(format!(
+            "failed to resolve core binary {}: {error}",
+            binary.display()
+        ))
+    })?;
+    let Some(pinned) = manifest.pinned(&canonical) else {
+        return Err(untrusted("is not in the pinned core manifest"));
+    };
+    let file_name = binary
+        .file_name()
+        .ok_or_else(|| untrusted("has no file name"))?;
+    let copy = copies_dir.join(pinned.to_ascii_lowercase()).join(file_name);
+    let preview = if copy.is_file() {
+        let actual = sha256_file(&canonical).map_err(read_failed)?;
+        if !actual.eq_ignore_ascii_case(pinned) {
+            return Err(untrusted("does not match its pinned SHA-256"));
+        }
+        PreviewBinary::Copy(copy)
+    } else {
+        let scratch_dir = copies_dir.join(PREVIEW_DIR_NAME);
+        let scratch = scratch_dir.join(file_name);
+        // Left behind only if the service died mid-preview.
+        let _ = std::fs::remove_file(&scratch);
+        std::fs::create_dir_all(&scratch_dir).map_err(read_failed)?;
+        let scratch = PreviewBinary::Scratch(scratch);
+        let actual = sha256_copy(&canonical, scratch.path()).map_err(read_failed)?;
+        if !actual.eq_ignore_ascii_case(pinned) {
+            return Err(untrusted("does not match its pinned SHA-256"));
+        }
+        scratch
+    };
+    Ok(Some(preview))
+}
+
+/// [`CoreManagerService::reverify_binary`] once the manifest is read.
+fn reverify_pinned(
+    manifest: Option<&CoreManifest>,
+    binary: &Path,
+    copy: Option<&PinnedCopy>,
+) -> Result<(), OpError> {
+    let Some(manifest) = manifest else {
+        return Ok(());
+    };
+    let untrusted = |reason: &str| {
+        OpError::with_kind(
+            error_kind::BINARY_UNTRUSTED,
+            format!("core binary {} {reason}", binary.display()),
+        )
+    };
+    let Some(copy) = copy else {
+        return Err(untrusted(
+            "was launched before the core manifest pinned it; start the core again",
+        ));
+    };
+    match manifest.pinned(&copy.origin) {
+        Some(pinned) if pinned.eq_ignore_ascii_case(&copy.sha256) => Ok(()),
+        _ => Err(untrusted(
+            "is no longer pinned at the SHA-256 it was verified with; start the core again",
+        )),
+    }
+}
+
+/// Removes the copies of builds the manifest no longer pins, unless `in_use`
+/// still names one. Best effort: a copy left behind costs only disk.
+fn prune_pinned_copies(service_config_dir: &Path, copies_dir: &Path, in_use: &[PathBuf]) {
+    let Ok(Some(manifest)) = CoreManifest::load(service_config_dir) else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(copies_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        let pinned = entry.file_name().to_str().is_some_and(|digest| {
+            manifest
+                .binaries
+                .values()
+                .any(|pin| pin.eq_ignore_ascii_case(digest))
+        });
+        // The preview scratch dir is a preview's own to clear.
+        let scratch = entry.file_name() == PREVIEW_DIR_NAME;
+        if pinned
+            || scratch
+            || !dir.is_dir()
+            || in_use.iter().any(|binary| binary.starts_with(&dir))
+        {
+            continue;
+        }
+        if let Err(error) = std::fs::remove_dir_all(&dir) {
+            tracing::warn!(
+                "failed to remove unpinned core copy {}: {error}",
+                dir.display()
+            );
         }
     }
 }
 
-// TODO: support system path search via a config or flag
-/// Search the binary path of the core: Data Dir -> Sidecar Dir
-fn find_binary_path(infos: &RuntimeInfos, core_type: &CoreType) -> std::io::Result<PathBuf> {
-    let data_dir = &infos.nyanpasu_data_dir;
-    let binary_path = data_dir.join(core_type.get_executable_name());
-    if binary_path.exists() {
-        return Ok(binary_path);
-    }
-    let app_dir = &infos.nyanpasu_app_dir;
-    let binary_path = app_dir.join(core_type.get_executable_name());
-    if binary_path.exists() {
-        return Ok(binary_path);
-    }
-    Err(std::io::Error::new(
-        std::io::ErrorKind::NotFound,
-        format!("{} not found", core_type.get_executable_name()),
-    ))
+/// An explicit `binary_path` from a request, accepted only when it sits
+/// directly in the nyanpasu data or app dir, a `--core-search-dir` or an
+/// `--allowed-core-dir`, and is named as `core_type`'s executable is. The
+/// search dirs are in because the service would run a core from them anyway.
+///
+/// The service runs the binary with its own privileges, so this is the whole
+/// of the check standing between a socket-ACL user and an arbitrary program.
+/// An allowed dir such as `/usr/bin` holds far more than cores, hence the
+/// name: the request can pick which copy of its core runs, not which program.
+/// The parent is canonicalized, not the binary itself: `..` or a symlinked
+/// directory cannot step outside the allowlist, while a binary that is a
+/// symlink, as distro alternatives are, keeps the name it was installed under.
+fn explicit_binary_path(
+    infos: &RuntimeInfos,
+    core_type: &CoreType,
+    requested: &Path,
+) -> Result<PathBuf, OpError> {
+    let not_allowed = || {
+        OpError::with_kind(
+            error_kind::BINARY_NOT_ALLOWED,
+            format!(
+                "core binary {} is not in an allowed directory",
+                requested.display()
+            ),
+        )
+    };
+    let executable = core_type.get_executable_name();
+    let not_found = || {
+        OpError::with_kind(
+            error_kind::BINARY_NOT_FOUND,
+            format!("core binary {} not found", requested.display()),
+        )
+    };
+    let (Some(parent), Some(file_name)) = (requested.parent(), requested.file_name()) else {
+        return Err(not_allowed());
+    };
+    if !requested.is_absolute() {
+        return Err(not_allowed());
+    }
+    if file_name.to_str() != Some(&*executable) {
+        return Err(OpError::with_kind(
+            error_kind::BINARY_NOT_ALLOWED,
+            format!(
+                "core binary {} is not named {executable}",
+                requested.display()
+            ),
+        ));
+    }
+    let parent = dunce::canonicalize(parent).map_err(|error| match error.kind() {
+        std::io::ErrorKind::NotFound => not_found(),
+        _ => OpError::plain(format!(
+            "failed to resolve core binary {}: {error}",
+            requested.display()
+        )),
+    })?;
+    let allowed = [&infos.nyanpasu_data_dir, &infos.nyanpasu_app_dir]
+        .into_iter()
+        .chain(&infos.core_search_dirs)
+        .chain(&infos.allowed_core_dirs)
+        .any(|dir| *dir == parent);
+    if !allowed {
+        return Err(not_allowed());
+    }
+    let binary = parent.join(file_name);
+    if !binary.is_file() {
+        return Err(not_found());
+    }
+    Ok(binary)
 }
 
 /// The absolute, non-verbatim, UTF-8 path the manager is handed for a config.
@@ -799,9 +2331,7 @@ async fn canonical_config_path(config_file: &Path) -> Result<Utf8PathBuf, OpErro
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
-
-    use nyanpasu_core_manager::StopReason;
+    use nyanpasu_core_manager::{Feature, SpecSummary, StopReason};
     use nyanpasu_ipc::api::ws::events::Event as TestEvent;
     use tokio::sync::watch;
 
@@ -909,20 +2439,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_config_input_takes_exactly_one_source() {
+        let file = Path::new("/etc/nyanpasu/config.yaml");
+        assert!(matches!(
+            ConfigInput::from_request(Some(file), None),
+            Ok(ConfigInput::File(path)) if path == file
+        ));
+        assert!(matches!(
+            ConfigInput::from_request(None, Some("mode: rule\n")),
+            Ok(ConfigInput::Inline("mode: rule\n"))
+        ));
+        for (config_file, config) in [(Some(file), Some("mode: rule\n")), (None, None)] {
+            let error = ConfigInput::from_request(config_file, config).unwrap_err();
+            assert_eq!(error.kind, None);
+        }
+        let oversized = "#".repeat(CORE_INLINE_CONFIG_MAX_BYTES + 1);
+        let error = ConfigInput::from_request(None, Some(&oversized)).unwrap_err();
+        assert_eq!(error.kind, Some(error_kind::INVALID_CONFIG));
+    }
+
     #[test]
     fn core_types_map_onto_manager_kinds() {
         let cases = [
-            (ClashCoreType::Mihomo, CoreKind::Mihomo),
-            (ClashCoreType::MihomoAlpha, CoreKind::Mihomo),
-            (ClashCoreType::ClashRust, CoreKind::ClashRust),
-            (ClashCoreType::ClashRustAlpha, CoreKind::ClashRust),
-            (ClashCoreType::ClashPremium, CoreKind::ClashPremium),
-            (ClashCoreType::Meow, CoreKind::Meow),
+            (
+                ClashCoreType::Mihomo,
+                CoreKind::Clash(ClashCoreKind::Mihomo),
+            ),
+            (
+                ClashCoreType::MihomoAlpha,
+                CoreKind::Clash(ClashCoreKind::Mihomo),
+            ),
+            (
+                ClashCoreType::ClashRust,
+                CoreKind::Clash(ClashCoreKind::ClashRust),
+            ),
+            (
+                ClashCoreType::ClashRustAlpha,
+                CoreKind::Clash(ClashCoreKind::ClashRust),
+            ),
+            (
+                ClashCoreType::ClashPremium,
+                CoreKind::Clash(ClashCoreKind::ClashPremium),
+            ),
+            (ClashCoreType::Meow, CoreKind::Clash(ClashCoreKind::Meow)),
         ];
         for (core_type, expected) in cases {
-            assert_eq!(core_kind(&CoreType::Clash(core_type)).unwrap(), expected);
+            assert_eq!(core_kind(&CoreType::Clash(core_type)), expected);
+            // The way back lands on a type of the same kind.
+            assert_eq!(core_kind(&wire_core_type(expected)), expected);
         }
-        assert!(core_kind(&CoreType::SingBox).is_err());
+        assert_eq!(core_kind(&CoreType::SingBox), CoreKind::SingBox);
+        assert_eq!(wire_core_type(CoreKind::SingBox), CoreType::SingBox);
     }
 
     #[test]
@@ -1166,12 +2734,15 @@ mod tests {
             (
                 ApplyOutcome::Restarted {
                     revision: revision(10),
+                    selections: RestoredSelections::default(),
                 },
                 ApplyOutcomeKind::Restarted,
             ),
             (
                 ApplyOutcome::Switched {
                     revision: revision(11),
+                    selections: RestoredSelections::default(),
+                    degraded: None,
                 },
                 ApplyOutcomeKind::Switched,
             ),
@@ -1182,9 +2753,115 @@ mod tests {
             assert_eq!(data.revision.effective_hash, "fedcba9876543210");
             assert!(data.warning.is_none());
             assert!(data.failed_apply.is_none());
+            assert!(data.selections.is_none());
+            assert!(data.degrade_reason.is_none());
+        }
+    }
+
+    #[test]
+    fn an_apply_plan_maps_onto_the_outcome_it_predicts() {
+        let cases = [
+            (PlannedRoute::Noop, ApplyOutcomeKind::Noop, None),
+            (PlannedRoute::Patch, ApplyOutcomeKind::Patched, None),
+            (PlannedRoute::Reload, ApplyOutcomeKind::Reloaded, None),
+            (
+                PlannedRoute::Switch { degraded: None },
+                ApplyOutcomeKind::Switched,
+                None,
+            ),
+            (
+                PlannedRoute::Switch {
+                    degraded: Some(DegradeReason::DnsListen),
+                },
+                ApplyOutcomeKind::Switched,
+                Some(SwitchDegradeReason::DnsListen),
+            ),
+        ];
+        for (route, outcome, degrade_reason) in cases {
+            let data = map_apply_plan(ApplyPlan {
+                route,
+                changed: vec!["dns.listen".to_owned()],
+            });
+            assert_eq!(
+                data,
+                CoreApplyPlanData {
+                    outcome,
+                    degrade_reason,
+                    changed: vec!["dns.listen".to_owned()],
+                }
+            );
         }
     }
 
+    #[test]
+    fn a_switch_reports_its_selections_and_why_it_degraded() {
+        let selection = |group: &str, proxy: &str| ProxySelection {
+            group: group.into(),
+            proxy: proxy.into(),
+        };
+        let data = map_apply_outcome(&ApplyOutcome::Switched {
+            revision: revision(11),
+            selections: RestoredSelections {
+                restored: vec![selection("PROXY", "jp-01")],
+                skipped: vec![selection("Streaming", "us-02")],
+            },
+            degraded: Some(DegradeReason::HttpController),
+        });
+        assert_eq!(
+            data.degrade_reason,
+            Some(SwitchDegradeReason::HttpController)
+        );
+        assert_eq!(
+            data.selections,
+            Some(RestoredSelectionsInfo {
+                restored: vec![ProxySelectionInfo {
+                    group: "PROXY".to_owned(),
+                    proxy: "jp-01".to_owned(),
+                }],
+                skipped: vec![ProxySelectionInfo {
+                    group: "Streaming".to_owned(),
+                    proxy: "us-02".to_owned(),
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn a_restart_reports_its_selections_and_keeps_every_warning() {
+        let data = map_switch_outcome(&SwitchOutcome::DurabilityUncertain {
+            outcome: Box::new(SwitchOutcome::Hard {
+                reason: DegradeReason::HttpController,
+                selections: RestoredSelections {
+                    restored: vec![ProxySelection {
+                        group: "PROXY".into(),
+                        proxy: "jp-01".into(),
+                    }],
+                    skipped: Vec::new(),
+                },
+            }),
+            warning: "directory sync unconfirmed".to_owned(),
+        });
+        assert_eq!(
+            data,
+            CoreRestartData {
+                selections: Some(RestoredSelectionsInfo {
+                    restored: vec![ProxySelectionInfo {
+                        group: "PROXY".to_owned(),
+                        proxy: "jp-01".to_owned(),
+                    }],
+                    skipped: Vec::new(),
+                }),
+                degrade_reason: Some(SwitchDegradeReason::HttpController),
+                warning: Some("directory sync unconfirmed".to_owned()),
+            }
+        );
+
+        let graceful = map_switch_outcome(&SwitchOutcome::Graceful {
+            selections: RestoredSelections::default(),
+        });
+        assert_eq!(graceful, CoreRestartData::default());
+    }
+
     /// The report's sharpest requirement: a rollback must not be
     /// indistinguishable from a success. The core is running the OLD config, so
     /// the reported revision is the old one and the rejection reason comes with
@@ -1194,6 +2871,7 @@ mod tests {
         let data = map_apply_outcome(&ApplyOutcome::RolledBack {
             revision: revision(7),
             failed_apply: "core failed to start".to_owned(),
+            selections: Default::default(),
         });
         assert_eq!(data.outcome, ApplyOutcomeKind::RolledBack);
         assert_eq!(data.revision.generation, 7);
@@ -1220,6 +2898,7 @@ mod tests {
                 outcome: Box::new(ApplyOutcome::RolledBack {
                     revision: revision(7),
                     failed_apply: "boom".to_owned(),
+                    selections: Default::default(),
                 }),
                 warning: "restore sync failed".to_owned(),
             }),
@@ -1236,7 +2915,7 @@ mod tests {
 
     #[test]
     fn manager_errors_map_onto_the_wire_error_kinds() {
-        let cases: [(ManagerError, Option<&str>); 8] = [
+        let cases: [(ManagerError, Option<&str>); 22] = [
             (ManagerError::NotStarted, Some("not_started")),
             (ManagerError::AlreadyRunning, Some("already_running")),
             (
@@ -1270,12 +2949,79 @@ mod tests {
                 },
                 Some("apply_failed"),
             ),
-            // Unmapped until report P3: no kind rather than a guessed one.
+            (
+                ManagerError::RevisionNotFound(RevisionId {
+                    epoch: 1,
+                    generation: 1,
+                    effective_hash: "0123456789abcdef".to_owned(),
+                }),
+                Some("revision_not_found"),
+            ),
+            (
+                ManagerError::ConfigNotFound(Utf8PathBuf::from("/missing.yaml")),
+                Some("config_not_found"),
+            ),
+            (
+                ManagerError::BinaryNotFound(Utf8PathBuf::from("/usr/bin/mihomo")),
+                Some("binary_not_found"),
+            ),
+            (
+                ManagerError::CoreVersionProbeFailed {
+                    binary_path: Utf8PathBuf::from("/usr/bin/mihomo"),
+                    detail: "exit status 1".to_owned(),
+                },
+                Some("version_probe_failed"),
+            ),
+            (
+                ManagerError::RequiredLocalIpcUnsupported {
+                    kind: CoreKind::Clash(ClashCoreKind::ClashPremium),
+                    version: "2023.08.17".to_owned(),
+                },
+                Some("local_ipc_unsupported"),
+            ),
+            (
+                ManagerError::InvalidConfig("not a mapping".to_owned()),
+                Some("invalid_config"),
+            ),
+            (
+                ManagerError::InvalidManagerOptions("no {epoch}".to_owned()),
+                Some("invalid_manager_options"),
+            ),
+            (
+                ManagerError::InvalidHealthPolicy("interval is zero".to_owned()),
+                Some("invalid_policy"),
+            ),
+            (
+                ManagerError::InvalidFilter("empty".to_owned()),
+                Some("invalid_filter"),
+            ),
+            (
+                ManagerError::UnsafeRuntimeArtifact(Utf8PathBuf::from("/run/nyanpasu")),
+                Some("unsafe_runtime_artifact"),
+            ),
+            (
+                ManagerError::RuntimeDirectoryOwned(Utf8PathBuf::from("/run/nyanpasu")),
+                Some("runtime_dir_owned"),
+            ),
+            (
+                ManagerError::StopUnconfirmed("pid 4242".to_owned()),
+                Some("stop_unconfirmed"),
+            ),
             (
                 ManagerError::StartupFailed {
                     stderr_tail: String::new(),
                 },
-                None,
+                Some("startup_failed"),
+            ),
+            (
+                ManagerError::StartupTimeout {
+                    stderr_tail: String::new(),
+                },
+                Some("startup_timeout"),
+            ),
+            (
+                ManagerError::Io(std::io::Error::other("disk full")),
+                Some("runtime_io"),
             ),
         ];
         for (error, expected) in cases {
@@ -1308,6 +3054,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn core_log_queries_cross_the_wire_with_a_bounded_limit() {
+        let query = map_log_query(&CoreLogsQueryReq::default());
+        assert_eq!(query.limit, CORE_LOGS_DEFAULT_LIMIT as usize);
+        assert_eq!(query.direction, LogDirection::Forward);
+        assert_eq!(query.cursor, None);
+
+        let query = map_log_query(&CoreLogsQueryReq {
+            level: Some(LogLevel::Warning),
+            cursor: Some(CoreLogCursor {
+                file: 4,
+                offset: 512,
+            }),
+            limit: Some(u32::MAX),
+            backward: true,
+            ..CoreLogsQueryReq::default()
+        });
+        assert_eq!(query.min_level, Some(LogLevel::Warning));
+        assert_eq!(
+            query.cursor,
+            Some(LogCursor {
+                seq: 4,
+                offset: 512
+            })
+        );
+        assert_eq!(query.limit, CORE_LOGS_MAX_LIMIT as usize);
+        assert_eq!(query.direction, LogDirection::Backward);
+
+        // Zero would be a page that can never make progress.
+        let query = map_log_query(&CoreLogsQueryReq {
+            limit: Some(0),
+            ..CoreLogsQueryReq::default()
+        });
+        assert_eq!(query.limit, 1);
+    }
+
+    #[test]
+    fn proxy_groups_keep_only_what_a_picker_needs() {
+        let group: Proxy = serde_json::from_str(concat!(
+            r#"{"name":"PROXY","type":"Selector","history":["#,
+            r#"{"time":"2024-01-01T00:00:00+08:00","delay":120},"#,
+            r#"{"time":"2024-01-01T00:01:00+08:00","delay":80}],"#,
+            r#""extra":{},"alive":true,"udp":true,"uot":false,"xudp":false,"tfo":false,"#,
+            r#""mptcp":false,"smux":false,"interface":"","routing-mark":0,"#,
+            r#""provider-name":"","dialer-proxy":"","now":"hk-01","all":["hk-01","DIRECT"]}"#
+        ))
+        .expect("mihomo group");
+        assert_eq!(
+            map_proxy_group(&group),
+            ProxyGroupInfo {
+                name: "PROXY".to_owned(),
+                r#type: "Selector".to_owned(),
+                now: Some("hk-01".to_owned()),
+                all: vec!["hk-01".to_owned(), "DIRECT".to_owned()],
+                hidden: false,
+                delay: Some(80),
+            }
+        );
+    }
+
+    #[test]
+    fn connections_keep_only_what_a_list_needs() {
+        let connection: Connection = serde_json::from_str(concat!(
+            r#"{"id":"5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01","metadata":{"network":"udp","#,
+            r#""type":"Tun","sourceIP":"fd00::2","destinationIP":"1.1.1.1","#,
+            r#""sourceGeoIP":null,"destinationGeoIP":null,"sourceIPASN":"","#,
+            r#""destinationIPASN":"","sourcePort":"50000","destinationPort":"443","#,
+            r#""inboundIP":"","inboundPort":"0","inboundName":"tun","inboundUser":"","#,
+            r#""rematchName":"","host":"","dnsMode":"normal","uid":0,"process":"curl","#,
+            r#""processPath":"/usr/bin/curl","specialProxy":"","specialRules":"","#,
+            r#""remoteDestination":"","dscp":0,"sniffHost":"one.one.one.one"},"#,
+            r#""upload":10,"download":20,"start":"2025-07-29T00:16:22.646+08:00","#,
+            r#""chains":["DIRECT"],"providerChains":[],"rule":"Match","rulePayload":""}"#
+        ))
+        .expect("mihomo connection");
+        assert_eq!(
+            map_connection(&connection),
+            ConnectionInfo {
+                id: "5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01".to_owned(),
+                network: Some(ConnectionNetwork::Udp),
+                host: "one.one.one.one".to_owned(),
+                destination: "1.1.1.1:443".to_owned(),
+                source: "[fd00::2]:50000".to_owned(),
+                process: "curl".to_owned(),
+                rule: "Match".to_owned(),
+                rule_payload: String::new(),
+                chains: vec!["DIRECT".to_owned()],
+                upload: 10,
+                download: 20,
+                start: 1_753_719_382_646,
+            }
+        );
+    }
+
     fn status_of(state: ManagerCoreState) -> CoreStatus {
         CoreStatus {
             state,
@@ -1332,13 +3172,24 @@ mod tests {
         }
     }
 
+    /// The service config's default bound.
+    const MAX_CONCURRENT_CHECKS: usize = 2;
+
     async fn test_service() -> (tempfile::TempDir, CoreManagerService) {
         let dir = tempfile::tempdir().expect("tempdir");
         let runtime_dir = Utf8PathBuf::from_path_buf(dir.path().join("core-runtime"))
             .expect("temp path is UTF-8");
-        let service = CoreManagerService::new(runtime_dir, LocalIpcPolicy::Disable)
-            .await
-            .expect("the manager builds on a fresh runtime dir");
+        let service = CoreManagerService::new(
+            ManagerOptions {
+                runtime_dir: Some(runtime_dir),
+                ..ManagerOptions::default()
+            },
+            InstanceOptions::default(),
+            MAX_CONCURRENT_CHECKS,
+            Duration::from_secs(120),
+        )
+        .await
+        .expect("the manager builds on a fresh runtime dir");
         (dir, service)
     }
 
@@ -1349,6 +3200,9 @@ mod tests {
             nyanpasu_config_dir: root.join("nyanpasu-config"),
             nyanpasu_data_dir: root.join("nyanpasu-data"),
             nyanpasu_app_dir: root.join("nyanpasu-app"),
+            core_search_dirs: Vec::new(),
+            core_search_system_path: false,
+            allowed_core_dirs: Vec::new(),
         }
     }
 
@@ -1501,7 +3355,7 @@ mod tests {
             })
             .collect();
         let refused = service
-            .check(&infos, &mihomo(), &missing)
+            .check(&infos, &mihomo(), ConfigInput::File(&missing), None)
             .await
             .expect_err("the overflow must be refused");
         assert!(
@@ -1513,7 +3367,7 @@ mod tests {
 
         drop(held);
         let resolved = service
-            .check(&infos, &mihomo(), &missing)
+            .check(&infos, &mihomo(), ConfigInput::File(&missing), None)
             .await
             .expect_err("the path does not exist");
         assert_eq!(resolved.kind, Some(error_kind::CONFIG_NOT_FOUND));
@@ -1536,7 +3390,7 @@ mod tests {
         std::fs::write(&config, b"mixed-port: 7890\n").unwrap();
 
         let error = service
-            .check(&infos, &mihomo(), &config)
+            .check(&infos, &mihomo(), ConfigInput::File(&config), None)
             .await
             .expect_err("no binary exists under either dir");
         assert_eq!(error.kind, Some(error_kind::BINARY_NOT_FOUND));
@@ -1547,6 +3401,405 @@ mod tests {
         );
     }
 
+    /// The nyanpasu dirs keep their precedence; the configured search dirs
+    /// only fill in, in the order they were given.
+    #[test]
+    fn the_binary_search_falls_back_to_the_configured_dirs_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut infos = test_infos(dir.path());
+        let executable = mihomo().get_executable_name();
+        let first = dir.path().join("first");
+        let second = dir.path().join("second");
+        for dir in [&infos.nyanpasu_data_dir, &first, &second] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        infos.core_search_dirs = vec![first.clone(), second.clone()];
+
+        std::fs::write(second.join(&executable), b"").unwrap();
+        assert_eq!(
+            find_binary_path(&infos, &mihomo()).unwrap(),
+            second.join(&executable)
+        );
+        std::fs::write(first.join(&executable), b"").unwrap();
+        assert_eq!(
+            find_binary_path(&infos, &mihomo()).unwrap(),
+            first.join(&executable)
+        );
+        std::fs::write(infos.nyanpasu_data_dir.join(&executable), b"").unwrap();
+        assert_eq!(
+            find_binary_path(&infos, &mihomo()).unwrap(),
+            infos.nyanpasu_data_dir.join(&executable)
+        );
+    }
+
+    /// The allowlist is checked against where the parent resolves, not how
+    /// the path is spelled, and a subdirectory of an allowed dir is not
+    /// allowed with it.
+    #[test]
+    fn an_explicit_binary_must_sit_directly_in_an_allowed_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        let mut infos = test_infos(&root);
+        let executable = mihomo().get_executable_name();
+        let allowed = root.join("allowed");
+        let elsewhere = root.join("elsewhere");
+        for dir in [&allowed.join("nested"), &elsewhere] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        for dir in [&allowed, &allowed.join("nested"), &elsewhere] {
+            std::fs::write(dir.join(&executable), b"").unwrap();
+        }
+        infos.allowed_core_dirs.push(allowed.clone());
+
+        assert_eq!(
+            explicit_binary_path(&infos, &mihomo(), &allowed.join(&executable)).unwrap(),
+            allowed.join(&executable)
+        );
+        for refused in [
+            elsewhere.join(&executable),
+            allowed.join("..").join("elsewhere").join(&executable),
+            allowed.join("nested").join(&executable),
+            PathBuf::from(&executable),
+        ] {
+            let error = explicit_binary_path(&infos, &mihomo(), &refused)
+                .expect_err("outside the allowlist");
+            assert_eq!(
+                error.kind,
+                Some(error_kind::BINARY_NOT_ALLOWED),
+                "{refused:?}"
+            );
+        }
+        std::fs::remove_file(allowed.join(&executable)).unwrap();
+        let error = explicit_binary_path(&infos, &mihomo(), &allowed.join(&executable))
+            .expect_err("no such binary in an allowed dir");
+        assert_eq!(error.kind, Some(error_kind::BINARY_NOT_FOUND));
+
+        // A search dir is allowed too: the service would find a core there.
+        let searched = root.join("searched");
+        std::fs::create_dir_all(&searched).unwrap();
+        std::fs::write(searched.join(&executable), b"").unwrap();
+        infos.core_search_dirs.push(searched.clone());
+        assert_eq!(
+            explicit_binary_path(&infos, &mihomo(), &searched.join(&executable)).unwrap(),
+            searched.join(&executable)
+        );
+    }
+
+    /// An allowed dir is not an allowlist of programs: whatever else sits in
+    /// it, only the requested core's own executable name is accepted.
+    #[test]
+    fn an_explicit_binary_must_be_named_as_the_requested_core() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        let mut infos = test_infos(&root);
+        let allowed = root.join("allowed");
+        std::fs::create_dir_all(&allowed).unwrap();
+        let other_core = CoreType::SingBox.get_executable_name().to_string();
+        let shell = format!("sh{}", std::env::consts::EXE_SUFFIX);
+        for name in [&other_core, &shell] {
+            std::fs::write(allowed.join(name), b"").unwrap();
+        }
+        infos.allowed_core_dirs.push(allowed.clone());
+
+        for refused in [allowed.join(&shell), allowed.join(&other_core)] {
+            let error = explicit_binary_path(&infos, &mihomo(), &refused)
+                .expect_err("not the requested core's executable");
+            assert_eq!(
+                error.kind,
+                Some(error_kind::BINARY_NOT_ALLOWED),
+                "{refused:?}"
+            );
+        }
+        assert_eq!(
+            explicit_binary_path(&infos, &CoreType::SingBox, &allowed.join(&other_core)).unwrap(),
+            allowed.join(&other_core)
+        );
+    }
+
+    /// With no manifest every binary runs; once one exists, only a binary it
+    /// pins, at the digest it pins, does, and what runs is the service's copy.
+    #[test]
+    fn a_pinned_manifest_refuses_unlisted_and_altered_binaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        let infos = test_infos(&root);
+        let copies = infos.service_data_dir.join(PINNED_CORES_DIR_NAME);
+        std::fs::create_dir_all(&infos.service_config_dir).unwrap();
+        let binary = root.join("mihomo");
+        let unlisted = root.join("clash-rs");
+        std::fs::write(&binary, b"core").unwrap();
+        std::fs::write(&unlisted, b"core").unwrap();
+
+        assert_eq!(
+            verify_pinned(&infos.service_config_dir, &copies, &binary).unwrap(),
+            None
+        );
+        assert!(!copies.exists(), "nothing is copied while pinning is off");
+
+        let digest = crate::utils::core_manifest::sha256_file(&binary).unwrap();
+        let mut manifest = CoreManifest::default();
+        manifest.binaries.insert(binary.clone(), digest.clone());
+        manifest.save(&infos.service_config_dir).unwrap();
+        let copy = verify_pinned(&infos.service_config_dir, &copies, &binary)
+            .unwrap()
+            .expect("pinned");
+        assert_eq!(copy.path, copies.join(&digest).join("mihomo"));
+        assert_eq!(copy.origin, binary);
+        assert_eq!(copy.sha256, digest);
+
+        // Replacing the original after the check no longer reaches what runs.
+        std::fs::write(&binary, b"replaced").unwrap();
+        assert_eq!(std::fs::read(&copy.path).unwrap(), b"core");
+
+        let error =
+            verify_pinned(&infos.service_config_dir, &copies, &unlisted).expect_err("not pinned");
+        assert_eq!(error.kind, Some(error_kind::BINARY_UNTRUSTED));
+        let error =
+            verify_pinned(&infos.service_config_dir, &copies, &binary).expect_err("digest moved");
+        assert_eq!(error.kind, Some(error_kind::BINARY_UNTRUSTED));
+    }
+
+    /// A preview refuses what a start would, but runs an existing copy or a
+    /// scratch one and leaves no copy of its own behind.
+    #[test]
+    fn a_preview_verifies_without_keeping_a_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        let infos = test_infos(&root);
+        let copies = infos.service_data_dir.join(PINNED_CORES_DIR_NAME);
+        std::fs::create_dir_all(&infos.service_config_dir).unwrap();
+        let binary = root.join("mihomo");
+        let unlisted = root.join("clash-rs");
+        std::fs::write(&binary, b"core").unwrap();
+        std::fs::write(&unlisted, b"core").unwrap();
+
+        assert_eq!(
+            preview_pinned(&infos.service_config_dir, &copies, &binary).unwrap(),
+            None
+        );
+
+        let digest = crate::utils::core_manifest::sha256_file(&binary).unwrap();
+        let mut manifest = CoreManifest::default();
+        manifest.binaries.insert(binary.clone(), digest.clone());
+        manifest.save(&infos.service_config_dir).unwrap();
+        let scratch = copies.join(PREVIEW_DIR_NAME).join("mihomo");
+        let preview = preview_pinned(&infos.service_config_dir, &copies, &binary)
+            .unwrap()
+            .expect("pinned");
+        assert_eq!(preview, PreviewBinary::Scratch(scratch.clone()));
+        assert_eq!(std::fs::read(&scratch).unwrap(), b"core");
+        drop(preview);
+        assert!(!scratch.exists(), "the scratch copy outlived its preview");
+        assert!(!copies.join(&digest).exists(), "a preview kept a copy");
+
+        let copy = verify_pinned(&infos.service_config_dir, &copies, &binary)
+            .unwrap()
+            .expect("pinned");
+        assert_eq!(
+            preview_pinned(&infos.service_config_dir, &copies, &binary).unwrap(),
+            Some(PreviewBinary::Copy(copy.path.clone()))
+        );
+
+        let error =
+            preview_pinned(&infos.service_config_dir, &copies, &unlisted).expect_err("not pinned");
+        assert_eq!(error.kind, Some(error_kind::BINARY_UNTRUSTED));
+        std::fs::write(&binary, b"replaced").unwrap();
+        let error =
+            preview_pinned(&infos.service_config_dir, &copies, &binary).expect_err("digest moved");
+        assert_eq!(error.kind, Some(error_kind::BINARY_UNTRUSTED));
+        assert!(
+            copy.path.is_file(),
+            "a refused preview removed the real copy"
+        );
+    }
+
+    /// A restart or rollback relaunches a copy only while the manifest in
+    /// force still pins the build it was made from.
+    #[test]
+    fn a_relaunch_needs_the_pin_its_copy_was_verified_with() {
+        let copy = PinnedCopy {
+            path: PathBuf::from("/service/pinned-cores/aa/mihomo"),
+            origin: PathBuf::from("/home/user/mihomo"),
+            sha256: "aa".to_owned(),
+        };
+        let pinned_at = |digest: &str| {
+            let mut manifest = CoreManifest::default();
+            manifest
+                .binaries
+                .insert(copy.origin.clone(), digest.to_owned());
+            manifest
+        };
+
+        reverify_pinned(None, &copy.path, None).expect("pinning is off");
+        reverify_pinned(Some(&pinned_at("AA")), &copy.path, Some(&copy)).expect("still pinned");
+        for (manifest, verified) in [
+            (pinned_at("bb"), Some(&copy)),
+            (CoreManifest::default(), Some(&copy)),
+            (pinned_at("aa"), None),
+        ] {
+            let error = reverify_pinned(Some(&manifest), &copy.path, verified)
+                .expect_err("not vouched for now");
+            assert_eq!(error.kind, Some(error_kind::BINARY_UNTRUSTED));
+        }
+    }
+
+    /// A crashed epoch keeps its spec summary in the terminal snapshot, so the
+    /// build block has to follow the state rather than the summary.
+    #[test]
+    fn the_build_identity_is_projected_only_while_the_core_is_up() {
+        let mut status = status_of(ManagerCoreState::Running {
+            epoch: 3,
+            pid: 4242,
+        });
+        status.spec = Some(SpecSummary {
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
+            binary_path: Utf8PathBuf::from("/usr/bin/mihomo"),
+            version: Some("Mihomo Meta v1.19.0".to_owned()),
+            distribution: None,
+            config_path: Utf8PathBuf::from("/etc/nyanpasu/config.yaml"),
+            capabilities: vec![Feature::UnixSocketIpc],
+            runtime_features: vec![RuntimeFeature::LocalIpc],
+            options: InstanceOptions::default(),
+        });
+        let build = project_core_infos(&status, None)
+            .build
+            .expect("a running core reports its build");
+        assert_eq!(build.version.as_deref(), Some("Mihomo Meta v1.19.0"));
+        assert_eq!(build.capabilities, vec![Feature::UnixSocketIpc]);
+        assert_eq!(build.runtime_features, vec![CoreRuntimeFeature::LocalIpc]);
+
+        status.state = ManagerCoreState::Stopped {
+            reason: Some(StopReason::Finished),
+        };
+        assert_eq!(project_core_infos(&status, None).build, None);
+    }
+
+    /// What the default 120 s request timeout leaves after the default 30 s
+    /// reconcile and two 10 s stops.
+    const STARTUP_TIMEOUT_LIMIT: Duration = Duration::from_secs(70);
+
+    /// Overrides change only the fields they name, and a bad one is refused
+    /// with its key before anything is touched.
+    #[test]
+    fn policy_overrides_land_on_the_service_options_within_bounds() {
+        let mut options = InstanceOptions::default();
+        override_options(
+            &mut options,
+            &CorePolicyOverrides {
+                startup_timeout_ms: Some(60_000),
+                failure_threshold: Some(5),
+                max_restarts: Some(0),
+                ..Default::default()
+            },
+            STARTUP_TIMEOUT_LIMIT,
+        )
+        .unwrap();
+        assert_eq!(options.startup_timeout, Duration::from_secs(60));
+        assert_eq!(options.health.failure_threshold().get(), 5);
+        assert_eq!(
+            options.health.interval(),
+            InstanceOptions::default().health.interval()
+        );
+        assert_eq!(
+            options.restart_policy,
+            RestartPolicy::OnFailure { max_restarts: 0 }
+        );
+
+        for (overrides, key) in [
+            (
+                CorePolicyOverrides {
+                    startup_timeout_ms: Some(600_000),
+                    ..Default::default()
+                },
+                "policy.startup_timeout_ms",
+            ),
+            (
+                CorePolicyOverrides {
+                    health_interval_ms: Some(0),
+                    ..Default::default()
+                },
+                "policy.health_interval_ms",
+            ),
+            (
+                CorePolicyOverrides {
+                    success_threshold: Some(0),
+                    ..Default::default()
+                },
+                "policy.success_threshold",
+            ),
+        ] {
+            let mut untouched = InstanceOptions::default();
+            let error =
+                override_options(&mut untouched, &overrides, STARTUP_TIMEOUT_LIMIT).expect_err(key);
+            assert_eq!(error.kind, Some(error_kind::INVALID_POLICY));
+            assert!(error.message.starts_with(key), "{}", error.message);
+            assert_eq!(
+                format!("{untouched:?}"),
+                format!("{:?}", InstanceOptions::default())
+            );
+        }
+    }
+
+    /// A startup timeout the wire allows is still refused when the service's
+    /// request timeout would cut the start off before it ran out.
+    #[test]
+    fn a_startup_timeout_override_stays_inside_the_request_timeout() {
+        let overrides = CorePolicyOverrides {
+            startup_timeout_ms: Some(40_000),
+            ..Default::default()
+        };
+        let mut options = InstanceOptions::default();
+        override_options(&mut options, &overrides, Duration::from_millis(40_001)).unwrap();
+        assert_eq!(options.startup_timeout, Duration::from_secs(40));
+
+        let mut untouched = InstanceOptions::default();
+        let error = override_options(&mut untouched, &overrides, Duration::from_secs(40))
+            .expect_err("as long as the limit");
+        assert_eq!(error.kind, Some(error_kind::INVALID_POLICY));
+        assert!(
+            error
+                .message
+                .starts_with("policy.startup_timeout_ms must be under 40000"),
+            "{}",
+            error.message
+        );
+        assert_eq!(
+            untouched.startup_timeout,
+            InstanceOptions::default().startup_timeout
+        );
+    }
+
+    #[test]
+    fn the_launch_policy_is_projected_only_while_the_core_is_up() {
+        let mut status = status_of(ManagerCoreState::Running {
+            epoch: 3,
+            pid: 4242,
+        });
+        let mut options = InstanceOptions::default();
+        options.restart_policy = RestartPolicy::Never;
+        status.spec = Some(SpecSummary {
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
+            binary_path: Utf8PathBuf::from("/usr/bin/mihomo"),
+            version: None,
+            distribution: None,
+            config_path: Utf8PathBuf::from("/etc/nyanpasu/config.yaml"),
+            capabilities: Vec::new(),
+            runtime_features: Vec::new(),
+            options,
+        });
+        let policy = project_core_infos(&status, None)
+            .policy
+            .expect("a running core reports its policy");
+        assert_eq!(policy.startup_timeout_ms, 30_000);
+        assert_eq!(policy.health, HealthConfig::default());
+        assert_eq!(policy.max_restarts, None);
+
+        status.state = ManagerCoreState::Stopped {
+            reason: Some(StopReason::Finished),
+        };
+        assert_eq!(project_core_infos(&status, None).policy, None);
+    }
+
     /// Mirrors the restructured bridge loop: what the legacy `CoreStateChanged`
     /// stream carries (after the unchanged suppression rules) and what the
     /// snapshot stream carries (one per manager transition, none suppressed).
@@ -1664,7 +3917,7 @@ mod wire_strings {
     /// One of these three ("core is already running") cannot be produced by a
     /// route-level unit test — it requires an actually-running core — so the
     /// producer constants themselves are pinned here, and the two siblings'
-    /// route tests prove the bail→envelope pipeline delivers them verbatim.
+    /// route tests prove the error→envelope pipeline delivers them verbatim.
     #[test]
     fn the_legacy_core_error_strings_are_protocol() {
         assert_eq!(super::MSG_CORE_ALREADY_RUNNING, "core is already running");
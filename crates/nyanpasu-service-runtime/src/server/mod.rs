@@ -1,16 +1,20 @@
+mod access;
+mod audit;
+mod config;
 pub mod consts;
 mod events;
 mod logger;
 mod manager_bridge;
 mod routing;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
+use audit::AuditLog;
+pub use config::{ServiceConfigState, ServiceSettings};
 use consts::RuntimeInfos;
 pub use events::EventHub;
 pub use logger::Logger;
 pub use manager_bridge::CoreManagerService as CoreManager;
-use nyanpasu_core_manager::LocalIpcPolicy;
 use nyanpasu_ipc::{SERVICE_PLACEHOLDER, server::create_server};
 use routing::{AppState, create_router};
 use tokio_util::sync::CancellationToken;
@@ -18,20 +22,38 @@ use tracing_attributes::instrument;
 
 const SERVER_DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
 
-#[instrument(skip(runtime))]
+#[instrument(skip(runtime, settings))]
 pub async fn run(
     runtime: RuntimeInfos,
-    local_ipc_policy: LocalIpcPolicy,
+    settings: ServiceSettings,
+    telemetry_interval: Duration,
     token: CancellationToken,
-    #[cfg(windows)] sids: &[&str],
+    #[cfg(windows)] sids: Vec<String>,
     #[cfg(not(windows))] sids: (),
 ) -> Result<(), anyhow::Error> {
     let runtime_dir =
         camino::Utf8PathBuf::from_path_buf(crate::utils::dirs::service_core_runtime_dir())
             .map_err(|path| anyhow::anyhow!("core runtime dir is not UTF-8: {}", path.display()))?;
-    let core_manager = CoreManager::new(runtime_dir, local_ipc_policy).await?;
-    let hub = EventHub::new();
-    core_manager.spawn_bridges(hub.clone());
+    let ServiceSettings {
+        manager: mut manager_options,
+        instance: instance_options,
+        max_concurrent_checks,
+        events,
+        request_timeout,
+        access,
+        report,
+        install_local_ipc_policy,
+    } = settings;
+    manager_options.runtime_dir = Some(runtime_dir);
+    let core_manager = CoreManager::new(
+        manager_options,
+        instance_options,
+        max_concurrent_checks,
+        request_timeout,
+    )
+    .await?;
+    let hub = EventHub::with_capacities(events);
+    core_manager.spawn_bridges(hub.clone(), telemetry_interval);
 
     // The tracing writer was bound to the global logger before `run`; share that
     // instance so the `/logs` routes read the buffer that is actually being fed.
@@ -39,13 +61,25 @@ pub async fn run(
     // `/status` reports the directory.
     let logger = Logger::global().clone();
 
+    // A reload sends a changed pipe ACL to the listener through this.
+    #[cfg(windows)]
+    let (sids_tx, sids) = tokio::sync::watch::channel(sids);
+    let audit = Arc::new(AuditLog::new(&runtime.service_data_dir));
     let state = AppState {
         core_manager: core_manager.clone(),
         hub,
         runtime: Arc::new(runtime),
         logger,
+        service_config: Arc::new(ServiceConfigState::new(
+            report,
+            access,
+            install_local_ipc_policy,
+            #[cfg(windows)]
+            sids_tx,
+        )),
+        audit,
     };
-    let app = create_router(state);
+    let app = create_router(state, request_timeout);
     tracing::info!("Starting server...");
     let shutdown_token = token.clone();
     let server = create_server(
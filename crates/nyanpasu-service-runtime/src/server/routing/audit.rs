@@ -0,0 +1,38 @@
+use std::borrow::Cow;
+
+use axum::{Json, Router, extract::State, http::StatusCode};
+use nyanpasu_ipc::{
+    api::{
+        RBuilder,
+        audit::{AuditQueryReq, AuditQueryRes},
+        contract::Audit,
+    },
+    server::RegisterOperation,
+};
+
+use super::AppState;
+
+pub fn setup() -> Router<AppState> {
+    Router::new().register(Audit, query_audit)
+}
+
+pub async fn query_audit(
+    State(state): State<AppState>,
+    Json(payload): Json<AuditQueryReq>,
+) -> (StatusCode, Json<AuditQueryRes<'static>>) {
+    match state.audit.query(&payload).await {
+        Ok(data) => (StatusCode::OK, Json(RBuilder::success(data))),
+        Err(error) => {
+            tracing::error!(
+                "failed to read the audit trail {}: {error}",
+                state.audit.path().display()
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(RBuilder::other_error(Cow::Owned(format!(
+                    "failed to read the audit trail: {error}"
+                )))),
+            )
+        }
+    }
+}
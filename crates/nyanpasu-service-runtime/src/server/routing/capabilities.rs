@@ -0,0 +1,47 @@
+use axum::{Json, Router, extract::State, http::StatusCode};
+
+use nyanpasu_ipc::{
+    api::{
+        RBuilder,
+        capabilities::{CapabilitiesData, CapabilitiesRes, ServiceFeature},
+        contract::Capabilities,
+        service_config::LocalIpcPolicyConfig,
+    },
+    server::RegisterOperation,
+};
+
+use crate::utils::core_manifest::CoreManifest;
+
+use super::AppState;
+
+pub fn setup() -> Router<AppState> {
+    Router::new().register(Capabilities, capabilities)
+}
+
+pub async fn capabilities(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<CapabilitiesRes<'static>>) {
+    let mut features = Vec::new();
+    if state.core_manager.core_log_dir().is_some() {
+        features.push(ServiceFeature::CoreLogArchive);
+    }
+    if state
+        .service_config
+        .report()
+        .effective
+        .manager
+        .local_ipc_policy
+        != Some(LocalIpcPolicyConfig::Disable)
+    {
+        features.push(ServiceFeature::LocalIpc);
+    }
+    // Read afresh like `verify_pinned` does, since `pin-core` needs no restart.
+    if tokio::fs::try_exists(CoreManifest::path(&state.runtime.service_config_dir))
+        .await
+        .unwrap_or(false)
+    {
+        features.push(ServiceFeature::PinnedCores);
+    }
+    let data = CapabilitiesData::of_this_build(crate::consts::APP_VERSION, features);
+    (StatusCode::OK, Json(RBuilder::success(data)))
+}
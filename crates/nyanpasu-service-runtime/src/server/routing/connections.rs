@@ -0,0 +1,59 @@
+use axum::{Json, Router, extract::State, http::StatusCode};
+use nyanpasu_ipc::{
+    api::{
+        RBuilder,
+        connections::{
+            ConnectionCloseAllRes, ConnectionCloseReq, ConnectionCloseRes, ConnectionFilter,
+            ConnectionsRes,
+        },
+        contract::{Connections, ConnectionsClose, ConnectionsCloseAll},
+    },
+    server::RegisterOperation,
+};
+
+use super::AppState;
+
+pub fn setup() -> Router<AppState> {
+    Router::new()
+        .register(Connections, list_connections)
+        .register(ConnectionsClose, close_connections)
+        .register(ConnectionsCloseAll, close_all_connections)
+}
+
+pub async fn list_connections(
+    State(state): State<AppState>,
+    Json(filter): Json<ConnectionFilter>,
+) -> (StatusCode, Json<ConnectionsRes<'static>>) {
+    match state.core_manager.connections(&filter).await {
+        Ok(data) => (StatusCode::OK, Json(RBuilder::success(data))),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error.into_envelope()),
+        ),
+    }
+}
+
+pub async fn close_connections(
+    State(state): State<AppState>,
+    Json(payload): Json<ConnectionCloseReq>,
+) -> (StatusCode, Json<ConnectionCloseRes<'static>>) {
+    match state.core_manager.close_connections(&payload).await {
+        Ok(data) => (StatusCode::OK, Json(RBuilder::success(data))),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error.into_envelope()),
+        ),
+    }
+}
+
+pub async fn close_all_connections(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ConnectionCloseAllRes<'static>>) {
+    match state.core_manager.close_all_connections().await {
+        Ok(()) => (StatusCode::OK, Json(RBuilder::success(()))),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error.into_envelope()),
+        ),
+    }
+}
@@ -1,22 +1,62 @@
+use std::path::PathBuf;
+
 use axum::{Json, extract::State, http::StatusCode};
 use nyanpasu_ipc::api::{
     RBuilder,
-    core::apply::{CoreApplyReq, CoreApplyRes},
+    core::apply::{CoreApplyPlanReq, CoreApplyPlanRes, CoreApplyReq, CoreApplyRes},
 };
 
-use crate::server::routing::AppState;
+use crate::server::{manager_bridge::ConfigInput, routing::AppState};
 
 pub async fn apply(
     State(state): State<AppState>,
     Json(payload): Json<CoreApplyReq<'_>>,
 ) -> (StatusCode, Json<CoreApplyRes<'static>>) {
+    let config = match ConfigInput::from_request(
+        payload.config_file.as_deref().map(PathBuf::as_path),
+        payload.config.as_deref(),
+    ) {
+        Ok(config) => config,
+        Err(error) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(error.into_envelope()),
+            );
+        }
+    };
     match state
         .core_manager
         .apply(
             &state.runtime,
             &payload.core_type,
-            &payload.config_file,
+            config,
             payload.expected_revision.as_ref(),
+            payload.binary_path.as_deref().map(PathBuf::as_path),
+            payload.distribution.as_ref(),
+            payload.policy.as_ref(),
+        )
+        .await
+    {
+        Ok(data) => (StatusCode::OK, Json(RBuilder::success(data))),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error.into_envelope()),
+        ),
+    }
+}
+
+pub async fn plan(
+    State(state): State<AppState>,
+    Json(payload): Json<CoreApplyPlanReq<'_>>,
+) -> (StatusCode, Json<CoreApplyPlanRes<'static>>) {
+    match state
+        .core_manager
+        .plan_apply(
+            &state.runtime,
+            &payload.core_type,
+            &payload.config_file,
+            payload.binary_path.as_deref().map(PathBuf::as_path),
+            payload.policy.as_ref(),
         )
         .await
     {
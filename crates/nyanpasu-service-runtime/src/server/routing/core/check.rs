@@ -1,18 +1,37 @@
+use std::path::PathBuf;
+
 use axum::{Json, extract::State, http::StatusCode};
 use nyanpasu_ipc::api::{
     RBuilder,
     core::check::{CoreCheckReq, CoreCheckRes},
 };
 
-use crate::server::routing::AppState;
+use crate::server::{manager_bridge::ConfigInput, routing::AppState};
 
 pub async fn check(
     State(state): State<AppState>,
     Json(payload): Json<CoreCheckReq<'_>>,
 ) -> (StatusCode, Json<CoreCheckRes<'static>>) {
+    let config = match ConfigInput::from_request(
+        payload.config_file.as_deref().map(PathBuf::as_path),
+        payload.config.as_deref(),
+    ) {
+        Ok(config) => config,
+        Err(error) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(error.into_envelope()),
+            );
+        }
+    };
     match state
         .core_manager
-        .check(&state.runtime, &payload.core_type, &payload.config_file)
+        .check(
+            &state.runtime,
+            &payload.core_type,
+            config,
+            payload.binary_path.as_deref().map(PathBuf::as_path),
+        )
         .await
     {
         Ok(()) => (StatusCode::OK, Json(RBuilder::success(()))),
@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+
+use axum::{Json, extract::State, http::StatusCode};
+use nyanpasu_ipc::api::{
+    RBuilder,
+    core::config::{CoreConfigDiffReq, CoreConfigDiffRes},
+};
+
+use crate::server::routing::AppState;
+
+pub async fn diff(
+    State(state): State<AppState>,
+    Json(payload): Json<CoreConfigDiffReq<'_>>,
+) -> (StatusCode, Json<CoreConfigDiffRes<'static>>) {
+    match state
+        .core_manager
+        .diff_config(
+            &state.runtime,
+            &payload.core_type,
+            &payload.config_file,
+            payload.binary_path.as_deref().map(PathBuf::as_path),
+            payload.policy.as_ref(),
+        )
+        .await
+    {
+        Ok(data) => (StatusCode::OK, Json(RBuilder::success(data))),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error.into_envelope()),
+        ),
+    }
+}
@@ -1,6 +1,9 @@
 use axum::Router;
 use nyanpasu_ipc::{
-    api::contract::{CoreApply, CoreCheck, CoreRecover, CoreRestart, CoreStart, CoreStop},
+    api::contract::{
+        CoreApply, CoreApplyPlan, CoreCheck, CoreConfigDiff, CoreRecover, CoreRestart,
+        CoreRevisions, CoreRollback, CoreStart, CoreStop,
+    },
     server::RegisterOperation,
 };
 
@@ -8,8 +11,11 @@ use super::AppState;
 
 pub mod apply;
 pub mod check;
+pub mod config;
 pub mod recover;
 pub mod restart;
+pub mod revisions;
+pub mod rollback;
 pub mod start;
 pub mod stop;
 
@@ -19,6 +25,10 @@ pub fn setup() -> Router<AppState> {
         .register(CoreStop, stop::stop)
         .register(CoreRestart, restart::restart)
         .register(CoreApply, apply::apply)
+        .register(CoreApplyPlan, apply::plan)
         .register(CoreCheck, check::check)
+        .register(CoreConfigDiff, config::diff)
         .register(CoreRecover, recover::recover)
+        .register(CoreRevisions, revisions::revisions)
+        .register(CoreRollback, rollback::rollback)
 }
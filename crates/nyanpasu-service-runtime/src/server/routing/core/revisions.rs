@@ -0,0 +1,11 @@
+use axum::{Json, extract::State, http::StatusCode};
+use nyanpasu_ipc::api::{RBuilder, core::revisions::CoreRevisionsRes};
+
+use crate::server::routing::AppState;
+
+pub async fn revisions(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<CoreRevisionsRes<'static>>) {
+    let data = state.core_manager.revisions().await;
+    (StatusCode::OK, Json(RBuilder::success(data)))
+}
@@ -0,0 +1,28 @@
+use axum::{Json, extract::State, http::StatusCode};
+use nyanpasu_ipc::api::{
+    RBuilder,
+    core::rollback::{CoreRollbackReq, CoreRollbackRes},
+};
+
+use crate::server::routing::AppState;
+
+pub async fn rollback(
+    State(state): State<AppState>,
+    Json(payload): Json<CoreRollbackReq>,
+) -> (StatusCode, Json<CoreRollbackRes<'static>>) {
+    match state
+        .core_manager
+        .rollback(
+            &state.runtime,
+            &payload.revision,
+            payload.expected_revision.as_ref(),
+        )
+        .await
+    {
+        Ok(data) => (StatusCode::OK, Json(RBuilder::success(data))),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error.into_envelope()),
+        ),
+    }
+}
@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::path::PathBuf;
 
 use axum::{Json, extract::State, http::StatusCode};
 use nyanpasu_ipc::api::{
@@ -17,16 +17,18 @@ pub async fn start(
         .start(
             &state.runtime,
             &payload.core_type,
-            camino::Utf8Path::from_path(&payload.config_file)
-                .expect("failed to convert config_file to Utf8Path"),
+            &payload.config_file,
+            payload.binary_path.as_deref().map(PathBuf::as_path),
+            payload.distribution.as_ref(),
+            payload.policy.as_ref(),
         )
         .await;
 
     match res {
         Ok(_) => (StatusCode::OK, Json(RBuilder::success(()))),
-        Err(e) => (
+        Err(error) => (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(RBuilder::other_error(Cow::Owned(e.to_string()))),
+            Json(error.into_envelope()),
         ),
     }
 }
@@ -1,5 +1,3 @@
-use std::borrow::Cow;
-
 use axum::{Json, extract::State, http::StatusCode};
 use nyanpasu_ipc::api::{RBuilder, core::stop::CoreStopRes};
 
@@ -9,9 +7,9 @@ pub async fn stop(State(state): State<AppState>) -> (StatusCode, Json<CoreStopRe
     let res = state.core_manager.stop().await;
     match res {
         Ok(_) => (StatusCode::OK, Json(RBuilder::success(()))),
-        Err(e) => (
+        Err(error) => (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(RBuilder::other_error(Cow::Owned(e.to_string()))),
+            Json(error.into_envelope()),
         ),
     }
 }
@@ -2,8 +2,9 @@ use axum::{Json, Router, extract::State, http::StatusCode};
 use nyanpasu_ipc::{
     api::{
         RBuilder,
-        contract::{LogsInspect, LogsRetrieve},
-        log::{LogsRes, LogsResBody},
+        contract::{LogsCoreQuery, LogsInspect, LogsRetrieve},
+        error_kind,
+        log::{CoreLogsQueryReq, CoreLogsQueryRes, LogsRes, LogsResBody},
     },
     server::RegisterOperation,
 };
@@ -14,6 +15,7 @@ pub fn setup() -> Router<AppState> {
     Router::new()
         .register(LogsRetrieve, retrieve_logs)
         .register(LogsInspect, inspect_logs)
+        .register(LogsCoreQuery, query_core_logs)
 }
 
 pub async fn retrieve_logs(State(state): State<AppState>) -> (StatusCode, Json<LogsRes<'static>>) {
@@ -31,3 +33,20 @@ pub async fn inspect_logs(State(state): State<AppState>) -> (StatusCode, Json<Lo
         Json(RBuilder::success(LogsResBody { logs })),
     )
 }
+
+pub async fn query_core_logs(
+    State(state): State<AppState>,
+    Json(payload): Json<CoreLogsQueryReq>,
+) -> (StatusCode, Json<CoreLogsQueryRes<'static>>) {
+    match state.core_manager.query_logs(&payload).await {
+        Ok(data) => (StatusCode::OK, Json(RBuilder::success(data))),
+        // Nothing to read is the service's configuration, not a failed read.
+        Err(error) if error.kind() == Some(error_kind::LOG_ARCHIVE_DISABLED) => {
+            (StatusCode::CONFLICT, Json(error.into_envelope()))
+        }
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error.into_envelope()),
+        ),
+    }
+}
@@ -1,27 +1,46 @@
-//! The layer stack shared by every route: request id, timeout, panic capture
-//! and the `R`-envelope fallbacks.
+//! The layer stack shared by every route: request id, timeout, panic capture,
+//! the `R`-envelope fallbacks, the access check and the audit trail.
 //!
-//! The rule for all four: they only produce a response where the client
+//! The rule for the first four: they only produce a response where the client
 //! previously got something worse (an empty body, a dropped connection, or no
-//! answer at all). None of them can fire on a working operation.
+//! answer at all). None of them can fire on a working operation. The access
+//! check refuses calls on purpose, and only under an `[access]` table an
+//! administrator narrowed: the default one admits every caller. The audit
+//! trail only watches.
 
 use std::{any::Any, borrow::Cow, time::Duration};
 
 use axum::{
     Json,
-    extract::Request,
+    extract::{ConnectInfo, Request, State},
     http::{Response, StatusCode},
     middleware::Next,
     response::IntoResponse,
 };
-use nyanpasu_ipc::api::{R, RBuilder};
+use nyanpasu_ipc::{
+    api::{
+        R, RBuilder,
+        audit::{AuditCaller, AuditRecord},
+        error_kind,
+    },
+    server::IpcPeer,
+};
 use tower_http::{catch_panic::ResponseForPanic, trace::MakeSpan};
 
+use super::AppState;
+use crate::server::audit::{self, AUDITED};
+
 /// Header carrying the per-request correlation id. Must match the header
 /// `tower_http::request_id`'s `x_request_id` constructors use.
 const REQUEST_ID_HEADER: &str = "x-request-id";
 
-/// Upper bound on a single request/response operation.
+/// The most of an audited request body the trail reads, matching the limit
+/// axum's `Json` extractor refuses a larger body at.
+const AUDIT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Default upper bound on a single request/response operation; the service
+/// config's `server.request_timeout_ms` replaces it, and is refused at or
+/// below the manager's worst case.
 ///
 /// This is a guard rail against a wedged handler holding an IPC connection
 /// forever, NOT a policy timeout: the core manager already bounds its own work
@@ -29,7 +48,7 @@ const REQUEST_ID_HEADER: &str = "x-request-id";
 /// stop_timeout(10s) = 80s` worst case (`nyanpasu_core_manager::spec`), so this
 /// sits above anything a working `/core/start` or `/core/restart` can take.
 /// The ws endpoint is a long-lived stream and is deliberately not bounded.
-pub(super) const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+pub(super) const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
 
 /// An error response in the legacy envelope: `{code, msg, data, ts}`.
 fn error_envelope(status: StatusCode, msg: Cow<'static, str>) -> axum::response::Response {
@@ -50,12 +69,17 @@ pub(super) async fn method_not_allowed() -> axum::response::Response {
     )
 }
 
-/// Bound a request/response operation at [`REQUEST_TIMEOUT`].
-pub(super) async fn enforce_timeout(request: Request, next: Next) -> axum::response::Response {
-    match tokio::time::timeout(REQUEST_TIMEOUT, next.run(request)).await {
+/// Bound a request/response operation at the configured timeout,
+/// [`DEFAULT_REQUEST_TIMEOUT`] unless the service config says otherwise.
+pub(super) async fn enforce_timeout(
+    State(timeout): State<Duration>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    match tokio::time::timeout(timeout, next.run(request)).await {
         Ok(response) => response,
         Err(_) => {
-            tracing::error!("request exceeded {REQUEST_TIMEOUT:?}; answering with a timeout");
+            tracing::error!("request exceeded {timeout:?}; answering with a timeout");
             error_envelope(
                 StatusCode::REQUEST_TIMEOUT,
                 Cow::Borrowed("request timed out"),
@@ -64,6 +88,124 @@ pub(super) async fn enforce_timeout(request: Request, next: Next) -> axum::respo
     }
 }
 
+/// Refuse a call the caller's role does not reach, before any handler runs.
+///
+/// The caller comes from the connection's peer credentials; a request without
+/// them (outside Linux, or not off the socket at all) is an unidentified
+/// caller and gets the table's default role.
+pub(super) async fn authorize(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    let credentials = request
+        .extensions()
+        .get::<ConnectInfo<IpcPeer>>()
+        .and_then(|ConnectInfo(peer)| peer.credentials);
+    let access = state.service_config.access();
+    let needed = access.required(request.uri().path());
+    let role = access.role_of(credentials.as_ref());
+    if role >= needed {
+        return next.run(request).await;
+    }
+    let caller = match credentials {
+        Some(credentials) => format!("uid {}", credentials.uid),
+        None => "an unidentified caller".to_owned(),
+    };
+    tracing::warn!(
+        pid = ?credentials.and_then(|credentials| credentials.pid),
+        "refused {} {} to {caller}: needs {}, has {}",
+        request.method(),
+        request.uri().path(),
+        needed.as_str(),
+        role.as_str(),
+    );
+    let body: R<'static, ()> = RBuilder::other_error_with_kind(
+        Cow::Owned(format!(
+            "permission denied: {} needs the {} role",
+            request.uri().path(),
+            needed.as_str()
+        )),
+        Some(Cow::Borrowed(error_kind::PERMISSION_DENIED)),
+    );
+    (StatusCode::FORBIDDEN, Json(body)).into_response()
+}
+
+/// Record an audited call once it has been answered, whatever the answer.
+///
+/// Sits outside the access check, so refused calls are recorded too, and
+/// outside the panic capture, so a handler that panicked is recorded with its
+/// 500. A record that cannot be written is logged and the response goes out
+/// regardless: the call has already happened.
+pub(super) async fn audit(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    let operation = request.uri().path().to_owned();
+    if !AUDITED.contains(&operation.as_str()) {
+        return next.run(request).await;
+    }
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    let caller = request
+        .extensions()
+        .get::<ConnectInfo<IpcPeer>>()
+        .and_then(|ConnectInfo(peer)| peer.credentials)
+        .map(|credentials| AuditCaller {
+            uid: credentials.uid,
+            gid: credentials.gid,
+            pid: credentials.pid,
+        });
+
+    let (parts, body) = request.into_parts();
+    let (summary, response) = match axum::body::to_bytes(body, AUDIT_BODY_LIMIT).await {
+        Ok(body) => {
+            let summary = audit::summarize(&operation, &body);
+            let request = Request::from_parts(parts, axum::body::Body::from(body));
+            (summary, next.run(request).await)
+        }
+        Err(_) => (
+            Default::default(),
+            error_envelope(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Cow::Borrowed("request body too large"),
+            ),
+        ),
+    };
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(error) => {
+            tracing::error!("failed to read the response to {operation}: {error}");
+            return error_envelope(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Cow::Borrowed("internal server error"),
+            );
+        }
+    };
+    let record = AuditRecord {
+        at: nyanpasu_ipc::utils::get_current_ts(),
+        request_id,
+        operation,
+        caller,
+        request: summary,
+        outcome: audit::outcome(parts.status.as_u16(), &body),
+    };
+    if let Err(error) = state.audit.append(&record).await {
+        tracing::error!(
+            "failed to write the audit record for {} to {}: {error}",
+            record.operation,
+            state.audit.path().display()
+        );
+    }
+    axum::response::Response::from_parts(parts, axum::body::Body::from(body))
+}
+
 /// Turn a handler panic into a 500 envelope instead of a dropped connection.
 ///
 /// The panic payload is logged, never sent: it can carry paths and internal
@@ -158,18 +300,21 @@ mod tests {
     }
 
     // `start_paused` auto-advances the clock while the runtime is idle, so the
-    // real 120s constant is exercised without the test taking 120s.
+    // real 120s default is exercised without the test taking 120s.
     #[tokio::test(start_paused = true)]
     async fn a_wedged_handler_answers_with_the_timeout_envelope() {
         let app = Router::new()
             .route(
                 "/slow",
                 get(|| async {
-                    tokio::time::sleep(REQUEST_TIMEOUT * 2).await;
+                    tokio::time::sleep(DEFAULT_REQUEST_TIMEOUT * 2).await;
                     "never"
                 }),
             )
-            .layer(axum::middleware::from_fn(enforce_timeout));
+            .layer(axum::middleware::from_fn_with_state(
+                DEFAULT_REQUEST_TIMEOUT,
+                enforce_timeout,
+            ));
 
         let response = app
             .oneshot(
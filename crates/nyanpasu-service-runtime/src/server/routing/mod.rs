@@ -1,14 +1,21 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use axum::Router;
 use tracing_attributes::instrument;
 
-use super::{CoreManager, EventHub, Logger, consts::RuntimeInfos};
+use super::{
+    CoreManager, EventHub, Logger, ServiceConfigState, audit::AuditLog, consts::RuntimeInfos,
+};
 
+pub mod audit;
+pub mod capabilities;
+pub mod connections;
 pub mod core;
 pub mod logs;
 mod middleware;
 pub mod network;
+pub mod proxies;
+pub mod service;
 pub mod status;
 pub mod ws;
 
@@ -21,10 +28,18 @@ pub struct AppState {
     pub hub: EventHub,
     pub runtime: Arc<RuntimeInfos>,
     pub logger: Logger<'static>,
+    /// The service config in force, reported by `/status` and replaced by
+    /// `/service/reload`.
+    pub service_config: Arc<ServiceConfigState>,
+    /// The audit trail, written by [`middleware::audit`] and read by
+    /// `POST /audit`.
+    pub audit: Arc<AuditLog>,
 }
 
+/// `request_timeout` bounds each request/response operation; see
+/// [`middleware::enforce_timeout`].
 #[instrument(skip(state))]
-pub fn create_router(state: AppState) -> Router {
+pub fn create_router(state: AppState, request_timeout: Duration) -> Router {
     tracing::info!("Applying routes...");
     let tracing_layer =
         tower_http::trace::TraceLayer::new_for_http().make_span_with(middleware::RequestSpan);
@@ -35,16 +50,32 @@ pub fn create_router(state: AppState) -> Router {
         .merge(core::setup())
         .merge(logs::setup())
         .merge(network::setup())
-        .layer(axum::middleware::from_fn(middleware::enforce_timeout));
+        .merge(proxies::setup())
+        .merge(connections::setup())
+        .merge(service::setup())
+        .merge(capabilities::setup())
+        .merge(audit::setup())
+        .layer(axum::middleware::from_fn_with_state(
+            request_timeout,
+            middleware::enforce_timeout,
+        ));
     Router::new()
         .merge(operations)
         .merge(ws::setup())
         .fallback(middleware::not_found)
         .method_not_allowed_fallback(middleware::method_not_allowed)
-        .with_state(state)
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::authorize,
+        ))
+        .with_state(state.clone())
         .layer(tower_http::catch_panic::CatchPanicLayer::custom(
             middleware::PanicEnvelope,
         ))
+        .layer(axum::middleware::from_fn_with_state(
+            state,
+            middleware::audit,
+        ))
         .layer(tower_http::request_id::PropagateRequestIdLayer::x_request_id())
         .layer(tracing_layer)
         .layer(tower_http::request_id::SetRequestIdLayer::x_request_id(
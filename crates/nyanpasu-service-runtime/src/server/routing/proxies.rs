@@ -0,0 +1,56 @@
+use axum::{Json, Router, extract::State, http::StatusCode};
+use nyanpasu_ipc::{
+    api::{
+        RBuilder,
+        contract::{Proxies, ProxiesDelay, ProxiesSelect},
+        proxies::{ProxiesRes, ProxyDelayReq, ProxyDelayRes, ProxySelectReq, ProxySelectRes},
+    },
+    server::RegisterOperation,
+};
+
+use super::AppState;
+
+pub fn setup() -> Router<AppState> {
+    Router::new()
+        .register(Proxies, list_proxies)
+        .register(ProxiesSelect, select_proxy)
+        .register(ProxiesDelay, proxy_delay)
+}
+
+pub async fn list_proxies(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ProxiesRes<'static>>) {
+    match state.core_manager.proxies().await {
+        Ok(data) => (StatusCode::OK, Json(RBuilder::success(data))),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error.into_envelope()),
+        ),
+    }
+}
+
+pub async fn select_proxy(
+    State(state): State<AppState>,
+    Json(payload): Json<ProxySelectReq>,
+) -> (StatusCode, Json<ProxySelectRes<'static>>) {
+    match state.core_manager.select_proxy(&payload).await {
+        Ok(()) => (StatusCode::OK, Json(RBuilder::success(()))),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error.into_envelope()),
+        ),
+    }
+}
+
+pub async fn proxy_delay(
+    State(state): State<AppState>,
+    Json(payload): Json<ProxyDelayReq>,
+) -> (StatusCode, Json<ProxyDelayRes<'static>>) {
+    match state.core_manager.proxy_delay(&payload).await {
+        Ok(data) => (StatusCode::OK, Json(RBuilder::success(data))),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(error.into_envelope()),
+        ),
+    }
+}
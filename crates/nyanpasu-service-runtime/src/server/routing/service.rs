@@ -0,0 +1,32 @@
+use std::borrow::Cow;
+
+use axum::{Json, Router, extract::State, http::StatusCode};
+use nyanpasu_ipc::{
+    api::{RBuilder, contract::ServiceReload, service_config::ServiceReloadRes},
+    server::RegisterOperation,
+};
+
+use super::AppState;
+
+pub fn setup() -> Router<AppState> {
+    Router::new().register(ServiceReload, reload)
+}
+
+pub async fn reload(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ServiceReloadRes<'static>>) {
+    match state
+        .service_config
+        .reload(&state.core_manager, &state.hub)
+        .await
+    {
+        Ok(data) => (StatusCode::OK, Json(RBuilder::success(data))),
+        Err(error) => {
+            tracing::error!("failed to reload the service config: {error:#}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(RBuilder::other_error(Cow::Owned(format!("{error:#}")))),
+            )
+        }
+    }
+}
@@ -34,6 +34,8 @@ pub async fn status(State(state): State<AppState>) -> (StatusCode, Json<StatusRe
             service_dir: crate::utils::dirs::service_logs_dir(),
             core_dir: state.core_manager.core_log_dir(),
         }),
+        core_binary: state.core_manager.core_binary(),
+        service_config: Some(state.service_config.report()),
     });
 
     (StatusCode::OK, Json(res))
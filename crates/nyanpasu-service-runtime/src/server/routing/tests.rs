@@ -2,6 +2,7 @@ use std::{borrow::Cow, sync::Arc};
 
 use axum::{
     body::{Body, to_bytes},
+    extract::ConnectInfo,
     http::{
         Method, Request, StatusCode,
         header::{
@@ -11,29 +12,46 @@ use axum::{
     response::Response,
 };
 use camino::Utf8PathBuf;
-use nyanpasu_core_manager::LocalIpcPolicy;
-use nyanpasu_ipc::api::{
-    ResponseCode,
-    contract::{
-        CoreApply, CoreCheck, CoreRecover, CoreRestart, CoreStart, CoreStop, IpcOperation,
-        LogsInspect, LogsRetrieve, NetworkSetDns, Status as StatusOp,
-    },
-    core::{
-        apply::{CoreApplyReq, CoreApplyRes},
-        check::{CoreCheckReq, CoreCheckRes},
-        recover::CoreRecoverRes,
-        stop::{CORE_STOP_ENDPOINT, CoreStopRes},
+use nyanpasu_core_manager::ManagerOptions;
+use nyanpasu_ipc::{
+    api::{
+        ResponseCode,
+        audit::AuditQueryRes,
+        capabilities::{CapabilitiesRes, ServiceFeature},
+        connections::{ConnectionCloseReq, ConnectionCloseRes, ConnectionFilter},
+        contract::{
+            self, Audit, Capabilities, ConnectionsClose, CoreApply, CoreCheck, CoreRecover,
+            CoreRestart, CoreRevisions, CoreStart, IpcOperation, ProxiesSelect, ServiceReload,
+        },
+        core::{
+            apply::{CoreApplyReq, CoreApplyRes},
+            check::{CoreCheckReq, CoreCheckRes},
+            recover::CoreRecoverRes,
+            revisions::CoreRevisionsRes,
+            start::{CoreStartReq, CoreStartRes},
+            stop::{CORE_STOP_ENDPOINT, CoreStopRes},
+        },
+        log::{CoreLogsQueryReq, CoreLogsQueryRes, LOGS_CORE_QUERY_ENDPOINT},
+        proxies::{ProxySelectReq, ProxySelectRes},
+        service_config::{LocalIpcPolicyConfig, SERVICE_CONFIG_FILE_NAME, ServiceReloadRes},
+        status::{CoreState, CoreStateDetail, STATUS_ENDPOINT, StatusRes},
+        ws::events::EVENT_URI,
     },
-    status::{CoreState, CoreStateDetail, STATUS_ENDPOINT, StatusRes},
-    ws::events::EVENT_URI,
+    server::{IpcPeer, PeerCredentials},
 };
 use nyanpasu_utils::core::{ClashCoreType, CoreType};
 use serde::de::DeserializeOwned;
 use tempfile::TempDir;
 use tower::ServiceExt;
 
-use super::{AppState, create_router};
-use crate::server::{CoreManager, EventHub, Logger, consts::RuntimeInfos};
+use super::{AppState, create_router, middleware::DEFAULT_REQUEST_TIMEOUT};
+use crate::{
+    server::{
+        CoreManager, EventHub, Logger, ServiceConfigState, ServiceSettings, audit::AuditLog,
+        consts::RuntimeInfos,
+    },
+    utils::core_manifest::CoreManifest,
+};
 
 struct TestEnv {
     state: AppState,
@@ -42,25 +60,53 @@ struct TestEnv {
 
 impl TestEnv {
     async fn new() -> Self {
+        Self::with_manager_options(|_| {}).await
+    }
+
+    /// The built-in defaults, with `tweak` applied to the manager's options.
+    async fn with_manager_options(tweak: impl FnOnce(&mut ManagerOptions)) -> Self {
         let dir = tempfile::tempdir().unwrap();
         let root = dir.path();
         let runtime_dir =
             Utf8PathBuf::from_path_buf(root.join("core-runtime")).expect("temp path is UTF-8");
-        let core_manager = CoreManager::new(runtime_dir, LocalIpcPolicy::Disable)
-            .await
-            .unwrap();
+        // No `service.toml` in the temp dir: the built-in defaults.
+        let settings =
+            ServiceSettings::load(&root.join("service-config"), LocalIpcPolicyConfig::Disable)
+                .unwrap();
+        let mut manager_options = settings.manager;
+        manager_options.runtime_dir = Some(runtime_dir);
+        tweak(&mut manager_options);
+        let core_manager = CoreManager::new(
+            manager_options,
+            settings.instance,
+            settings.max_concurrent_checks,
+            settings.request_timeout,
+        )
+        .await
+        .unwrap();
         let runtime = Arc::new(RuntimeInfos {
             service_data_dir: root.join("service-data"),
             service_config_dir: root.join("service-config"),
             nyanpasu_config_dir: root.join("nyanpasu-config"),
             nyanpasu_data_dir: root.join("nyanpasu-data"),
             nyanpasu_app_dir: root.join("nyanpasu-app"),
+            core_search_dirs: Vec::new(),
+            core_search_system_path: false,
+            allowed_core_dirs: Vec::new(),
         });
         let state = AppState {
             core_manager,
             hub: EventHub::new(),
             runtime,
             logger: Logger::new(),
+            service_config: Arc::new(ServiceConfigState::new(
+                settings.report,
+                settings.access,
+                settings.install_local_ipc_policy,
+                #[cfg(windows)]
+                tokio::sync::watch::channel(Vec::new()).0,
+            )),
+            audit: Arc::new(AuditLog::new(&root.join("service-data"))),
         };
         Self { state, _dir: dir }
     }
@@ -75,7 +121,7 @@ async fn body_of<T: DeserializeOwned>(response: Response) -> T {
 async fn status_reports_a_stopped_core_and_echoes_the_injected_runtime_dirs() {
     let env = TestEnv::new().await;
     let runtime = env.state.runtime.clone();
-    let response = create_router(env.state.clone())
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .uri(STATUS_ENDPOINT)
@@ -110,12 +156,247 @@ async fn status_reports_a_stopped_core_and_echoes_the_injected_runtime_dirs() {
         body.runtime_infos.nyanpasu_data_dir.as_ref(),
         &runtime.nyanpasu_data_dir
     );
+    let service_config = body.service_config.unwrap();
+    assert!(!service_config.loaded);
+    assert!(service_config.path.starts_with(&runtime.service_config_dir));
+    assert_eq!(
+        service_config.effective.manager.local_ipc_policy,
+        Some(LocalIpcPolicyConfig::Disable)
+    );
+}
+
+/// The live key changes `/status` straight away; the restart-only one is
+/// reported and keeps its old value there.
+#[tokio::test]
+async fn a_reload_applies_live_settings_and_reports_the_rest() {
+    let env = TestEnv::new().await;
+    let service_config_dir = &env.state.runtime.service_config_dir;
+    std::fs::create_dir_all(service_config_dir).unwrap();
+    std::fs::write(
+        service_config_dir.join(SERVICE_CONFIG_FILE_NAME),
+        "[manager]\nstop_timeout_ms = 5000\n\n[events]\nlog_capacity = 4096\n",
+    )
+    .unwrap();
+
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(ServiceReload::PATH)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let envelope: ServiceReloadRes<'static> = body_of(response).await;
+    let data = envelope.data.unwrap();
+    assert_eq!(data.applied, ["events.log_capacity"]);
+    assert_eq!(data.restart_required, ["manager.stop_timeout_ms"]);
+    let reported = env.state.service_config.report();
+    assert!(reported.loaded);
+    assert_eq!(reported.effective.events.log_capacity, 4096);
+    assert_eq!(reported.effective.manager.stop_timeout_ms, 10_000);
+    assert_eq!(reported, data.config);
+}
+
+/// The advertised operations are the contract's, and a feature follows the
+/// service's state rather than being fixed at start.
+#[tokio::test]
+async fn capabilities_advertise_the_contract_and_the_features_in_force() {
+    let env = TestEnv::new().await;
+    let capabilities = |state: AppState| async move {
+        let response = create_router(state, DEFAULT_REQUEST_TIMEOUT)
+            .oneshot(
+                Request::builder()
+                    .uri(Capabilities::PATH)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let envelope: CapabilitiesRes<'static> = body_of(response).await;
+        envelope.data.unwrap()
+    };
+
+    let before = capabilities(env.state.clone()).await;
+    assert_eq!(before.version, crate::consts::APP_VERSION);
+    assert_eq!(before.operations, contract::operations());
+    assert!(before.missing().is_empty());
+    // The test service runs with local IPC disabled and nothing pinned.
+    assert!(!before.features.contains(&ServiceFeature::LocalIpc));
+    assert!(!before.features.contains(&ServiceFeature::PinnedCores));
+
+    let service_config_dir = &env.state.runtime.service_config_dir;
+    std::fs::create_dir_all(service_config_dir).unwrap();
+    std::fs::write(CoreManifest::path(service_config_dir), r#"{"binaries":{}}"#).unwrap();
+    let after = capabilities(env.state.clone()).await;
+    assert!(after.features.contains(&ServiceFeature::PinnedCores));
+}
+
+/// Refused whole: the report still describes what was in force before.
+#[tokio::test]
+async fn an_invalid_reload_changes_nothing() {
+    let env = TestEnv::new().await;
+    let service_config_dir = &env.state.runtime.service_config_dir;
+    std::fs::create_dir_all(service_config_dir).unwrap();
+    std::fs::write(
+        service_config_dir.join(SERVICE_CONFIG_FILE_NAME),
+        "[events]\nlog_capacity = 0\n",
+    )
+    .unwrap();
+    let before = env.state.service_config.report();
+
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(ServiceReload::PATH)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let envelope: ServiceReloadRes<'static> = body_of(response).await;
+    assert_eq!(envelope.code, ResponseCode::OtherError);
+    assert!(
+        envelope.msg.contains("events.log_capacity"),
+        "{}",
+        envelope.msg
+    );
+    assert_eq!(env.state.service_config.report(), before);
+}
+
+/// A request as the listener hands it over for a caller running as `uid`.
+async fn call_as(state: AppState, uid: u32, method: Method, path: &str) -> Response {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(path)
+        .body(Body::empty())
+        .unwrap();
+    request.extensions_mut().insert(ConnectInfo(IpcPeer {
+        endpoint: "test".to_owned(),
+        credentials: Some(PeerCredentials {
+            uid,
+            gid: uid,
+            pid: None,
+        }),
+    }));
+    create_router(state, DEFAULT_REQUEST_TIMEOUT)
+        .oneshot(request)
+        .await
+        .unwrap()
+}
+
+/// The table is reloaded live, and a refused call never reaches its handler.
+#[tokio::test]
+async fn a_caller_below_the_needed_role_is_refused_with_its_kind() {
+    let env = TestEnv::new().await;
+    let service_config_dir = &env.state.runtime.service_config_dir;
+    std::fs::create_dir_all(service_config_dir).unwrap();
+    std::fs::write(
+        service_config_dir.join(SERVICE_CONFIG_FILE_NAME),
+        "[access]\ndefault_role = \"read-only\"\n\n[access.users]\n1000 = \"control\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        probe(env.state.clone(), Method::POST, ServiceReload::PATH).await,
+        StatusCode::OK
+    );
+
+    let status = call_as(env.state.clone(), 1001, Method::GET, STATUS_ENDPOINT).await;
+    assert_eq!(status.status(), StatusCode::OK);
+    let response = call_as(env.state.clone(), 1001, Method::POST, CORE_STOP_ENDPOINT).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let envelope: CoreStopRes<'static> = body_of(response).await;
+    assert_eq!(envelope.code, ResponseCode::OtherError);
+    assert_eq!(envelope.error_kind.as_deref(), Some("permission_denied"));
+    assert!(envelope.msg.contains("control"), "{}", envelope.msg);
+
+    // Past the check, the idle core's own error.
+    let response = call_as(env.state.clone(), 1000, Method::POST, CORE_STOP_ENDPOINT).await;
+    assert_ne!(response.status(), StatusCode::FORBIDDEN);
+    let recover = |uid| call_as(env.state.clone(), uid, Method::POST, CoreRecover::PATH);
+    assert_eq!(recover(1000).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(recover(0).await.status(), StatusCode::OK);
+}
+
+/// Refused calls are recorded like any other, with who made them; reads are
+/// not recorded at all.
+#[tokio::test]
+async fn the_audit_trail_records_control_calls_and_their_callers() {
+    let env = TestEnv::new().await;
+    let service_config_dir = &env.state.runtime.service_config_dir;
+    std::fs::create_dir_all(service_config_dir).unwrap();
+    std::fs::write(
+        service_config_dir.join(SERVICE_CONFIG_FILE_NAME),
+        "[access]\ndefault_role = \"read-only\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        probe(env.state.clone(), Method::POST, ServiceReload::PATH).await,
+        StatusCode::OK
+    );
+    let refused = call_as(env.state.clone(), 1001, Method::POST, CORE_STOP_ENDPOINT).await;
+    assert_eq!(refused.status(), StatusCode::FORBIDDEN);
+    let request_id = refused
+        .headers()
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    call_as(env.state.clone(), 1001, Method::GET, STATUS_ENDPOINT).await;
+
+    let mut request = Request::builder()
+        .method(Method::POST)
+        .uri(Audit::PATH)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"backward":true}"#))
+        .unwrap();
+    request.extensions_mut().insert(ConnectInfo(IpcPeer {
+        endpoint: "test".to_owned(),
+        credentials: Some(PeerCredentials {
+            uid: 0,
+            gid: 0,
+            pid: None,
+        }),
+    }));
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
+        .oneshot(request)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let envelope: AuditQueryRes<'static> = body_of(response).await;
+    let page = envelope.data.unwrap();
+    assert!(page.exhausted);
+    let operations = page
+        .records
+        .iter()
+        .map(|record| record.operation.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(operations, [CORE_STOP_ENDPOINT, ServiceReload::PATH]);
+
+    let stop = &page.records[0];
+    assert_eq!(stop.request_id, request_id);
+    assert_eq!(stop.caller.map(|caller| caller.uid), Some(1001));
+    assert!(!stop.outcome.ok);
+    assert_eq!(stop.outcome.status, 403);
+    assert_eq!(
+        stop.outcome.error_kind.as_deref(),
+        Some("permission_denied")
+    );
+    let reload = &page.records[1];
+    assert!(reload.caller.is_none());
+    assert!(reload.outcome.ok);
 }
 
 #[tokio::test]
 async fn stopping_an_idle_core_keeps_the_legacy_error_envelope() {
     let env = TestEnv::new().await;
-    let response = create_router(env.state.clone())
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .method(Method::POST)
@@ -130,13 +411,14 @@ async fn stopping_an_idle_core_keeps_the_legacy_error_envelope() {
     let envelope: CoreStopRes<'static> = body_of(response).await;
     assert_eq!(envelope.code, ResponseCode::OtherError);
     assert_eq!(envelope.msg, "core is already stopped");
+    assert_eq!(envelope.error_kind.as_deref(), Some("not_started"));
     assert!(envelope.data.is_none());
 }
 
 #[tokio::test]
 async fn restart_before_any_start_reports_the_legacy_error() {
     let env = TestEnv::new().await;
-    let response = create_router(env.state.clone())
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .method(Method::POST)
@@ -151,6 +433,35 @@ async fn restart_before_any_start_reports_the_legacy_error() {
     let envelope: CoreStopRes<'static> = body_of(response).await;
     assert_eq!(envelope.code, ResponseCode::OtherError);
     assert_eq!(envelope.msg, "core have not been started yet");
+    assert_eq!(envelope.error_kind.as_deref(), Some("not_started"));
+    assert!(envelope.data.is_none());
+}
+
+/// Start resolves the config file the way apply does, so a missing one is
+/// classified the same way.
+#[tokio::test]
+async fn starting_from_a_missing_config_reports_its_kind() {
+    let env = TestEnv::new().await;
+    let core_type = CoreType::Clash(ClashCoreType::Mihomo);
+    let config = env.state.runtime.nyanpasu_data_dir.join("missing.yaml");
+
+    let response = post_json(
+        env.state.clone(),
+        CoreStart::PATH,
+        &CoreStartReq {
+            core_type: Cow::Borrowed(&core_type),
+            config_file: Cow::Borrowed(&config),
+            binary_path: None,
+            distribution: None,
+            policy: None,
+        },
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let envelope: CoreStartRes<'static> = body_of(response).await;
+    assert_eq!(envelope.code, ResponseCode::OtherError);
+    assert_eq!(envelope.error_kind.as_deref(), Some("config_not_found"));
     assert!(envelope.data.is_none());
 }
 
@@ -164,7 +475,7 @@ async fn two_states_are_independent() {
         second.state.runtime.service_data_dir
     );
 
-    let first_response = create_router(first.state.clone())
+    let first_response = create_router(first.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .uri(STATUS_ENDPOINT)
@@ -173,7 +484,7 @@ async fn two_states_are_independent() {
         )
         .await
         .unwrap();
-    let second_response = create_router(second.state.clone())
+    let second_response = create_router(second.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .uri(STATUS_ENDPOINT)
@@ -203,7 +514,7 @@ async fn two_states_are_independent() {
 /// A body-less POST to `/core/start` is answered 4xx by the extractor, which
 /// still proves the route exists — 404/405 are the only failures here.
 async fn probe(state: AppState, method: Method, path: &str) -> StatusCode {
-    create_router(state)
+    create_router(state, DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .method(method)
@@ -219,19 +530,9 @@ async fn probe(state: AppState, method: Method, path: &str) -> StatusCode {
 #[tokio::test]
 async fn every_operation_is_mounted_where_its_contract_says() {
     let env = TestEnv::new().await;
-    let addresses = [
-        (StatusOp::METHOD, StatusOp::PATH),
-        (CoreStart::METHOD, CoreStart::PATH),
-        (CoreStop::METHOD, CoreStop::PATH),
-        (CoreRestart::METHOD, CoreRestart::PATH),
-        (CoreApply::METHOD, CoreApply::PATH),
-        (CoreCheck::METHOD, CoreCheck::PATH),
-        (CoreRecover::METHOD, CoreRecover::PATH),
-        (LogsRetrieve::METHOD, LogsRetrieve::PATH),
-        (LogsInspect::METHOD, LogsInspect::PATH),
-        (NetworkSetDns::METHOD, NetworkSetDns::PATH),
-    ];
-    for (method, path) in addresses {
+    for operation in contract::operations() {
+        let method = Method::from_bytes(operation.method.as_bytes()).unwrap();
+        let path = operation.path.as_str();
         let status = probe(env.state.clone(), method, path).await;
         assert_ne!(status, StatusCode::NOT_FOUND, "{path} is not mounted");
         assert_ne!(
@@ -245,7 +546,7 @@ async fn every_operation_is_mounted_where_its_contract_says() {
 #[tokio::test]
 async fn an_unknown_path_answers_with_the_envelope() {
     let env = TestEnv::new().await;
-    let response = create_router(env.state.clone())
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .uri("/does/not/exist")
@@ -264,7 +565,7 @@ async fn an_unknown_path_answers_with_the_envelope() {
 #[tokio::test]
 async fn a_wrong_method_answers_with_the_envelope() {
     let env = TestEnv::new().await;
-    let response = create_router(env.state.clone())
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .method(Method::POST)
@@ -286,7 +587,7 @@ async fn responses_carry_a_request_id() {
     let env = TestEnv::new().await;
     let header = HeaderName::from_static("x-request-id");
 
-    let generated = create_router(env.state.clone())
+    let generated = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .uri(STATUS_ENDPOINT)
@@ -297,7 +598,7 @@ async fn responses_carry_a_request_id() {
         .unwrap();
     assert!(generated.headers().contains_key(&header));
 
-    let echoed = create_router(env.state.clone())
+    let echoed = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .uri(STATUS_ENDPOINT)
@@ -313,7 +614,7 @@ async fn responses_carry_a_request_id() {
 #[tokio::test]
 async fn status_projects_the_new_fields_from_the_manager_snapshot() {
     let env = TestEnv::new().await;
-    let response = create_router(env.state.clone())
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .uri(STATUS_ENDPOINT)
@@ -341,7 +642,7 @@ async fn status_projects_the_new_fields_from_the_manager_snapshot() {
 
 /// A JSON POST through the production router.
 async fn post_json<T: serde::Serialize>(state: AppState, path: &str, payload: &T) -> Response {
-    create_router(state)
+    create_router(state, DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .method(Method::POST)
@@ -354,6 +655,44 @@ async fn post_json<T: serde::Serialize>(state: AppState, path: &str, payload: &T
         .unwrap()
 }
 
+/// A fresh service has an archive directory and nothing in it yet: the query
+/// succeeds with an empty, exhausted page rather than failing.
+#[tokio::test]
+async fn an_empty_core_log_archive_answers_with_an_empty_page() {
+    let env = TestEnv::new().await;
+    let response = post_json(
+        env.state.clone(),
+        LOGS_CORE_QUERY_ENDPOINT,
+        &CoreLogsQueryReq::default(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let envelope: CoreLogsQueryRes<'static> = body_of(response).await;
+    let page = envelope.data.unwrap();
+    assert!(page.frames.is_empty());
+    assert!(page.exhausted);
+    assert_eq!(page.dropped, 0);
+}
+
+/// Without an archive the query is refused with its own kind and a 409, so a
+/// client can tell "nothing to read" apart from a read that failed.
+#[tokio::test]
+async fn a_disabled_core_log_archive_is_refused_with_its_kind() {
+    let env = TestEnv::with_manager_options(|options| options.log_sink_enabled = false).await;
+    let response = post_json(
+        env.state.clone(),
+        LOGS_CORE_QUERY_ENDPOINT,
+        &CoreLogsQueryReq::default(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let envelope: CoreLogsQueryRes<'static> = body_of(response).await;
+    assert_eq!(envelope.code, ResponseCode::OtherError);
+    assert_eq!(envelope.error_kind.as_deref(), Some("log_archive_disabled"));
+}
+
 /// `apply` refuses a stopped core instead of starting one, and the refusal
 /// carries both the legacy string and the new machine-readable kind.
 ///
@@ -375,8 +714,12 @@ async fn applying_to_a_stopped_core_reports_not_started_with_its_kind() {
         CoreApply::PATH,
         &CoreApplyReq {
             core_type: Cow::Borrowed(&core_type),
-            config_file: Cow::Borrowed(&config),
+            config_file: Some(Cow::Borrowed(&config)),
+            config: None,
             expected_revision: None,
+            binary_path: None,
+            distribution: None,
+            policy: None,
         },
     )
     .await;
@@ -389,6 +732,86 @@ async fn applying_to_a_stopped_core_reports_not_started_with_its_kind() {
     assert!(envelope.data.is_none());
 }
 
+/// A service that has run nothing has nothing to roll back to, and lists that
+/// as an empty history rather than an error.
+#[tokio::test]
+async fn a_fresh_service_lists_an_empty_revision_history() {
+    let env = TestEnv::new().await;
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri(CoreRevisions::PATH)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let envelope: CoreRevisionsRes<'static> = body_of(response).await;
+    assert_eq!(envelope.code, ResponseCode::Ok);
+    assert!(envelope.data.expect("data").revisions.is_empty());
+}
+
+/// Proxy control has no core to talk to, and says so the way every other
+/// operation on a stopped core does.
+#[tokio::test]
+async fn selecting_a_proxy_without_a_core_reports_not_started() {
+    let env = TestEnv::new().await;
+    let response = post_json(
+        env.state.clone(),
+        ProxiesSelect::PATH,
+        &ProxySelectReq {
+            group: "PROXY".to_owned(),
+            proxy: "DIRECT".to_owned(),
+        },
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let envelope: ProxySelectRes<'static> = body_of(response).await;
+    assert_eq!(envelope.msg, "core have not been started yet");
+    assert_eq!(envelope.error_kind.as_deref(), Some("not_started"));
+}
+
+/// The request's shape is checked before the core is asked anything, so these
+/// fail the same way with or without one running.
+#[tokio::test]
+async fn a_connection_close_takes_ids_or_a_filter_but_not_both() {
+    let env = TestEnv::new().await;
+    let close = |req: ConnectionCloseReq| {
+        let state = env.state.clone();
+        async move {
+            let response = post_json(state, ConnectionsClose::PATH, &req).await;
+            assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+            let envelope: ConnectionCloseRes<'static> = body_of(response).await;
+            envelope.msg.into_owned()
+        }
+    };
+
+    assert_eq!(
+        close(ConnectionCloseReq::default()).await,
+        "one of ids and filter is required"
+    );
+    assert_eq!(
+        close(ConnectionCloseReq {
+            ids: vec!["5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01".to_owned()],
+            filter: Some(ConnectionFilter::default()),
+        })
+        .await,
+        "ids and filter are exclusive; set only one"
+    );
+    assert_eq!(
+        close(ConnectionCloseReq {
+            ids: vec!["not-a-uuid".to_owned()],
+            filter: None,
+        })
+        .await,
+        "not a connection id: not-a-uuid"
+    );
+}
+
 /// An unresolvable config path is answered in the envelope, not by a panic the
 /// catch layer has to convert — and with the kind that says which of the two
 /// paths in the request was the bad one.
@@ -403,7 +826,9 @@ async fn checking_an_unresolvable_config_answers_in_the_envelope() {
         CoreCheck::PATH,
         &CoreCheckReq {
             core_type: Cow::Borrowed(&core_type),
-            config_file: Cow::Borrowed(&missing),
+            config_file: Some(Cow::Borrowed(&missing)),
+            config: None,
+            binary_path: None,
         },
     )
     .await;
@@ -436,8 +861,12 @@ async fn applying_without_a_core_binary_reports_binary_not_found() {
         CoreApply::PATH,
         &CoreApplyReq {
             core_type: Cow::Borrowed(&core_type),
-            config_file: Cow::Borrowed(&config),
+            config_file: Some(Cow::Borrowed(&config)),
+            config: None,
             expected_revision: None,
+            binary_path: None,
+            distribution: None,
+            policy: None,
         },
     )
     .await;
@@ -458,7 +887,7 @@ async fn applying_without_a_core_binary_reports_binary_not_found() {
 #[tokio::test]
 async fn recovering_without_a_quarantine_succeeds() {
     let env = TestEnv::new().await;
-    let response = create_router(env.state.clone())
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .method(Method::POST)
@@ -476,9 +905,10 @@ async fn recovering_without_a_quarantine_succeeds() {
     assert!(envelope.data.is_none());
 }
 
-/// The query string is not protocol: `/ws/events` takes no parameters and must
-/// ignore whatever it is handed — including the duplicated key that a `Query`
-/// extractor would reject with 400, which is the regression this pins.
+/// The query string is barely protocol: `/ws/events` reads only its opt-in
+/// replay parameters and must ignore whatever else it is handed — including the
+/// duplicated key that a `Query` extractor would reject with 400, which is the
+/// regression this pins, and a replay request it cannot parse.
 ///
 /// 426 is as far as it can get here: `tower::oneshot` hands the router no hyper
 /// upgrade state, so `WebSocketUpgrade` rejects with `ConnectionNotUpgradable`
@@ -488,8 +918,13 @@ async fn recovering_without_a_quarantine_succeeds() {
 #[tokio::test]
 async fn the_event_stream_ignores_any_query() {
     let env = TestEnv::new().await;
-    for uri in [EVENT_URI, "/ws/events?v=1&v=2"] {
-        let response = create_router(env.state.clone())
+    for uri in [
+        EVENT_URI,
+        "/ws/events?v=1&v=2",
+        "/ws/events?replay=lots",
+        "/ws/events?replay=20",
+    ] {
+        let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
             .oneshot(
                 Request::builder()
                     .method(Method::GET)
@@ -514,7 +949,7 @@ async fn the_event_stream_ignores_any_query() {
 #[tokio::test]
 async fn the_status_response_reports_the_log_directories() {
     let env = TestEnv::new().await;
-    let response = create_router(env.state.clone())
+    let response = create_router(env.state.clone(), DEFAULT_REQUEST_TIMEOUT)
         .oneshot(
             Request::builder()
                 .uri(STATUS_ENDPOINT)
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use axum::{
     Router,
     extract::{
-        State, WebSocketUpgrade,
+        RawQuery, State, WebSocketUpgrade,
         ws::{Message, WebSocket},
     },
     response::Response,
@@ -11,8 +11,11 @@ use axum::{
 };
 use futures_util::{SinkExt, StreamExt, stream::SplitSink};
 use nyanpasu_core_manager::LogFrame;
-use nyanpasu_ipc::api::ws::events::{EVENT_URI, Event};
-use tokio::sync::broadcast::error::RecvError;
+use nyanpasu_ipc::api::ws::events::{EVENT_URI, Event, EventFilter, EventKind, LogReplay};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    watch,
+};
 
 use super::AppState;
 use crate::server::{CoreManager, events::EventHub};
@@ -22,87 +25,250 @@ pub fn setup() -> Router<AppState> {
     router.route(EVENT_URI, any(ws_handler))
 }
 
-/// One turn of the sender loop. The two rings are read inside `select!` and
-/// every mutation happens after it, so no receiver is reassigned while the
-/// other branch's future is still alive.
+/// One turn of the sender loop. The rings are read inside `select!` and every
+/// mutation happens after it, so no receiver is reassigned while another
+/// branch's future is still alive.
 #[allow(clippy::large_enum_variant)]
 enum Next {
     Send(Event),
     Log(Arc<LogFrame>),
     StatusLag(u64),
     LogLag(u64),
-    Closed,
+    TelemetryLag(u64),
+    /// A config reload resized the ring, so the hub replaced it and closed
+    /// the old one once it was drained. Never the hub going away: this
+    /// connection holds a clone of it, which keeps every sender alive.
+    StatusReplaced,
+    LogReplaced,
+    TelemetryReplaced,
+}
+
+/// The three rings one connection reads, and the hub it re-takes them from.
+struct Subscriptions {
+    hub: EventHub,
+    events: broadcast::Receiver<Event>,
+    logs: broadcast::Receiver<Arc<LogFrame>>,
+    /// Held only while the filter admits telemetry: a subscriber is what keeps
+    /// the sampler polling the core, so a connection that filtered it out
+    /// must not count as one.
+    telemetry: Option<broadcast::Receiver<Event>>,
+}
+
+impl Subscriptions {
+    /// Subscribing *before* the snapshot is read is deliberate: a transition
+    /// landing in between is then delivered twice rather than lost.
+    fn new(
+        hub: EventHub,
+        replay: Option<LogReplay>,
+        filter: &EventFilter,
+    ) -> (Option<Vec<Arc<LogFrame>>>, Self) {
+        let events = hub.subscribe();
+        let (replayed, logs) = match replay {
+            Some(replay) => {
+                let (frames, logs) = hub.subscribe_logs_with_replay(replay);
+                (Some(frames), logs)
+            }
+            None => (None, hub.subscribe_logs()),
+        };
+        let telemetry = filter
+            .wants(EventKind::Telemetry)
+            .then(|| hub.subscribe_telemetry());
+        let subscriptions = Self {
+            hub,
+            events,
+            logs,
+            telemetry,
+        };
+        (replayed, subscriptions)
+    }
+
+    /// Take or drop the telemetry ring after the filter changed.
+    fn follow(&mut self, filter: &EventFilter) {
+        let wanted = filter.wants(EventKind::Telemetry);
+        match (wanted, self.telemetry.is_some()) {
+            (true, false) => self.telemetry = Some(self.hub.subscribe_telemetry()),
+            (false, true) => self.telemetry = None,
+            _ => {}
+        }
+    }
+
+    /// The next thing the sender has to act on. A lagged ring is skipped to
+    /// its tail and a replaced one is taken afresh from the hub before this
+    /// returns, so the caller only decides what the client is told.
+    async fn next(&mut self) -> Next {
+        // Unbiased on purpose: no stream may starve another.
+        let next = tokio::select! {
+            received = self.events.recv() => match received {
+                Ok(event) => Next::Send(event),
+                Err(RecvError::Lagged(skipped)) => Next::StatusLag(skipped),
+                Err(RecvError::Closed) => Next::StatusReplaced,
+            },
+            received = self.logs.recv() => match received {
+                Ok(frame) => Next::Log(frame),
+                Err(RecvError::Lagged(skipped)) => Next::LogLag(skipped),
+                Err(RecvError::Closed) => Next::LogReplaced,
+            },
+            // Telemetry events are already events; they go through the
+            // same filtered send as status.
+            received = recv_telemetry(&mut self.telemetry) => match received {
+                Ok(event) => Next::Send(event),
+                Err(RecvError::Lagged(skipped)) => Next::TelemetryLag(skipped),
+                Err(RecvError::Closed) => Next::TelemetryReplaced,
+            },
+        };
+        // The receiver skips the backlog, so a full ring cannot spin the
+        // loop in Lagged.
+        match next {
+            Next::StatusLag(_) => self.events = self.events.resubscribe(),
+            Next::LogLag(_) => self.logs = self.logs.resubscribe(),
+            Next::TelemetryLag(_) => {
+                if let Some(telemetry) = &mut self.telemetry {
+                    *telemetry = telemetry.resubscribe();
+                }
+            }
+            Next::StatusReplaced => self.events = self.hub.subscribe(),
+            Next::LogReplaced => self.logs = self.hub.subscribe_logs(),
+            Next::TelemetryReplaced => self.telemetry = Some(self.hub.subscribe_telemetry()),
+            Next::Send(_) | Next::Log(_) => {}
+        }
+        next
+    }
+}
+
+/// The telemetry ring, or a branch that never fires when there is none.
+async fn recv_telemetry(
+    telemetry: &mut Option<broadcast::Receiver<Event>>,
+) -> Result<Event, RecvError> {
+    match telemetry {
+        Some(telemetry) => telemetry.recv().await,
+        None => std::future::pending().await,
+    }
 }
 
 /// One protocol, no negotiation: the service binary ships with the program that
 /// consumes it, so there is no client to shield from a variant it cannot decode.
-/// The query string is not extracted at all — routing ignores it, and so do we.
-async fn ws_handler(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
-    ws.on_upgrade(move |socket| handle_socket(socket, state.hub, state.core_manager))
+/// The query string is read raw rather than through `Query`, which would reject
+/// a malformed one with 400: the only thing in it is an opt-in [`LogReplay`],
+/// and a request for one the service cannot read is served without it.
+async fn ws_handler(
+    State(state): State<AppState>,
+    RawQuery(query): RawQuery,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let replay = query.as_deref().and_then(LogReplay::from_query);
+    ws.on_upgrade(move |socket| handle_socket(socket, state.hub, state.core_manager, replay))
 }
 
-async fn handle_socket(socket: WebSocket, hub: EventHub, core_manager: CoreManager) {
+async fn handle_socket(
+    socket: WebSocket,
+    hub: EventHub,
+    core_manager: CoreManager,
+    replay: Option<LogReplay>,
+) {
+    // The receive half's only job is the client's filter, handed to the sender
+    // through a watch so the sender always reads the latest one and never
+    // waits for it.
+    let (filter_tx, mut filter_rx) = watch::channel(EventFilter::default());
     // The subscriptions live and die with this task; there is no registry to
-    // insert into and no id to collide with. Subscribing *before* the snapshot
-    // is read is deliberate: a transition landing in between is then delivered
-    // twice rather than lost.
-    let mut events = hub.subscribe();
-    let mut logs = hub.subscribe_logs();
+    // insert into and no id to collide with.
+    let (replayed, mut subscriptions) = Subscriptions::new(hub, replay, &filter_rx.borrow());
     let (mut sink, mut stream) = socket.split();
 
-    let handler = async { while let Some(Ok(_)) = stream.next().await {} };
+    let handler = async {
+        while let Some(Ok(message)) = stream.next().await {
+            let decoded = match &message {
+                Message::Text(text) => serde_json::from_str::<EventFilter>(text.as_str()),
+                Message::Binary(bytes) => serde_json::from_slice::<EventFilter>(bytes),
+                _ => continue,
+            };
+            match decoded {
+                Ok(filter) => {
+                    filter_tx.send_replace(filter);
+                }
+                // The old filter stays: a client that sent garbage keeps the
+                // subscription it had rather than silently widening to all.
+                Err(error) => tracing::debug!("ignoring an undecodable ws filter: {error}"),
+            }
+        }
+    };
 
     let sender = async {
         // Snapshot-on-connect, for everyone: the socket's first frame is the
         // current status, so a client never has to poll `/status` to find out
-        // what it reconnected to. There is no equivalent for logs — a log
-        // stream has no "current value", and the history lives in the JSONL
-        // archive whose directory `/status` reports.
+        // what it reconnected to. Logs have no "current value", so their
+        // equivalent is opt-in: the recent frames the connection asked for,
+        // then the marker that ends them.
         if !send_snapshot(&mut sink, &core_manager).await {
             return;
         }
+        if let Some(frames) = replayed {
+            let replayed = u32::try_from(frames.len()).unwrap_or(u32::MAX);
+            for frame in frames {
+                if !send_event(&mut sink, &Event::new_core_log(frame)).await {
+                    return;
+                }
+            }
+            if !send_event(&mut sink, &Event::new_core_log_replay_end(replayed)).await {
+                return;
+            }
+        }
         loop {
-            // Unbiased on purpose: neither stream may starve the other.
+            // A new filter may take or drop the telemetry ring, so it is
+            // acted on as soon as it lands rather than with the next event.
             let next = tokio::select! {
-                received = events.recv() => match received {
-                    Ok(event) => Next::Send(event),
-                    Err(RecvError::Lagged(skipped)) => Next::StatusLag(skipped),
-                    Err(RecvError::Closed) => Next::Closed,
-                },
-                received = logs.recv() => match received {
-                    Ok(frame) => Next::Log(frame),
-                    Err(RecvError::Lagged(skipped)) => Next::LogLag(skipped),
-                    Err(RecvError::Closed) => Next::Closed,
-                },
+                next = subscriptions.next() => next,
+                changed = filter_rx.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    subscriptions.follow(&filter_rx.borrow_and_update());
+                    continue;
+                }
             };
             match next {
+                // Each filter is read into a `bool` before the send: the watch
+                // guard must not live across an await, where it would hold the
+                // lock the receive half needs to store the next filter.
                 Next::Send(event) => {
-                    if !send_event(&mut sink, &event).await {
+                    let admitted = filter_rx.borrow().admits(&event);
+                    if admitted && !send_event(&mut sink, &event).await {
                         break;
                     }
                 }
                 // The ring carries frames, so the envelope is built here, once
-                // per connection that is actually listening.
+                // per connection that is actually listening — and only for a
+                // frame its filter lets through.
                 Next::Log(frame) => {
-                    if !send_event(&mut sink, &Event::new_core_log(frame)).await {
+                    let admitted = filter_rx.borrow().admits_log(&frame);
+                    if admitted && !send_event(&mut sink, &Event::new_core_log(frame)).await {
                         break;
                     }
                 }
-                // Only this connection pays for being slow. Warn once, then
-                // jump to the live tail: the receiver skips the backlog, so a
-                // full ring cannot spin us in a Lagged loop. Until L3 this line
+                // Only this connection pays for being slow. Warn once; the
+                // receiver is already at the live tail. Until L3 this line
                 // needed a dedicated tracing target, because it would otherwise
                 // have re-entered the very ring it had just overflowed. No
                 // tracing output becomes an event now, so it is an ordinary log
                 // line.
                 Next::StatusLag(skipped) => {
                     tracing::warn!("ws subscriber dropped {skipped} events");
-                    events = events.resubscribe();
                     // The gap may have swallowed a transition, so the client is
                     // resynchronised exactly as it was on connect. This is what
                     // the snapshot variant is for: nobody has to poll `/status`
-                    // after a lag.
-                    if !send_snapshot(&mut sink, &core_manager).await {
+                    // after a lag. A connection that filtered status out has
+                    // nothing to resynchronise.
+                    let wanted = filter_rx.borrow().wants(EventKind::Status);
+                    if wanted && !send_snapshot(&mut sink, &core_manager).await {
+                        break;
+                    }
+                }
+                // Whatever the new ring carried before this connection took
+                // it was never seen here, so this is a gap like a lag's, and
+                // is closed the same way. The connection itself stays up.
+                Next::StatusReplaced => {
+                    tracing::debug!("ws subscriber moved to a resized status ring");
+                    let wanted = filter_rx.borrow().wants(EventKind::Status);
+                    if wanted && !send_snapshot(&mut sink, &core_manager).await {
                         break;
                     }
                 }
@@ -112,11 +278,16 @@ async fn handle_socket(socket: WebSocket, hub: EventHub, core_manager: CoreManag
                 // core into a resynchronisation loop.
                 Next::LogLag(skipped) => {
                     tracing::debug!("ws subscriber dropped {skipped} core log frames");
-                    logs = logs.resubscribe();
                 }
-                // Both rings belong to the same hub, so either closing means
-                // the service is going away.
-                Next::Closed => break,
+                // Nothing to resend either: the next sample supersedes every
+                // one that was skipped.
+                Next::TelemetryLag(skipped) => {
+                    tracing::debug!("ws subscriber dropped {skipped} telemetry samples");
+                }
+                // As with a lag, lines or samples lost in the move stay lost.
+                Next::LogReplaced | Next::TelemetryReplaced => {
+                    tracing::debug!("ws subscriber moved to a resized ring");
+                }
             }
         }
     };
@@ -152,3 +323,80 @@ async fn send_event(sink: &mut SplitSink<WebSocket, Message>, event: &Event) ->
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nyanpasu_ipc::api::{status::CoreState, ws::events::MemorySample};
+
+    use super::*;
+    use crate::server::events::EventCapacities;
+
+    /// A reload that resizes every ring moves a connection onto the new ones
+    /// instead of ending it: each replaced ring reports once, and what is sent
+    /// afterwards still arrives.
+    #[tokio::test]
+    async fn a_subscription_survives_a_resize() {
+        let hub = EventHub::new();
+        let (_, mut subscriptions) = Subscriptions::new(hub.clone(), None, &EventFilter::default());
+
+        hub.resize(EventCapacities {
+            status: 8,
+            log: 8,
+            log_backlog: 8,
+            telemetry: 8,
+        });
+        let mut replaced = Vec::new();
+        for _ in 0..3 {
+            match subscriptions.next().await {
+                Next::StatusReplaced => replaced.push("status"),
+                Next::LogReplaced => replaced.push("log"),
+                Next::TelemetryReplaced => replaced.push("telemetry"),
+                _ => panic!("only the replaced rings have anything to report"),
+            }
+        }
+        replaced.sort_unstable();
+        assert_eq!(replaced, ["log", "status", "telemetry"]);
+
+        hub.send(Event::new_core_state_changed(CoreState::Running));
+        assert!(matches!(
+            subscriptions.next().await,
+            Next::Send(Event::CoreStateChanged(CoreState::Running))
+        ));
+        hub.send_telemetry(Event::new_memory(MemorySample {
+            epoch: 1,
+            in_use: 1,
+            os_limit: 0,
+        }));
+        assert!(matches!(
+            subscriptions.next().await,
+            Next::Send(Event::Memory(_))
+        ));
+    }
+    /// A connection whose filter leaves telemetry out is no telemetry
+    /// subscriber, and becomes one once a new filter lets it back in.
+    #[tokio::test]
+    async fn the_telemetry_ring_follows_the_filter() {
+        let hub = EventHub::new();
+        let status_only = EventFilter {
+            kinds: Some(vec![EventKind::Status]),
+            ..Default::default()
+        };
+        let (_, mut subscriptions) = Subscriptions::new(hub.clone(), None, &status_only);
+        assert!(!hub.has_telemetry_subscribers());
+
+        subscriptions.follow(&EventFilter::default());
+        assert!(hub.has_telemetry_subscribers());
+        hub.send_telemetry(Event::new_memory(MemorySample {
+            epoch: 1,
+            in_use: 1,
+            os_limit: 0,
+        }));
+        assert!(matches!(
+            subscriptions.next().await,
+            Next::Send(Event::Memory(_))
+        ));
+
+        subscriptions.follow(&status_only);
+        assert!(!hub.has_telemetry_subscribers());
+    }
+}
@@ -0,0 +1,156 @@
+//! The pinned core manifest: the SHA-256 each core binary must hash to before
+//! the service will run it.
+//!
+//! The service runs cores with its own privileges, and the default lookup
+//! finds them in the user-writable nyanpasu data dir. The manifest lives in the
+//! service config dir, which only an administrator can write, and only the
+//! elevated `pin-core` command writes it. Verification is opt-in: with no
+//! manifest file every binary the lookup finds runs, as before; once one
+//! exists, a binary it does not list is refused, and one it does is copied
+//! out of the user's reach before it runs.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const MANIFEST_FILE_NAME: &str = "core-manifest.json";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoreManifest {
+    /// Canonical binary path to its lowercase hex SHA-256.
+    pub binaries: BTreeMap<PathBuf, String>,
+}
+
+impl CoreManifest {
+    pub fn path(service_config_dir: &Path) -> PathBuf {
+        service_config_dir.join(MANIFEST_FILE_NAME)
+    }
+
+    /// `None` when there is no manifest, i.e. verification is off. A manifest
+    /// that exists but does not parse is an error, never `None`: failing open
+    /// on a corrupted file would defeat the point of pinning.
+    pub fn load(service_config_dir: &Path) -> io::Result<Option<Self>> {
+        let bytes = match std::fs::read(Self::path(service_config_dir)) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Written to a sibling file and renamed over the old one, so the service
+    /// never reads half a manifest.
+    pub fn save(&self, service_config_dir: &Path) -> io::Result<()> {
+        let path = Self::path(service_config_dir);
+        let staging = path.with_extension("json.tmp");
+        let bytes = serde_json::to_vec_pretty(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        std::fs::write(&staging, bytes)?;
+        std::fs::rename(&staging, &path)
+    }
+
+    pub fn pinned(&self, binary: &Path) -> Option<&str> {
+        self.binaries.get(binary).map(String::as_str)
+    }
+}
+
+/// Lowercase hex SHA-256 of the file at `path`, streamed rather than read
+/// whole: core binaries run to tens of megabytes.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex(hasher))
+}
+
+/// Copies `source` to a new file at `dest` and returns the SHA-256 of exactly
+/// the bytes written. One open file is both hashed and copied, so replacing
+/// `source` mid-way cannot make the digest vouch for other bytes than the copy
+/// holds. The copy is executable and fails if `dest` already exists.
+pub fn sha256_copy(source: &Path, dest: &Path) -> io::Result<String> {
+    let mut file = std::fs::File::open(source)?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o755);
+    let mut copy = options.open(dest)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        copy.write_all(&buffer[..read])?;
+    }
+    copy.sync_all()?;
+    Ok(hex(hasher))
+}
+
+fn hex(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_digest_is_lowercase_hex_sha256() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("core");
+        std::fs::write(&path, b"").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn a_copy_carries_the_digest_of_what_it_holds() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("core");
+        let dest = dir.path().join("copy");
+        std::fs::write(&source, b"core").unwrap();
+
+        let digest = sha256_copy(&source, &dest).unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), b"core");
+        assert_eq!(digest, sha256_file(&dest).unwrap());
+        assert!(sha256_copy(&source, &dest).is_err(), "never overwrites");
+    }
+
+    #[test]
+    fn a_missing_manifest_is_none_but_a_corrupt_one_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(CoreManifest::load(dir.path()).unwrap(), None);
+
+        let mut manifest = CoreManifest::default();
+        manifest
+            .binaries
+            .insert(PathBuf::from("/usr/bin/mihomo"), "00".repeat(32));
+        manifest.save(dir.path()).unwrap();
+        assert_eq!(CoreManifest::load(dir.path()).unwrap(), Some(manifest));
+
+        std::fs::write(CoreManifest::path(dir.path()), b"{").unwrap();
+        assert!(CoreManifest::load(dir.path()).is_err());
+    }
+}
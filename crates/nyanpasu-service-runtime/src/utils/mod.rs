@@ -4,6 +4,7 @@ use tracing_panic::panic_hook;
 
 #[cfg(windows)]
 pub mod acl;
+pub mod core_manifest;
 pub mod dirs;
 pub mod os;
 pub mod service;
@@ -0,0 +1,86 @@
+//! Who may call what over the socket.
+//!
+//! Each address needs a [`Role`]: an operation's own
+//! [`IpcOperation::ROLE`](contract::IpcOperation::ROLE), and `read-only` for
+//! the `/ws/events` stream. The service config's `[access]` table says which
+//! role each caller has and may raise or lower what an address needs; a call
+//! its caller's role does not reach is refused with
+//! `error_kind = "permission_denied"` before any handler runs.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{contract, ws::events::EVENT_URI};
+
+/// What a caller may do. Each role includes the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    /// Watch: status, logs, events and the proxy and connection lists.
+    ReadOnly,
+    /// Run cores: start, stop, apply, roll back, and drive the running core's
+    /// proxies and connections.
+    Control,
+    /// Change the host or the service itself: DNS, recovery, reloading the
+    /// service config.
+    Admin,
+}
+
+impl Role {
+    /// The spelling the `[access]` table uses.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReadOnly => "read-only",
+            Self::Control => "control",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+/// What `/ws/events` needs unless the service config says otherwise.
+pub const EVENTS_ROLE: Role = Role::ReadOnly;
+
+/// The role every address needs unless the service config says otherwise,
+/// keyed by path: paths are unique across the contract, so an `[access]`
+/// override can name an operation by its path alone.
+pub fn default_roles() -> BTreeMap<String, Role> {
+    contract::registry()
+        .into_iter()
+        .map(|(operation, role)| (operation.path, role))
+        .chain([(EVENT_URI.to_owned(), EVENTS_ROLE)])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_address_has_a_role() {
+        let roles = default_roles();
+        assert_eq!(roles.len(), contract::operations().len() + 1);
+        assert_eq!(roles["/status"], Role::ReadOnly);
+        assert_eq!(roles[EVENT_URI], Role::ReadOnly);
+        assert_eq!(roles["/core/stop"], Role::Control);
+        assert_eq!(roles["/network/set_dns"], Role::Admin);
+    }
+
+    #[test]
+    fn each_role_includes_the_ones_before_it() {
+        assert!(Role::ReadOnly < Role::Control);
+        assert!(Role::Control < Role::Admin);
+    }
+
+    #[test]
+    fn the_spelling_matches_the_wire() {
+        for role in [Role::ReadOnly, Role::Control, Role::Admin] {
+            assert_eq!(
+                serde_json::to_string(&role).unwrap(),
+                format!("\"{}\"", role.as_str())
+            );
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! The audit trail: one record per control-plane call, kept in an
+//! append-only JSONL file in the service data dir.
+//!
+//! A record is written once the call has been answered, refused ones
+//! included, so a core that stopped can be traced to the client that stopped
+//! it. Once the file reaches [`AUDIT_MAX_BYTES`] it is set aside and a new
+//! one started; the oldest set-aside file goes once there are more than
+//! [`AUDIT_MAX_FILES`] in all.
+
+use std::{net::IpAddr, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{R, connections::ConnectionFilter, status::RevisionIdInfo};
+
+pub const AUDIT_ENDPOINT: &str = "/audit";
+
+/// The file name, inside the service data dir.
+pub const AUDIT_FILE_NAME: &str = "audit.jsonl";
+
+/// The size past which the file is rotated. It can overshoot by one record.
+pub const AUDIT_MAX_BYTES: u64 = 4 * 1024 * 1024;
+/// Files kept, the one being written included.
+pub const AUDIT_MAX_FILES: usize = 5;
+
+/// Page size used when [`AuditQueryReq::limit`] is absent.
+pub const AUDIT_DEFAULT_LIMIT: u32 = 100;
+/// Larger limits are clamped to this rather than refused.
+pub const AUDIT_MAX_LIMIT: u32 = 1000;
+
+/// Who made a call, as the socket reported it. Only Linux reports one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AuditCaller {
+    pub uid: u32,
+    pub gid: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+}
+
+/// The parts of a request that say what it was asked to do. Each is present
+/// only when the operation takes it; an inline config is never copied here.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AuditRequestSummary {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "Option<crate::api::core::CoreTypeSchema>")
+    )]
+    pub core_type: Option<nyanpasu_utils::core::CoreType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_revision: Option<RevisionIdInfo>,
+    /// The revision `/core/rollback` was asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<RevisionIdInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_servers: Option<Vec<IpAddr>>,
+    /// The group and member `/proxies/select` was asked to pick.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// What `/connections/close` was asked to close.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_ids: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_filter: Option<ConnectionFilter>,
+}
+
+/// How the call was answered, from its response envelope.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AuditOutcome {
+    pub ok: bool,
+    /// The HTTP status the call was answered with.
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<String>,
+    /// The envelope's `msg`, on failure only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub msg: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AuditRecord {
+    /// When the call was answered, unix milliseconds.
+    pub at: i64,
+    /// The `x-request-id` the caller was answered with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// The operation's path, `/core/stop` and the like.
+    pub operation: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caller: Option<AuditCaller>,
+    pub request: AuditRequestSummary,
+    pub outcome: AuditOutcome,
+}
+
+/// A page request against the audit trail. An empty body returns the oldest
+/// page. Absent fields are omitted from the wire.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AuditQueryReq {
+    /// Where to continue from: the `cursor` of the previous page, sent with the
+    /// same `backward`. Absent starts at the oldest record going forward and at
+    /// the newest going backward.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u64>,
+    /// Defaults to [`AUDIT_DEFAULT_LIMIT`], clamped to [`AUDIT_MAX_LIMIT`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Walk newest first.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub backward: bool,
+}
+
+/// One page of the audit trail. The cursor counts records from the first the
+/// service ever wrote, so it stays valid as records are appended and old ones
+/// rotated away; a cursor older than the oldest record kept resumes there.
+/// Paging forward to `exhausted` and polling with the returned `cursor` tails
+/// the trail.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AuditQueryData {
+    /// Oldest first going forward, newest first going backward.
+    pub records: Vec<AuditRecord>,
+    pub cursor: u64,
+    /// Nothing further in this direction — for now, going forward.
+    pub exhausted: bool,
+}
+
+pub type AuditQueryRes<'a> = R<'a, AuditQueryData>;
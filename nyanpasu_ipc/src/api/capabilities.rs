@@ -0,0 +1,162 @@
+//! What a running service speaks, so a client can find out at connect time
+//! instead of from a 404 halfway through a session.
+//!
+//! The lists are built from the same declarations the client and server are
+//! built from — [`contract::operations`], [`EventKind::ALL`],
+//! [`Event::VARIANTS`] and [`error_kind::ALL`] — so a service cannot
+//! advertise something it does not mount.
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{
+    R, contract, error_kind,
+    ws::events::{Event, EventKind},
+};
+
+pub const CAPABILITIES_ENDPOINT: &str = "/capabilities";
+
+/// Bumped with every addition to the wire contract: an operation, an event
+/// variant, an `error_kind` or a [`ServiceFeature`]. Additions only, so a
+/// client needing something specific can still check for it by name; the
+/// revision is for telling a user which side to update.
+pub const PROTOCOL_REVISION: u32 = 4;
+
+/// An operation's wire address.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct OperationInfo {
+    pub method: String,
+    pub path: String,
+}
+
+impl OperationInfo {
+    pub fn of<Op: contract::IpcOperation>() -> Self {
+        Self {
+            method: Op::METHOD.to_string(),
+            path: Op::PATH.to_owned(),
+        }
+    }
+}
+
+/// Optional behavior a service may or may not have turned on, as opposed to
+/// what its protocol revision supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum ServiceFeature {
+    /// Core logs are archived, so `POST /logs/core/query` has pages to serve.
+    CoreLogArchive,
+    /// The service controls cores over local IPC where it can, per its
+    /// local IPC policy.
+    LocalIpc,
+    /// A pinned core manifest is in force: a binary it does not list is
+    /// refused with `error_kind = "binary_untrusted"`.
+    PinnedCores,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CapabilitiesData {
+    /// The service binary's version, as `/status` reports it.
+    pub version: String,
+    pub protocol_revision: u32,
+    pub operations: Vec<OperationInfo>,
+    /// The families an [`EventFilter`](super::ws::events::EventFilter) can
+    /// name.
+    pub event_kinds: Vec<EventKind>,
+    /// The [`Event`] variants `/ws/events` can send, by their wire tag.
+    pub events: Vec<String>,
+    pub error_kinds: Vec<String>,
+    pub features: Vec<ServiceFeature>,
+}
+
+impl CapabilitiesData {
+    /// Everything this copy of the crate declares. The service answers with
+    /// this plus its own version and features.
+    pub fn of_this_build(version: impl Into<String>, features: Vec<ServiceFeature>) -> Self {
+        Self {
+            version: version.into(),
+            protocol_revision: PROTOCOL_REVISION,
+            operations: contract::operations(),
+            event_kinds: EventKind::ALL.to_vec(),
+            events: Event::VARIANTS
+                .iter()
+                .map(|tag| (*tag).to_owned())
+                .collect(),
+            error_kinds: error_kind::ALL
+                .iter()
+                .map(|kind| (*kind).to_owned())
+                .collect(),
+            features,
+        }
+    }
+
+    /// What this copy of the crate declares and the service does not serve.
+    /// Error kinds and features are left out: a kind the service never sends
+    /// or a feature it has off breaks nothing.
+    pub fn missing(&self) -> MissingCapabilities {
+        MissingCapabilities {
+            operations: contract::operations()
+                .into_iter()
+                .filter(|operation| !self.operations.contains(operation))
+                .collect(),
+            event_kinds: EventKind::ALL
+                .into_iter()
+                .filter(|kind| !self.event_kinds.contains(kind))
+                .collect(),
+            events: Event::VARIANTS
+                .iter()
+                .filter(|tag| !self.events.iter().any(|event| event == *tag))
+                .map(|tag| (*tag).to_owned())
+                .collect(),
+        }
+    }
+}
+
+/// See [`CapabilitiesData::missing`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MissingCapabilities {
+    /// Calls to these fail with HTTP 404.
+    pub operations: Vec<OperationInfo>,
+    /// Filtering on these matches nothing.
+    pub event_kinds: Vec<EventKind>,
+    /// These never arrive.
+    pub events: Vec<String>,
+}
+
+impl MissingCapabilities {
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty() && self.event_kinds.is_empty() && self.events.is_empty()
+    }
+}
+
+pub type CapabilitiesRes<'a> = R<'a, CapabilitiesData>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_service_of_the_same_build_is_missing_nothing() {
+        let capabilities = CapabilitiesData::of_this_build("1.0.0", Vec::new());
+        assert!(capabilities.missing().is_empty());
+    }
+
+    #[test]
+    fn an_older_service_is_missing_what_it_does_not_list() {
+        let mut capabilities = CapabilitiesData::of_this_build("1.0.0", Vec::new());
+        let reload = OperationInfo::of::<contract::ServiceReload>();
+        capabilities
+            .operations
+            .retain(|operation| operation != &reload);
+        capabilities.events.retain(|event| event != "Memory");
+
+        let missing = capabilities.missing();
+        assert_eq!(missing.operations, [reload]);
+        assert!(missing.event_kinds.is_empty());
+        assert_eq!(missing.events, ["Memory"]);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::api::R;
+use serde::{Deserialize, Serialize};
+
+pub const CONNECTIONS_ENDPOINT: &str = "/connections";
+pub const CONNECTIONS_CLOSE_ENDPOINT: &str = "/connections/close";
+pub const CONNECTIONS_CLOSE_ALL_ENDPOINT: &str = "/connections/close_all";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionNetwork {
+    Tcp,
+    Udp,
+}
+
+/// Narrows a listing or selects what to close. Every field that is set must
+/// match, and `{}` matches everything. Text fields match case-insensitively
+/// anywhere in the value; a blank one counts as unset.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConnectionFilter {
+    /// The requested host, its sniffed host or the destination IP.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// The matched rule or its payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Any proxy or group the connection went through.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+    /// The originating process name or path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<ConnectionNetwork>,
+}
+
+/// One open connection as the running core reports it.
+///
+/// Only what a connection list needs is carried; the core's metadata object
+/// is core-specific and grows between releases.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConnectionInfo {
+    /// The core's own random UUID; pass it to `POST /connections/close`. It
+    /// carries no epoch: an id from an earlier core matches nothing in the
+    /// next one, so closing it is reported closed and does nothing.
+    pub id: String,
+    /// Absent when the core reported no usable network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<ConnectionNetwork>,
+    /// The requested host, or the sniffed one when the request had none.
+    /// Empty for a connection made to a bare IP.
+    pub host: String,
+    /// `ip:port`.
+    pub destination: String,
+    /// `ip:port`.
+    pub source: String,
+    /// Empty unless the core resolves processes.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub process: String,
+    pub rule: String,
+    pub rule_payload: String,
+    /// The outbound first, the group the rule chose last.
+    pub chains: Vec<String>,
+    /// Bytes, over the connection's lifetime.
+    pub upload: i64,
+    pub download: i64,
+    /// Unix milliseconds.
+    pub start: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConnectionsData {
+    /// The core's totals since it started, whatever the filter.
+    pub upload_total: i64,
+    pub download_total: i64,
+    pub connections: Vec<ConnectionInfo>,
+}
+
+pub type ConnectionsRes<'a> = R<'a, ConnectionsData>;
+
+/// Close by id, or close whatever a filter matches. Exactly one of the two is
+/// set; closing everything is `POST /connections/close_all`, never an empty
+/// filter.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConnectionCloseReq {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<ConnectionFilter>,
+}
+
+/// The ids the request closed. For a close by id this echoes the request,
+/// including ids the core had already forgotten.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConnectionCloseData {
+    pub closed: Vec<String>,
+}
+
+pub type ConnectionCloseRes<'a> = R<'a, ConnectionCloseData>;
+
+pub type ConnectionCloseAllRes<'a> = R<'a, ()>;
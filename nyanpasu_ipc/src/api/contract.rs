@@ -11,7 +11,8 @@
 //! request/response operation, and has no `R` envelope. It keeps its own
 //! constant in [`super::ws::events::EVENT_URI`].
 //!
-//! Written by hand on purpose. Ten impls cost less than a macro to maintain.
+//! Written by hand on purpose. An impl per operation costs less than a macro
+//! to maintain.
 
 use std::fmt::Debug;
 
@@ -20,16 +21,34 @@ use serde::{Serialize, de::DeserializeOwned};
 
 use super::{
     R,
+    access::Role,
+    audit::{AUDIT_ENDPOINT, AuditQueryData, AuditQueryReq},
+    capabilities::{CAPABILITIES_ENDPOINT, CapabilitiesData, OperationInfo},
+    connections::{
+        CONNECTIONS_CLOSE_ALL_ENDPOINT, CONNECTIONS_CLOSE_ENDPOINT, CONNECTIONS_ENDPOINT,
+        ConnectionCloseData, ConnectionCloseReq, ConnectionFilter, ConnectionsData,
+    },
     core::{
-        apply::{CORE_APPLY_ENDPOINT, CoreApplyData},
+        apply::{CORE_APPLY_ENDPOINT, CORE_APPLY_PLAN_ENDPOINT, CoreApplyData, CoreApplyPlanData},
         check::CORE_CHECK_ENDPOINT,
+        config::{CORE_CONFIG_DIFF_ENDPOINT, CoreConfigDiffData},
         recover::CORE_RECOVER_ENDPOINT,
         restart::CORE_RESTART_ENDPOINT,
+        revisions::{CORE_REVISIONS_ENDPOINT, CoreRevisionsData},
+        rollback::{CORE_ROLLBACK_ENDPOINT, CoreRollbackReq},
         start::CORE_START_ENDPOINT,
         stop::CORE_STOP_ENDPOINT,
     },
-    log::{LOGS_INSPECT_ENDPOINT, LOGS_RETRIEVE_ENDPOINT, LogsResBody},
+    log::{
+        CoreLogsQueryData, CoreLogsQueryReq, LOGS_CORE_QUERY_ENDPOINT, LOGS_INSPECT_ENDPOINT,
+        LOGS_RETRIEVE_ENDPOINT, LogsResBody,
+    },
     network::set_dns::{NETWORK_SET_DNS_ENDPOINT, NetworkSetDnsReq},
+    proxies::{
+        PROXIES_DELAY_ENDPOINT, PROXIES_ENDPOINT, PROXIES_SELECT_ENDPOINT, ProxiesData,
+        ProxyDelayData, ProxyDelayReq, ProxySelectReq,
+    },
+    service_config::{SERVICE_RELOAD_ENDPOINT, ServiceReloadData},
     status::{STATUS_ENDPOINT, StatusResBody},
 };
 
@@ -39,6 +58,11 @@ pub trait IpcOperation {
     const METHOD: Method;
     /// The endpoint path. Always one of the `*_ENDPOINT` constants.
     const PATH: &'static str;
+    /// The least role a caller needs, unless the service config's `[access]`
+    /// table says otherwise. Anything that reads a file the client names, or
+    /// spawns a process, is `Control` at least: the service does it with its
+    /// own privileges.
+    const ROLE: Role;
     /// The JSON request body. `()` for the operations that send none — those
     /// are called with `None` and no body is written to the wire.
     type Req<'a>: Serialize;
@@ -55,6 +79,7 @@ pub struct Status;
 impl IpcOperation for Status {
     const METHOD: Method = Method::GET;
     const PATH: &'static str = STATUS_ENDPOINT;
+    const ROLE: Role = Role::ReadOnly;
     type Req<'a> = ();
     type Data = StatusResBody<'static>;
 }
@@ -65,6 +90,7 @@ pub struct CoreStart;
 impl IpcOperation for CoreStart {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = CORE_START_ENDPOINT;
+    const ROLE: Role = Role::Control;
     type Req<'a> = super::core::start::CoreStartReq<'a>;
     type Data = ();
 }
@@ -75,6 +101,7 @@ pub struct CoreStop;
 impl IpcOperation for CoreStop {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = CORE_STOP_ENDPOINT;
+    const ROLE: Role = Role::Control;
     type Req<'a> = ();
     type Data = ();
 }
@@ -85,8 +112,9 @@ pub struct CoreRestart;
 impl IpcOperation for CoreRestart {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = CORE_RESTART_ENDPOINT;
+    const ROLE: Role = Role::Control;
     type Req<'a> = ();
-    type Data = ();
+    type Data = super::core::restart::CoreRestartData;
 }
 
 /// `POST /core/apply`
@@ -95,26 +123,73 @@ pub struct CoreApply;
 impl IpcOperation for CoreApply {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = CORE_APPLY_ENDPOINT;
+    const ROLE: Role = Role::Control;
     type Req<'a> = super::core::apply::CoreApplyReq<'a>;
     type Data = CoreApplyData;
 }
 
+/// `POST /core/apply/plan`
+pub struct CoreApplyPlan;
+
+impl IpcOperation for CoreApplyPlan {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = CORE_APPLY_PLAN_ENDPOINT;
+    const ROLE: Role = Role::Control;
+    type Req<'a> = super::core::apply::CoreApplyPlanReq<'a>;
+    type Data = CoreApplyPlanData;
+}
+
 /// `POST /core/check`
 pub struct CoreCheck;
 
 impl IpcOperation for CoreCheck {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = CORE_CHECK_ENDPOINT;
+    const ROLE: Role = Role::Control;
     type Req<'a> = super::core::check::CoreCheckReq<'a>;
     type Data = ();
 }
 
+/// `POST /core/config/diff`
+pub struct CoreConfigDiff;
+
+impl IpcOperation for CoreConfigDiff {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = CORE_CONFIG_DIFF_ENDPOINT;
+    const ROLE: Role = Role::Control;
+    type Req<'a> = super::core::config::CoreConfigDiffReq<'a>;
+    type Data = CoreConfigDiffData;
+}
+
+/// `GET /core/revisions`
+pub struct CoreRevisions;
+
+impl IpcOperation for CoreRevisions {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = CORE_REVISIONS_ENDPOINT;
+    const ROLE: Role = Role::ReadOnly;
+    type Req<'a> = ();
+    type Data = CoreRevisionsData;
+}
+
+/// `POST /core/rollback`
+pub struct CoreRollback;
+
+impl IpcOperation for CoreRollback {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = CORE_ROLLBACK_ENDPOINT;
+    const ROLE: Role = Role::Control;
+    type Req<'a> = CoreRollbackReq;
+    type Data = CoreApplyData;
+}
+
 /// `POST /core/recover`
 pub struct CoreRecover;
 
 impl IpcOperation for CoreRecover {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = CORE_RECOVER_ENDPOINT;
+    const ROLE: Role = Role::Admin;
     type Req<'a> = ();
     type Data = ();
 }
@@ -125,6 +200,7 @@ pub struct LogsRetrieve;
 impl IpcOperation for LogsRetrieve {
     const METHOD: Method = Method::GET;
     const PATH: &'static str = LOGS_RETRIEVE_ENDPOINT;
+    const ROLE: Role = Role::ReadOnly;
     type Req<'a> = ();
     type Data = LogsResBody<'static>;
 }
@@ -135,20 +211,182 @@ pub struct LogsInspect;
 impl IpcOperation for LogsInspect {
     const METHOD: Method = Method::GET;
     const PATH: &'static str = LOGS_INSPECT_ENDPOINT;
+    const ROLE: Role = Role::ReadOnly;
     type Req<'a> = ();
     type Data = LogsResBody<'static>;
 }
 
+/// `POST /logs/core/query`
+///
+/// `POST` although it reads nothing but logs: the query is a JSON body, like
+/// every other operation's, rather than a query string.
+pub struct LogsCoreQuery;
+
+impl IpcOperation for LogsCoreQuery {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = LOGS_CORE_QUERY_ENDPOINT;
+    const ROLE: Role = Role::ReadOnly;
+    type Req<'a> = CoreLogsQueryReq;
+    type Data = CoreLogsQueryData;
+}
+
 /// `POST /network/set_dns`
 pub struct NetworkSetDns;
 
 impl IpcOperation for NetworkSetDns {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = NETWORK_SET_DNS_ENDPOINT;
+    const ROLE: Role = Role::Admin;
     type Req<'a> = NetworkSetDnsReq<'a>;
     type Data = ();
 }
 
+/// `GET /proxies`
+pub struct Proxies;
+
+impl IpcOperation for Proxies {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = PROXIES_ENDPOINT;
+    const ROLE: Role = Role::ReadOnly;
+    type Req<'a> = ();
+    type Data = ProxiesData;
+}
+
+/// `POST /proxies/select`
+pub struct ProxiesSelect;
+
+impl IpcOperation for ProxiesSelect {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = PROXIES_SELECT_ENDPOINT;
+    const ROLE: Role = Role::Control;
+    type Req<'a> = ProxySelectReq;
+    type Data = ();
+}
+
+/// `POST /proxies/delay`
+pub struct ProxiesDelay;
+
+impl IpcOperation for ProxiesDelay {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = PROXIES_DELAY_ENDPOINT;
+    const ROLE: Role = Role::Control;
+    type Req<'a> = ProxyDelayReq;
+    type Data = ProxyDelayData;
+}
+
+/// `POST /connections`
+///
+/// A POST for the filter body, like `/logs/core/query`; it changes nothing.
+pub struct Connections;
+
+impl IpcOperation for Connections {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = CONNECTIONS_ENDPOINT;
+    const ROLE: Role = Role::ReadOnly;
+    type Req<'a> = ConnectionFilter;
+    type Data = ConnectionsData;
+}
+
+/// `POST /connections/close`
+pub struct ConnectionsClose;
+
+impl IpcOperation for ConnectionsClose {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = CONNECTIONS_CLOSE_ENDPOINT;
+    const ROLE: Role = Role::Control;
+    type Req<'a> = ConnectionCloseReq;
+    type Data = ConnectionCloseData;
+}
+
+/// `POST /connections/close_all`
+pub struct ConnectionsCloseAll;
+
+impl IpcOperation for ConnectionsCloseAll {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = CONNECTIONS_CLOSE_ALL_ENDPOINT;
+    const ROLE: Role = Role::Control;
+    type Req<'a> = ();
+    type Data = ();
+}
+
+/// `POST /service/reload`
+pub struct ServiceReload;
+
+impl IpcOperation for ServiceReload {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = SERVICE_RELOAD_ENDPOINT;
+    const ROLE: Role = Role::Admin;
+    type Req<'a> = ();
+    type Data = ServiceReloadData;
+}
+
+/// `GET /capabilities`
+pub struct Capabilities;
+
+impl IpcOperation for Capabilities {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = CAPABILITIES_ENDPOINT;
+    const ROLE: Role = Role::ReadOnly;
+    type Req<'a> = ();
+    type Data = CapabilitiesData;
+}
+
+/// `POST /audit`
+pub struct Audit;
+
+impl IpcOperation for Audit {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = AUDIT_ENDPOINT;
+    const ROLE: Role = Role::Admin;
+    type Req<'a> = AuditQueryReq;
+    type Data = AuditQueryData;
+}
+
+/// Every operation above, with the role it needs. `/capabilities` advertises
+/// this list, the server's routing test checks each entry is mounted, and the
+/// server reads each operation's role from it, so an operation left out here
+/// is neither advertised, checked nor callable by anyone but an admin.
+pub fn registry() -> Vec<(OperationInfo, Role)> {
+    vec![
+        entry::<Status>(),
+        entry::<CoreStart>(),
+        entry::<CoreStop>(),
+        entry::<CoreRestart>(),
+        entry::<CoreApply>(),
+        entry::<CoreApplyPlan>(),
+        entry::<CoreCheck>(),
+        entry::<CoreConfigDiff>(),
+        entry::<CoreRevisions>(),
+        entry::<CoreRollback>(),
+        entry::<CoreRecover>(),
+        entry::<LogsRetrieve>(),
+        entry::<LogsInspect>(),
+        entry::<LogsCoreQuery>(),
+        entry::<NetworkSetDns>(),
+        entry::<Proxies>(),
+        entry::<ProxiesSelect>(),
+        entry::<ProxiesDelay>(),
+        entry::<Connections>(),
+        entry::<ConnectionsClose>(),
+        entry::<ConnectionsCloseAll>(),
+        entry::<ServiceReload>(),
+        entry::<Capabilities>(),
+        entry::<Audit>(),
+    ]
+}
+
+fn entry<Op: IpcOperation>() -> (OperationInfo, Role) {
+    (OperationInfo::of::<Op>(), Op::ROLE)
+}
+
+/// The addresses in [`registry`], in the same order.
+pub fn operations() -> Vec<OperationInfo> {
+    registry()
+        .into_iter()
+        .map(|(operation, _)| operation)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -202,4 +440,104 @@ mod tests {
             (Method::POST, "/core/recover")
         );
     }
+
+    #[test]
+    fn the_core_log_query_is_addressed_as_documented() {
+        assert_eq!(
+            (LogsCoreQuery::METHOD, LogsCoreQuery::PATH),
+            (Method::POST, "/logs/core/query")
+        );
+    }
+
+    #[test]
+    fn the_apply_plan_is_addressed_as_documented() {
+        assert_eq!(
+            (CoreApplyPlan::METHOD, CoreApplyPlan::PATH),
+            (Method::POST, "/core/apply/plan")
+        );
+    }
+
+    #[test]
+    fn the_config_diff_is_addressed_as_documented() {
+        assert_eq!(
+            (CoreConfigDiff::METHOD, CoreConfigDiff::PATH),
+            (Method::POST, "/core/config/diff")
+        );
+    }
+
+    #[test]
+    fn the_revision_operations_are_addressed_as_documented() {
+        assert_eq!(
+            (CoreRevisions::METHOD, CoreRevisions::PATH),
+            (Method::GET, "/core/revisions")
+        );
+        assert_eq!(
+            (CoreRollback::METHOD, CoreRollback::PATH),
+            (Method::POST, "/core/rollback")
+        );
+    }
+
+    #[test]
+    fn the_proxy_operations_are_addressed_as_documented() {
+        assert_eq!((Proxies::METHOD, Proxies::PATH), (Method::GET, "/proxies"));
+        assert_eq!(
+            (ProxiesSelect::METHOD, ProxiesSelect::PATH),
+            (Method::POST, "/proxies/select")
+        );
+        assert_eq!(
+            (ProxiesDelay::METHOD, ProxiesDelay::PATH),
+            (Method::POST, "/proxies/delay")
+        );
+    }
+
+    #[test]
+    fn the_connection_operations_are_addressed_as_documented() {
+        assert_eq!(
+            (Connections::METHOD, Connections::PATH),
+            (Method::POST, "/connections")
+        );
+        assert_eq!(
+            (ConnectionsClose::METHOD, ConnectionsClose::PATH),
+            (Method::POST, "/connections/close")
+        );
+        assert_eq!(
+            (ConnectionsCloseAll::METHOD, ConnectionsCloseAll::PATH),
+            (Method::POST, "/connections/close_all")
+        );
+    }
+
+    #[test]
+    fn the_service_reload_is_addressed_as_documented() {
+        assert_eq!(
+            (ServiceReload::METHOD, ServiceReload::PATH),
+            (Method::POST, "/service/reload")
+        );
+    }
+
+    #[test]
+    fn the_capabilities_are_addressed_as_documented() {
+        assert_eq!(
+            (Capabilities::METHOD, Capabilities::PATH),
+            (Method::GET, "/capabilities")
+        );
+    }
+
+    #[test]
+    fn the_audit_trail_is_addressed_as_documented() {
+        assert_eq!((Audit::METHOD, Audit::PATH), (Method::POST, "/audit"));
+        assert_eq!(Audit::ROLE, Role::Admin);
+    }
+
+    #[test]
+    fn the_registry_lists_each_address_once() {
+        let operations = operations();
+        for (index, operation) in operations.iter().enumerate() {
+            assert!(
+                !operations[index + 1..].contains(operation),
+                "{} {} is listed twice",
+                operation.method,
+                operation.path
+            );
+        }
+    }
 }
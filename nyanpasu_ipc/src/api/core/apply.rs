@@ -1,11 +1,19 @@
 use crate::api::{
     R,
-    status::{ConfigRevisionInfo, RevisionIdInfo},
+    core::start::CorePolicyOverrides,
+    proxies::RestoredSelectionsInfo,
+    status::{ConfigRevisionInfo, CoreDistribution, RevisionIdInfo},
 };
 use serde::{Deserialize, Serialize};
 use std::{borrow::Cow, path::PathBuf};
 
 pub const CORE_APPLY_ENDPOINT: &str = "/core/apply";
+pub const CORE_APPLY_PLAN_ENDPOINT: &str = "/core/apply/plan";
+
+/// Largest inline `config` [`CoreApplyReq`] and
+/// [`CoreCheckReq`](super::check::CoreCheckReq) accept, in bytes. Bigger
+/// configs go through `config_file`.
+pub const CORE_INLINE_CONFIG_MAX_BYTES: usize = 1024 * 1024;
 
 /// Apply a config to the running core.
 ///
@@ -18,21 +26,47 @@ pub const CORE_APPLY_ENDPOINT: &str = "/core/apply";
 /// one is therefore legal and means "switch to this core".
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CoreApplyReq<'n> {
+    #[cfg_attr(feature = "schemars", schemars(with = "super::CoreTypeSchema"))]
     pub core_type: Cow<'n, nyanpasu_utils::core::CoreType>,
     /// The caller's own config file, and only ever the *source*: the service
     /// commits a canonicalized private copy and the core runs that one.
-    pub config_file: Cow<'n, PathBuf>,
+    /// Exactly one of `config_file` and `config` must be set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_file: Option<Cow<'n, PathBuf>>,
+    /// The source YAML itself, for a caller whose files the service cannot
+    /// read. At most [`CORE_INLINE_CONFIG_MAX_BYTES`]; the revision's
+    /// `source_hash` is the one a file holding the same bytes would get.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<Cow<'n, str>>,
     /// Compare-and-swap token. `None` applies unconditionally; `Some` applies
     /// nothing and fails with `error_kind = "revision_conflict"` when the
     /// running revision has moved on. Omitted from the wire when `None`.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub expected_revision: Option<RevisionIdInfo>,
+    /// As in [`CoreStartReq`](super::start::CoreStartReq). A binary other
+    /// than the running one is a process-spec change, so it switches cores.
+    /// `None` keeps the running binary, or searches for one when the running
+    /// core is of another type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<Cow<'n, PathBuf>>,
+    /// As in [`CoreStartReq`](super::start::CoreStartReq). Changing it alone
+    /// relaunches nothing, and an apply that turns out to be a no-op keeps
+    /// the one already reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<CoreDistribution>,
+    /// As in [`CoreStartReq`](super::start::CoreStartReq). The policy is part
+    /// of the launch, so one different from the running core's switches
+    /// cores. Leaving it out keeps the running core's, overrides included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<CorePolicyOverrides>,
 }
 
 /// How the manager carried the change.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum ApplyOutcomeKind {
     /// The config was already in effect; the core was not touched.
@@ -45,13 +79,12 @@ pub enum ApplyOutcomeKind {
     /// The core process was replaced within the same epoch.
     Restarted,
     /// The process spec itself changed — a different core, binary, or launch
-    /// option — so the old epoch was stopped and a new one started. Distinct
-    /// from [`Self::Restarted`], which replaces the process inside one epoch.
+    /// option — so a new epoch replaced the old one. Distinct from
+    /// [`Self::Restarted`], which replaces the process inside one epoch.
     ///
-    /// This is a hard switch today: the apply path runs a stop → start with
-    /// old-epoch rollback (`manager/apply.rs`, `switch_with_compensation`), not
-    /// the manager's graceful zero-downtime switch, which only
-    /// `restart()`/`switch()` reach.
+    /// The manager overlaps the two epochs whenever it can, so live
+    /// connections survive; [`CoreApplyData::degrade_reason`] is set when it
+    /// could not and stopped the old epoch first.
     Switched,
     /// The apply failed and the previous revision was restored. **The core is
     /// running the OLD config**, `revision` is the old revision, and
@@ -59,6 +92,28 @@ pub enum ApplyOutcomeKind {
     RolledBack,
 }
 
+/// Why a switch could not overlap the old and new epochs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchDegradeReason {
+    /// There was no running epoch to overlap.
+    NotRunning,
+    /// Only mihomo can boot on a bootstrap config and be patched up to the
+    /// full one.
+    UnsupportedKind,
+    /// The config listens for DNS, which two epochs cannot share.
+    DnsListen,
+    /// The config declares inbound listeners that two epochs cannot share.
+    InboundConflict,
+    /// The new epoch booted but rejected the patch to its full config, so it
+    /// was restarted on the full config instead.
+    PatchFailed,
+    /// The controller is an HTTP port, which two epochs cannot share.
+    HttpController,
+}
+
 /// The result of an apply.
 ///
 /// `outcome` is the field to branch on. A rolled-back apply is reported as a
@@ -67,6 +122,7 @@ pub enum ApplyOutcomeKind {
 /// indistinguishable from "nothing happened" (report §4 P0-C).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CoreApplyData {
     pub outcome: ApplyOutcomeKind,
     /// The revision the core is running now, `source_hash` included: a caller
@@ -82,6 +138,59 @@ pub struct CoreApplyData {
     /// [`ApplyOutcomeKind::RolledBack`].
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub failed_apply: Option<String>,
+    /// Selector choices carried over from the replaced process. Set only for
+    /// [`ApplyOutcomeKind::Restarted`] and [`ApplyOutcomeKind::Switched`], or
+    /// for a [`ApplyOutcomeKind::RolledBack`] that relaunched the old process,
+    /// and only when the old process had any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selections: Option<RestoredSelectionsInfo>,
+    /// Set only for a [`ApplyOutcomeKind::Switched`] that had to stop the old
+    /// epoch before the new one could take over.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub degrade_reason: Option<SwitchDegradeReason>,
 }
 
 pub type CoreApplyRes<'a> = R<'a, CoreApplyData>;
+
+/// Predict what `POST /core/apply` would do with a config, without doing it.
+///
+/// The service reads and classifies the config exactly as an apply would, but
+/// stages no runtime copy and never reaches the core, so a GUI can warn "this
+/// will restart the core" before the user saves. The core binary does not
+/// check the config here; that is `POST /core/check`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreApplyPlanReq<'n> {
+    #[cfg_attr(feature = "schemars", schemars(with = "super::CoreTypeSchema"))]
+    pub core_type: Cow<'n, nyanpasu_utils::core::CoreType>,
+    pub config_file: Cow<'n, PathBuf>,
+    /// As in [`CoreApplyReq`], so the preview is of the binary the apply
+    /// would run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<Cow<'n, PathBuf>>,
+    /// As in [`CoreApplyReq`]: a policy other than the running core's
+    /// predicts a switch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<CorePolicyOverrides>,
+}
+
+/// The predicted route of an apply, against the revision running now.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreApplyPlanData {
+    /// Only ever `noop`, `patched`, `reloaded` or `switched`. A restart or
+    /// rollback is how an apply recovers when the core rejects an in-place
+    /// route, and cannot be told in advance.
+    pub outcome: ApplyOutcomeKind,
+    /// Set only for a `switched` plan that will stop the old epoch before the
+    /// new one takes over, dropping live connections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub degrade_reason: Option<SwitchDegradeReason>,
+    /// Dotted key paths of the source config that differ from the running
+    /// one, e.g. `dns.enhanced-mode`.
+    pub changed: Vec<String>,
+}
+
+pub type CoreApplyPlanRes<'a> = R<'a, CoreApplyPlanData>;
@@ -13,9 +13,20 @@ pub const CORE_CHECK_ENDPOINT: &str = "/core/check";
 /// take the same two fields today and must stay free to diverge.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CoreCheckReq<'n> {
+    #[cfg_attr(feature = "schemars", schemars(with = "super::CoreTypeSchema"))]
     pub core_type: Cow<'n, nyanpasu_utils::core::CoreType>,
-    pub config_file: Cow<'n, PathBuf>,
+    /// Exactly one of `config_file` and `config` must be set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_file: Option<Cow<'n, PathBuf>>,
+    /// The YAML itself, as in [`CoreApplyReq`](super::apply::CoreApplyReq).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<Cow<'n, str>>,
+    /// As in [`CoreApplyReq`](super::apply::CoreApplyReq), so the preview is of the binary
+    /// the apply would run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<Cow<'n, PathBuf>>,
 }
 
 /// A rejected config is an error envelope with
@@ -0,0 +1,72 @@
+use crate::api::{R, core::start::CorePolicyOverrides};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, path::PathBuf};
+
+pub const CORE_CONFIG_DIFF_ENDPOINT: &str = "/core/config/diff";
+
+/// Diff a candidate config against the one the running core adopted.
+///
+/// Both sides are the manager's *effective* documents — the candidate is
+/// prepared exactly as `POST /core/apply` would prepare it, then discarded — so
+/// the managed controller rewrite never shows up as a change. Nothing is staged
+/// and the core is not touched.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreConfigDiffReq<'n> {
+    #[cfg_attr(feature = "schemars", schemars(with = "super::CoreTypeSchema"))]
+    pub core_type: Cow<'n, nyanpasu_utils::core::CoreType>,
+    pub config_file: Cow<'n, PathBuf>,
+    /// As in [`CoreApplyReq`](super::apply::CoreApplyReq), so the preview is of the binary
+    /// the apply would run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<Cow<'n, PathBuf>>,
+    /// As in [`CoreApplyReq`](super::apply::CoreApplyReq).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<CorePolicyOverrides>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigDiffKind {
+    /// Only the candidate has the path.
+    Added,
+    /// Only the running config has the path.
+    Removed,
+    /// Both have it, with different values.
+    Changed,
+}
+
+/// One differing leaf. A sequence is a leaf: a changed rule list is reported
+/// whole, not item by item.
+///
+/// The controller keys (`external-controller*`, `secret`) and credentials —
+/// passwords, keys, tokens, UUIDs, wherever they nest — arrive as the string
+/// `"<redacted>"`: the path says they changed, never what to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConfigDiffEntryInfo {
+    /// Dotted key path, e.g. `dns.enhanced-mode`.
+    pub path: String,
+    pub kind: ConfigDiffKind,
+    /// Absent for [`ConfigDiffKind::Added`]. Branch on `kind`, not on
+    /// presence: a YAML `null` value is indistinguishable from no value here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old: Option<serde_json::Value>,
+    /// Absent for [`ConfigDiffKind::Removed`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreConfigDiffData {
+    /// Sorted by path. Empty when the candidate is what already runs.
+    pub entries: Vec<ConfigDiffEntryInfo>,
+}
+
+pub type CoreConfigDiffRes<'a> = R<'a, CoreConfigDiffData>;
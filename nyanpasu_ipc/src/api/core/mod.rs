@@ -1,6 +1,22 @@
 pub mod apply;
 pub mod check;
+pub mod config;
 pub mod recover;
 pub mod restart;
+pub mod revisions;
+pub mod rollback;
 pub mod start;
 pub mod stop;
+
+/// The JSON Schema of `nyanpasu_utils::core::CoreType`, which derives none of
+/// its own. Spelled the way `every_core_type_tag_is_pinned` pins it: the
+/// resource variant names are the `ClashCoreType` tags.
+#[cfg(feature = "schemars")]
+#[derive(schemars::JsonSchema)]
+#[schemars(rename = "CoreType")]
+#[serde(rename_all = "lowercase")]
+#[allow(dead_code)]
+pub(crate) enum CoreTypeSchema {
+    Clash(nyanpasu_core_metadata::ClashCoreResourceVariant),
+    SingBox,
+}
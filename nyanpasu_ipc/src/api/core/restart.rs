@@ -1,5 +1,35 @@
-use crate::api::R;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{R, core::apply::SwitchDegradeReason, proxies::RestoredSelectionsInfo};
 
 pub const CORE_RESTART_ENDPOINT: &str = "/core/restart";
 
-pub type CoreRestartRes<'a> = R<'a, ()>;
+/// What a restart carried into the new process. A restart always replaces
+/// the process, so this is the [`Switched`](super::apply::ApplyOutcomeKind::Switched)
+/// half of [`CoreApplyData`](super::apply::CoreApplyData) with nothing to
+/// classify.
+///
+/// Not an addition: before protocol revision 4 `/core/restart` answered with
+/// `data: null`, and it now always answers with an object, `{}` when there is
+/// nothing to report. A client built against an older revision that decodes
+/// `data` as a unit has to be updated; this crate's client reads a `null`
+/// from an older service as the default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreRestartData {
+    /// Selector choices carried over from the replaced process, when it had
+    /// any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selections: Option<RestoredSelectionsInfo>,
+    /// Set when the old process had to stop before the new one could take
+    /// over.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub degrade_reason: Option<SwitchDegradeReason>,
+    /// The manager's durability warning, as on
+    /// [`CoreApplyData::warning`](super::apply::CoreApplyData::warning).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+pub type CoreRestartRes<'a> = R<'a, CoreRestartData>;
@@ -0,0 +1,29 @@
+use crate::api::{R, status::ConfigRevisionInfo, ws::events::CoreKind};
+use serde::{Deserialize, Serialize};
+
+pub const CORE_REVISIONS_ENDPOINT: &str = "/core/revisions";
+
+/// One revision the manager has run and can roll back to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreRevisionInfo {
+    pub revision: ConfigRevisionInfo,
+    /// The core the revision ran on. A rollback to a revision of another core
+    /// switches back to it.
+    pub kind: CoreKind,
+    /// Unix milliseconds at which the revision started running.
+    pub recorded_at: i64,
+}
+
+/// The manager's bounded revision history, newest first, the running revision
+/// included. It lives as long as the service process: a restarted service
+/// starts with an empty one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreRevisionsData {
+    pub revisions: Vec<CoreRevisionInfo>,
+}
+
+pub type CoreRevisionsRes<'a> = R<'a, CoreRevisionsData>;
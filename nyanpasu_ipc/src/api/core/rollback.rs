@@ -0,0 +1,26 @@
+use crate::api::{R, core::apply::CoreApplyData, status::RevisionIdInfo};
+use serde::{Deserialize, Serialize};
+
+pub const CORE_ROLLBACK_ENDPOINT: &str = "/core/rollback";
+
+/// Re-apply a revision from `GET /core/revisions`.
+///
+/// The kept copy goes through the same engine as `POST /core/apply`, so the
+/// response is an apply result: the route is classified against what runs now,
+/// and the core ends up on a *new* revision carrying the old one's
+/// `source_hash`. The caller's own config file is not rewritten: the core runs
+/// from a copy the service keeps, so a later `POST /core/restart` stays on the
+/// restored config. An unknown or evicted target fails with
+/// `error_kind = "revision_not_found"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreRollbackReq {
+    pub revision: RevisionIdInfo,
+    /// Compare-and-swap token against the running revision, exactly as in
+    /// `POST /core/apply`. Omitted from the wire when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_revision: Option<RevisionIdInfo>,
+}
+
+pub type CoreRollbackRes<'a> = R<'a, CoreApplyData>;
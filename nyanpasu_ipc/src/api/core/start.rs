@@ -1,14 +1,76 @@
-use crate::api::R;
+use crate::api::{R, status::CoreDistribution};
 use serde::{Deserialize, Serialize};
-use std::{borrow::Cow, path::PathBuf};
+use std::{borrow::Cow, ops::RangeInclusive, path::PathBuf};
 
 pub const CORE_START_ENDPOINT: &str = "/core/start";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CoreStartReq<'n> {
+    #[cfg_attr(feature = "schemars", schemars(with = "super::CoreTypeSchema"))]
     pub core_type: Cow<'n, nyanpasu_utils::core::CoreType>,
     pub config_file: Cow<'n, PathBuf>,
+    /// Run this core binary instead of the one the service's search finds.
+    /// It must sit directly in a directory the service was installed to
+    /// allow and carry `core_type`'s executable name, or the start fails
+    /// with `error_kind = "binary_not_allowed"`.
+    /// Omitted from the wire when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_path: Option<Cow<'n, PathBuf>>,
+    /// Which distributed build the binary is, as the caller's resource layer
+    /// knows it. Reported back in `/status`, never checked. Omitted from the
+    /// wire when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<CoreDistribution>,
+    /// Overrides of the service's startup and supervision settings for this
+    /// launch. Omitted from the wire when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<CorePolicyOverrides>,
+}
+
+/// Per-launch overrides of the service's `[instance]` settings, for a config
+/// that needs more room than they give it: one with large rule providers can
+/// take well past the default 30s to boot.
+///
+/// Each field is optional and falls back to the service's own setting. A value
+/// outside the range named by the matching associated constant fails the
+/// request with `error_kind = "invalid_policy"`, and nothing is launched.
+/// `/status` reports the policy in force as `core_infos.policy`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CorePolicyOverrides {
+    /// Spawn to first healthy probe, in total.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup_timeout_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_interval_ms: Option<u64>,
+    /// Consecutive failed probes before the core is reported unhealthy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_threshold: Option<u32>,
+    /// Consecutive passing probes before it is reported healthy again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success_threshold: Option<u32>,
+    /// Failures inside this window after a start do not count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_period_ms: Option<u64>,
+    /// Replaces the service's restart policy: the core is restarted up to
+    /// this many times after exiting on its own, `0` meaning not at all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_restarts: Option<u32>,
+}
+
+impl CorePolicyOverrides {
+    /// At most twice the default. A service whose `server.request_timeout_ms`
+    /// leaves less room, after its reconcile and stop budgets, refuses a
+    /// value under this bound too, with `error_kind = "invalid_policy"`.
+    pub const STARTUP_TIMEOUT_MS: RangeInclusive<u64> = 1_000..=60_000;
+    pub const HEALTH_INTERVAL_MS: RangeInclusive<u64> = 50..=10_000;
+    /// Both thresholds.
+    pub const THRESHOLD: RangeInclusive<u32> = 1..=20;
+    pub const START_PERIOD_MS: RangeInclusive<u64> = 0..=300_000;
+    pub const MAX_RESTARTS: RangeInclusive<u32> = 0..=20;
 }
 
 pub type CoreStartRes<'a> = R<'a, ()>;
@@ -1,15 +1,96 @@
 use crate::api::R;
+use nyanpasu_core_metadata::{LogFrame, LogLevel, LogStream};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 
 pub const LOGS_RETRIEVE_ENDPOINT: &str = "/logs/retrieve";
 pub const LOGS_INSPECT_ENDPOINT: &str = "/logs/inspect";
+pub const LOGS_CORE_QUERY_ENDPOINT: &str = "/logs/core/query";
+
+/// Page size used when [`CoreLogsQueryReq::limit`] is absent.
+pub const CORE_LOGS_DEFAULT_LIMIT: u32 = 200;
+/// Larger limits are clamped to this rather than refused.
+pub const CORE_LOGS_MAX_LIMIT: u32 = 1000;
 
 // TODO: more health check fields
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct LogsResBody<'a> {
     pub logs: Vec<Cow<'a, str>>,
 }
 
 pub type LogsRes<'a> = R<'a, LogsResBody<'a>>;
+
+/// An opaque position in the core log archive. Only ever hand back one the
+/// service returned; its fields are the archive file and a byte offset into
+/// it, and carry no meaning a caller can compute with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreLogCursor {
+    pub file: u64,
+    pub offset: u64,
+}
+
+/// A query against the core log archive — the history that predates the
+/// caller's `/ws/events` connection, which the caller cannot read from disk.
+///
+/// Every field is optional and the filters combine with AND; an empty body
+/// returns the oldest retained page. Absent fields are omitted from the wire.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreLogsQueryReq {
+    /// Inclusive lower bound on [`LogFrame::at`], unix milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<i64>,
+    /// Exclusive upper bound on [`LogFrame::at`], unix milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<i64>,
+    /// Level floor: frames below it are skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<LogLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<LogStream>,
+    /// Case-sensitive substring of [`LogFrame::message`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contains: Option<String>,
+    /// Where to continue from: the `cursor` of the previous page, sent with the
+    /// same `backward`. Absent starts at the oldest record going forward and at
+    /// the newest going backward.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<CoreLogCursor>,
+    /// Defaults to [`CORE_LOGS_DEFAULT_LIMIT`], clamped to
+    /// [`CORE_LOGS_MAX_LIMIT`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Walk newest first, for scrolling back from the present.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub backward: bool,
+}
+
+/// One page of the core log archive.
+///
+/// Paging forward to `exhausted` and then polling with the returned `cursor`
+/// tails the archive: the cursor sits where the next record will be written.
+/// A cursor does not outlive retention — once its file is pruned the next page
+/// starts at the oldest file still kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreLogsQueryData {
+    /// Oldest first going forward, newest first going backward.
+    pub frames: Vec<LogFrame>,
+    pub cursor: CoreLogCursor,
+    /// Nothing further in this direction — for now, going forward.
+    pub exhausted: bool,
+    /// Frames the archive itself recorded as lost in the stretch this page
+    /// covered, because the writer fell behind the core. Not subject to the
+    /// filters.
+    pub dropped: u64,
+}
+
+pub type CoreLogsQueryRes<'a> = R<'a, CoreLogsQueryData>;
@@ -1,7 +1,13 @@
+pub mod access;
+pub mod audit;
+pub mod capabilities;
+pub mod connections;
 pub mod contract;
 pub mod core;
 pub mod log;
 pub mod network;
+pub mod proxies;
+pub mod service_config;
 pub mod status;
 pub mod ws;
 
@@ -9,6 +15,8 @@ use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use std::{borrow::Cow, fmt::Debug, io::Error as IoError};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ResponseCode {
     #[default]
     Ok = 0,
@@ -32,9 +40,11 @@ impl ResponseCode {
 /// variant would make every older client fail to decode the whole envelope —
 /// so the detail lives in an additive string field instead (report §4 P0-B).
 ///
-/// Absent means "not classified", never "no error": only the operations added
-/// in S8 populate it, and only for the failures listed here. Mapping the
-/// manager's remaining error variants is report P3.
+/// Every core operation, `start`/`stop`/`restart` included, classifies every
+/// failure the core manager can report; the legacy `msg` strings are kept
+/// alongside, so a GUI that still matches them keeps working. Absent means "not
+/// classified", never "no error": a failure outside the manager, such as the
+/// request's own shape or a DNS change, generally carries none.
 pub mod error_kind {
     /// The operation needs a running core and there is none.
     pub const NOT_STARTED: &str = "not_started";
@@ -43,6 +53,9 @@ pub mod error_kind {
     /// `expected_revision` did not match the running revision. Nothing was
     /// applied; re-read `/status` for the current one and retry.
     pub const REVISION_CONFLICT: &str = "revision_conflict";
+    /// The rollback target is not in the manager's history: it never ran
+    /// under this service process, or it was evicted.
+    pub const REVISION_NOT_FOUND: &str = "revision_not_found";
     /// An epoch whose death could not be confirmed has latched the manager.
     /// Every lifecycle operation is refused until `POST /core/recover` clears
     /// it.
@@ -51,8 +64,20 @@ pub mod error_kind {
     pub const CONFIG_CHECK_FAILED: &str = "config_check_failed";
     pub const CONFIG_NOT_FOUND: &str = "config_not_found";
     pub const BINARY_NOT_FOUND: &str = "binary_not_found";
+    /// An explicit `binary_path` does not sit directly in a directory the
+    /// service allows core binaries from.
+    pub const BINARY_NOT_ALLOWED: &str = "binary_not_allowed";
+    /// The service has a pinned core manifest and the binary is not in it, or
+    /// does not hash to the SHA-256 pinned for it. Nothing was launched.
+    pub const BINARY_UNTRUSTED: &str = "binary_untrusted";
+    /// A `policy` override is out of bounds, or its thresholds and intervals
+    /// do not make a valid health policy together. Nothing was launched.
+    pub const INVALID_POLICY: &str = "invalid_policy";
     /// The config could not be parsed or canonicalized.
     pub const INVALID_CONFIG: &str = "invalid_config";
+    /// A connection close was given a filter that matches everything. Closing
+    /// every connection is `close_all`, never an empty filter.
+    pub const INVALID_FILTER: &str = "invalid_filter";
     /// The config declares no external controller, so the core cannot be
     /// health-probed.
     pub const CONTROLLER_MISSING: &str = "controller_missing";
@@ -63,12 +88,87 @@ pub mod error_kind {
     /// A core process could not be proven dead; the manager is now
     /// quarantined.
     pub const STOP_UNCONFIRMED: &str = "stop_unconfirmed";
+    /// The service is shutting down and takes no new lifecycle operations.
+    pub const SHUTTING_DOWN: &str = "shutting_down";
+    /// The core binary could not be run with `-v`, or printed nothing the
+    /// manager could read a version from.
+    pub const VERSION_PROBE_FAILED: &str = "version_probe_failed";
+    /// The service requires local IPC and this core build does not support
+    /// the platform transport.
+    pub const LOCAL_IPC_UNSUPPORTED: &str = "local_ipc_unsupported";
+    /// The core process stopped before it became healthy; `msg` carries the
+    /// tail of its output.
+    pub const STARTUP_FAILED: &str = "startup_failed";
+    /// The core did not become healthy within the startup timeout; `msg`
+    /// carries the tail of its output.
+    pub const STARTUP_TIMEOUT: &str = "startup_timeout";
+    /// The core process could not be spawned, signalled or waited on.
+    pub const PROCESS_FAILED: &str = "process_failed";
+    /// A request to the running core's controller failed.
+    pub const CONTROLLER_REQUEST_FAILED: &str = "controller_request_failed";
+    /// Reading or writing the manager's runtime files failed.
+    pub const RUNTIME_IO: &str = "runtime_io";
+    /// A file in the manager's runtime directory is not one the manager
+    /// created, or has permissions it did not set. Nothing was read from it.
+    pub const UNSAFE_RUNTIME_ARTIFACT: &str = "unsafe_runtime_artifact";
+    /// Another manager holds the runtime directory.
+    pub const RUNTIME_DIR_OWNED: &str = "runtime_dir_owned";
+    /// The service's own manager settings were refused. A service
+    /// configuration problem, not the request's.
+    pub const INVALID_MANAGER_OPTIONS: &str = "invalid_manager_options";
+    /// The caller's role does not reach the one the operation needs under the
+    /// service config's `[access]` table. Nothing was run.
+    pub const PERMISSION_DENIED: &str = "permission_denied";
+    /// The service runs without a core log archive, so there is nothing to
+    /// page through. Not a failure: live frames still reach `/ws/events`, and
+    /// the refusal comes with a 409 rather than a 5xx.
+    pub const LOG_ARCHIVE_DISABLED: &str = "log_archive_disabled";
+
+    /// Every kind above, as `/capabilities` advertises them.
+    pub const ALL: &[&str] = &[
+        NOT_STARTED,
+        ALREADY_RUNNING,
+        REVISION_CONFLICT,
+        REVISION_NOT_FOUND,
+        QUARANTINED,
+        CONFIG_CHECK_FAILED,
+        CONFIG_NOT_FOUND,
+        BINARY_NOT_FOUND,
+        BINARY_NOT_ALLOWED,
+        BINARY_UNTRUSTED,
+        INVALID_POLICY,
+        INVALID_CONFIG,
+        INVALID_FILTER,
+        CONTROLLER_MISSING,
+        APPLY_FAILED,
+        APPLY_ROLLBACK_FAILED,
+        STOP_UNCONFIRMED,
+        SHUTTING_DOWN,
+        VERSION_PROBE_FAILED,
+        LOCAL_IPC_UNSUPPORTED,
+        STARTUP_FAILED,
+        STARTUP_TIMEOUT,
+        PROCESS_FAILED,
+        CONTROLLER_REQUEST_FAILED,
+        RUNTIME_IO,
+        UNSAFE_RUNTIME_ARTIFACT,
+        RUNTIME_DIR_OWNED,
+        INVALID_MANAGER_OPTIONS,
+        PERMISSION_DENIED,
+        LOG_ARCHIVE_DISABLED,
+    ];
 }
 
 /// The IPC Response body definition
 #[derive(Debug, Serialize, Deserialize, Clone, Builder)]
 #[builder(build_fn(validate = "Self::validate"))]
 #[serde(bound = "T: Serialize + DeserializeOwned")]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(bound = "T: schemars::JsonSchema")
+)]
 pub struct R<'a, T: Serialize + DeserializeOwned + Debug> {
     pub code: ResponseCode,
     #[builder(default = "self.default_msg()")]
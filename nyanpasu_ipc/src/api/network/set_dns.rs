@@ -6,6 +6,7 @@ pub const NETWORK_SET_DNS_ENDPOINT: &str = "/network/set_dns";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NetworkSetDnsReq<'n> {
     pub dns_servers: Option<Vec<Cow<'n, IpAddr>>>,
 }
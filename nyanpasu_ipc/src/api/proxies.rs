@@ -0,0 +1,112 @@
+use crate::api::R;
+use serde::{Deserialize, Serialize};
+
+pub const PROXIES_ENDPOINT: &str = "/proxies";
+pub const PROXIES_SELECT_ENDPOINT: &str = "/proxies/select";
+pub const PROXIES_DELAY_ENDPOINT: &str = "/proxies/delay";
+
+/// Used when [`ProxyDelayReq::timeout_ms`] is absent.
+pub const PROXY_DELAY_DEFAULT_TIMEOUT_MS: u32 = 5000;
+/// Longer timeouts are clamped to this rather than refused, keeping a test
+/// well inside the service's per-request bound.
+pub const PROXY_DELAY_MAX_TIMEOUT_MS: u32 = 30_000;
+
+/// A proxy group as the running core reports it.
+///
+/// Only what a group picker needs is carried; the rest of the core's proxy
+/// object is core-specific and changes between releases.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ProxyGroupInfo {
+    pub name: String,
+    /// The core's own group type, e.g. `Selector` or `URLTest`. Only a
+    /// `Selector` accepts `POST /proxies/select`.
+    pub r#type: String,
+    /// The member currently in use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub now: Option<String>,
+    /// Every member, in the config's order.
+    pub all: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    /// The group's most recent URL-test result in milliseconds, 0 for a
+    /// failed test. Absent if it was never tested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ProxiesData {
+    pub groups: Vec<ProxyGroupInfo>,
+}
+
+pub type ProxiesRes<'a> = R<'a, ProxiesData>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ProxySelectReq {
+    pub group: String,
+    pub proxy: String,
+}
+
+pub type ProxySelectRes<'a> = R<'a, ()>;
+
+/// One selector group's chosen member.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ProxySelectionInfo {
+    pub group: String,
+    pub proxy: String,
+}
+
+/// The selector choices a replaced core process had, and which of them the new
+/// process was given back. A fresh process starts every selector on its first
+/// member, so anything under `skipped` is a choice the user has lost — its
+/// group or member is gone from the new config, or the core refused it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RestoredSelectionsInfo {
+    pub restored: Vec<ProxySelectionInfo>,
+    pub skipped: Vec<ProxySelectionInfo>,
+}
+
+/// A URL test run by the core on the caller's behalf.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ProxyDelayReq {
+    /// A proxy, or with `group` set a group whose members are all tested.
+    pub name: String,
+    pub url: String,
+    /// Defaults to [`PROXY_DELAY_DEFAULT_TIMEOUT_MS`], clamped to
+    /// [`PROXY_DELAY_MAX_TIMEOUT_MS`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u32>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub group: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ProxyDelayInfo {
+    pub name: String,
+    /// Milliseconds; 0 means the test failed or timed out.
+    pub delay: u16,
+}
+
+/// One entry for a single proxy, one per member for a group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ProxyDelayData {
+    pub delays: Vec<ProxyDelayInfo>,
+}
+
+pub type ProxyDelayRes<'a> = R<'a, ProxyDelayData>;
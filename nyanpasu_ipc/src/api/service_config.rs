@@ -0,0 +1,291 @@
+//! The service's own configuration file, `service.toml` in the service config
+//! dir, and the effective form of it `/status` reports back.
+//!
+//! One type for both on purpose: what an administrator writes and what the
+//! service says it is running are the same shape, so neither can drift from
+//! the other. Every field has a default equal to the service's built-in value,
+//! and a table or key may be left out; an unknown key is an error rather than
+//! a silently ignored typo. Durations are whole milliseconds.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{R, access::Role};
+
+/// The file name, inside the service config dir.
+pub const SERVICE_CONFIG_FILE_NAME: &str = "service.toml";
+
+pub const SERVICE_RELOAD_ENDPOINT: &str = "/service/reload";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct ServiceConfig {
+    pub manager: ManagerConfig,
+    pub instance: InstanceConfig,
+    pub events: EventsConfig,
+    pub server: ServerConfig,
+    pub access: AccessConfig,
+}
+
+/// The spelling of the core manager's `LocalIpcPolicy`, matching the
+/// `--local-ipc-policy` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum LocalIpcPolicyConfig {
+    Force,
+    Prefer,
+    Disable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct ManagerConfig {
+    /// Overrides the install-time `--local-ipc-policy` when set. `/status`
+    /// always reports the policy in force, whichever side it came from. A
+    /// reload applies it from the next core launch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_ipc_policy: Option<LocalIpcPolicyConfig>,
+    /// Local IPC endpoint template containing `{epoch}`; the platform default
+    /// when absent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub controller_template: Option<String>,
+    pub control_timeout_ms: u64,
+    pub reconcile_timeout_ms: u64,
+    pub stop_timeout_ms: u64,
+    /// Write the core log archive at all.
+    pub log_sink_enabled: bool,
+    pub log_max_bytes: u64,
+    pub log_max_files: u32,
+    /// Committed revisions kept for rollback, the running one included.
+    pub revision_history: u32,
+    /// Config checks allowed to run at once; each spawns a core process.
+    pub max_concurrent_checks: u32,
+}
+
+impl Default for ManagerConfig {
+    fn default() -> Self {
+        Self {
+            local_ipc_policy: None,
+            controller_template: None,
+            control_timeout_ms: 10_000,
+            reconcile_timeout_ms: 30_000,
+            stop_timeout_ms: 10_000,
+            log_sink_enabled: true,
+            log_max_bytes: 4 * 1024 * 1024,
+            log_max_files: 5,
+            revision_history: 10,
+            max_concurrent_checks: 2,
+        }
+    }
+}
+
+/// How every core the service launches is started, watched and restarted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct InstanceConfig {
+    /// Spawn to first healthy probe, in total.
+    pub startup_timeout_ms: u64,
+    pub health: HealthConfig,
+    pub restart: RestartConfig,
+}
+
+impl Default for InstanceConfig {
+    fn default() -> Self {
+        Self {
+            startup_timeout_ms: 30_000,
+            health: HealthConfig::default(),
+            restart: RestartConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthConfig {
+    pub interval_ms: u64,
+    pub timeout_ms: u64,
+    /// Consecutive failed probes before the core is reported unhealthy.
+    pub failure_threshold: u32,
+    /// Consecutive passing probes before it is reported healthy again.
+    pub success_threshold: u32,
+    /// Failures inside this window after a start do not count.
+    pub start_period_ms: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: 250,
+            timeout_ms: 1_000,
+            failure_threshold: 3,
+            success_threshold: 1,
+            start_period_ms: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicyConfig {
+    Never,
+    OnFailure,
+}
+
+/// What the supervisor does when a running core exits on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct RestartConfig {
+    pub policy: RestartPolicyConfig,
+    /// Only read under `on-failure`.
+    pub max_restarts: u32,
+    /// The first restart's delay, doubled per attempt up to `backoff_max_ms`.
+    pub backoff_initial_ms: u64,
+    pub backoff_max_ms: u64,
+    /// Spread each delay by up to a quarter either way.
+    pub backoff_jitter: bool,
+}
+
+impl Default for RestartConfig {
+    fn default() -> Self {
+        Self {
+            policy: RestartPolicyConfig::OnFailure,
+            max_restarts: 5,
+            backoff_initial_ms: 1_000,
+            backoff_max_ms: 30_000,
+            backoff_jitter: true,
+        }
+    }
+}
+
+/// Buffer sizes for the `/ws/events` stream, per subscriber unless noted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct EventsConfig {
+    pub status_capacity: u32,
+    pub log_capacity: u32,
+    /// Core log frames kept for connect-time replay. One ring for the whole
+    /// service, resident whether or not anyone is connected.
+    pub log_backlog_capacity: u32,
+    pub telemetry_capacity: u32,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            status_capacity: 256,
+            log_capacity: 1024,
+            log_backlog_capacity: 512,
+            telemetry_capacity: 64,
+        }
+    }
+}
+
+/// The IPC server itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Upper bound on one request/response operation. A guard rail against a
+    /// wedged handler, so it must sit above the longest start or restart the
+    /// manager and instance timeouts allow.
+    pub request_timeout_ms: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout_ms: 120_000,
+        }
+    }
+}
+
+/// Who may call what; see [`crate::api::access`].
+///
+/// Only Linux tells the service who is calling. Elsewhere every caller is
+/// unidentified and gets `default_role`, so on Windows and macOS the socket's
+/// own ACL or mode stays the gate that matters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct AccessConfig {
+    /// The role of a caller `users` and `groups` do not name, or that the
+    /// service cannot identify. `admin` unless set, which leaves the socket
+    /// permissions as the only gate, as they were before this table.
+    pub default_role: Role,
+    /// Roles by numeric uid, as `id -u` prints it. An entry here wins over the
+    /// caller's group. uid 0 is always `admin`.
+    pub users: BTreeMap<String, Role>,
+    /// Roles by numeric gid, as `id -G` prints them. The caller's primary and
+    /// supplementary groups all count, and the highest role among them wins.
+    pub groups: BTreeMap<String, Role>,
+    /// The role an address needs, by path (`"/core/stop"`, `"/ws/events"`),
+    /// in place of its built-in one.
+    pub operations: BTreeMap<String, Role>,
+}
+
+impl Default for AccessConfig {
+    fn default() -> Self {
+        Self {
+            default_role: Role::Admin,
+            users: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            operations: BTreeMap::new(),
+        }
+    }
+}
+
+/// What `/status` reports about the service configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ServiceConfigInfo {
+    /// Where the service looked for the file.
+    pub path: PathBuf,
+    /// Whether the file existed. When it did not, `effective` is the defaults
+    /// plus the install-time local IPC policy.
+    pub loaded: bool,
+    pub effective: ServiceConfig,
+}
+
+/// What `/service/reload` did. Keys are dotted as in the file
+/// (`instance.health.interval_ms`); `acl` is the Windows pipe ACL list, which
+/// is not part of `service.toml` but is re-read alongside it, and applies to
+/// the connections made after the reload.
+///
+/// A setting that cannot change live keeps its old value, and `config.effective`
+/// says so: it is what is in force now, not the file verbatim, and it is what
+/// `/status` reports from here on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ServiceReloadData {
+    pub config: ServiceConfigInfo,
+    /// Changed settings now in force. Instance settings apply from the next
+    /// core launch; a running core keeps the ones it was started with.
+    pub applied: Vec<String>,
+    /// Changed settings that wait for the service to restart.
+    pub restart_required: Vec<String>,
+}
+
+/// Re-read `service.toml` and apply what can change without a restart. No
+/// request body. An invalid file is refused whole and nothing changes.
+pub type ServiceReloadRes<'a> = R<'a, ServiceReloadData>;
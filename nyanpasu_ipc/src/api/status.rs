@@ -1,11 +1,19 @@
-use crate::api::R;
+use crate::api::{
+    R,
+    service_config::{HealthConfig, ServiceConfigInfo},
+};
 use serde::{Deserialize, Serialize};
 use std::{borrow::Cow, path::PathBuf};
 
+/// The build identity vocabulary, re-exported so a consumer of
+/// [`CoreBuildInfo`] never has to name the metadata crate to spell it.
+pub use nyanpasu_core_metadata::{CoreDistribution, Feature, VariantTag};
+
 pub const STATUS_ENDPOINT: &str = "/status";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum CoreState {
     Running,
     Stopped(Option<String>),
@@ -26,6 +34,7 @@ impl Default for CoreState {
 /// socket ACL.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum CoreControllerInfo {
     NamedPipe(PathBuf),
     UnixSocket(PathBuf),
@@ -36,6 +45,7 @@ pub enum CoreControllerInfo {
 /// Health observation state for the active core.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum CoreHealthState {
     Starting,
     Healthy,
@@ -46,6 +56,7 @@ pub enum CoreHealthState {
 /// core is stopping or stopped.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CoreHealthInfo {
     pub state: CoreHealthState,
     /// Unix milliseconds of the last health-state transition.
@@ -68,6 +79,7 @@ pub struct CoreHealthInfo {
 /// mislead.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ConfigRevisionInfo {
     pub epoch: u64,
     pub generation: u64,
@@ -82,6 +94,7 @@ pub struct ConfigRevisionInfo {
 /// would imply it takes part.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RevisionIdInfo {
     pub epoch: u64,
     pub generation: u64,
@@ -112,6 +125,7 @@ impl ConfigRevisionInfo {
 /// when present.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum CoreStateDetail {
     Stopped { reason: Option<String> },
     Starting { epoch: u64 },
@@ -121,9 +135,62 @@ pub enum CoreStateDetail {
     Stopping { epoch: u64 },
 }
 
+/// Functionality the manager enabled for the running epoch, mirroring the
+/// manager's `RuntimeFeature` and spelled the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum CoreRuntimeFeature {
+    /// The core is controlled over a manager-owned named pipe or Unix socket
+    /// rather than its HTTP `external-controller`.
+    LocalIpc,
+}
+
+/// Which build of the core is running, and what it was found able to do.
+///
+/// `capabilities` is what the build supports; `runtime_features` is what the
+/// manager turned on. The two differ wherever policy overrides ability, so
+/// "is local IPC active" is answered by the latter alone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreBuildInfo {
+    /// The version capabilities were resolved from, as the core printed it
+    /// for `-v`. Absent for a kind with no version-gated features.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Echoed from the start or apply that launched this revision. The
+    /// service never derives it, so it is absent unless the caller said.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<CoreDistribution>,
+    pub capabilities: Vec<Feature>,
+    pub runtime_features: Vec<CoreRuntimeFeature>,
+}
+
+/// How the running core was started and is being supervised: the service's
+/// `[instance]` settings with the start or apply request's `policy` overrides
+/// on top. The health table is spelled as in `service.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CorePolicyInfo {
+    pub startup_timeout_ms: u64,
+    pub health: HealthConfig,
+    /// Restarts allowed after the core exits on its own. Absent when the
+    /// restart policy is `never`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_restarts: Option<u32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CoreInfos {
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "Option<crate::api::core::CoreTypeSchema>")
+    )]
     pub r#type: Option<nyanpasu_utils::core::CoreType>,
     pub state: CoreState,
     pub state_changed_at: i64,
@@ -138,6 +205,12 @@ pub struct CoreInfos {
     pub revision: Option<ConfigRevisionInfo>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub detail: Option<CoreStateDetail>,
+    /// Absent while the core is stopped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<CoreBuildInfo>,
+    /// Absent while the core is stopped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<CorePolicyInfo>,
 }
 
 /// Where this service writes logs.
@@ -149,19 +222,16 @@ pub struct CoreInfos {
 /// account the service runs under plus local administrators, so a caller
 /// running as an ordinary user generally cannot read them — surface the path
 /// for a support request rather than building a tail on it. Live core output is
-/// on the event stream; the service's own logs are not streamed at all.
+/// on the event stream and the core archive is served by `POST
+/// /logs/core/query`; the service's own logs are not streamed at all.
 ///
-/// TODO: that restriction is the open question here, not a settled posture. A
-/// user-level GUI reading the archive would need one of: a widened DACL (which
-/// means teaching `nyanpasu_utils::io::atomic_fs`'s hardener *and* its verifier
-/// a second acceptable shape — the verifier rejects user-readable DACLs today,
-/// so both move together or directory creation starts failing), some other
-/// grant mechanism, or an RPC that serves the archive so the directory stays
-/// closed. All three are acceptable; none is chosen yet. The two sites that
-/// would change are `log_sink::prepare_dir` and the service's own log directory
-/// setup.
+/// The archive is served rather than opened up on purpose: a widened DACL would
+/// have meant teaching `nyanpasu_utils::io::atomic_fs`'s hardener *and* its
+/// verifier a second acceptable shape, and the query keeps the directory
+/// closed. The service's own log directory is still location-only.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct LogPathsInfo {
     /// The service's own logs: JSON lines from `tracing-appender`, rotated
     /// daily, seven files kept.
@@ -173,8 +243,24 @@ pub struct LogPathsInfo {
     pub core_dir: Option<PathBuf>,
 }
 
+/// The running core's binary, as verified against the service's pinned
+/// manifest before it was launched.
+///
+/// `path` is the binary as pinned. What runs is a copy the service made of it
+/// while hashing, where only the service can write, so the digest still
+/// describes the running bytes even if the file at `path` was replaced since.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CoreBinaryInfo {
+    pub path: PathBuf,
+    /// Lowercase hex.
+    pub sha256: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RuntimeInfos<'a> {
     pub service_data_dir: Cow<'a, PathBuf>,
     pub service_config_dir: Cow<'a, PathBuf>,
@@ -184,6 +270,7 @@ pub struct RuntimeInfos<'a> {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct StatusResBody<'a> {
     pub version: Cow<'a, str>,
     pub core_infos: CoreInfos,
@@ -194,6 +281,14 @@ pub struct StatusResBody<'a> {
     /// existing golden literal stays unchanged.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub logs: Option<LogPathsInfo>,
+    /// Absent when no core is running, or when the service has no pinned
+    /// manifest and so verified nothing. Declared last for the same reason as
+    /// `logs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub core_binary: Option<CoreBinaryInfo>,
+    /// Always sent; optional on the wire for the same reason as `logs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_config: Option<ServiceConfigInfo>,
 }
 
 pub type StatusRes<'a> = R<'a, StatusResBody<'a>>;
@@ -7,14 +7,167 @@ use crate::api::status::{CoreInfos, CoreState};
 /// The core log vocabulary, re-exported so a consumer of this stream never has
 /// to name the metadata crate to spell the payload it just decoded.
 pub use nyanpasu_core_metadata::{
-    ClashCoreKind, LogField, LogFrame, LogLevel, LogStream, LogTimestamp,
+    ClashCoreKind, CoreKind, LogField, LogFrame, LogLevel, LogStream, LogTimestamp,
 };
 
 /// The event endpoint. There is no protocol negotiation and no version
 /// parameter: the service binary ships with the program that consumes it, so
-/// every connection speaks the same stream and any query string is ignored.
+/// every connection speaks the same stream. The only parameters it reads are
+/// the opt-in [`LogReplay`] ones; anything else in the query string is ignored.
 pub const EVENT_URI: &str = "/ws/events";
 
+/// Query parameter for [`LogReplay::Last`].
+pub const EVENT_REPLAY_PARAM: &str = "replay";
+/// Query parameter for [`LogReplay::Since`].
+pub const EVENT_REPLAY_SINCE_PARAM: &str = "replay_since";
+
+/// A connect-time request to backfill from the service's in-memory backlog of
+/// recent core log frames, so a client that reconnects after a sleep does not
+/// show an empty console until the core next prints.
+///
+/// The backlog is small and lives only as long as the service process; it is a
+/// convenience for reconnects, not history. History is the archive behind
+/// `POST /logs/core/query`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogReplay {
+    /// The newest `n` frames held, capped by the backlog's size.
+    Last(u32),
+    /// Every held frame whose [`LogFrame::at`] is at or after this unix
+    /// millisecond instant.
+    Since(i64),
+}
+
+impl LogReplay {
+    /// The query string, without its leading `?`, that requests this replay.
+    pub fn to_query(self) -> String {
+        match self {
+            Self::Last(frames) => format!("{EVENT_REPLAY_PARAM}={frames}"),
+            Self::Since(at) => format!("{EVENT_REPLAY_SINCE_PARAM}={at}"),
+        }
+    }
+
+    /// The replay a raw query string asks for, if any.
+    ///
+    /// Lenient on purpose, because the endpoint has always accepted any query:
+    /// unknown keys and unparsable values are skipped rather than rejected, and
+    /// when a parameter repeats or both are present the last one wins.
+    pub fn from_query(query: &str) -> Option<Self> {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .fold(None, |replay, (key, value)| match key {
+                EVENT_REPLAY_PARAM => value.parse().ok().map(Self::Last).or(replay),
+                EVENT_REPLAY_SINCE_PARAM => value.parse().ok().map(Self::Since).or(replay),
+                _ => replay,
+            })
+    }
+}
+
+/// One `/traffic` sample from the running core, relayed so a client can chart
+/// bandwidth without holding the controller secret.
+///
+/// Rates are bytes per second and totals are bytes, both as the core's signed
+/// counters report them. The totals count from the start of `epoch`: they
+/// restart at zero whenever the epoch changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TrafficSample {
+    pub epoch: u64,
+    pub up: i64,
+    pub down: i64,
+    pub up_total: i64,
+    pub down_total: i64,
+}
+
+/// One `/memory` sample from the running core, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MemorySample {
+    pub epoch: u64,
+    pub in_use: u64,
+    /// Zero when the core reports no limit.
+    pub os_limit: u64,
+}
+
+/// The families of [`Event`] a connection can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// [`Event::CoreStateChanged`] and [`Event::CoreStatusChanged`].
+    Status,
+    /// [`Event::CoreLog`] and [`Event::CoreLogReplayEnd`].
+    Log,
+    /// [`Event::Traffic`] and [`Event::Memory`].
+    Telemetry,
+}
+
+impl EventKind {
+    pub const ALL: [Self; 3] = [Self::Status, Self::Log, Self::Telemetry];
+}
+
+/// A connection's subscription, narrowed on the service side so that a client
+/// which only wants status — a tray icon — never decodes a debug-level core
+/// flood.
+///
+/// Sent by the client as one JSON message, text or binary, at any time after
+/// connecting; each message replaces the previous filter outright, and one the
+/// service cannot decode is ignored. The connect-time snapshot and any
+/// requested [`LogReplay`] are sent before a filter can arrive and are always
+/// delivered. The default, which is also what every connection starts with,
+/// passes everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct EventFilter {
+    /// The families to deliver. Absent means all of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kinds: Option<Vec<EventKind>>,
+    /// Level floor for [`Event::CoreLog`]: frames below it are not sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_level: Option<LogLevel>,
+    /// Case-sensitive substring a [`Event::CoreLog`] frame must contain in its
+    /// message or its target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+impl EventFilter {
+    pub fn wants(&self, kind: EventKind) -> bool {
+        self.kinds
+            .as_ref()
+            .is_none_or(|kinds| kinds.contains(&kind))
+    }
+
+    /// Whether a core log frame passes, checked before the frame is wrapped in
+    /// an [`Event`] so a rejected one is never serialized.
+    pub fn admits_log(&self, frame: &LogFrame) -> bool {
+        self.wants(EventKind::Log)
+            && self.min_level.is_none_or(|level| frame.level >= level)
+            && self.pattern.as_deref().is_none_or(|pattern| {
+                frame.message.contains(pattern)
+                    || frame
+                        .target
+                        .as_deref()
+                        .is_some_and(|target| target.contains(pattern))
+            })
+    }
+
+    pub fn admits(&self, event: &Event) -> bool {
+        match event {
+            Event::CoreStateChanged(_) | Event::CoreStatusChanged(_) => {
+                self.wants(EventKind::Status)
+            }
+            Event::CoreLog(frame) => self.admits_log(frame),
+            Event::CoreLogReplayEnd { .. } => self.wants(EventKind::Log),
+            Event::Traffic(_) | Event::Memory(_) => self.wants(EventKind::Telemetry),
+        }
+    }
+}
+
 /// The status snapshot is the only large variant, and it is deliberately not
 /// boxed: since the log ring started carrying frames rather than events, every
 /// `Event` that exists travels on the status ring, where a `CoreStatusChanged`
@@ -24,6 +177,7 @@ pub const EVENT_URI: &str = "/ws/events";
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum Event {
     /// The lossy state, kept exactly as it has always been: `Starting` and
     /// `Restarting` are reported as `Stopped(None)`, so a crash loop is
@@ -58,15 +212,47 @@ pub enum Event {
     /// status variants**. They render in different panels and have no causal
     /// relationship.
     ///
-    /// Nothing is replayed on connect. The authoritative history is the JSONL
-    /// archive the manager writes, whose directory `/status` reports.
+    /// Nothing is replayed on connect unless the connection asks for it with
+    /// [`LogReplay`]. The authoritative history is the JSONL archive the
+    /// manager writes, served by `POST /logs/core/query`.
     ///
     /// The `Arc` is a service-side fan-out detail and is invisible on the wire:
     /// serde encodes it as the frame itself.
     CoreLog(Arc<LogFrame>),
+    /// The boundary between replay and live output, sent once to a connection
+    /// that asked for a [`LogReplay`]: every [`Self::CoreLog`] before it came
+    /// from the backlog, every one after it is live. `replayed` counts the
+    /// frames in between, and the marker is sent even when that is zero.
+    ///
+    /// The replay is seamless: a frame is either in it or arrives live after
+    /// the marker, never both and never neither — short of the connection
+    /// falling behind, which loses live log frames exactly as it always has.
+    CoreLogReplayEnd { replayed: u32 },
+    /// The running core's traffic, at most once per the service's telemetry
+    /// interval and always the newest sample. Relayed from whichever epoch is
+    /// running, across restarts and switches; nothing arrives while the core
+    /// is stopped.
+    ///
+    /// Carried on a third ring inside the service, for the same reason logs
+    /// have their own: a lost sample is superseded by the next one and must
+    /// never cost a status resend. Like logs, it has no ordering guarantee
+    /// against the status variants, and nothing is replayed on connect.
+    Traffic(TrafficSample),
+    /// The running core's memory use, relayed like [`Self::Traffic`].
+    Memory(MemorySample),
 }
 
 impl Event {
+    /// Every variant's wire tag, as `/capabilities` advertises them.
+    pub const VARIANTS: &[&str] = &[
+        "CoreStateChanged",
+        "CoreStatusChanged",
+        "CoreLog",
+        "CoreLogReplayEnd",
+        "Traffic",
+        "Memory",
+    ];
+
     pub fn new_core_state_changed(state: CoreState) -> Self {
         Self::CoreStateChanged(state)
     }
@@ -78,4 +264,160 @@ impl Event {
     pub fn new_core_log(frame: Arc<LogFrame>) -> Self {
         Self::CoreLog(frame)
     }
+
+    pub fn new_core_log_replay_end(replayed: u32) -> Self {
+        Self::CoreLogReplayEnd { replayed }
+    }
+
+    pub fn new_traffic(sample: TrafficSample) -> Self {
+        Self::Traffic(sample)
+    }
+
+    pub fn new_memory(sample: MemorySample) -> Self {
+        Self::Memory(sample)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(level: LogLevel, target: Option<&str>, message: &str) -> LogFrame {
+        LogFrame {
+            at: 0,
+            epoch: 1,
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
+            stream: LogStream::Stdout,
+            level,
+            timestamp: None,
+            target: target.map(str::to_owned),
+            message: message.to_owned(),
+            fields: Vec::new(),
+            raw: message.to_owned(),
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn the_default_filter_passes_everything() {
+        let filter = EventFilter::default();
+        assert!(filter.admits(&Event::new_core_state_changed(CoreState::Running)));
+        assert!(filter.admits_log(&frame(LogLevel::Trace, None, "noise")));
+        assert!(filter.admits(&Event::new_core_log_replay_end(0)));
+    }
+
+    #[test]
+    fn a_status_only_filter_drops_every_log_event() {
+        let filter = EventFilter {
+            kinds: Some(vec![EventKind::Status]),
+            ..EventFilter::default()
+        };
+        assert!(filter.admits(&Event::new_core_state_changed(CoreState::Running)));
+        assert!(!filter.admits_log(&frame(LogLevel::Error, None, "boom")));
+        assert!(!filter.admits(&Event::new_core_log_replay_end(0)));
+
+        let logs_only = EventFilter {
+            kinds: Some(vec![EventKind::Log]),
+            ..EventFilter::default()
+        };
+        assert!(!logs_only.admits(&Event::new_core_state_changed(CoreState::Running)));
+    }
+
+    #[test]
+    fn telemetry_is_its_own_family() {
+        let traffic = Event::new_traffic(TrafficSample {
+            epoch: 1,
+            up: 10,
+            down: 20,
+            up_total: 100,
+            down_total: 200,
+        });
+        let memory = Event::new_memory(MemorySample {
+            epoch: 1,
+            in_use: 1024,
+            os_limit: 0,
+        });
+        let telemetry_only = EventFilter {
+            kinds: Some(vec![EventKind::Telemetry]),
+            ..EventFilter::default()
+        };
+        assert!(telemetry_only.admits(&traffic));
+        assert!(telemetry_only.admits(&memory));
+        assert!(!telemetry_only.admits(&Event::new_core_state_changed(CoreState::Running)));
+
+        let status_only = EventFilter {
+            kinds: Some(vec![EventKind::Status]),
+            ..EventFilter::default()
+        };
+        assert!(!status_only.admits(&traffic));
+        assert!(!status_only.admits(&memory));
+    }
+
+    #[test]
+    fn every_variant_tag_is_listed() {
+        let events = [
+            Event::new_core_state_changed(CoreState::Running),
+            Event::new_core_log(Arc::new(frame(LogLevel::Info, None, "line"))),
+            Event::new_core_log_replay_end(0),
+            Event::new_traffic(TrafficSample {
+                epoch: 1,
+                up: 0,
+                down: 0,
+                up_total: 0,
+                down_total: 0,
+            }),
+            Event::new_memory(MemorySample {
+                epoch: 1,
+                in_use: 0,
+                os_limit: 0,
+            }),
+        ];
+        for event in events {
+            let value = serde_json::to_value(&event).unwrap();
+            let tag = match &value {
+                serde_json::Value::String(tag) => tag.clone(),
+                serde_json::Value::Object(map) => map.keys().next().unwrap().clone(),
+                other => panic!("unexpected encoding {other}"),
+            };
+            assert!(
+                Event::VARIANTS.contains(&tag.as_str()),
+                "{tag} is not listed"
+            );
+        }
+    }
+
+    #[test]
+    fn log_filters_match_level_and_either_message_or_target() {
+        let filter = EventFilter {
+            min_level: Some(LogLevel::Warning),
+            pattern: Some("dns".to_owned()),
+            ..EventFilter::default()
+        };
+        assert!(filter.admits_log(&frame(LogLevel::Error, None, "dns timeout")));
+        assert!(filter.admits_log(&frame(LogLevel::Warning, Some("dns"), "timeout")));
+        assert!(!filter.admits_log(&frame(LogLevel::Info, None, "dns timeout")));
+        assert!(!filter.admits_log(&frame(LogLevel::Error, Some("tun"), "timeout")));
+    }
+
+    #[test]
+    fn a_replay_request_survives_its_own_query_string() {
+        for replay in [LogReplay::Last(50), LogReplay::Since(1_700_000_000_000)] {
+            assert_eq!(LogReplay::from_query(&replay.to_query()), Some(replay));
+        }
+    }
+
+    #[test]
+    fn replay_parameters_are_read_leniently() {
+        assert_eq!(LogReplay::from_query(""), None);
+        assert_eq!(LogReplay::from_query("v=1&v=2"), None);
+        assert_eq!(LogReplay::from_query("replay=lots"), None);
+        assert_eq!(
+            LogReplay::from_query("replay=10&replay=lots"),
+            Some(LogReplay::Last(10))
+        );
+        assert_eq!(
+            LogReplay::from_query("replay=10&replay_since=-5"),
+            Some(LogReplay::Since(-5))
+        );
+    }
 }
@@ -3,19 +3,32 @@ use std::{
     task::{Context, Poll},
 };
 
-use futures_util::{Stream, StreamExt};
-use reqwest_websocket::{Message, Upgrade};
+use futures_util::{SinkExt, Stream, StreamExt, stream::SplitSink};
+use reqwest::StatusCode;
+use reqwest_websocket::{Message, Upgrade, WebSocket};
 
 use crate::api::{
     self,
+    audit::{AUDIT_ENDPOINT, AuditQueryData},
+    capabilities::{CAPABILITIES_ENDPOINT, CapabilitiesData, MissingCapabilities},
+    connections::{CONNECTIONS_CLOSE_ENDPOINT, CONNECTIONS_ENDPOINT, ConnectionsData},
     contract::{
-        CoreApply, CoreCheck, CoreRecover, CoreRestart, CoreStart, CoreStop, LogsInspect,
-        LogsRetrieve, NetworkSetDns, Status,
+        Audit, Capabilities, Connections, ConnectionsClose, ConnectionsCloseAll, CoreApply,
+        CoreApplyPlan, CoreCheck, CoreConfigDiff, CoreRecover, CoreRestart, CoreRevisions,
+        CoreRollback, CoreStart, CoreStop, LogsCoreQuery, LogsInspect, LogsRetrieve, NetworkSetDns,
+        Proxies, ProxiesDelay, ProxiesSelect, ServiceReload, Status,
     },
-    core::apply::{CORE_APPLY_ENDPOINT, CoreApplyData},
-    log::{LOGS_INSPECT_ENDPOINT, LOGS_RETRIEVE_ENDPOINT},
+    core::{
+        apply::{CORE_APPLY_ENDPOINT, CORE_APPLY_PLAN_ENDPOINT, CoreApplyData, CoreApplyPlanData},
+        restart::CoreRestartData,
+    },
+    log::{
+        CoreLogsQueryData, LOGS_CORE_QUERY_ENDPOINT, LOGS_INSPECT_ENDPOINT, LOGS_RETRIEVE_ENDPOINT,
+    },
+    proxies::{PROXIES_DELAY_ENDPOINT, PROXIES_ENDPOINT, ProxiesData, ProxyDelayData},
+    service_config::{SERVICE_RELOAD_ENDPOINT, ServiceReloadData},
     status::STATUS_ENDPOINT,
-    ws::events::{EVENT_URI, Event},
+    ws::events::{EVENT_URI, Event, EventFilter, LogReplay},
 };
 
 use super::{ClientError, Result};
@@ -40,8 +53,15 @@ impl Client {
         self.call::<CoreStop>(None).await.map(|_| ())
     }
 
-    pub async fn restart_core(&self) -> Result<()> {
-        self.call::<CoreRestart>(None).await.map(|_| ())
+    /// Restart the running core; the result says which selector choices made
+    /// it into the new process. A service older than protocol revision 4
+    /// answers with no data, read here as nothing carried over.
+    pub async fn restart_core(&self) -> Result<CoreRestartData> {
+        Ok(self
+            .call::<CoreRestart>(None)
+            .await?
+            .data
+            .unwrap_or_default())
     }
 
     /// Apply a config to the running core. See
@@ -60,6 +80,55 @@ impl Client {
             })
     }
 
+    /// Predict what [`Self::apply_config`] would do with a config, without
+    /// applying it.
+    pub async fn plan_apply(
+        &self,
+        payload: &api::core::apply::CoreApplyPlanReq<'_>,
+    ) -> Result<CoreApplyPlanData> {
+        self.call::<CoreApplyPlan>(Some(payload))
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: CORE_APPLY_PLAN_ENDPOINT,
+            })
+    }
+
+    /// Diff a config against the one the running core adopted, with
+    /// credentials redacted.
+    pub async fn diff_config(
+        &self,
+        payload: &api::core::config::CoreConfigDiffReq<'_>,
+    ) -> Result<CoreConfigDiffData> {
+        self.call::<CoreConfigDiff>(Some(payload))
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: CORE_CONFIG_DIFF_ENDPOINT,
+            })
+    }
+
+    /// The revisions the service can roll back to, newest first.
+    pub async fn revisions(&self) -> Result<CoreRevisionsData> {
+        self.call::<CoreRevisions>(None)
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: CORE_REVISIONS_ENDPOINT,
+            })
+    }
+
+    /// Re-apply a revision from [`Self::revisions`]. The result reads like
+    /// [`Self::apply_config`]'s, `rolled_back` included.
+    pub async fn rollback(&self, payload: &CoreRollbackReq) -> Result<CoreApplyData> {
+        self.call::<CoreRollback>(Some(payload))
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: CORE_ROLLBACK_ENDPOINT,
+            })
+    }
+
     /// Dry-run a config against a core binary without touching the running one.
     pub async fn check_config(&self, payload: &api::core::check::CoreCheckReq<'_>) -> Result<()> {
         self.call::<CoreCheck>(Some(payload)).await.map(|_| ())
@@ -70,6 +139,60 @@ impl Client {
         self.call::<CoreRecover>(None).await.map(|_| ())
     }
 
+    /// Re-read the service's `service.toml`. See
+    /// [`ServiceReloadData::restart_required`] for what did not take effect.
+    pub async fn reload_service(&self) -> Result<ServiceReloadData> {
+        self.call::<ServiceReload>(None)
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: SERVICE_RELOAD_ENDPOINT,
+            })
+    }
+
+    pub async fn capabilities(&self) -> Result<CapabilitiesData> {
+        self.call::<Capabilities>(None)
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: CAPABILITIES_ENDPOINT,
+            })
+    }
+
+    /// Ask the service what it speaks and compare it with this build of the
+    /// crate. Meant for connect time, so a GUI can say "update the service"
+    /// once instead of failing one call at a time.
+    ///
+    /// A service too old to have `/capabilities` is [`Compatibility::Predates`],
+    /// not an error; any other failure is returned as it is.
+    pub async fn check_compatibility(&self) -> Result<Compatibility> {
+        match self.capabilities().await {
+            Ok(capabilities) => {
+                let missing = capabilities.missing();
+                Ok(if missing.is_empty() {
+                    Compatibility::Compatible(capabilities)
+                } else {
+                    Compatibility::Degraded {
+                        capabilities,
+                        missing,
+                    }
+                })
+            }
+            Err(ClientError::HttpStatus {
+                status: StatusCode::NOT_FOUND,
+                ..
+            }) => Ok(Compatibility::Predates),
+            // A service with the enveloped not-found fallback but no
+            // `/capabilities` says so in the envelope, with no kind.
+            Err(ClientError::Server {
+                msg,
+                error_kind: None,
+                ..
+            }) if msg == "not found" => Ok(Compatibility::Predates),
+            Err(error) => Err(error),
+        }
+    }
+
     pub async fn inspect_logs(&self) -> Result<api::log::LogsResBody<'static>> {
         self.call::<LogsInspect>(None)
             .await?
@@ -88,6 +211,90 @@ impl Client {
             })
     }
 
+    /// Read one page of the audit trail. Feed the returned `cursor` back into
+    /// the next request to continue.
+    pub async fn audit(&self, payload: &api::audit::AuditQueryReq) -> Result<AuditQueryData> {
+        self.call::<Audit>(Some(payload))
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: AUDIT_ENDPOINT,
+            })
+    }
+
+    /// Read one page of the core log archive. Feed the returned `cursor` back
+    /// into the next request to continue.
+    pub async fn query_core_logs(
+        &self,
+        payload: &api::log::CoreLogsQueryReq,
+    ) -> Result<CoreLogsQueryData> {
+        self.call::<LogsCoreQuery>(Some(payload))
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: LOGS_CORE_QUERY_ENDPOINT,
+            })
+    }
+
+    /// The running core's proxy groups. The service talks to the core's
+    /// controller itself, so the caller needs no secret for it.
+    pub async fn proxies(&self) -> Result<ProxiesData> {
+        self.call::<Proxies>(None)
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: PROXIES_ENDPOINT,
+            })
+    }
+
+    pub async fn select_proxy(&self, payload: &api::proxies::ProxySelectReq) -> Result<()> {
+        self.call::<ProxiesSelect>(Some(payload)).await.map(|_| ())
+    }
+
+    pub async fn proxy_delay(
+        &self,
+        payload: &api::proxies::ProxyDelayReq,
+    ) -> Result<ProxyDelayData> {
+        self.call::<ProxiesDelay>(Some(payload))
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: PROXIES_DELAY_ENDPOINT,
+            })
+    }
+
+    /// The running core's open connections that `filter` matches; pass the
+    /// default filter for all of them.
+    pub async fn connections(
+        &self,
+        filter: &api::connections::ConnectionFilter,
+    ) -> Result<ConnectionsData> {
+        self.call::<Connections>(Some(filter))
+            .await?
+            .data
+            .ok_or(ClientError::EmptyData {
+                operation: CONNECTIONS_ENDPOINT,
+            })
+    }
+
+    /// Close connections by id or by filter, returning the ids closed.
+    pub async fn close_connections(
+        &self,
+        payload: &api::connections::ConnectionCloseReq,
+    ) -> Result<Vec<String>> {
+        self.call::<ConnectionsClose>(Some(payload))
+            .await?
+            .data
+            .map(|data| data.closed)
+            .ok_or(ClientError::EmptyData {
+                operation: CONNECTIONS_CLOSE_ENDPOINT,
+            })
+    }
+
+    pub async fn close_all_connections(&self) -> Result<()> {
+        self.call::<ConnectionsCloseAll>(None).await.map(|_| ())
+    }
+
     pub async fn set_dns(
         &self,
         payload: &api::network::set_dns::NetworkSetDnsReq<'_>,
@@ -101,22 +308,34 @@ impl Client {
     /// moment the socket opens, one after every dropped-event recovery, and one
     /// per manager transition — including the `Starting`/`Restarting`
     /// transitions the two-valued [`Event::CoreStateChanged`] cannot express.
-    /// There is nothing to negotiate and no version parameter to pass; the
-    /// service ignores the query string.
+    /// There is nothing to negotiate and no version parameter to pass; what
+    /// arrives can be narrowed afterwards with [`EventStream::set_filter`].
     ///
     /// [`Event::CoreStateChanged`] keeps arriving alongside the snapshots, so a
     /// consumer of both sees each transition twice. The snapshot is idempotent,
     /// so the simplest correct handling is to let the last frame win.
     pub async fn events(&self) -> Result<EventStream> {
-        let response = self
-            .get(EVENT_URI)
-            .upgrade()
-            .send()
+        self.open_events(EVENT_URI).await
+    }
+
+    /// [`Self::events`], opening with recent core log frames from the
+    /// service's backlog. They arrive after the first snapshot and are closed
+    /// by one [`Event::CoreLogReplayEnd`]; every frame after it is live.
+    pub async fn events_with_replay(&self, replay: LogReplay) -> Result<EventStream> {
+        self.open_events(&format!("{EVENT_URI}?{}", replay.to_query()))
             .await
-            .map_err(|source| ClientError::WebSocket {
-                operation: EVENT_URI,
-                source,
-            })?;
+    }
+
+    async fn open_events(&self, uri: &str) -> Result<EventStream> {
+        let response =
+            self.get(uri)
+                .upgrade()
+                .send()
+                .await
+                .map_err(|source| ClientError::WebSocket {
+                    operation: EVENT_URI,
+                    source,
+                })?;
         let websocket =
             response
                 .into_websocket()
@@ -125,7 +344,8 @@ impl Client {
                     operation: EVENT_URI,
                     source,
                 })?;
-        let stream = websocket.filter_map(|message| async move {
+        let (control, stream) = websocket.split();
+        let stream = stream.filter_map(|message| async move {
             let bytes = match message {
                 Ok(Message::Binary(bytes)) => bytes,
                 Ok(Message::Text(text)) => text.into(),
@@ -147,13 +367,48 @@ impl Client {
         });
         Ok(EventStream {
             inner: Box::pin(stream),
+            control,
         })
     }
 }
 
+/// What [`Client::check_compatibility`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compatibility {
+    /// The service serves everything this build of the crate declares.
+    Compatible(CapabilitiesData),
+    /// The service is older than this build: it answers, but the operations
+    /// and events in `missing` will not work against it.
+    Degraded {
+        capabilities: CapabilitiesData,
+        missing: MissingCapabilities,
+    },
+    /// The service predates `/capabilities`, so what else it lacks is
+    /// unknown until a call fails.
+    Predates,
+}
+
 /// A stream of [`Event`]s pushed by the service.
 pub struct EventStream {
     inner: Pin<Box<dyn Stream<Item = Result<Event>> + Send>>,
+    control: SplitSink<WebSocket, Message>,
+}
+
+impl EventStream {
+    /// Narrow what the service sends on this connection from now on. Replaces
+    /// any earlier filter; [`EventFilter::default`] widens back to everything.
+    /// Events already in flight may still arrive unfiltered.
+    pub async fn set_filter(&mut self, filter: &EventFilter) -> Result<()> {
+        let payload =
+            serde_json::to_string(filter).expect("an event filter always serializes to JSON");
+        self.control
+            .send(Message::Text(payload))
+            .await
+            .map_err(|source| ClientError::WebSocket {
+                operation: EVENT_URI,
+                source,
+            })
+    }
 }
 
 impl Stream for EventStream {
@@ -4,6 +4,8 @@ extern crate derive_builder;
 pub mod api;
 #[cfg(feature = "client")]
 pub mod client;
+#[cfg(feature = "schema")]
+pub mod schema;
 #[cfg(feature = "server")]
 pub mod server;
 pub mod types;
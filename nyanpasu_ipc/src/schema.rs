@@ -0,0 +1,179 @@
+//! The IPC contract as TypeScript definitions and JSON Schema documents, for
+//! clients that are not written in Rust.
+//!
+//! Walks every [`IpcOperation`] and the `/ws/events` stream, and emits each
+//! from the types' own `specta` and `schemars` derives, so a field added to a
+//! wire struct shows up here without touching this module. The walk is
+//! written by hand like the contract itself; the `schema` test checks it
+//! against [`contract::operations`] and pins the output per
+//! [`PROTOCOL_REVISION`].
+//!
+//! 64-bit integers are exported as `number`: the service sends them as plain
+//! JSON numbers, and none of them (millisecond timestamps, byte counts,
+//! epochs) gets near 2^53.
+
+use std::{
+    any::{TypeId, type_name},
+    collections::BTreeMap,
+    io,
+    path::Path,
+};
+
+use schemars::{JsonSchema, schema_for};
+use serde_json::{Value, json};
+use specta::{Type, TypeCollection};
+use specta_typescript::{BigIntExportBehavior, Typescript};
+
+use crate::api::{
+    R,
+    capabilities::PROTOCOL_REVISION,
+    contract::{self, IpcOperation},
+    ws::events::{EVENT_URI, Event, EventFilter},
+};
+
+/// The TypeScript definitions, at the top of the output directory.
+pub const TYPESCRIPT_FILE_NAME: &str = "nyanpasu-ipc.ts";
+/// The JSON Schema documents, one per body, beside an `operations.json`
+/// index naming which document belongs to which address.
+pub const JSON_SCHEMA_DIR: &str = "json-schema";
+
+/// Everything [`export`] produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractSchema {
+    /// File contents keyed by their path inside the output directory,
+    /// `/`-separated on every platform.
+    pub files: BTreeMap<String, String>,
+}
+
+impl ContractSchema {
+    /// Write every file under `dir`, creating directories as needed. A file
+    /// already there that the export does not produce is left alone.
+    pub fn write_to(&self, dir: &Path) -> io::Result<()> {
+        for (name, contents) in &self.files {
+            let path = dir.join(name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("failed to export the TypeScript definitions: {0}")]
+pub struct ExportError(#[from] specta_typescript::ExportError);
+
+pub fn export() -> Result<ContractSchema, ExportError> {
+    let mut exporter = Exporter::default();
+    exporter.operation::<contract::Status>();
+    exporter.operation::<contract::CoreStart>();
+    exporter.operation::<contract::CoreStop>();
+    exporter.operation::<contract::CoreRestart>();
+    exporter.operation::<contract::CoreApply>();
+    exporter.operation::<contract::CoreApplyPlan>();
+    exporter.operation::<contract::CoreCheck>();
+    exporter.operation::<contract::CoreConfigDiff>();
+    exporter.operation::<contract::CoreRevisions>();
+    exporter.operation::<contract::CoreRollback>();
+    exporter.operation::<contract::CoreRecover>();
+    exporter.operation::<contract::LogsRetrieve>();
+    exporter.operation::<contract::LogsInspect>();
+    exporter.operation::<contract::LogsCoreQuery>();
+    exporter.operation::<contract::NetworkSetDns>();
+    exporter.operation::<contract::Proxies>();
+    exporter.operation::<contract::ProxiesSelect>();
+    exporter.operation::<contract::ProxiesDelay>();
+    exporter.operation::<contract::Connections>();
+    exporter.operation::<contract::ConnectionsClose>();
+    exporter.operation::<contract::ConnectionsCloseAll>();
+    exporter.operation::<contract::ServiceReload>();
+    exporter.operation::<contract::Capabilities>();
+    exporter.operation::<contract::Audit>();
+    exporter.finish()
+}
+
+#[derive(Default)]
+struct Exporter {
+    types: TypeCollection,
+    operations: Vec<Value>,
+    files: BTreeMap<String, String>,
+}
+
+impl Exporter {
+    /// A body-less operation (`Req<'a> = ()`) gets no request document, and
+    /// `null` for one in the index.
+    fn operation<Op>(&mut self)
+    where
+        Op: IpcOperation,
+        Op::Req<'static>: Type + JsonSchema + 'static,
+        Op::Data: Type + JsonSchema,
+    {
+        let name = short_name::<Op>();
+        let request = (TypeId::of::<Op::Req<'static>>() != TypeId::of::<()>()).then(|| {
+            self.collect::<Op::Req<'static>>();
+            self.document::<Op::Req<'static>>(&format!("{name}.request.json"))
+        });
+        self.collect::<R<'static, Op::Data>>();
+        let response = self.document::<R<'static, Op::Data>>(&format!("{name}.response.json"));
+        self.operations.push(json!({
+            "name": name,
+            "method": Op::METHOD.as_str(),
+            "path": Op::PATH,
+            "request": request,
+            "response": response,
+        }));
+    }
+
+    /// Adds `T` and everything it names to the TypeScript output.
+    fn collect<T: Type>(&mut self) {
+        T::reference(&mut self.types, &[]);
+    }
+
+    fn document<T: JsonSchema>(&mut self, file_name: &str) -> String {
+        self.write_json(file_name, &schema_for!(T));
+        file_name.to_owned()
+    }
+
+    fn write_json(&mut self, file_name: &str, value: &impl serde::Serialize) {
+        let json = serde_json::to_string_pretty(value).expect("a schema always serializes to JSON");
+        self.files
+            .insert(format!("{JSON_SCHEMA_DIR}/{file_name}"), json + "\n");
+    }
+
+    fn finish(mut self) -> Result<ContractSchema, ExportError> {
+        // The event stream has no envelope: every message is an `Event`, and
+        // the client may send an `EventFilter` back.
+        self.collect::<Event>();
+        self.collect::<EventFilter>();
+        let events = json!({
+            "path": EVENT_URI,
+            "message": self.document::<Event>("Event.json"),
+            "filter": self.document::<EventFilter>("EventFilter.json"),
+        });
+        let index = json!({
+            "protocol_revision": PROTOCOL_REVISION,
+            "operations": self.operations,
+            "events": events,
+        });
+        self.write_json("operations.json", &index);
+
+        let typescript = Typescript::default()
+            .header(format!(
+                "// Generated by `nyanpasu-service schema` from the IPC contract, protocol revision {PROTOCOL_REVISION}. Do not edit."
+            ))
+            .bigint(BigIntExportBehavior::Number)
+            .export(&self.types)?;
+        self.files.insert(
+            TYPESCRIPT_FILE_NAME.to_owned(),
+            format!("{typescript}\nexport const PROTOCOL_REVISION = {PROTOCOL_REVISION};\n"),
+        );
+        Ok(ContractSchema { files: self.files })
+    }
+}
+
+/// `CoreStart` for `nyanpasu_ipc::api::contract::CoreStart`.
+fn short_name<Op>() -> &'static str {
+    let path = type_name::<Op>();
+    path.rsplit("::").next().unwrap_or(path)
+}
@@ -24,7 +24,71 @@ pub enum ServerError {
     Other(#[from] anyhow::Error),
 }
 
-pub struct InterProcessListener(Listener, String);
+pub struct InterProcessListener {
+    listener: Listener,
+    name: String,
+    /// The pipe ACL, as SIDs. Every pipe instance takes the security
+    /// descriptor its listener was created with, so a new list rebinds the
+    /// listener and applies to the connections accepted after it.
+    #[cfg(windows)]
+    sids: tokio::sync::watch::Receiver<Vec<String>>,
+}
+
+impl InterProcessListener {
+    /// Rebinds under the SIDs now in `self.sids`. A listener that cannot be
+    /// rebound keeps serving under the ACL it already has.
+    #[cfg(windows)]
+    fn rebind(&mut self) {
+        let sids = self.sids.borrow_and_update().clone();
+        match bind(&self.name, &sids) {
+            Ok(listener) => {
+                self.listener = listener;
+                tracing::info!(?sids, "rebound the pipe under a new acl");
+            }
+            Err(e) => tracing::error!("failed to rebind the pipe under a new acl: {e}"),
+        }
+    }
+}
+
+/// The other end of an accepted connection, handed to every request on it as
+/// `ConnectInfo<IpcPeer>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpcPeer {
+    /// The socket name the connection came in on.
+    pub endpoint: String,
+    /// `None` outside Linux, or when the kernel would not say.
+    pub credentials: Option<PeerCredentials>,
+}
+
+/// The caller as `SO_PEERCRED` reports it: the process that connected, with
+/// the ids it had at `connect` time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerCredentials {
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: Option<u32>,
+}
+
+#[cfg(target_os = "linux")]
+fn peer_credentials(stream: &InterProcessStream) -> Option<PeerCredentials> {
+    let creds = match stream.peer_creds() {
+        Ok(creds) => creds,
+        Err(e) => {
+            tracing::warn!("failed to read the peer credentials: {e}");
+            return None;
+        }
+    };
+    Some(PeerCredentials {
+        uid: creds.euid()?,
+        gid: creds.egid()?,
+        pid: creds.pid().and_then(|pid| u32::try_from(pid).ok()),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peer_credentials(_stream: &InterProcessStream) -> Option<PeerCredentials> {
+    None
+}
 
 fn configure_listener_mode<'n>(options: ListenerOptions<'n>) -> ListenerOptions<'n> {
     // Interprocess applies this mode with fchmod() before bind(). macOS does not support
@@ -71,12 +135,31 @@ fn is_connection_error(e: &std::io::Error) -> bool {
 impl axum::serve::Listener for InterProcessListener {
     type Io = InterProcessStream;
     // FIXME: it should be supported by upstream, or waiting for upstream got supported listener trait
-    type Addr = String;
+    type Addr = IpcPeer;
 
     async fn accept(&mut self) -> (Self::Io, Self::Addr) {
         loop {
-            match self.0.accept().await {
-                Ok(stream) => return (stream, self.1.clone()),
+            #[cfg(windows)]
+            let accepted = tokio::select! {
+                accepted = self.listener.accept() => accepted,
+                changed = self.sids.changed() => {
+                    // A closed channel means the list can no longer change.
+                    if changed.is_ok() {
+                        self.rebind();
+                    }
+                    continue;
+                }
+            };
+            #[cfg(not(windows))]
+            let accepted = self.listener.accept().await;
+            match accepted {
+                Ok(stream) => {
+                    let peer = IpcPeer {
+                        endpoint: self.name.clone(),
+                        credentials: peer_credentials(&stream),
+                    };
+                    return (stream, peer);
+                }
                 Err(e) => handle_accept_error(e).await,
             }
         }
@@ -84,24 +167,24 @@ impl axum::serve::Listener for InterProcessListener {
 
     #[inline]
     fn local_addr(&self) -> tokio::io::Result<Self::Addr> {
-        Ok(self.1.clone())
+        Ok(IpcPeer {
+            endpoint: self.name.clone(),
+            credentials: None,
+        })
     }
 }
 
-#[instrument(skip(with_graceful_shutdown))]
-pub async fn create_server(
-    placeholder: &str,
-    app: Router,
-    with_graceful_shutdown: Option<impl Future<Output = ()> + Send + 'static>,
-    #[cfg(windows)] sids: &[&str],
-    #[cfg(not(windows))] sids: (),
-) -> Result<()> {
-    let name_str = crate::utils::get_name_string(placeholder);
-    let name = name_str.as_str().to_fs_name::<GenericFilePath>()?;
-    #[cfg(unix)]
-    {
-        crate::utils::remove_socket_if_exists(placeholder).await?;
+impl axum::extract::connect_info::Connected<axum::serve::IncomingStream<'_, InterProcessListener>>
+    for IpcPeer
+{
+    fn connect_info(stream: axum::serve::IncomingStream<'_, InterProcessListener>) -> Self {
+        stream.remote_addr().clone()
     }
+}
+
+/// Creates the listener for `name_str`, under the pipe ACL `sids` on Windows.
+fn bind(name_str: &str, #[cfg(windows)] sids: &[String]) -> Result<Listener> {
+    let name = name_str.to_fs_name::<GenericFilePath>()?;
     tracing::debug!("socket name: {:?}", name);
     let options = ListenerOptions::new()
         .name(name)
@@ -110,7 +193,8 @@ pub async fn create_server(
     let options = {
         use anyhow::Context;
         use widestring::U16CString;
-        let sdsf = crate::utils::acl::generate_windows_security_descriptor(sids, None, None)
+        let sids = sids.iter().map(String::as_str).collect::<Vec<_>>();
+        let sdsf = crate::utils::acl::generate_windows_security_descriptor(&sids, None, None)
             .context("failed to generate sdsf")?;
         let sdsf = U16CString::from_str(&sdsf).context("failed to convert sdsf to u16cstring")?;
         let sw = SecurityDescriptor::deserialize(&sdsf)?;
@@ -119,16 +203,49 @@ pub async fn create_server(
     // Set the mode atomically on platforms that support fchmod() on socket descriptors.
     // Other Unix platforms use change_socket_mode() after the socket path is created.
     let options = configure_listener_mode(options);
+    Ok(options.create_tokio()?)
+}
 
-    let listener = options.create_tokio()?;
-    let listener = InterProcessListener(listener, name_str);
+/// Serves `app` until `with_graceful_shutdown` resolves. On Windows the pipe
+/// ACL follows `sids`: a new list applies to every connection accepted after
+/// it, and connections already open keep going.
+#[instrument(skip(with_graceful_shutdown))]
+pub async fn create_server(
+    placeholder: &str,
+    app: Router,
+    with_graceful_shutdown: Option<impl Future<Output = ()> + Send + 'static>,
+    #[cfg(windows)] mut sids: tokio::sync::watch::Receiver<Vec<String>>,
+    #[cfg(not(windows))] sids: (),
+) -> Result<()> {
+    let name_str = crate::utils::get_name_string(placeholder);
+    #[cfg(unix)]
+    {
+        crate::utils::remove_socket_if_exists(placeholder).await?;
+    }
+    #[cfg(windows)]
+    let listener = {
+        let initial = sids.borrow_and_update().clone();
+        InterProcessListener {
+            listener: bind(&name_str, &initial)?,
+            name: name_str,
+            sids,
+        }
+    };
+    #[cfg(not(windows))]
+    let listener = InterProcessListener {
+        listener: bind(&name_str)?,
+        name: name_str,
+    };
     // change the socket group
     tracing::debug!("changing socket group and permissions...");
     crate::utils::os::change_socket_group(placeholder)?;
     crate::utils::os::change_socket_mode(placeholder)?;
 
     tracing::debug!("mounting service...");
-    let server = axum::serve(listener, app);
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<IpcPeer>(),
+    );
     match with_graceful_shutdown {
         Some(graceful_shutdown) => server.with_graceful_shutdown(graceful_shutdown).await?,
         None => server.await?,
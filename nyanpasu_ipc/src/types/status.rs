@@ -5,6 +5,7 @@ use std::borrow::Cow;
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Eq)]
 #[serde(rename_all = "snake_case")]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ServiceStatus {
     NotInstalled,
     Stopped,
@@ -13,6 +14,7 @@ pub enum ServiceStatus {
 
 #[derive(Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct StatusInfo<'n> {
     pub name: Cow<'n, str>,    // The client program name
     pub version: Cow<'n, str>, // The client program version
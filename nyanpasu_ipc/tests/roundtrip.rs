@@ -56,7 +56,7 @@ use nyanpasu_ipc::{
             RuntimeInfos, STATUS_ENDPOINT, StatusRes, StatusResBody,
         },
         ws::events::{
-            ClashCoreKind, EVENT_URI, Event, LogFrame, LogLevel, LogStream, LogTimestamp,
+            ClashCoreKind, CoreKind, EVENT_URI, Event, LogFrame, LogLevel, LogStream, LogTimestamp,
         },
     },
     client::{Client, ClientError},
@@ -215,6 +215,8 @@ fn test_status_body() -> StatusResBody<'static> {
             health: None,
             revision: None,
             detail: None,
+            build: None,
+            policy: None,
         },
         runtime_infos: RuntimeInfos {
             service_data_dir: Cow::Owned(PathBuf::from("/srv/data")),
@@ -223,6 +225,8 @@ fn test_status_body() -> StatusResBody<'static> {
             nyanpasu_data_dir: Cow::Owned(PathBuf::from("/home/data")),
         },
         logs: None,
+        core_binary: None,
+        service_config: None,
     }
 }
 
@@ -274,7 +278,7 @@ async fn restart_core_handler(
     State(state): State<Shared>,
 ) -> (StatusCode, Json<CoreRestartRes<'static>>) {
     state.lock().unwrap().restart_core_calls += 1;
-    (StatusCode::OK, Json(RBuilder::success(())))
+    (StatusCode::OK, Json(RBuilder::success(Default::default())))
 }
 
 async fn inspect_logs_handler() -> (StatusCode, Json<LogsRes<'static>>) {
@@ -313,7 +317,7 @@ async fn ws_handler(ws: WebSocketUpgrade) -> Response {
             Event::new_core_log(std::sync::Arc::new(LogFrame {
                 at: 1_700_000_000_000,
                 epoch: 4,
-                kind: ClashCoreKind::Mihomo,
+                kind: CoreKind::Clash(ClashCoreKind::Mihomo),
                 stream: LogStream::Stdout,
                 level: LogLevel::Info,
                 timestamp: Some(LogTimestamp {
@@ -354,6 +358,8 @@ fn test_snapshot() -> CoreInfos {
             epoch: 3,
             attempt: 2,
         }),
+        build: None,
+        policy: None,
     }
 }
 
@@ -392,6 +398,8 @@ async fn apply_config_handler(
             },
             warning: Some("directory sync failed".to_owned()),
             failed_apply: Some("core failed to start".to_owned()),
+            selections: None,
+            degrade_reason: None,
         })),
     )
 }
@@ -409,12 +417,16 @@ async fn apply_config_conflict_handler() -> (StatusCode, Json<CoreApplyRes<'stat
 fn apply_payload() -> CoreApplyReq<'static> {
     CoreApplyReq {
         core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
-        config_file: Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml")),
+        config_file: Some(Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml"))),
+        config: None,
         expected_revision: Some(RevisionIdInfo {
             epoch: 3,
             generation: 7,
             effective_hash: "eff".to_owned(),
         }),
+        binary_path: None,
+        distribution: None,
+        policy: None,
     }
 }
 
@@ -461,6 +473,9 @@ async fn rest_roundtrip() {
         .start_core(&CoreStartReq {
             core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
             config_file: Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml")),
+            binary_path: None,
+            distribution: None,
+            policy: None,
         })
         .await
         .expect("start_core should succeed");
@@ -599,7 +614,7 @@ async fn core_log_roundtrip() {
     let log = seen.expect("the stream should carry a core log frame");
     assert_eq!(log.at, 1_700_000_000_000);
     assert_eq!(log.epoch, 4);
-    assert_eq!(log.kind, ClashCoreKind::Mihomo);
+    assert_eq!(log.kind, CoreKind::Clash(ClashCoreKind::Mihomo));
     assert_eq!(log.stream, LogStream::Stdout);
     assert_eq!(log.level, LogLevel::Info);
     let timestamp = log.timestamp.as_ref().expect("the fixture parsed a header");
@@ -693,6 +708,9 @@ async fn json_posts_send_the_exact_payload() {
     let payload = CoreStartReq {
         core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
         config_file: Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml")),
+        binary_path: None,
+        distribution: None,
+        policy: None,
     };
 
     client
@@ -0,0 +1,139 @@
+//! The emitted TypeScript and JSON Schema, pinned per protocol revision.
+//!
+//! `tests/schema/revision-N` holds exactly what `nyanpasu-service schema`
+//! writes at `PROTOCOL_REVISION = N`. A wire change makes the output differ
+//! from the pinned copy and fails here until the revision is bumped. This test
+//! only compares; the copy for a new revision is recorded with
+//!
+//! ```sh
+//! cargo run -p nyanpasu-service -- schema --out-dir nyanpasu_ipc/tests/schema/revision-N
+//! ```
+//!
+//! and committed with the bump. Older revisions stay in the tree as the record
+//! of what each one meant.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use nyanpasu_ipc::{
+    api::{capabilities::PROTOCOL_REVISION, contract},
+    schema::{self, JSON_SCHEMA_DIR},
+};
+
+fn pinned_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/schema")
+        .join(format!("revision-{PROTOCOL_REVISION}"))
+}
+
+fn read_tree(root: &Path, dir: &Path, files: &mut BTreeMap<String, String>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            read_tree(root, &path, files);
+            continue;
+        }
+        let name = path
+            .strip_prefix(root)
+            .unwrap()
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        // Normalized so a checkout with CRLF line endings still compares.
+        let contents = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("\r\n", "\n");
+        files.insert(name, contents);
+    }
+}
+
+#[test]
+fn the_emitted_schema_is_pinned_to_its_protocol_revision() {
+    let emitted = schema::export().unwrap();
+    let dir = pinned_dir();
+    assert!(
+        dir.is_dir(),
+        "nothing is pinned for protocol revision {PROTOCOL_REVISION}: run \
+         `cargo run -p nyanpasu-service -- schema --out-dir nyanpasu_ipc/tests/schema/revision-{PROTOCOL_REVISION}` \
+         and commit {}",
+        dir.display()
+    );
+
+    let mut pinned = BTreeMap::new();
+    read_tree(&dir, &dir, &mut pinned);
+    let changed = emitted
+        .files
+        .keys()
+        .chain(pinned.keys())
+        .filter(|name| emitted.files.get(*name) != pinned.get(*name))
+        .collect::<BTreeSet<_>>();
+    assert!(
+        changed.is_empty(),
+        "the wire contract changed without a protocol revision bump ({changed:?} differ from {}); \
+         bump PROTOCOL_REVISION and record the new revision with \
+         `cargo run -p nyanpasu-service -- schema --out-dir nyanpasu_ipc/tests/schema/revision-<N>`",
+        dir.display()
+    );
+}
+
+/// The exporter walks the operations by hand, so check it walked the same
+/// ones `/capabilities` advertises.
+#[test]
+fn every_operation_in_the_contract_is_exported() {
+    let emitted = schema::export().unwrap();
+    let index: serde_json::Value =
+        serde_json::from_str(&emitted.files[&format!("{JSON_SCHEMA_DIR}/operations.json")])
+            .unwrap();
+    let exported = index["operations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|operation| {
+            (
+                operation["method"].as_str().unwrap().to_owned(),
+                operation["path"].as_str().unwrap().to_owned(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let declared = contract::operations()
+        .into_iter()
+        .map(|operation| (operation.method, operation.path))
+        .collect::<Vec<_>>();
+    assert_eq!(exported, declared);
+    assert_eq!(index["protocol_revision"], PROTOCOL_REVISION);
+}
+
+#[test]
+fn every_named_body_has_its_own_document() {
+    let emitted = schema::export().unwrap();
+    for name in [
+        "Status.response.json",
+        "CoreStart.request.json",
+        "CoreApply.response.json",
+        "Event.json",
+        "EventFilter.json",
+    ] {
+        assert!(
+            emitted
+                .files
+                .contains_key(&format!("{JSON_SCHEMA_DIR}/{name}")),
+            "{name} is missing"
+        );
+    }
+    assert!(
+        !emitted
+            .files
+            .contains_key(&format!("{JSON_SCHEMA_DIR}/Status.request.json")),
+        "a body-less operation has no request document"
+    );
+    let typescript = &emitted.files[schema::TYPESCRIPT_FILE_NAME];
+    for name in ["CoreInfos", "CoreApplyData", "Event"] {
+        assert!(
+            typescript.contains(&format!("export type {name} ")),
+            "{name} is not exported"
+        );
+    }
+}
@@ -14,20 +14,47 @@ use std::{
 
 use nyanpasu_ipc::api::{
     R, RBuilder, ResponseCode,
+    access::Role,
+    audit::{
+        AuditCaller, AuditOutcome, AuditQueryData, AuditQueryReq, AuditRecord, AuditRequestSummary,
+    },
+    capabilities::{CapabilitiesData, OperationInfo, PROTOCOL_REVISION, ServiceFeature},
+    connections::{
+        ConnectionCloseData, ConnectionCloseReq, ConnectionFilter, ConnectionInfo,
+        ConnectionNetwork, ConnectionsData,
+    },
     core::{
-        apply::{ApplyOutcomeKind, CoreApplyData, CoreApplyReq},
+        apply::{
+            ApplyOutcomeKind, CORE_INLINE_CONFIG_MAX_BYTES, CoreApplyData, CoreApplyPlanData,
+            CoreApplyPlanReq, CoreApplyReq, SwitchDegradeReason,
+        },
         check::CoreCheckReq,
-        start::CoreStartReq,
+        config::{ConfigDiffEntryInfo, ConfigDiffKind, CoreConfigDiffData},
+        restart::CoreRestartData,
+        revisions::{CoreRevisionInfo, CoreRevisionsData},
+        rollback::CoreRollbackReq,
+        start::{CorePolicyOverrides, CoreStartReq},
     },
     error_kind,
-    log::LogsResBody,
+    log::{CoreLogCursor, CoreLogsQueryData, CoreLogsQueryReq, LogsResBody},
     network::set_dns::NetworkSetDnsReq,
+    proxies::{
+        ProxiesData, ProxyDelayData, ProxyDelayInfo, ProxyDelayReq, ProxyGroupInfo, ProxySelectReq,
+        ProxySelectionInfo, RestoredSelectionsInfo,
+    },
+    service_config::{
+        AccessConfig, HealthConfig, LocalIpcPolicyConfig, RestartPolicyConfig, ServiceConfig,
+        ServiceConfigInfo, ServiceReloadData,
+    },
     status::{
-        ConfigRevisionInfo, CoreControllerInfo, CoreHealthInfo, CoreHealthState, CoreInfos,
-        CoreState, CoreStateDetail, LogPathsInfo, RevisionIdInfo, RuntimeInfos, StatusResBody,
+        ConfigRevisionInfo, CoreBinaryInfo, CoreBuildInfo, CoreControllerInfo, CoreDistribution,
+        CoreHealthInfo, CoreHealthState, CoreInfos, CorePolicyInfo, CoreRuntimeFeature, CoreState,
+        CoreStateDetail, Feature, LogPathsInfo, RevisionIdInfo, RuntimeInfos, StatusResBody,
+        VariantTag,
     },
     ws::events::{
-        ClashCoreKind, EVENT_URI, Event, LogField, LogFrame, LogLevel, LogStream, LogTimestamp,
+        ClashCoreKind, CoreKind, EVENT_URI, Event, EventFilter, EventKind, LogField, LogFrame,
+        LogLevel, LogReplay, LogStream, LogTimestamp, MemorySample, TrafficSample,
     },
 };
 use nyanpasu_utils::core::{ClashCoreType, CoreType};
@@ -92,6 +119,8 @@ fn enriched_core_infos() -> CoreInfos {
             epoch: 3,
             pid: 4242,
         }),
+        build: None,
+        policy: None,
     }
 }
 
@@ -106,6 +135,8 @@ fn minimal_core_infos() -> CoreInfos {
         health: None,
         revision: None,
         detail: Some(CoreStateDetail::Stopped { reason: None }),
+        build: None,
+        policy: None,
     }
 }
 
@@ -225,19 +256,27 @@ fn the_unit_response_envelope_is_pinned() {
 }
 
 /// The three legacy core error strings are protocol, not diagnostics: the GUI
-/// branches on them.
+/// branches on them. Each now carries its kind too, appended after `ts` so
+/// everything before it reads exactly as it did.
 #[test]
 fn the_legacy_core_error_envelopes_are_pinned() {
-    for msg in [
-        "core is already running",
-        "core is already stopped",
-        "core have not been started yet",
+    for (msg, kind) in [
+        ("core is already running", error_kind::ALREADY_RUNNING),
+        ("core is already stopped", error_kind::NOT_STARTED),
+        ("core have not been started yet", error_kind::NOT_STARTED),
     ] {
         let envelope: R<'static, ()> = error_envelope(msg);
         assert_eq!(
             serde_json::to_string(&envelope).unwrap(),
             format!(r#"{{"code":"OtherError","msg":"{msg}","data":null,"ts":1700000000}}"#)
         );
+        let envelope: R<'static, ()> = error_envelope_with_kind(msg, kind);
+        assert_eq!(
+            serde_json::to_string(&envelope).unwrap(),
+            format!(
+                r#"{{"code":"OtherError","msg":"{msg}","data":null,"ts":1700000000,"error_kind":"{kind}"}}"#
+            )
+        );
     }
 }
 
@@ -246,11 +285,26 @@ fn the_core_start_request_is_pinned() {
     let request = CoreStartReq {
         core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
         config_file: Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml")),
+        binary_path: None,
+        distribution: None,
+        policy: None,
     };
     assert_eq!(
         serde_json::to_string(&request).unwrap(),
         r#"{"core_type":{"clash":"mihomo"},"config_file":"/etc/nyanpasu/config.yaml"}"#
     );
+    let explicit = CoreStartReq {
+        binary_path: Some(Cow::Owned(PathBuf::from("/usr/bin/mihomo"))),
+        ..request
+    };
+    assert_eq!(
+        serde_json::to_string(&explicit).unwrap(),
+        concat!(
+            r#"{"core_type":{"clash":"mihomo"},"#,
+            r#""config_file":"/etc/nyanpasu/config.yaml","#,
+            r#""binary_path":"/usr/bin/mihomo"}"#
+        )
+    );
 }
 
 #[test]
@@ -307,6 +361,243 @@ fn the_logs_response_is_pinned() {
     );
 }
 
+/// An empty query is an empty object: every filter is omitted, not null.
+#[test]
+fn the_core_log_query_request_is_pinned() {
+    assert_eq!(
+        serde_json::to_string(&CoreLogsQueryReq::default()).unwrap(),
+        "{}"
+    );
+    let request = CoreLogsQueryReq {
+        since: Some(1_700_000_000_000),
+        until: Some(1_700_000_060_000),
+        level: Some(LogLevel::Warning),
+        epoch: Some(3),
+        stream: Some(LogStream::Stderr),
+        contains: Some("dial".to_owned()),
+        cursor: Some(CoreLogCursor {
+            file: 2,
+            offset: 4096,
+        }),
+        limit: Some(50),
+        backward: true,
+    };
+    assert_eq!(
+        serde_json::to_string(&request).unwrap(),
+        concat!(
+            r#"{"since":1700000000000,"until":1700000060000,"level":"warning","epoch":3,"#,
+            r#""stream":"stderr","contains":"dial","cursor":{"file":2,"offset":4096},"#,
+            r#""limit":50,"backward":true}"#
+        )
+    );
+}
+
+#[test]
+fn the_core_log_query_response_is_pinned() {
+    let body = CoreLogsQueryData {
+        frames: vec![LogFrame {
+            at: 1_700_000_000_001,
+            epoch: 2,
+            kind: CoreKind::Clash(ClashCoreKind::ClashRust),
+            stream: LogStream::Stderr,
+            level: LogLevel::Warning,
+            timestamp: None,
+            target: None,
+            message: "unparsed line".to_owned(),
+            fields: Vec::new(),
+            raw: "unparsed line".to_owned(),
+            truncated: false,
+        }],
+        cursor: CoreLogCursor {
+            file: 2,
+            offset: 4311,
+        },
+        exhausted: true,
+        dropped: 5,
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(body)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"frames":[{"at":1700000000001,"epoch":2,"#,
+            r#""kind":"clash-rs","stream":"stderr","level":"warning","timestamp":null,"#,
+            r#""target":null,"message":"unparsed line","fields":[],"raw":"unparsed line","#,
+            r#""truncated":false}],"cursor":{"file":2,"offset":4311},"exhausted":true,"#,
+            r#""dropped":5},"ts":1700000000}"#
+        )
+    );
+}
+
+#[test]
+fn the_proxy_groups_response_is_pinned() {
+    let body = ProxiesData {
+        groups: vec![
+            ProxyGroupInfo {
+                name: "PROXY".to_owned(),
+                r#type: "Selector".to_owned(),
+                now: Some("hk-01".to_owned()),
+                all: vec!["hk-01".to_owned(), "DIRECT".to_owned()],
+                hidden: false,
+                delay: Some(42),
+            },
+            ProxyGroupInfo {
+                name: "GLOBAL".to_owned(),
+                r#type: "Selector".to_owned(),
+                now: None,
+                all: Vec::new(),
+                hidden: true,
+                delay: None,
+            },
+        ],
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(body)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"groups":[{"name":"PROXY","type":"Selector","#,
+            r#""now":"hk-01","all":["hk-01","DIRECT"],"delay":42},{"name":"GLOBAL","#,
+            r#""type":"Selector","all":[],"hidden":true}]},"ts":1700000000}"#
+        )
+    );
+}
+
+#[test]
+fn the_proxy_select_and_delay_requests_are_pinned() {
+    let select = ProxySelectReq {
+        group: "PROXY".to_owned(),
+        proxy: "jp-01".to_owned(),
+    };
+    assert_eq!(
+        serde_json::to_string(&select).unwrap(),
+        r#"{"group":"PROXY","proxy":"jp-01"}"#
+    );
+    let single = ProxyDelayReq {
+        name: "jp-01".to_owned(),
+        url: "https://www.gstatic.com/generate_204".to_owned(),
+        timeout_ms: None,
+        group: false,
+    };
+    assert_eq!(
+        serde_json::to_string(&single).unwrap(),
+        r#"{"name":"jp-01","url":"https://www.gstatic.com/generate_204"}"#
+    );
+    let group = ProxyDelayReq {
+        name: "auto".to_owned(),
+        url: "https://www.gstatic.com/generate_204".to_owned(),
+        timeout_ms: Some(3000),
+        group: true,
+    };
+    assert_eq!(
+        serde_json::to_string(&group).unwrap(),
+        concat!(
+            r#"{"name":"auto","url":"https://www.gstatic.com/generate_204","#,
+            r#""timeout_ms":3000,"group":true}"#
+        )
+    );
+}
+
+#[test]
+fn the_proxy_delay_response_is_pinned() {
+    let body = ProxyDelayData {
+        delays: vec![
+            ProxyDelayInfo {
+                name: "hk-01".to_owned(),
+                delay: 42,
+            },
+            ProxyDelayInfo {
+                name: "jp-01".to_owned(),
+                delay: 0,
+            },
+        ],
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(body)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"delays":[{"name":"hk-01","delay":42},"#,
+            r#"{"name":"jp-01","delay":0}]},"ts":1700000000}"#
+        )
+    );
+}
+
+#[test]
+fn the_connection_requests_are_pinned() {
+    assert_eq!(
+        serde_json::to_string(&ConnectionFilter::default()).unwrap(),
+        "{}"
+    );
+    let filter = ConnectionFilter {
+        host: Some("github".to_owned()),
+        rule: None,
+        chain: Some("PROXY".to_owned()),
+        process: None,
+        network: Some(ConnectionNetwork::Tcp),
+    };
+    assert_eq!(
+        serde_json::to_string(&filter).unwrap(),
+        r#"{"host":"github","chain":"PROXY","network":"tcp"}"#
+    );
+    let by_id = ConnectionCloseReq {
+        ids: vec!["5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01".to_owned()],
+        filter: None,
+    };
+    assert_eq!(
+        serde_json::to_string(&by_id).unwrap(),
+        r#"{"ids":["5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01"]}"#
+    );
+    let by_filter = ConnectionCloseReq {
+        ids: Vec::new(),
+        filter: Some(ConnectionFilter {
+            process: Some("curl".to_owned()),
+            ..ConnectionFilter::default()
+        }),
+    };
+    assert_eq!(
+        serde_json::to_string(&by_filter).unwrap(),
+        r#"{"filter":{"process":"curl"}}"#
+    );
+}
+
+#[test]
+fn the_connection_responses_are_pinned() {
+    let body = ConnectionsData {
+        upload_total: 1024,
+        download_total: 2048,
+        connections: vec![ConnectionInfo {
+            id: "5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01".to_owned(),
+            network: Some(ConnectionNetwork::Tcp),
+            host: "dl.example.com".to_owned(),
+            destination: "93.184.216.34:443".to_owned(),
+            source: "127.0.0.1:50000".to_owned(),
+            process: String::new(),
+            rule: "DomainSuffix".to_owned(),
+            rule_payload: "example.com".to_owned(),
+            chains: vec!["hk-01".to_owned(), "PROXY".to_owned()],
+            upload: 10,
+            download: 20,
+            start: 1_753_719_382_646,
+        }],
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(body)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"upload_total":1024,"download_total":2048,"#,
+            r#""connections":[{"id":"5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01","network":"tcp","#,
+            r#""host":"dl.example.com","destination":"93.184.216.34:443","#,
+            r#""source":"127.0.0.1:50000","rule":"DomainSuffix","rule_payload":"example.com","#,
+            r#""chains":["hk-01","PROXY"],"upload":10,"download":20,"#,
+            r#""start":1753719382646}]},"ts":1700000000}"#
+        )
+    );
+    let closed = ConnectionCloseData {
+        closed: vec!["5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01".to_owned()],
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(closed)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"closed":"#,
+            r#"["5f1c0a52-7d25-4a57-9b3e-1b8a3c2e9d01"]},"ts":1700000000}"#
+        )
+    );
+}
+
 /// The absent S7 fields keep this pre-S7 JSON byte-identical.
 #[test]
 fn the_status_response_is_pinned() {
@@ -321,6 +612,8 @@ fn the_status_response_is_pinned() {
             health: None,
             revision: None,
             detail: None,
+            build: None,
+            policy: None,
         },
         runtime_infos: RuntimeInfos {
             service_data_dir: Cow::Owned(PathBuf::from("/srv/data")),
@@ -329,6 +622,8 @@ fn the_status_response_is_pinned() {
             nyanpasu_data_dir: Cow::Owned(PathBuf::from("/home/data")),
         },
         logs: None,
+        core_binary: None,
+        service_config: None,
     };
     assert_eq!(
         serde_json::to_string(&ok_envelope(body)).unwrap(),
@@ -381,7 +676,7 @@ fn pinned_core_log() -> Event {
     Event::new_core_log(Arc::new(LogFrame {
         at: 1_700_000_000_000,
         epoch: 1,
-        kind: ClashCoreKind::Mihomo,
+        kind: CoreKind::Clash(ClashCoreKind::Mihomo),
         stream: LogStream::Stdout,
         level: LogLevel::Info,
         timestamp: Some(LogTimestamp {
@@ -424,7 +719,7 @@ fn a_degraded_core_log_event_is_pinned() {
     let event = Event::new_core_log(Arc::new(LogFrame {
         at: 1_700_000_000_001,
         epoch: 2,
-        kind: ClashCoreKind::ClashRust,
+        kind: CoreKind::Clash(ClashCoreKind::ClashRust),
         stream: LogStream::Stderr,
         level: LogLevel::Warning,
         timestamp: None,
@@ -445,6 +740,73 @@ fn a_degraded_core_log_event_is_pinned() {
     );
 }
 
+#[test]
+fn the_replay_marker_event_is_pinned() {
+    assert_eq!(
+        serde_json::to_string(&Event::new_core_log_replay_end(3)).unwrap(),
+        r#"{"CoreLogReplayEnd":{"replayed":3}}"#
+    );
+}
+
+#[test]
+fn the_telemetry_events_are_pinned() {
+    let traffic = Event::new_traffic(TrafficSample {
+        epoch: 2,
+        up: 1024,
+        down: 4096,
+        up_total: 1_048_576,
+        down_total: 8_388_608,
+    });
+    assert_eq!(
+        serde_json::to_string(&traffic).unwrap(),
+        concat!(
+            r#"{"Traffic":{"epoch":2,"up":1024,"down":4096,"#,
+            r#""up_total":1048576,"down_total":8388608}}"#
+        )
+    );
+    let memory = Event::new_memory(MemorySample {
+        epoch: 2,
+        in_use: 52_428_800,
+        os_limit: 0,
+    });
+    assert_eq!(
+        serde_json::to_string(&memory).unwrap(),
+        r#"{"Memory":{"epoch":2,"in_use":52428800,"os_limit":0}}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&EventKind::Telemetry).unwrap(),
+        r#""telemetry""#
+    );
+}
+
+/// The filter is the one message a client sends on the event stream.
+#[test]
+fn the_event_filter_message_is_pinned() {
+    assert_eq!(
+        serde_json::to_string(&EventFilter::default()).unwrap(),
+        "{}"
+    );
+    let filter = EventFilter {
+        kinds: Some(vec![EventKind::Status, EventKind::Log]),
+        min_level: Some(LogLevel::Warning),
+        pattern: Some("dns".to_owned()),
+    };
+    assert_eq!(
+        serde_json::to_string(&filter).unwrap(),
+        r#"{"kinds":["status","log"],"min_level":"warning","pattern":"dns"}"#
+    );
+}
+
+/// The replay parameters are part of the address a client connects to.
+#[test]
+fn the_replay_query_strings_are_pinned() {
+    assert_eq!(LogReplay::Last(50).to_query(), "replay=50");
+    assert_eq!(
+        LogReplay::Since(1_700_000_000_000).to_query(),
+        "replay_since=1700000000000"
+    );
+}
+
 #[test]
 fn the_error_envelope_decodes_back() {
     let encoded = serde_json::to_string(&error_envelope::<()>("core is already stopped")).unwrap();
@@ -605,6 +967,8 @@ fn the_enriched_status_response_is_pinned() {
                 epoch: 3,
                 pid: 4242,
             }),
+            build: None,
+            policy: None,
         },
         runtime_infos: RuntimeInfos {
             service_data_dir: Cow::Owned(PathBuf::from("/srv/data")),
@@ -613,6 +977,8 @@ fn the_enriched_status_response_is_pinned() {
             nyanpasu_data_dir: Cow::Owned(PathBuf::from("/home/data")),
         },
         logs: None,
+        core_binary: None,
+        service_config: None,
     };
     assert_eq!(
         serde_json::to_string(&ok_envelope(body)).unwrap(),
@@ -663,6 +1029,221 @@ fn the_status_log_paths_are_pinned() {
     );
 }
 
+#[test]
+fn the_status_core_binary_is_pinned() {
+    assert_eq!(
+        serde_json::to_string(&CoreBinaryInfo {
+            path: PathBuf::from("/usr/bin/mihomo"),
+            sha256: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_owned(),
+        })
+        .unwrap(),
+        concat!(
+            r#"{"path":"/usr/bin/mihomo","#,
+            r#""sha256":"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"}"#
+        )
+    );
+}
+
+/// The defaults are the service's built-in values, so this literal is also
+/// what a service without a `service.toml` reports.
+#[test]
+fn the_service_config_report_is_pinned() {
+    let mut effective = ServiceConfig::default();
+    effective.manager.local_ipc_policy = Some(LocalIpcPolicyConfig::Disable);
+    let info = ServiceConfigInfo {
+        path: PathBuf::from("/srv/config/service.toml"),
+        loaded: false,
+        effective,
+    };
+    assert_eq!(
+        serde_json::to_string(&info).unwrap(),
+        concat!(
+            r#"{"path":"/srv/config/service.toml","loaded":false,"effective":{"#,
+            r#""manager":{"local_ipc_policy":"disable","control_timeout_ms":10000,"#,
+            r#""reconcile_timeout_ms":30000,"stop_timeout_ms":10000,"#,
+            r#""log_sink_enabled":true,"log_max_bytes":4194304,"log_max_files":5,"#,
+            r#""revision_history":10,"max_concurrent_checks":2},"#,
+            r#""instance":{"startup_timeout_ms":30000,"#,
+            r#""health":{"interval_ms":250,"timeout_ms":1000,"failure_threshold":3,"#,
+            r#""success_threshold":1,"start_period_ms":0},"#,
+            r#""restart":{"policy":"on-failure","max_restarts":5,"#,
+            r#""backoff_initial_ms":1000,"backoff_max_ms":30000,"backoff_jitter":true}},"#,
+            r#""events":{"status_capacity":256,"log_capacity":1024,"#,
+            r#""log_backlog_capacity":512,"telemetry_capacity":64},"#,
+            r#""server":{"request_timeout_ms":120000},"#,
+            r#""access":{"default_role":"admin","users":{},"groups":{},"operations":{}}}}"#
+        )
+    );
+}
+
+/// A file names only what it changes, and a misspelt key is refused rather
+/// than quietly leaving the default in place.
+#[test]
+fn a_partial_service_config_fills_in_defaults_and_rejects_unknown_keys() {
+    let partial: ServiceConfig = serde_json::from_str(
+        r#"{"instance":{"restart":{"policy":"never"}},"events":{"log_capacity":4096}}"#,
+    )
+    .unwrap();
+    let mut expected = ServiceConfig::default();
+    expected.instance.restart.policy = RestartPolicyConfig::Never;
+    expected.events.log_capacity = 4096;
+    assert_eq!(partial, expected);
+
+    assert!(serde_json::from_str::<ServiceConfig>(r#"{"manager":{"stop_timeout":1}}"#).is_err());
+}
+
+/// Ids are map keys, so they are strings on the wire as in the file.
+#[test]
+fn the_access_table_is_pinned() {
+    let access: AccessConfig = serde_json::from_str(
+        r#"{"default_role":"read-only","users":{"1000":"control"},"operations":{"/ws/events":"control"}}"#,
+    )
+    .unwrap();
+    assert_eq!(access.default_role, Role::ReadOnly);
+    assert_eq!(access.users["1000"], Role::Control);
+    assert!(access.groups.is_empty());
+    assert_eq!(access.operations["/ws/events"], Role::Control);
+
+    for (role, expected) in [
+        (Role::ReadOnly, r#""read-only""#),
+        (Role::Control, r#""control""#),
+        (Role::Admin, r#""admin""#),
+    ] {
+        assert_eq!(serde_json::to_string(&role).unwrap(), expected);
+    }
+}
+
+/// Both lists are always present, empty or not. The config inside is the
+/// shape pinned above, so only its position is checked here.
+#[test]
+fn the_service_reload_report_is_pinned() {
+    let data = ServiceReloadData {
+        config: ServiceConfigInfo {
+            path: PathBuf::from("/srv/config/service.toml"),
+            loaded: true,
+            effective: ServiceConfig::default(),
+        },
+        applied: vec!["events.log_capacity".to_owned()],
+        restart_required: Vec::new(),
+    };
+    let json = serde_json::to_string(&ok_envelope(data)).unwrap();
+    assert!(
+        json.starts_with(
+            r#"{"code":"Ok","msg":"ok","data":{"config":{"path":"/srv/config/service.toml","loaded":true,"effective":{"#
+        ),
+        "{json}"
+    );
+    assert!(
+        json.ends_with(concat!(
+            r#""applied":["events.log_capacity"],"restart_required":[]},"#,
+            r#""ts":1700000000}"#
+        )),
+        "{json}"
+    );
+}
+
+/// Distribution and version are omitted when unknown; the two feature lists
+/// are always present, empty or not.
+#[test]
+fn the_core_build_identity_is_pinned() {
+    let mut distribution = CoreDistribution::new(CoreKind::Clash(ClashCoreKind::Mihomo));
+    distribution.variant = Some("alpha-goamd64-v2".to_owned());
+    distribution.tags.insert(VariantTag::new("channel:alpha"));
+    let build = CoreBuildInfo {
+        version: Some("Mihomo Meta v1.19.0 linux amd64".to_owned()),
+        distribution: Some(distribution),
+        capabilities: vec![Feature::UnixSocketIpc],
+        runtime_features: vec![CoreRuntimeFeature::LocalIpc],
+    };
+    assert_eq!(
+        serde_json::to_string(&build).unwrap(),
+        concat!(
+            r#"{"version":"Mihomo Meta v1.19.0 linux amd64","#,
+            r#""distribution":{"kind":"mihomo","variant":"alpha-goamd64-v2","#,
+            r#""tags":["channel:alpha"]},"#,
+            r#""capabilities":["unix-socket-ipc"],"runtime_features":["local-ipc"]}"#
+        )
+    );
+    let unknown = CoreBuildInfo {
+        version: None,
+        distribution: None,
+        capabilities: Vec::new(),
+        runtime_features: Vec::new(),
+    };
+    assert_eq!(
+        serde_json::to_string(&unknown).unwrap(),
+        r#"{"capabilities":[],"runtime_features":[]}"#
+    );
+
+    let request = CoreStartReq {
+        core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
+        config_file: Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml")),
+        binary_path: None,
+        distribution: build.distribution,
+        policy: None,
+    };
+    assert_eq!(
+        serde_json::to_string(&request).unwrap(),
+        concat!(
+            r#"{"core_type":{"clash":"mihomo"},"#,
+            r#""config_file":"/etc/nyanpasu/config.yaml","#,
+            r#""distribution":{"kind":"mihomo","variant":"alpha-goamd64-v2","#,
+            r#""tags":["channel:alpha"]}}"#
+        )
+    );
+}
+
+/// Only the overrides given go on the wire; the report always carries the
+/// whole policy, bar `max_restarts` under a `never` restart policy.
+#[test]
+fn the_core_policy_overrides_and_report_are_pinned() {
+    let request = CoreStartReq {
+        core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
+        config_file: Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml")),
+        binary_path: None,
+        distribution: None,
+        policy: Some(CorePolicyOverrides {
+            startup_timeout_ms: Some(60_000),
+            max_restarts: Some(0),
+            ..Default::default()
+        }),
+    };
+    assert_eq!(
+        serde_json::to_string(&request).unwrap(),
+        concat!(
+            r#"{"core_type":{"clash":"mihomo"},"#,
+            r#""config_file":"/etc/nyanpasu/config.yaml","#,
+            r#""policy":{"startup_timeout_ms":60000,"max_restarts":0}}"#
+        )
+    );
+
+    let report = CorePolicyInfo {
+        startup_timeout_ms: 60_000,
+        health: HealthConfig::default(),
+        max_restarts: Some(0),
+    };
+    assert_eq!(
+        serde_json::to_string(&report).unwrap(),
+        concat!(
+            r#"{"startup_timeout_ms":60000,"#,
+            r#""health":{"interval_ms":250,"timeout_ms":1000,"failure_threshold":3,"#,
+            r#""success_threshold":1,"start_period_ms":0},"max_restarts":0}"#
+        )
+    );
+    let never = CorePolicyInfo {
+        max_restarts: None,
+        ..report
+    };
+    assert_eq!(
+        serde_json::to_string(&never).unwrap(),
+        concat!(
+            r#"{"startup_timeout_ms":60000,"#,
+            r#""health":{"interval_ms":250,"timeout_ms":1000,"failure_threshold":3,"#,
+            r#""success_threshold":1,"start_period_ms":0}}"#
+        )
+    );
+}
+
 /// The other half of the compatibility gate: a payload written by a pre-S7
 /// service must still decode, with the new fields absent rather than an error.
 #[test]
@@ -706,15 +1287,42 @@ fn the_error_kind_strings_are_pinned() {
     assert_eq!(error_kind::NOT_STARTED, "not_started");
     assert_eq!(error_kind::ALREADY_RUNNING, "already_running");
     assert_eq!(error_kind::REVISION_CONFLICT, "revision_conflict");
+    assert_eq!(error_kind::REVISION_NOT_FOUND, "revision_not_found");
     assert_eq!(error_kind::QUARANTINED, "quarantined");
     assert_eq!(error_kind::CONFIG_CHECK_FAILED, "config_check_failed");
     assert_eq!(error_kind::CONFIG_NOT_FOUND, "config_not_found");
     assert_eq!(error_kind::BINARY_NOT_FOUND, "binary_not_found");
+    assert_eq!(error_kind::BINARY_NOT_ALLOWED, "binary_not_allowed");
+    assert_eq!(error_kind::BINARY_UNTRUSTED, "binary_untrusted");
+    assert_eq!(error_kind::INVALID_POLICY, "invalid_policy");
+    assert_eq!(error_kind::SHUTTING_DOWN, "shutting_down");
+    assert_eq!(error_kind::VERSION_PROBE_FAILED, "version_probe_failed");
+    assert_eq!(error_kind::LOCAL_IPC_UNSUPPORTED, "local_ipc_unsupported");
+    assert_eq!(error_kind::STARTUP_FAILED, "startup_failed");
+    assert_eq!(error_kind::STARTUP_TIMEOUT, "startup_timeout");
+    assert_eq!(error_kind::PROCESS_FAILED, "process_failed");
+    assert_eq!(
+        error_kind::CONTROLLER_REQUEST_FAILED,
+        "controller_request_failed"
+    );
+    assert_eq!(error_kind::RUNTIME_IO, "runtime_io");
+    assert_eq!(
+        error_kind::UNSAFE_RUNTIME_ARTIFACT,
+        "unsafe_runtime_artifact"
+    );
+    assert_eq!(error_kind::RUNTIME_DIR_OWNED, "runtime_dir_owned");
+    assert_eq!(
+        error_kind::INVALID_MANAGER_OPTIONS,
+        "invalid_manager_options"
+    );
     assert_eq!(error_kind::INVALID_CONFIG, "invalid_config");
     assert_eq!(error_kind::CONTROLLER_MISSING, "controller_missing");
     assert_eq!(error_kind::APPLY_FAILED, "apply_failed");
     assert_eq!(error_kind::APPLY_ROLLBACK_FAILED, "apply_rollback_failed");
     assert_eq!(error_kind::STOP_UNCONFIRMED, "stop_unconfirmed");
+    assert_eq!(error_kind::PERMISSION_DENIED, "permission_denied");
+    assert_eq!(error_kind::LOG_ARCHIVE_DISABLED, "log_archive_disabled");
+    assert_eq!(error_kind::INVALID_FILTER, "invalid_filter");
 }
 
 /// The new field is appended, so no existing key moves; the absent case is
@@ -769,8 +1377,12 @@ fn the_revision_id_info_is_pinned() {
 fn the_core_apply_request_is_pinned() {
     let without = CoreApplyReq {
         core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
-        config_file: Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml")),
+        config_file: Some(Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml"))),
+        config: None,
         expected_revision: None,
+        binary_path: None,
+        distribution: None,
+        policy: None,
     };
     // No CAS token: the key is omitted, not sent as null.
     assert_eq!(
@@ -796,11 +1408,186 @@ fn the_core_apply_request_is_pinned() {
     );
 }
 
+#[test]
+fn the_apply_plan_is_pinned() {
+    let request = CoreApplyPlanReq {
+        core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
+        config_file: Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml")),
+        binary_path: None,
+        policy: None,
+    };
+    assert_eq!(
+        serde_json::to_string(&request).unwrap(),
+        r#"{"core_type":{"clash":"mihomo"},"config_file":"/etc/nyanpasu/config.yaml"}"#
+    );
+    let request = CoreApplyPlanReq {
+        binary_path: Some(Cow::Owned(PathBuf::from("/usr/lib/clash-nyanpasu/mihomo"))),
+        ..request
+    };
+    assert_eq!(
+        serde_json::to_string(&request).unwrap(),
+        concat!(
+            r#"{"core_type":{"clash":"mihomo"},"config_file":"/etc/nyanpasu/config.yaml","#,
+            r#""binary_path":"/usr/lib/clash-nyanpasu/mihomo"}"#
+        )
+    );
+    let reload = CoreApplyPlanData {
+        outcome: ApplyOutcomeKind::Reloaded,
+        degrade_reason: None,
+        changed: vec!["dns.enhanced-mode".to_owned(), "rules".to_owned()],
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(reload)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"outcome":"reloaded","#,
+            r#""changed":["dns.enhanced-mode","rules"]},"ts":1700000000}"#
+        )
+    );
+    let switch = CoreApplyPlanData {
+        outcome: ApplyOutcomeKind::Switched,
+        degrade_reason: Some(SwitchDegradeReason::DnsListen),
+        changed: vec!["dns.listen".to_owned()],
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(switch)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"outcome":"switched","#,
+            r#""degrade_reason":"dns_listen","changed":["dns.listen"]},"ts":1700000000}"#
+        )
+    );
+}
+
+#[test]
+fn the_config_diff_is_pinned() {
+    let data = CoreConfigDiffData {
+        entries: vec![
+            ConfigDiffEntryInfo {
+                path: "dns.enable".to_owned(),
+                kind: ConfigDiffKind::Added,
+                old: None,
+                new: Some(serde_json::json!(true)),
+            },
+            ConfigDiffEntryInfo {
+                path: "mode".to_owned(),
+                kind: ConfigDiffKind::Removed,
+                old: Some(serde_json::json!("rule")),
+                new: None,
+            },
+            ConfigDiffEntryInfo {
+                path: "secret".to_owned(),
+                kind: ConfigDiffKind::Changed,
+                old: Some(serde_json::json!("<redacted>")),
+                new: Some(serde_json::json!("<redacted>")),
+            },
+        ],
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(data)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"entries":["#,
+            r#"{"path":"dns.enable","kind":"added","new":true},"#,
+            r#"{"path":"mode","kind":"removed","old":"rule"},"#,
+            r#"{"path":"secret","kind":"changed","old":"<redacted>","new":"<redacted>"}"#,
+            r#"]},"ts":1700000000}"#
+        )
+    );
+}
+
+#[test]
+fn the_revision_history_is_pinned() {
+    let data = CoreRevisionsData {
+        revisions: vec![CoreRevisionInfo {
+            revision: ConfigRevisionInfo {
+                epoch: 3,
+                generation: 7,
+                source_hash: "0123456789abcdef".to_owned(),
+                effective_hash: "fedcba9876543210".to_owned(),
+            },
+            kind: CoreKind::Clash(ClashCoreKind::Mihomo),
+            recorded_at: 1_700_000_000_123,
+        }],
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(data)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"revisions":[{"#,
+            r#""revision":{"epoch":3,"generation":7,"#,
+            r#""source_hash":"0123456789abcdef","#,
+            r#""effective_hash":"fedcba9876543210"},"#,
+            r#""kind":"mihomo","recorded_at":1700000000123}]},"ts":1700000000}"#
+        )
+    );
+}
+
+#[test]
+fn the_rollback_request_is_pinned() {
+    let target = RevisionIdInfo {
+        epoch: 3,
+        generation: 7,
+        effective_hash: "fedcba9876543210".to_owned(),
+    };
+    let without = CoreRollbackReq {
+        revision: target.clone(),
+        expected_revision: None,
+    };
+    assert_eq!(
+        serde_json::to_string(&without).unwrap(),
+        r#"{"revision":{"epoch":3,"generation":7,"effective_hash":"fedcba9876543210"}}"#
+    );
+    let with = CoreRollbackReq {
+        expected_revision: Some(RevisionIdInfo {
+            generation: 8,
+            ..target
+        }),
+        ..without
+    };
+    assert_eq!(
+        serde_json::to_string(&with).unwrap(),
+        concat!(
+            r#"{"revision":{"epoch":3,"generation":7,"effective_hash":"fedcba9876543210"},"#,
+            r#""expected_revision":{"epoch":3,"generation":8,"#,
+            r#""effective_hash":"fedcba9876543210"}}"#
+        )
+    );
+}
+
+/// The inline alternative replaces `config_file` on the wire rather than
+/// joining it: the absent one is omitted, never sent as null.
+#[test]
+fn the_inline_config_requests_are_pinned() {
+    let apply = CoreApplyReq {
+        core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
+        config_file: None,
+        config: Some(Cow::Borrowed("mode: rule\n")),
+        expected_revision: None,
+        binary_path: None,
+        distribution: None,
+        policy: None,
+    };
+    assert_eq!(
+        serde_json::to_string(&apply).unwrap(),
+        r#"{"core_type":{"clash":"mihomo"},"config":"mode: rule\n"}"#
+    );
+    let check = CoreCheckReq {
+        core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
+        config_file: None,
+        config: Some(Cow::Borrowed("mode: rule\n")),
+        binary_path: None,
+    };
+    assert_eq!(
+        serde_json::to_string(&check).unwrap(),
+        r#"{"core_type":{"clash":"mihomo"},"config":"mode: rule\n"}"#
+    );
+    assert_eq!(CORE_INLINE_CONFIG_MAX_BYTES, 1024 * 1024);
+}
+
 #[test]
 fn the_core_check_request_is_pinned() {
     let request = CoreCheckReq {
         core_type: Cow::Owned(CoreType::Clash(ClashCoreType::Mihomo)),
-        config_file: Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml")),
+        config_file: Some(Cow::Owned(PathBuf::from("/etc/nyanpasu/config.yaml"))),
+        config: None,
+        binary_path: None,
     };
     assert_eq!(
         serde_json::to_string(&request).unwrap(),
@@ -821,6 +1608,8 @@ fn the_core_apply_response_is_pinned() {
         revision: revision.clone(),
         warning: None,
         failed_apply: None,
+        selections: None,
+        degrade_reason: None,
     };
     assert_eq!(
         serde_json::to_string(&ok_envelope(clean)).unwrap(),
@@ -837,6 +1626,8 @@ fn the_core_apply_response_is_pinned() {
         revision,
         warning: Some("runtime directory sync failed".to_owned()),
         failed_apply: Some("core failed to start".to_owned()),
+        selections: None,
+        degrade_reason: None,
     };
     assert_eq!(
         serde_json::to_string(&ok_envelope(rolled_back)).unwrap(),
@@ -850,3 +1641,212 @@ fn the_core_apply_response_is_pinned() {
         )
     );
 }
+
+#[test]
+fn the_restored_selections_of_a_switch_are_pinned() {
+    let switched = CoreApplyData {
+        outcome: ApplyOutcomeKind::Switched,
+        revision: ConfigRevisionInfo {
+            epoch: 4,
+            generation: 1,
+            source_hash: "0123456789abcdef".to_owned(),
+            effective_hash: "fedcba9876543210".to_owned(),
+        },
+        warning: None,
+        failed_apply: None,
+        selections: Some(RestoredSelectionsInfo {
+            restored: vec![ProxySelectionInfo {
+                group: "PROXY".to_owned(),
+                proxy: "jp-01".to_owned(),
+            }],
+            skipped: vec![ProxySelectionInfo {
+                group: "Streaming".to_owned(),
+                proxy: "us-02".to_owned(),
+            }],
+        }),
+        degrade_reason: None,
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(switched)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"outcome":"switched","#,
+            r#""revision":{"epoch":4,"generation":1,"#,
+            r#""source_hash":"0123456789abcdef","#,
+            r#""effective_hash":"fedcba9876543210"},"#,
+            r#""selections":{"restored":[{"group":"PROXY","proxy":"jp-01"}],"#,
+            r#""skipped":[{"group":"Streaming","proxy":"us-02"}]}},"ts":1700000000}"#
+        )
+    );
+}
+
+#[test]
+fn a_degraded_switch_names_its_reason() {
+    let switched = CoreApplyData {
+        outcome: ApplyOutcomeKind::Switched,
+        revision: ConfigRevisionInfo {
+            epoch: 4,
+            generation: 1,
+            source_hash: "0123456789abcdef".to_owned(),
+            effective_hash: "fedcba9876543210".to_owned(),
+        },
+        warning: None,
+        failed_apply: None,
+        selections: None,
+        degrade_reason: Some(SwitchDegradeReason::HttpController),
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(switched)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"outcome":"switched","#,
+            r#""revision":{"epoch":4,"generation":1,"#,
+            r#""source_hash":"0123456789abcdef","#,
+            r#""effective_hash":"fedcba9876543210"},"#,
+            r#""degrade_reason":"http_controller"},"ts":1700000000}"#
+        )
+    );
+}
+
+/// A restart without selectors to carry answers `{}`, not `null`; the `null`
+/// an older service answers with still decodes, as no data.
+#[test]
+fn a_restart_reports_what_it_carried_over() {
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(CoreRestartData::default())).unwrap(),
+        r#"{"code":"Ok","msg":"ok","data":{},"ts":1700000000}"#
+    );
+    let older: R<'static, CoreRestartData> =
+        serde_json::from_str(r#"{"code":"Ok","msg":"ok","data":null,"ts":1700000000}"#).unwrap();
+    assert_eq!(older.data, None);
+    let restarted = CoreRestartData {
+        selections: Some(RestoredSelectionsInfo {
+            restored: vec![ProxySelectionInfo {
+                group: "PROXY".to_owned(),
+                proxy: "jp-01".to_owned(),
+            }],
+            skipped: Vec::new(),
+        }),
+        degrade_reason: Some(SwitchDegradeReason::HttpController),
+        warning: None,
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(restarted)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"#,
+            r#""selections":{"restored":[{"group":"PROXY","proxy":"jp-01"}],"skipped":[]},"#,
+            r#""degrade_reason":"http_controller"},"ts":1700000000}"#
+        )
+    );
+}
+
+/// One line of `audit.jsonl` and one `/audit` page are the same record, so
+/// the file is pinned here too.
+#[test]
+fn an_audit_page_is_pinned() {
+    let record = AuditRecord {
+        at: 1_700_000_000_123,
+        request_id: Some("7f0c".to_owned()),
+        operation: "/core/start".to_owned(),
+        caller: Some(AuditCaller {
+            uid: 1000,
+            gid: 1000,
+            pid: Some(4242),
+        }),
+        request: AuditRequestSummary {
+            config_file: Some(PathBuf::from("/etc/nyanpasu/config.yaml")),
+            core_type: Some(CoreType::Clash(ClashCoreType::Mihomo)),
+            ..Default::default()
+        },
+        outcome: AuditOutcome {
+            ok: false,
+            status: 500,
+            error_kind: Some(error_kind::STARTUP_TIMEOUT.to_owned()),
+            msg: Some("core did not become healthy".to_owned()),
+        },
+    };
+    let data = AuditQueryData {
+        records: vec![record],
+        cursor: 12,
+        exhausted: true,
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(data)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"records":[{"at":1700000000123,"#,
+            r#""request_id":"7f0c","operation":"/core/start","#,
+            r#""caller":{"uid":1000,"gid":1000,"pid":4242},"#,
+            r#""request":{"config_file":"/etc/nyanpasu/config.yaml","#,
+            r#""core_type":{"clash":"mihomo"}},"#,
+            r#""outcome":{"ok":false,"status":500,"error_kind":"startup_timeout","#,
+            r#""msg":"core did not become healthy"}}],"#,
+            r#""cursor":12,"exhausted":true},"ts":1700000000}"#
+        )
+    );
+
+    // An empty query is an empty body.
+    assert_eq!(
+        serde_json::to_string(&AuditQueryReq::default()).unwrap(),
+        "{}"
+    );
+}
+
+/// Hand-built rather than [`CapabilitiesData::of_this_build`], so the shape is
+/// pinned and not the current lists.
+#[test]
+fn the_capabilities_report_is_pinned() {
+    let data = CapabilitiesData {
+        version: "2.0.0".to_owned(),
+        protocol_revision: 1,
+        operations: vec![OperationInfo {
+            method: "GET".to_owned(),
+            path: "/status".to_owned(),
+        }],
+        event_kinds: vec![EventKind::Status],
+        events: vec!["CoreStatusChanged".to_owned()],
+        error_kinds: vec![error_kind::NOT_STARTED.to_owned()],
+        features: vec![
+            ServiceFeature::CoreLogArchive,
+            ServiceFeature::LocalIpc,
+            ServiceFeature::PinnedCores,
+        ],
+    };
+    assert_eq!(
+        serde_json::to_string(&ok_envelope(data)).unwrap(),
+        concat!(
+            r#"{"code":"Ok","msg":"ok","data":{"version":"2.0.0","protocol_revision":1,"#,
+            r#""operations":[{"method":"GET","path":"/status"}],"#,
+            r#""event_kinds":["status"],"events":["CoreStatusChanged"],"#,
+            r#""error_kinds":["not_started"],"#,
+            r#""features":["core-log-archive","local-ipc","pinned-cores"]},"#,
+            r#""ts":1700000000}"#
+        )
+    );
+}
+
+/// The lists are additive: a revision bump adds to them and never renames or
+/// drops an entry a client may be checking for.
+#[test]
+fn this_build_advertises_every_declared_name() {
+    let data = CapabilitiesData::of_this_build("2.0.0", Vec::new());
+    assert_eq!(data.protocol_revision, PROTOCOL_REVISION);
+    assert!(data.operations.contains(&OperationInfo {
+        method: "GET".to_owned(),
+        path: "/capabilities".to_owned(),
+    }));
+    assert_eq!(
+        serde_json::to_string(&data.event_kinds).unwrap(),
+        r#"["status","log","telemetry"]"#
+    );
+    assert_eq!(
+        data.events,
+        [
+            "CoreStateChanged",
+            "CoreStatusChanged",
+            "CoreLog",
+            "CoreLogReplayEnd",
+            "Traffic",
+            "Memory"
+        ]
+    );
+    assert_eq!(data.error_kinds.len(), error_kind::ALL.len());
+    assert!(data.error_kinds.iter().any(|kind| kind == "invalid_policy"));
+}